@@ -3,12 +3,14 @@ mod renderer;
 mod types;
 mod ui;
 
+use crate::renderer::asset_analyzer::AssetAnalyzer;
+use crate::types::envelope::Envelope;
 use crate::types::media::{VideoClip, VideoMetadata};
 use crate::types::playback_state::PlaybackState;
-use crate::types::project::{Project, ProjectSettings};
-use crate::types::timeline::Timeline;
+use crate::types::project::Project;
 use crate::types::track::{Track, VideoTrack};
 use crate::ui::app::{AppState, CutioApp};
+use crate::ui::log_panel::{LogPanel, init_logger};
 use crate::ui::timeline_widget::TimelineState;
 use crate::ui::video_player::VideoPlayer;
 use gstreamer as gst;
@@ -16,7 +18,12 @@ use gstreamer as gst;
 use std::path::PathBuf;
 
 fn main() -> eframe::Result<()> {
+    let log_buffer = init_logger(log::LevelFilter::Info);
     let _ = gst::init();
+    let plugin_report = ops::check_plugins();
+    for message in plugin_report.messages() {
+        log::warn!("{}", message);
+    }
     // Dummy video clip and track for testing
     let video_clip = VideoClip {
         id: "clip1".to_string(),
@@ -25,41 +32,34 @@ fn main() -> eframe::Result<()> {
         out_point: 5.0,
         start_time: 0.0,
         duration: 5.0,
+        enabled: true,
+        effects: Vec::new(),
+        opacity_envelope: Envelope::new(),
+        freeze: false,
+        speed: 1.0,
+        time_map: None,
+        reversed: false,
+        mute_audio: false,
+        crop: None,
+        group_id: None,
         metadata: VideoMetadata {
             resolution: (1920, 1080),
             frame_rate: 30.0,
             codec: "h264".to_string(),
+            color_space: None,
         },
     };
 
-    let timeline = Timeline {
-        tracks: vec![],
-        frame_rate: 30.0,
-        resolution: (1920, 1080),
-        duration: 600.0,
-        // frame_rate and resolution are private, so do not set them here
-    };
+    let mut project = Project::from_template("1080p30");
+    project.timeline.duration = 600.0;
+    let timeline = project.timeline.clone();
     use std::sync::{Arc, RwLock};
     let timeline_arc = Arc::new(RwLock::new(timeline.clone()));
 
-    let project = Project {
-        name: "Untitled Project".to_string(),
-        description: None,
-        project_file_path: "".to_string(),
-        created_at: "".to_string(),
-        last_modified: "".to_string(),
-        media_library: crate::types::media_library::MediaLibrary::new(),
-        timeline: timeline.clone(),
-        cache_dir: "".to_string(),
-        render_output_dir: "".to_string(),
-        settings: ProjectSettings {
-            resolution: (1920, 1080),
-            frame_rate: 30.0,
-            color_space: "sRGB".to_string(),
-        },
-    };
-
     let playback_state = PlaybackState::new();
+    let ui_state = crate::types::ui_state::UiState::default_path()
+        .map(|path| crate::types::ui_state::UiState::load(&path))
+        .unwrap_or_default();
 
     let timeline_arc = Arc::new(RwLock::new(timeline.clone()));
     let video_player = VideoPlayer::new(
@@ -75,11 +75,34 @@ fn main() -> eframe::Result<()> {
         video_player,
         timeline: timeline_arc.clone(),
         timeline_state: TimelineState::new(),
+        asset_analyzer: AssetAnalyzer::new(),
+        scrub_previews: crate::ui::medialib::ScrubPreviews::default(),
+        log_panel: LogPanel {
+            // Surface missing-plugin warnings right away
+            open: !plugin_report.all_available(),
+            ..LogPanel::new(log_buffer)
+        },
+        show_stats: false,
+        project_stats: None,
+        goto_input: None,
+        dirty: false,
+        close_prompt: None,
+        new_project_template: None,
+        render_queue: crate::ops::render_queue::RenderQueue::new(),
+        show_render_queue: false,
+        history: crate::types::history::EditHistory::new(&timeline),
+        ui_state,
+        open_warnings: None,
+        target_video_track: None,
+        target_audio_track: None,
     };
 
-    let app = CutioApp { state: app_state };
+    let mut native_options = eframe::NativeOptions::default();
+    if let Some((width, height)) = app_state.ui_state.window_size {
+        native_options.viewport = native_options.viewport.with_inner_size([width, height]);
+    }
+    let app = CutioApp::new(app_state);
 
-    let native_options = eframe::NativeOptions::default();
     eframe::run_native(
         "Cutio NLE",
         native_options,
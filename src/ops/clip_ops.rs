@@ -2,6 +2,13 @@ use crate::types::media::{AudioClip, AudioMetadata, Clip, VideoClip, VideoMetada
 
 /// Cuts a clip at the given playhead position, returning two new clips if the cut is valid.
 /// Returns None if the playhead is outside the clip's range.
+///
+/// The playhead is mapped into the source by the clip's in/out span over its duration,
+/// so clips whose source range differs from their length (speed changes) split at the
+/// source frame actually shown there. Both halves meet at that source time.
+///
+/// A grouped clip's right part joins a new group (see `split_group_id`), so cutting
+/// through a linked pair at one time leaves a left pair and a right pair.
 pub fn cut_clip_at<T>(clip: &T, playhead: f64) -> Option<(T, T)>
 where
     T: Clip + Clone + ClipSplit,
@@ -14,24 +21,52 @@ where
     }
 
     let (mut left, mut right) = clip.split();
+    let (left_id, right_id) = split_ids(clip.id());
+
+    let source_per_second = (clip.out_point() - clip.in_point()) / clip.duration();
+    let source_offset = (playhead - clip_start) * source_per_second;
+    // Source ranges of the left and right parts; a reversed clip plays its source from
+    // the out point back, so its left part holds the end of the range
+    let (left_range, right_range) = if clip.reversed() {
+        let split = clip.out_point() - source_offset;
+        ((split, clip.out_point()), (clip.in_point(), split))
+    } else {
+        let split = clip.in_point() + source_offset;
+        ((clip.in_point(), split), (split, clip.out_point()))
+    };
+    debug_assert!(clip.reversed() || left_range.1 == right_range.0);
 
     // Left part: from original start to playhead
-    left.set_id(format!("{}_left", clip.id()));
-    left.set_in_point(clip.in_point());
-    left.set_out_point(clip.in_point() + (playhead - clip_start));
+    left.set_id(left_id);
+    left.set_in_point(left_range.0);
+    left.set_out_point(left_range.1);
     left.set_start_time(clip_start);
     left.set_duration(playhead - clip_start);
 
     // Right part: from playhead to original end
-    right.set_id(format!("{}_right", clip.id()));
-    right.set_in_point(clip.in_point() + (playhead - clip_start));
-    right.set_out_point(clip.out_point());
+    right.set_id(right_id);
+    right.set_in_point(right_range.0);
+    right.set_out_point(right_range.1);
     right.set_start_time(playhead);
     right.set_duration(clip_end - playhead);
+    right.shift_local_times(playhead - clip_start);
+    if let Some(group) = clip.group_id() {
+        right.set_group_id(Some(split_group_id(group, playhead)));
+    }
 
     Some((left, right))
 }
 
+/// Group `cut_clip_at` gives the parts right of a cut at `time` through clips of `group`
+pub fn split_group_id(group: &str, time: f64) -> String {
+    format!("{}_{:.3}", group, time)
+}
+
+/// Ids `cut_clip_at` gives the left and right parts of the clip `id`
+pub fn split_ids(id: &str) -> (String, String) {
+    (format!("{}_left", id), format!("{}_right", id))
+}
+
 /// Trait to allow setting fields on a Clip for splitting/cutting.
 /// This is needed because the base Clip trait only has getters.
 pub trait ClipSplit: Clip {
@@ -43,6 +78,18 @@ pub trait ClipSplit: Clip {
     fn split(&self) -> (Self, Self)
     where
         Self: Sized;
+    /// Whether the clip plays its source backwards, from the out point to the in point
+    fn reversed(&self) -> bool {
+        false
+    }
+    /// Move clip-local keyframe times `offset` seconds earlier, for a part that now
+    /// starts that far into the original clip
+    fn shift_local_times(&mut self, _offset: f64) {}
+    /// Group of clips that move together, if any
+    fn group_id(&self) -> Option<&str> {
+        None
+    }
+    fn set_group_id(&mut self, _group_id: Option<String>) {}
 }
 
 impl ClipSplit for VideoClip {
@@ -64,6 +111,21 @@ impl ClipSplit for VideoClip {
     fn split(&self) -> (Self, Self) {
         (self.clone(), self.clone())
     }
+    fn reversed(&self) -> bool {
+        self.reversed
+    }
+    fn shift_local_times(&mut self, offset: f64) {
+        for (local, _) in self.time_map.iter_mut().flatten() {
+            *local -= offset;
+        }
+        self.opacity_envelope.shift(offset);
+    }
+    fn group_id(&self) -> Option<&str> {
+        self.group_id.as_deref()
+    }
+    fn set_group_id(&mut self, group_id: Option<String>) {
+        self.group_id = group_id;
+    }
 }
 
 impl ClipSplit for AudioClip {
@@ -85,11 +147,18 @@ impl ClipSplit for AudioClip {
     fn split(&self) -> (Self, Self) {
         (self.clone(), self.clone())
     }
+    fn group_id(&self) -> Option<&str> {
+        self.group_id.as_deref()
+    }
+    fn set_group_id(&mut self, group_id: Option<String>) {
+        self.group_id = group_id;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::envelope::Envelope;
     use crate::types::media::{AudioClip, AudioMetadata, VideoClip, VideoMetadata};
 
     #[test]
@@ -101,10 +170,21 @@ mod tests {
             out_point: 10.0,
             start_time: 0.0,
             duration: 10.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
             metadata: VideoMetadata {
                 resolution: (1920, 1080),
                 frame_rate: 30.0,
                 codec: "h264".to_string(),
+                color_space: None,
             },
         };
         let playhead = 4.0;
@@ -132,6 +212,9 @@ mod tests {
             out_point: 8.0,
             start_time: 2.0,
             duration: 8.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
             metadata: AudioMetadata {
                 sample_rate: 48000,
                 channels: 2,
@@ -164,10 +247,21 @@ mod tests {
             out_point: 10.0,
             start_time: 0.0,
             duration: 10.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
             metadata: VideoMetadata {
                 resolution: (1920, 1080),
                 frame_rate: 30.0,
                 codec: "h264".to_string(),
+                color_space: None,
             },
         };
         // Playhead before start
@@ -179,4 +273,90 @@ mod tests {
         // Playhead after end
         assert!(cut_clip_at(&clip, 12.0).is_none());
     }
+
+    /// Clip at 0s showing `in_point..out_point` of its source over `duration` seconds
+    fn retimed_clip(in_point: f64, out_point: f64, duration: f64) -> VideoClip {
+        VideoClip {
+            id: "vc".to_string(),
+            asset_path: "video.mp4".to_string(),
+            in_point,
+            out_point,
+            start_time: 0.0,
+            duration,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: (out_point - in_point) / duration,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_cut_clip_with_shorter_source_range() {
+        // 2s of source stretched over 8s (quarter speed)
+        let clip = retimed_clip(2.0, 4.0, 8.0);
+        let (left, right) = cut_clip_at(&clip, 4.0).unwrap();
+        assert_eq!((left.in_point, left.out_point), (2.0, 3.0));
+        assert_eq!((right.in_point, right.out_point), (3.0, 4.0));
+        assert_eq!(left.out_point, right.in_point);
+        assert_eq!((left.duration, right.duration), (4.0, 4.0));
+        // The split point shows the same source frame as before
+        assert_eq!(right.source_time_at(4.0), clip.source_time_at(4.0));
+
+        // Double speed: 10s of source over 5s
+        let clip = retimed_clip(0.0, 10.0, 5.0);
+        let (left, right) = cut_clip_at(&clip, 1.0).unwrap();
+        assert_eq!((left.in_point, left.out_point), (0.0, 2.0));
+        assert_eq!((right.in_point, right.out_point), (2.0, 10.0));
+    }
+
+    #[test]
+    fn test_cut_reversed_clip() {
+        let mut clip = retimed_clip(2.0, 6.0, 4.0);
+        clip.reversed = true;
+        let (left, right) = cut_clip_at(&clip, 1.0).unwrap();
+        // The left part plays the end of the source range
+        assert_eq!((left.in_point, left.out_point), (5.0, 6.0));
+        assert_eq!((right.in_point, right.out_point), (2.0, 5.0));
+        assert_eq!(left.source_time_at(0.5), clip.source_time_at(0.5));
+        assert_eq!(right.source_time_at(2.5), clip.source_time_at(2.5));
+    }
+
+    #[test]
+    fn test_cut_clip_keeps_opacity_keyframes() {
+        let mut clip = retimed_clip(0.0, 4.0, 4.0);
+        clip.start_time = 1.0;
+        clip.opacity_envelope.add_point(0.0, 0.0);
+        clip.opacity_envelope.add_point(2.0, 1.0);
+        let (left, right) = cut_clip_at(&clip, 2.0).unwrap();
+        assert_eq!(left.opacity_envelope, clip.opacity_envelope);
+        // Halfway up the fade at the cut, fully up a second later
+        assert_eq!(right.opacity_envelope.value_at(0.0), Some(0.5));
+        assert_eq!(right.opacity_envelope.value_at(1.0), Some(1.0));
+    }
+
+    #[test]
+    fn test_cut_clip_keeps_speed_ramp() {
+        let mut clip = retimed_clip(0.0, 4.0, 6.0);
+        clip.start_time = 1.0;
+        clip.time_map = Some(vec![(0.0, 0.0), (2.0, 2.0), (6.0, 4.0)]);
+        let (left, right) = cut_clip_at(&clip, 4.0).unwrap();
+        for time in [1.0, 2.5, 3.0, 3.9] {
+            assert_eq!(left.source_time_at(time), clip.source_time_at(time));
+        }
+        for time in [4.0, 5.5, 7.0] {
+            assert_eq!(right.source_time_at(time), clip.source_time_at(time));
+        }
+    }
 }
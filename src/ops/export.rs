@@ -0,0 +1,519 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock, mpsc};
+
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+
+use crate::ops::video_funcs::{DEFAULT_PIPELINE_TIMEOUT, wait_for_eos};
+use crate::renderer::timeline_renderer::TimelineRenderer;
+use crate::types::media::ColorSpace;
+use crate::types::timeline::{GapFade, Timeline};
+
+/// Settings for rendering the timeline to a video file
+#[derive(Debug, Clone)]
+pub struct ExportSettings {
+    /// Path of the output file
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    /// Output frame rate override; `None` exports at the timeline frame rate
+    pub target_fps: Option<f64>,
+    /// Working color space frames are composited in
+    pub color_space: ColorSpace,
+    /// Threads rendering frames; above 1 each thread has its own renderer and decoders
+    pub threads: usize,
+    /// Dissolve to and from black across short gaps, as in the preview
+    pub gap_fade: Option<GapFade>,
+}
+
+impl ExportSettings {
+    pub fn new(output: &str, width: u32, height: u32) -> Self {
+        Self {
+            output: output.to_string(),
+            width,
+            height,
+            target_fps: None,
+            color_space: ColorSpace::default(),
+            threads: 1,
+            gap_fade: None,
+        }
+    }
+
+    pub fn target_fps(mut self, fps: f64) -> Self {
+        self.target_fps = Some(fps);
+        self
+    }
+
+    pub fn color_space(mut self, space: ColorSpace) -> Self {
+        self.color_space = space;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn gap_fade(mut self, fade: Option<GapFade>) -> Self {
+        self.gap_fade = fade;
+        self
+    }
+
+    /// Frame rate of the exported file for a timeline running at `timeline_fps`
+    pub fn output_fps(&self, timeline_fps: f64) -> f64 {
+        self.target_fps
+            .filter(|fps| *fps > 0.0)
+            .unwrap_or(timeline_fps)
+    }
+
+    /// Whether the export has to resample the timeline temporally
+    pub fn needs_rate_conversion(&self, timeline_fps: f64) -> bool {
+        (self.output_fps(timeline_fps) - timeline_fps).abs() > 1e-6
+    }
+}
+
+/// Timeline timestamps (in seconds) of every output frame when exporting
+/// `duration` seconds at `fps`. A trailing partial frame is included.
+pub fn output_timestamps(duration: f64, fps: f64) -> Vec<f64> {
+    if duration <= 0.0 || fps <= 0.0 {
+        return Vec::new();
+    }
+    let count = (duration * fps - 1e-9).ceil() as usize;
+    (0..count).map(|i| i as f64 / fps).collect()
+}
+
+/// Frame rate as a caps fraction, using the 1001 denominator for NTSC rates
+/// (e.g. 29.97 -> 30000/1001).
+fn fps_fraction(fps: f64) -> (i32, i32) {
+    if (fps - fps.round()).abs() < 1e-6 {
+        return (fps.round() as i32, 1);
+    }
+    let ntsc = fps * 1.001;
+    if (ntsc - ntsc.round()).abs() < 0.01 {
+        ((ntsc.round() * 1000.0) as i32, 1001)
+    } else {
+        ((fps * 1000.0).round() as i32, 1000)
+    }
+}
+
+fn pipeline_description(settings: &ExportSettings, timeline_fps: f64) -> String {
+    let rate = if settings.needs_rate_conversion(timeline_fps) {
+        let (num, den) = fps_fraction(settings.output_fps(timeline_fps));
+        format!("videorate ! video/x-raw,framerate={}/{} ! ", num, den)
+    } else {
+        String::new()
+    };
+    // The filesink is added by `export_range`, so the output path needs no quoting here
+    format!(
+        "appsrc name=src ! videoconvert ! {}x264enc ! mp4mux name=mux",
+        rate
+    )
+}
+
+/// Renders the timeline frame by frame and encodes it to `settings.output`.
+///
+/// When `target_fps` differs from the timeline frame rate the timeline is sampled
+/// at each output timestamp, duplicating or dropping source frames as needed.
+pub fn export_timeline(
+    timeline: Arc<RwLock<Timeline>>,
+    settings: &ExportSettings,
+) -> Result<(), Box<dyn Error>> {
+    let duration = timeline.read().unwrap().duration;
+    export_range(timeline, settings, 0.0, duration)
+}
+
+/// Output file for the `index`th (1-based) exported region: the preset's file
+/// name with a two-digit suffix, e.g. `promo.mp4` -> `out_dir/promo_01.mp4`.
+pub fn region_output_path(out_dir: &Path, preset_output: &str, index: usize) -> PathBuf {
+    let preset = Path::new(preset_output);
+    let stem = preset
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "region".to_string());
+    let extension = preset
+        .extension()
+        .map(|e| e.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "mp4".to_string());
+    out_dir.join(format!("{}_{:02}.{}", stem, index, extension))
+}
+
+/// Export each `(start, end)` region of the timeline to its own file in `out_dir`,
+/// named by `region_output_path`. `preset` supplies everything but the output path.
+/// Returns the written path or the error for each region, in order.
+pub fn export_regions(
+    timeline: Arc<RwLock<Timeline>>,
+    regions: &[(f64, f64)],
+    out_dir: &Path,
+    preset: &ExportSettings,
+) -> Vec<Result<String, Box<dyn Error>>> {
+    regions
+        .iter()
+        .enumerate()
+        .map(|(i, &(start, end))| {
+            let output = region_output_path(out_dir, &preset.output, i + 1)
+                .to_string_lossy()
+                .into_owned();
+            let settings = ExportSettings {
+                output: output.clone(),
+                ..preset.clone()
+            };
+            export_range(timeline.clone(), &settings, start, end).map(|_| output)
+        })
+        .collect()
+}
+
+/// Export the part of the timeline between `start` and `end` seconds.
+pub fn export_range(
+    timeline: Arc<RwLock<Timeline>>,
+    settings: &ExportSettings,
+    start: f64,
+    end: f64,
+) -> Result<(), Box<dyn Error>> {
+    export_range_with_progress(timeline, settings, start, end, &mut |_| {})
+}
+
+/// `export_range`, calling `on_progress` with the fraction of frames rendered
+/// (0.0..=1.0) after each frame.
+pub fn export_range_with_progress(
+    timeline: Arc<RwLock<Timeline>>,
+    settings: &ExportSettings,
+    start: f64,
+    end: f64,
+    on_progress: &mut dyn FnMut(f32),
+) -> Result<(), Box<dyn Error>> {
+    if end <= start {
+        return Err(format!("Empty export range {:.3}..{:.3}", start, end).into());
+    }
+    gst::init()?;
+
+    let timeline_fps = timeline.read().unwrap().frame_rate;
+    let fps = settings.output_fps(timeline_fps);
+    let (num, den) = fps_fraction(fps);
+
+    let pipeline = gst::parse::launch(&pipeline_description(settings, timeline_fps))?;
+    let pipeline = pipeline
+        .downcast::<gst::Pipeline>()
+        .expect("Expected a gst::Pipeline");
+    let sink = gst::ElementFactory::make("filesink")
+        .property("location", settings.output.as_str())
+        .build()?;
+    pipeline.add(&sink)?;
+    pipeline
+        .by_name("mux")
+        .ok_or("Export pipeline has no muxer")?
+        .link(&sink)?;
+    let appsrc = pipeline
+        .by_name("src")
+        .ok_or("Export pipeline has no appsrc")?
+        .downcast::<gst_app::AppSrc>()
+        .map_err(|_| "Export source is not an AppSrc")?;
+
+    let caps = gst::Caps::builder("video/x-raw")
+        .field("format", "RGBA")
+        .field("width", settings.width as i32)
+        .field("height", settings.height as i32)
+        .field("framerate", gst::Fraction::new(num, den))
+        .build();
+    appsrc.set_caps(Some(&caps));
+    appsrc.set_format(gst::Format::Time);
+
+    pipeline.set_state(gst::State::Playing)?;
+
+    let frame_duration = gst::ClockTime::from_nseconds((1_000_000_000.0 / fps) as u64);
+    let timestamps = output_timestamps(end - start, fps);
+    let frame_count = timestamps.len();
+    let mut push_frame = |i: usize, data: Vec<u8>| -> Result<(), Box<dyn Error>> {
+        let offset = timestamps[i];
+        let mut buffer = gst::Buffer::from_mut_slice(data);
+        {
+            let buffer = buffer.get_mut().unwrap();
+            buffer.set_pts(gst::ClockTime::from_nseconds(
+                (offset * 1_000_000_000.0) as u64,
+            ));
+            buffer.set_duration(frame_duration);
+        }
+        appsrc
+            .push_buffer(buffer)
+            .map_err(|e| format!("Failed to push frame at {:.3}s: {:?}", start + offset, e))?;
+        on_progress((i + 1) as f32 / frame_count as f32);
+        Ok(())
+    };
+
+    let threads = settings.threads.min(frame_count).max(1);
+    let streamed = if threads == 1 {
+        let mut renderer = export_renderer(timeline, settings, timeline_fps);
+        let frames = timestamps.iter().map(|offset| start + offset).enumerate();
+        render_frames(&mut renderer, frames, timeline_fps, &mut push_frame)
+    } else {
+        // Worker k renders frames k, k + threads, ... with its own renderer
+        let work = |worker: usize, emit: &mut FrameSink| {
+            let mut renderer = export_renderer(timeline.clone(), settings, timeline_fps);
+            let frames = timestamps
+                .iter()
+                .map(|offset| start + offset)
+                .enumerate()
+                .skip(worker)
+                .step_by(threads);
+            // An error only means the encoder side has given up
+            let _ = render_frames(&mut renderer, frames, timeline_fps, emit);
+        };
+        render_in_order(threads, frame_count, work, &mut push_frame)
+    };
+
+    // The pipeline only goes down once EOS has flushed the encoder and the muxer has
+    // finished the file, or once the export has failed
+    let result = streamed.and_then(|()| finish_stream(&pipeline, &appsrc));
+    pipeline.set_state(gst::State::Null)?;
+    result
+}
+
+/// End the stream from `appsrc` and wait until EOS has come out of `pipeline`, so
+/// frames still held by the encoder and muxer reach the file. A muxer that never
+/// finishes fails the export after `DEFAULT_PIPELINE_TIMEOUT` rather than holding up
+/// the render queue.
+fn finish_stream(pipeline: &gst::Pipeline, appsrc: &gst_app::AppSrc) -> Result<(), Box<dyn Error>> {
+    appsrc
+        .end_of_stream()
+        .map_err(|e| format!("Failed to end stream: {:?}", e))?;
+    wait_for_eos(pipeline, DEFAULT_PIPELINE_TIMEOUT)
+}
+
+/// Renderer set up to produce export frames for `settings`. It renders at the timeline
+/// rate so its frame numbers match source frames.
+fn export_renderer(
+    timeline: Arc<RwLock<Timeline>>,
+    settings: &ExportSettings,
+    timeline_fps: f64,
+) -> TimelineRenderer {
+    let mut renderer =
+        TimelineRenderer::new(timeline, settings.width, settings.height, timeline_fps);
+    renderer.exporting = true;
+    renderer.set_color_space(settings.color_space);
+    renderer.set_gap_fade(settings.gap_fade);
+    renderer
+}
+
+/// Receives rendered frames by index; an error tells the renderer to stop
+type FrameSink<'a> = dyn FnMut(usize, Vec<u8>) -> Result<(), Box<dyn Error>> + 'a;
+
+/// Run `work(k, sink)` on `threads` threads and hand the frames to `emit` in index
+/// order. Worker k must send frames k, k + threads, ... in turn; each has a channel
+/// of one frame, so at most two frames per worker wait for the encoder. When `emit`
+/// fails the channels are closed, so workers stop instead of blocking on a full one.
+fn render_in_order(
+    threads: usize,
+    frame_count: usize,
+    work: impl Fn(usize, &mut FrameSink) + Sync,
+    emit: &mut FrameSink,
+) -> Result<(), Box<dyn Error>> {
+    std::thread::scope(|scope| {
+        let mut receivers = Vec::with_capacity(threads);
+        for worker in 0..threads {
+            let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(1);
+            receivers.push(receiver);
+            let work = &work;
+            scope.spawn(move || {
+                work(worker, &mut |_, data| {
+                    sender.send(data).map_err(|_| "Export was stopped".into())
+                })
+            });
+        }
+        // Returning early drops `receivers`, before the scope waits for the workers
+        for i in 0..frame_count {
+            let data = receivers[i % threads]
+                .recv()
+                .map_err(|_| "Export workers stopped before rendering every frame")?;
+            emit(i, data)?;
+        }
+        Ok(())
+    })
+}
+
+/// Render each `(index, time)` frame in turn and pass its RGBA data to `emit`
+fn render_frames(
+    renderer: &mut TimelineRenderer,
+    frames: impl Iterator<Item = (usize, f64)>,
+    timeline_fps: f64,
+    emit: &mut FrameSink,
+) -> Result<(), Box<dyn Error>> {
+    let mut last_source_frame = None;
+    for (i, time) in frames {
+        // Only keep the current source frame cached (reused when duplicating)
+        let source_frame = (time * timeline_fps) as u64;
+        if last_source_frame != Some(source_frame) {
+            renderer.clear_cache();
+            last_source_frame = Some(source_frame);
+        }
+        emit(i, renderer.render_frame(time).data)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_timestamps_60fps_from_30fps_timeline() {
+        let settings = ExportSettings::new("out.mp4", 1920, 1080).target_fps(60.0);
+        let fps = settings.output_fps(30.0);
+        let timestamps = output_timestamps(2.0, fps);
+
+        assert_eq!(timestamps.len(), 120);
+        assert_eq!(timestamps[0], 0.0);
+        assert!((timestamps[1] - 1.0 / 60.0).abs() < 1e-9);
+        assert!((timestamps[119] - 119.0 / 60.0).abs() < 1e-9);
+        // Every source frame is sampled twice
+        let source_frames: Vec<u64> = timestamps.iter().map(|t| (t * 30.0) as u64).collect();
+        assert_eq!(&source_frames[..4], &[0, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_output_timestamps_edge_cases() {
+        assert_eq!(output_timestamps(2.0, 30.0).len(), 60);
+        assert_eq!(output_timestamps(2.0, 25.0).len(), 50);
+        // Trailing partial frame is kept
+        assert_eq!(output_timestamps(2.01, 30.0).len(), 61);
+        assert!(output_timestamps(0.0, 30.0).is_empty());
+        assert!(output_timestamps(2.0, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_videorate_only_when_rate_differs() {
+        let same = ExportSettings::new("out.mp4", 1280, 720);
+        assert!(!pipeline_description(&same, 30.0).contains("videorate"));
+        let same = same.target_fps(30.0);
+        assert!(!pipeline_description(&same, 30.0).contains("videorate"));
+
+        let converted = ExportSettings::new("out.mp4", 1280, 720).target_fps(25.0);
+        assert!(
+            pipeline_description(&converted, 30.0)
+                .contains("videorate ! video/x-raw,framerate=25/1")
+        );
+    }
+
+    #[test]
+    fn test_output_path_stays_out_of_the_description() {
+        let settings = ExportSettings::new("/tmp/say \"cheese\" ! fakesink.mp4", 1280, 720);
+        assert!(!pipeline_description(&settings, 30.0).contains("cheese"));
+    }
+
+    #[test]
+    fn test_region_output_path() {
+        let dir = Path::new("/exports");
+        assert_eq!(
+            region_output_path(dir, "/tmp/promo.mp4", 1),
+            PathBuf::from("/exports/promo_01.mp4")
+        );
+        assert_eq!(
+            region_output_path(dir, "clip.mov", 12),
+            PathBuf::from("/exports/clip_12.mov")
+        );
+        assert_eq!(
+            region_output_path(dir, "", 3),
+            PathBuf::from("/exports/region_03.mp4")
+        );
+    }
+
+    #[test]
+    fn test_export_regions_writes_one_file_per_region() {
+        // Needs a working encoder
+        if !crate::ops::plugins::check_plugins().all_available() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let mut timeline = Timeline::new();
+        timeline.duration = 2.0;
+        let timeline = Arc::new(RwLock::new(timeline));
+        let preset = ExportSettings::new("social.mp4", 64, 64);
+
+        let results = export_regions(timeline, &[(0.0, 0.2), (1.0, 1.3)], dir.path(), &preset);
+
+        assert_eq!(results.len(), 2);
+        for (i, result) in results.into_iter().enumerate() {
+            let path = result.unwrap();
+            assert_eq!(
+                PathBuf::from(&path),
+                dir.path().join(format!("social_0{}.mp4", i + 1))
+            );
+            assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        }
+    }
+
+    #[test]
+    fn test_exported_file_covers_the_whole_range() {
+        // Needs a working encoder
+        if !crate::ops::plugins::check_plugins().all_available() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("range.mp4").to_string_lossy().into_owned();
+        let mut timeline = Timeline::new();
+        timeline.duration = 3.0;
+        let fps = timeline.frame_rate;
+        let timeline = Arc::new(RwLock::new(timeline));
+        let settings = ExportSettings::new(&output, 64, 64);
+
+        export_range(timeline, &settings, 0.5, 2.0).unwrap();
+
+        // Frames still in the encoder when the last one was pushed made it to the file
+        let duration = crate::ui::timeline_widget::get_media_duration(&output).unwrap();
+        assert!(
+            (duration - 1.5).abs() <= 1.0 / fps,
+            "exported {:.3}s of a 1.5s range",
+            duration
+        );
+    }
+
+    #[test]
+    fn test_fps_fraction() {
+        assert_eq!(fps_fraction(30.0), (30, 1));
+        assert_eq!(fps_fraction(29.97), (30000, 1001));
+        assert_eq!(fps_fraction(23.976), (24000, 1001));
+        assert_eq!(fps_fraction(12.5), (12500, 1000));
+    }
+
+    #[test]
+    fn test_render_in_order_emits_frames_by_index() {
+        // Workers finish at different speeds; frames still come out in order
+        let work = |worker: usize, emit: &mut FrameSink| {
+            for i in (worker..10).step_by(3) {
+                std::thread::sleep(std::time::Duration::from_millis((3 - worker as u64) * 2));
+                if emit(i, vec![i as u8]).is_err() {
+                    return;
+                }
+            }
+        };
+        let mut emitted = Vec::new();
+        render_in_order(3, 10, work, &mut |i, data| {
+            emitted.push((i, data[0]));
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(emitted, (0..10).map(|i| (i, i as u8)).collect::<Vec<_>>());
+
+        // An encoder failure stops the workers instead of leaving them blocked
+        let mut emitted = 0;
+        let result = render_in_order(3, 100, work_all, &mut |_, _| {
+            emitted += 1;
+            if emitted == 2 {
+                return Err("encoder failed".into());
+            }
+            Ok(())
+        });
+        assert_eq!(result.unwrap_err().to_string(), "encoder failed");
+
+        // Workers that give up early end the export
+        let result = render_in_order(2, 4, |_, _: &mut FrameSink| {}, &mut |_, _| Ok(()));
+        assert!(result.is_err());
+    }
+
+    /// Worker that sends all of its frames of a 100 frame export, ignoring errors
+    fn work_all(worker: usize, emit: &mut FrameSink) {
+        for i in (worker..100).step_by(3) {
+            let _ = emit(i, vec![0; 16]);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use gst::prelude::*;
+use gstreamer as gst;
+
+/// Why `drain_bus_until` stopped before its condition was met
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GstBusError {
+    /// An element posted an error
+    Element {
+        /// Name of the element that failed, if the message said
+        element: Option<String>,
+        message: String,
+        /// GStreamer's debug details, usually naming the source file and cause
+        debug: Option<String>,
+    },
+    /// Nothing matched within the time limit
+    Timeout { limit: Duration },
+}
+
+impl fmt::Display for GstBusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GstBusError::Element {
+                element,
+                message,
+                debug,
+            } => {
+                write!(
+                    f,
+                    "{}: {}",
+                    element.as_deref().unwrap_or("pipeline"),
+                    message
+                )?;
+                if let Some(debug) = debug {
+                    write!(f, " ({})", debug)?;
+                }
+                Ok(())
+            }
+            GstBusError::Timeout { limit } => {
+                write!(f, "no reply within {:.1}s", limit.as_secs_f64())
+            }
+        }
+    }
+}
+
+impl Error for GstBusError {}
+
+/// Pop messages off `bus` until `done` accepts one, for at most `timeout` of wall-clock
+/// time (`None` waits for as long as it takes). Warnings are logged along the way; the
+/// first error ends the wait with the element that posted it.
+pub fn drain_bus_until(
+    bus: &gst::Bus,
+    mut done: impl FnMut(&gst::Message) -> bool,
+    timeout: Option<Duration>,
+) -> Result<(), GstBusError> {
+    let deadline = timeout.map(|limit| Instant::now() + limit);
+    loop {
+        let wait = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(GstBusError::Timeout {
+                        limit: timeout.unwrap_or_default(),
+                    });
+                }
+                Some(gst::ClockTime::from_nseconds(remaining.as_nanos() as u64))
+            }
+            None => gst::ClockTime::NONE,
+        };
+        let Some(msg) = bus.timed_pop(wait) else {
+            continue;
+        };
+        let element = || msg.src().map(|src| src.name().to_string());
+        match msg.view() {
+            gst::MessageView::Error(err) => {
+                return Err(GstBusError::Element {
+                    element: element(),
+                    message: err.error().to_string(),
+                    debug: err.debug().map(|debug| debug.to_string()),
+                });
+            }
+            gst::MessageView::Warning(warn) => {
+                log::warn!(
+                    "{}: {}",
+                    element().as_deref().unwrap_or("pipeline"),
+                    warn.error()
+                );
+            }
+            _ => {}
+        }
+        if done(&msg) {
+            return Ok(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bus_error_names_its_element() {
+        if gst::init().is_err() {
+            return;
+        }
+        let bus = gst::Bus::new();
+        let decoder = gst::Bin::with_name("decoder");
+        bus.post(
+            gst::message::Error::builder(gst::CoreError::Failed, "could not decode")
+                .src(&decoder)
+                .debug("bad header")
+                .build(),
+        )
+        .unwrap();
+
+        let err = drain_bus_until(&bus, |_| false, Some(Duration::from_secs(1))).unwrap_err();
+        assert_eq!(
+            err,
+            GstBusError::Element {
+                element: Some("decoder".to_string()),
+                message: "could not decode".to_string(),
+                debug: Some("bad header".to_string()),
+            }
+        );
+        assert_eq!(err.to_string(), "decoder: could not decode (bad header)");
+
+        // An empty bus runs out the clock
+        let limit = Duration::from_millis(50);
+        assert_eq!(
+            drain_bus_until(&bus, |_| false, Some(limit)),
+            Err(GstBusError::Timeout { limit })
+        );
+    }
+}
@@ -1,3 +1,10 @@
 pub mod clip_ops;
+pub mod export;
+pub mod gst_util;
+pub mod plugins;
+pub mod render_queue;
+pub mod srt;
 pub mod utils;
 pub mod video_funcs;
+
+pub use plugins::check_plugins;
@@ -0,0 +1,122 @@
+use gstreamer as gst;
+
+/// GStreamer elements the crate uses: (element, plugin package, what it is used for)
+pub const REQUIRED_ELEMENTS: &[(&str, &str, &str)] = &[
+    ("filesrc", "gstreamer (core)", "reading media files"),
+    ("filesink", "gstreamer (core)", "writing exports"),
+    ("multifilesink", "gst-plugins-good", "writing thumbnails"),
+    ("fakesink", "gstreamer (core)", "probing media"),
+    ("queue", "gstreamer (core)", "mixing and muxing"),
+    ("capsfilter", "gstreamer (core)", "audio limiting"),
+    ("concat", "gstreamer (core)", "concatenating videos"),
+    ("decodebin", "gst-plugins-base", "decoding media"),
+    ("appsrc", "gst-plugins-base", "timeline export"),
+    ("appsink", "gst-plugins-base", "frame extraction"),
+    ("videoconvert", "gst-plugins-base", "video color conversion"),
+    ("videoscale", "gst-plugins-base", "video scaling"),
+    ("videorate", "gst-plugins-base", "export frame rates"),
+    ("audioconvert", "gst-plugins-base", "audio conversion"),
+    ("audioresample", "gst-plugins-base", "audio resampling"),
+    ("audiomixer", "gst-plugins-base", "audio mixing"),
+    ("volume", "gst-plugins-base", "master volume"),
+    ("x264enc", "gst-plugins-ugly", "H.264 video encoding"),
+    ("voaacenc", "gst-plugins-bad", "AAC audio encoding"),
+    ("mp4mux", "gst-plugins-good", "MP4 export"),
+    ("wavenc", "gst-plugins-good", "WAV audio export"),
+    ("jpegenc", "gst-plugins-good", "thumbnail encoding"),
+];
+
+/// Availability of a single required element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginStatus {
+    pub element: &'static str,
+    /// Package that usually provides the element
+    pub package: &'static str,
+    pub purpose: &'static str,
+    pub available: bool,
+}
+
+/// Result of `check_plugins`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginReport {
+    pub entries: Vec<PluginStatus>,
+}
+
+impl PluginReport {
+    pub fn missing(&self) -> impl Iterator<Item = &PluginStatus> {
+        self.entries.iter().filter(|e| !e.available)
+    }
+
+    pub fn all_available(&self) -> bool {
+        self.entries.iter().all(|e| e.available)
+    }
+
+    /// One user-facing message per missing element, naming the package to install
+    pub fn messages(&self) -> Vec<String> {
+        self.missing()
+            .map(|e| {
+                format!(
+                    "GStreamer element '{}' is missing ({} will not work). Install {}.",
+                    e.element, e.purpose, e.package
+                )
+            })
+            .collect()
+    }
+}
+
+/// Probe the GStreamer registry for every element the crate depends on.
+/// If GStreamer itself cannot be initialized, every element is reported missing.
+pub fn check_plugins() -> PluginReport {
+    let initialized = gst::init().is_ok();
+    let entries = REQUIRED_ELEMENTS
+        .iter()
+        .map(|&(element, package, purpose)| PluginStatus {
+            element,
+            package,
+            purpose,
+            available: initialized && gst::ElementFactory::find(element).is_some(),
+        })
+        .collect();
+    PluginReport { entries }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_has_entry_per_required_element() {
+        let report = check_plugins();
+        let names: Vec<&str> = report.entries.iter().map(|e| e.element).collect();
+        for (element, _, _) in REQUIRED_ELEMENTS {
+            assert!(names.contains(element), "no entry for {}", element);
+        }
+        assert_eq!(report.entries.len(), REQUIRED_ELEMENTS.len());
+        assert_eq!(report.messages().len(), report.missing().count());
+    }
+
+    #[test]
+    fn test_messages_name_missing_elements() {
+        let report = PluginReport {
+            entries: vec![
+                PluginStatus {
+                    element: "x264enc",
+                    package: "gst-plugins-ugly",
+                    purpose: "H.264 video encoding",
+                    available: false,
+                },
+                PluginStatus {
+                    element: "filesrc",
+                    package: "gstreamer (core)",
+                    purpose: "reading media files",
+                    available: true,
+                },
+            ],
+        };
+        assert!(!report.all_available());
+        let messages = report.messages();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].contains("x264enc"));
+        assert!(messages[0].contains("gst-plugins-ugly"));
+    }
+}
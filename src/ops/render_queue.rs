@@ -0,0 +1,314 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, RwLock};
+use std::thread;
+
+use crate::ops::export::{ExportSettings, export_range_with_progress};
+use crate::types::timeline::Timeline;
+
+/// Where a queued export is in its lifecycle
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobStatus {
+    Queued,
+    /// Fraction of frames rendered so far
+    Running(f32),
+    Done,
+    Failed(String),
+}
+
+impl JobStatus {
+    /// Finished, successfully or not
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Done | JobStatus::Failed(_))
+    }
+}
+
+/// One export in the queue: a snapshot of the timeline and what to render from it
+#[derive(Debug, Clone)]
+pub struct RenderJob {
+    pub id: u64,
+    pub settings: ExportSettings,
+    pub start: f64,
+    pub end: f64,
+    pub status: JobStatus,
+    /// Timeline as it was when the job was queued; later edits don't affect it
+    pub timeline: Arc<RwLock<Timeline>>,
+}
+
+/// Message from the worker thread about the job it is running
+enum JobUpdate {
+    Progress(u64, f32),
+    Finished(u64, Result<(), String>),
+}
+
+/// Export jobs run one at a time, in queue order, on a background thread.
+///
+/// Jobs wait as `Queued` until `poll` hands the first one to the worker, so queued
+/// jobs can still be reordered or removed. Call `poll` regularly (e.g. every UI frame)
+/// to pick up progress and start the next job.
+pub struct RenderQueue {
+    jobs: Vec<RenderJob>,
+    next_id: u64,
+    job_tx: Sender<RenderJob>,
+    update_rx: Receiver<JobUpdate>,
+}
+
+impl RenderQueue {
+    /// Create a queue that renders jobs with `export_range`.
+    pub fn new() -> Self {
+        Self::with_worker(|job, on_progress| {
+            export_range_with_progress(
+                job.timeline.clone(),
+                &job.settings,
+                job.start,
+                job.end,
+                on_progress,
+            )
+            .map_err(|e| e.to_string())
+        })
+    }
+
+    /// Create a queue with a custom render function (runs on the worker thread).
+    pub fn with_worker<F>(worker: F) -> Self
+    where
+        F: Fn(&RenderJob, &mut dyn FnMut(f32)) -> Result<(), String> + Send + 'static,
+    {
+        let (job_tx, job_rx) = mpsc::channel::<RenderJob>();
+        let (update_tx, update_rx) = mpsc::channel();
+        thread::spawn(move || {
+            for job in job_rx {
+                let progress_tx = update_tx.clone();
+                let result = worker(&job, &mut |fraction| {
+                    let _ = progress_tx.send(JobUpdate::Progress(job.id, fraction));
+                });
+                if update_tx.send(JobUpdate::Finished(job.id, result)).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            jobs: Vec::new(),
+            next_id: 1,
+            job_tx,
+            update_rx,
+        }
+    }
+
+    /// Queue an export of `start..end` seconds of a snapshot of `timeline`.
+    /// Returns the new job's id.
+    pub fn add(
+        &mut self,
+        timeline: &Timeline,
+        settings: ExportSettings,
+        start: f64,
+        end: f64,
+    ) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(RenderJob {
+            id,
+            settings,
+            start,
+            end,
+            status: JobStatus::Queued,
+            timeline: Arc::new(RwLock::new(timeline.clone())),
+        });
+        id
+    }
+
+    /// Remove a job that isn't running. Returns false for the running job or an unknown id.
+    pub fn remove(&mut self, id: u64) -> bool {
+        match self.jobs.iter().position(|j| j.id == id) {
+            Some(i) if !matches!(self.jobs[i].status, JobStatus::Running(_)) => {
+                self.jobs.remove(i);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Move the job at index `from` to index `to`, shifting the jobs in between.
+    pub fn move_job(&mut self, from: usize, to: usize) {
+        if from < self.jobs.len() && to < self.jobs.len() {
+            let job = self.jobs.remove(from);
+            self.jobs.insert(to, job);
+        }
+    }
+
+    /// Drop every finished job from the list
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|j| !j.status.is_finished());
+    }
+
+    pub fn jobs(&self) -> &[RenderJob] {
+        &self.jobs
+    }
+
+    /// Whether a job is running or waiting to run
+    pub fn is_busy(&self) -> bool {
+        self.jobs.iter().any(|j| !j.status.is_finished())
+    }
+
+    /// (finished jobs, all jobs)
+    pub fn overall_progress(&self) -> (usize, usize) {
+        let finished = self.jobs.iter().filter(|j| j.status.is_finished()).count();
+        (finished, self.jobs.len())
+    }
+
+    /// Apply progress reported by the worker and start the next queued job once
+    /// nothing is running. Returns true if any job changed.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok(update) = self.update_rx.try_recv() {
+            let (id, status) = match update {
+                JobUpdate::Progress(id, fraction) => (id, JobStatus::Running(fraction)),
+                JobUpdate::Finished(id, Ok(())) => (id, JobStatus::Done),
+                JobUpdate::Finished(id, Err(e)) => (id, JobStatus::Failed(e)),
+            };
+            if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                job.status = status;
+                changed = true;
+            }
+        }
+
+        let running = self
+            .jobs
+            .iter()
+            .any(|j| matches!(j.status, JobStatus::Running(_)));
+        if !running {
+            if let Some(job) = self.jobs.iter_mut().find(|j| j.status == JobStatus::Queued) {
+                job.status = JobStatus::Running(0.0);
+                if self.job_tx.send(job.clone()).is_err() {
+                    job.status = JobStatus::Failed("Render worker stopped".to_string());
+                }
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+impl Default for RenderQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    fn poll_until(queue: &mut RenderQueue, done: impl Fn(&RenderQueue) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !done(queue) && Instant::now() < deadline {
+            queue.poll();
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        assert!(done(queue), "timed out waiting for the render queue");
+    }
+
+    fn settings(output: &str) -> ExportSettings {
+        ExportSettings::new(output, 64, 64)
+    }
+
+    /// Worker that blocks each job until the test sends on the returned gate
+    fn gated_queue() -> (RenderQueue, Sender<Result<(), String>>) {
+        let (gate_tx, gate_rx) = mpsc::channel::<Result<(), String>>();
+        let gate_rx = Mutex::new(gate_rx);
+        let queue = RenderQueue::with_worker(move |_job, on_progress| {
+            on_progress(0.5);
+            gate_rx.lock().unwrap().recv().unwrap()
+        });
+        (queue, gate_tx)
+    }
+
+    #[test]
+    fn test_jobs_run_in_queue_order() {
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let worker_order = order.clone();
+        let mut queue = RenderQueue::with_worker(move |job, _| {
+            worker_order
+                .lock()
+                .unwrap()
+                .push(job.settings.output.clone());
+            Ok(())
+        });
+        let timeline = Timeline::new();
+        queue.add(&timeline, settings("a.mp4"), 0.0, 1.0);
+        queue.add(&timeline, settings("b.mp4"), 0.0, 1.0);
+        queue.add(&timeline, settings("c.mp4"), 0.0, 1.0);
+        // Move the last job to the front before anything starts
+        queue.move_job(2, 0);
+        assert!(queue.is_busy());
+
+        poll_until(&mut queue, |q| !q.is_busy());
+
+        assert_eq!(*order.lock().unwrap(), vec!["c.mp4", "a.mp4", "b.mp4"]);
+        assert_eq!(queue.overall_progress(), (3, 3));
+        assert!(queue.jobs().iter().all(|j| j.status == JobStatus::Done));
+    }
+
+    #[test]
+    fn test_remove_queued_but_not_running_job() {
+        let (mut queue, gate) = gated_queue();
+        let timeline = Timeline::new();
+        let first = queue.add(&timeline, settings("a.mp4"), 0.0, 1.0);
+        let second = queue.add(&timeline, settings("b.mp4"), 0.0, 1.0);
+        queue.poll();
+        assert_eq!(queue.jobs()[0].status, JobStatus::Running(0.0));
+
+        assert!(!queue.remove(first));
+        assert!(queue.remove(second));
+        assert!(!queue.remove(second));
+        assert_eq!(queue.jobs().len(), 1);
+
+        gate.send(Ok(())).unwrap();
+        poll_until(&mut queue, |q| !q.is_busy());
+        assert_eq!(queue.overall_progress(), (1, 1));
+
+        // Finished jobs can be removed
+        assert!(queue.remove(first));
+        assert!(queue.jobs().is_empty());
+    }
+
+    #[test]
+    fn test_status_transitions() {
+        let (mut queue, gate) = gated_queue();
+        let timeline = Timeline::new();
+        let ok = queue.add(&timeline, settings("ok.mp4"), 0.0, 1.0);
+        let failing = queue.add(&timeline, settings("fail.mp4"), 0.0, 1.0);
+        let status = |queue: &RenderQueue, id: u64| {
+            queue
+                .jobs()
+                .iter()
+                .find(|j| j.id == id)
+                .unwrap()
+                .status
+                .clone()
+        };
+        assert_eq!(status(&queue, ok), JobStatus::Queued);
+
+        queue.poll();
+        assert_eq!(status(&queue, ok), JobStatus::Running(0.0));
+        poll_until(&mut queue, |q| status(q, ok) == JobStatus::Running(0.5));
+        // Only one job runs at a time
+        assert_eq!(status(&queue, failing), JobStatus::Queued);
+
+        gate.send(Ok(())).unwrap();
+        poll_until(&mut queue, |q| status(q, ok) == JobStatus::Done);
+        poll_until(&mut queue, |q| {
+            status(q, failing) == JobStatus::Running(0.5)
+        });
+
+        gate.send(Err("encoder missing".to_string())).unwrap();
+        poll_until(&mut queue, |q| !q.is_busy());
+        assert_eq!(
+            status(&queue, failing),
+            JobStatus::Failed("encoder missing".to_string())
+        );
+
+        queue.clear_finished();
+        assert!(queue.jobs().is_empty());
+    }
+}
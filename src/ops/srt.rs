@@ -0,0 +1,192 @@
+use crate::types::subtitle::Cue;
+
+/// Why an SRT file could not be parsed. Line numbers are 1-based.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SrtError {
+    /// A cue has no `start --> end` line
+    MissingTiming { line: usize },
+    /// A timestamp isn't `HH:MM:SS,mmm`
+    BadTimestamp { line: usize, text: String },
+    /// A cue ends before it starts
+    EndBeforeStart { line: usize },
+}
+
+impl std::fmt::Display for SrtError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SrtError::MissingTiming { line } => {
+                write!(f, "line {}: expected a 'start --> end' timing line", line)
+            }
+            SrtError::BadTimestamp { line, text } => {
+                write!(f, "line {}: invalid timestamp '{}'", line, text)
+            }
+            SrtError::EndBeforeStart { line } => {
+                write!(f, "line {}: cue ends before it starts", line)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SrtError {}
+
+/// Parse `HH:MM:SS,mmm` (a `.` before the milliseconds is accepted too) into seconds
+fn parse_timestamp(text: &str) -> Option<f64> {
+    let (hms, millis) = text.split_once([',', '.'])?;
+    let parts: Vec<&str> = hms.split(':').collect();
+    let [hours, minutes, seconds] = parts.as_slice() else {
+        return None;
+    };
+    let number = |p: &str| -> Option<u64> {
+        (!p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+            .then(|| p.parse().ok())
+            .flatten()
+    };
+    let (hours, minutes, seconds) = (number(hours)?, number(minutes)?, number(seconds)?);
+    if minutes >= 60 || seconds >= 60 || millis.len() != 3 {
+        return None;
+    }
+    let millis = number(millis)?;
+    Some((hours * 3600 + minutes * 60 + seconds) as f64 + millis as f64 / 1000.0)
+}
+
+/// Parse SubRip subtitles into cues, in file order.
+///
+/// Cues are separated by blank lines; each has an optional index line, a
+/// `00:00:01,000 --> 00:00:02,500` timing line (anything after the end time, such
+/// as position hints, is ignored) and one or more lines of text.
+pub fn parse_srt(input: &str) -> Result<Vec<Cue>, SrtError> {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+    let mut cues = Vec::new();
+    let mut lines = input
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .enumerate()
+        .peekable();
+
+    loop {
+        // Skip blank lines between cues
+        while lines.next_if(|(_, l)| l.trim().is_empty()).is_some() {}
+        let Some((i, first)) = lines.next() else {
+            break;
+        };
+
+        let (line, timing) = if first.contains("-->") {
+            (i + 1, first)
+        } else {
+            match lines.next() {
+                Some((j, timing)) if timing.contains("-->") => (j + 1, timing),
+                Some((j, _)) => return Err(SrtError::MissingTiming { line: j + 1 }),
+                None => return Err(SrtError::MissingTiming { line: i + 2 }),
+            }
+        };
+        let (start, rest) = timing.split_once("-->").unwrap_or_default();
+        let end = rest.split_whitespace().next().unwrap_or("");
+        let timestamp = |text: &str| {
+            parse_timestamp(text.trim()).ok_or_else(|| SrtError::BadTimestamp {
+                line,
+                text: text.trim().to_string(),
+            })
+        };
+        let (start, end) = (timestamp(start)?, timestamp(end)?);
+        if end < start {
+            return Err(SrtError::EndBeforeStart { line });
+        }
+
+        let mut text = Vec::new();
+        while let Some((_, l)) = lines.next_if(|(_, l)| !l.trim().is_empty()) {
+            text.push(l);
+        }
+        cues.push(Cue {
+            start,
+            end,
+            text: text.join("\n"),
+        });
+    }
+    Ok(cues)
+}
+
+/// Read and parse an SRT file
+pub fn load_srt(path: &std::path::Path) -> Result<Vec<Cue>, Box<dyn std::error::Error>> {
+    Ok(parse_srt(&std::fs::read_to_string(path)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_multiple_cues() {
+        let srt = "\u{feff}1\r\n\
+                   00:00:01,000 --> 00:00:03,500\r\n\
+                   Hello there.\r\n\
+                   \r\n\
+                   2\r\n\
+                   00:00:04,250 --> 00:00:06,000 X1:40 X2:600\r\n\
+                   Two lines\r\n\
+                   of text\r\n\
+                   \r\n\
+                   \r\n\
+                   3\r\n\
+                   01:02:03.004 --> 01:02:05,000\r\n\
+                   Late cue\r\n";
+        let cues = parse_srt(srt).unwrap();
+        assert_eq!(
+            cues,
+            vec![
+                Cue {
+                    start: 1.0,
+                    end: 3.5,
+                    text: "Hello there.".to_string()
+                },
+                Cue {
+                    start: 4.25,
+                    end: 6.0,
+                    text: "Two lines\nof text".to_string()
+                },
+                Cue {
+                    start: 3723.004,
+                    end: 3725.0,
+                    text: "Late cue".to_string()
+                },
+            ]
+        );
+        assert!(parse_srt("").unwrap().is_empty());
+        assert!(parse_srt("\n\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_timestamps() {
+        let bad = |timing: &str| parse_srt(&format!("1\n{}\nText\n", timing)).unwrap_err();
+        assert_eq!(
+            bad("00:00:01 --> 00:00:02,000"),
+            SrtError::BadTimestamp {
+                line: 2,
+                text: "00:00:01".to_string()
+            }
+        );
+        assert_eq!(
+            bad("00:00:01,000 --> 00:61:00,000"),
+            SrtError::BadTimestamp {
+                line: 2,
+                text: "00:61:00,000".to_string()
+            }
+        );
+        assert!(matches!(
+            bad("00:00:01,5 --> 00:00:02,000"),
+            SrtError::BadTimestamp { .. }
+        ));
+        assert!(matches!(
+            bad("aa:00:01,000 --> 00:00:02,000"),
+            SrtError::BadTimestamp { .. }
+        ));
+        assert_eq!(
+            bad("00:00:05,000 --> 00:00:02,000"),
+            SrtError::EndBeforeStart { line: 2 }
+        );
+        // The second cue has text where its timing should be
+        assert_eq!(
+            parse_srt("1\n00:00:01,000 --> 00:00:02,000\nOk\n\n2\nNo timing\n").unwrap_err(),
+            SrtError::MissingTiming { line: 6 }
+        );
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::Path;
+use std::process::Command;
+
+/// Host platform families with different file manager conventions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Windows,
+    MacOs,
+    Linux,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(target_os = "windows") {
+            Platform::Windows
+        } else if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else {
+            Platform::Linux
+        }
+    }
+}
+
+/// Build the program and arguments that reveal `path` in the platform's file manager.
+///
+/// Existing files are selected in their folder where the platform supports it
+/// (`explorer.exe /select,` and `open -R`). Missing files fall back to opening the
+/// nearest existing parent directory, as does Linux (`xdg-open` cannot select).
+pub fn reveal_command(path: &Path, platform: Platform) -> (String, Vec<String>) {
+    let select = path.is_file() && platform != Platform::Linux;
+    let target = if select || path.is_dir() {
+        path.to_path_buf()
+    } else {
+        path.ancestors()
+            .skip(1)
+            .find(|p| p.is_dir())
+            .unwrap_or(Path::new("."))
+            .to_path_buf()
+    };
+    let target = target.to_string_lossy().to_string();
+
+    match (platform, select) {
+        (Platform::Windows, true) => (
+            "explorer.exe".to_string(),
+            vec![format!("/select,{}", target)],
+        ),
+        (Platform::Windows, false) => ("explorer.exe".to_string(), vec![target]),
+        (Platform::MacOs, true) => ("open".to_string(), vec!["-R".to_string(), target]),
+        (Platform::MacOs, false) => ("open".to_string(), vec![target]),
+        (Platform::Linux, _) => ("xdg-open".to_string(), vec![target]),
+    }
+}
+
+/// Open the OS file manager at `path` (selecting the file where supported).
+pub fn reveal_in_explorer(path: &Path) -> std::io::Result<()> {
+    let (program, args) = reveal_command(path, Platform::current());
+    Command::new(program).args(args).spawn().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reveal_command_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("clip.mp4");
+        std::fs::write(&file, b"data").unwrap();
+        let file_str = file.to_string_lossy().to_string();
+        let dir_str = dir.path().to_string_lossy().to_string();
+
+        assert_eq!(
+            reveal_command(&file, Platform::Windows),
+            (
+                "explorer.exe".to_string(),
+                vec![format!("/select,{}", file_str)]
+            )
+        );
+        assert_eq!(
+            reveal_command(&file, Platform::MacOs),
+            ("open".to_string(), vec!["-R".to_string(), file_str])
+        );
+        assert_eq!(
+            reveal_command(&file, Platform::Linux),
+            ("xdg-open".to_string(), vec![dir_str])
+        );
+    }
+
+    #[test]
+    fn test_reveal_command_missing_file_opens_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("gone").join("clip.mp4");
+        let dir_str = dir.path().to_string_lossy().to_string();
+
+        assert_eq!(
+            reveal_command(&missing, Platform::Windows),
+            ("explorer.exe".to_string(), vec![dir_str.clone()])
+        );
+        assert_eq!(
+            reveal_command(&missing, Platform::MacOs),
+            ("open".to_string(), vec![dir_str.clone()])
+        );
+        assert_eq!(
+            reveal_command(&missing, Platform::Linux),
+            ("xdg-open".to_string(), vec![dir_str])
+        );
+    }
+}
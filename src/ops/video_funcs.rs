@@ -1,14 +1,72 @@
 use std::error::Error;
+use std::fmt;
+use std::time::Duration;
 
 use gst::prelude::*;
 use gstreamer as gst;
 
+use crate::ops::gst_util::{GstBusError, drain_bus_until};
+use crate::types::media::AudioClip;
+use crate::types::track::AudioTrack;
+
 /// Initializes GStreamer (safe to call multiple times).
 fn ensure_gst_init() -> Result<(), Box<dyn Error>> {
     gst::init()?;
     Ok(())
 }
 
+/// How long a pipeline may run before `wait_for_eos` gives up on it
+pub const DEFAULT_PIPELINE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A pipeline didn't reach end-of-stream within its time limit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeout {
+    pub limit: Duration,
+}
+
+impl fmt::Display for Timeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pipeline did not finish within {:.1}s",
+            self.limit.as_secs_f64()
+        )
+    }
+}
+
+impl Error for Timeout {}
+
+/// Waits for end-of-stream or an error on the pipeline's bus, for at most `timeout`
+/// of wall-clock time. A pipeline that times out is shut down and `Timeout` returned.
+pub fn wait_for_eos(pipeline: &gst::Pipeline, timeout: Duration) -> Result<(), Box<dyn Error>> {
+    let bus = pipeline.bus().unwrap();
+    let eos = |msg: &gst::Message| matches!(msg.view(), gst::MessageView::Eos(..));
+    match drain_bus_until(&bus, eos, Some(timeout)) {
+        Ok(()) => Ok(()),
+        Err(GstBusError::Timeout { limit }) => {
+            pipeline.set_state(gst::State::Null)?;
+            Err(Box::new(Timeout { limit }))
+        }
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
+/// Waits up to 5s for the pipeline to preroll after going to PAUSED, failing only
+/// if an element reports an error
+fn wait_for_preroll(pipeline: &gst::Pipeline) -> Result<(), Box<dyn Error>> {
+    let bus = pipeline.bus().unwrap();
+    let prerolled = |msg: &gst::Message| {
+        matches!(
+            msg.view(),
+            gst::MessageView::AsyncDone(_) | gst::MessageView::StateChanged(_)
+        )
+    };
+    match drain_bus_until(&bus, prerolled, Some(Duration::from_secs(5))) {
+        Ok(()) | Err(GstBusError::Timeout { .. }) => Ok(()),
+        Err(e) => Err(Box::new(e)),
+    }
+}
+
 /// Trims a video file using GStreamer.
 ///
 /// # Arguments
@@ -40,18 +98,7 @@ pub fn trim_video_gst(
     pipeline.set_state(gst::State::Paused)?;
 
     // Wait for preroll
-    let bus = pipeline.bus().unwrap();
-    loop {
-        use gst::MessageView;
-        match bus.timed_pop(gst::ClockTime::from_seconds(5)) {
-            Some(msg) => match msg.view() {
-                MessageView::AsyncDone(_) | MessageView::StateChanged(_) => break,
-                MessageView::Error(err) => return Err(Box::new(err.error().clone())),
-                _ => {}
-            },
-            None => break,
-        }
-    }
+    wait_for_preroll(&pipeline)?;
 
     // Seek to start and set stop at end
     let start_ns = (start * 1_000_000_000.0) as u64;
@@ -69,14 +116,7 @@ pub fn trim_video_gst(
     pipeline.set_state(gst::State::Playing)?;
 
     // Wait for EOS or Error
-    for msg in bus.iter_timed(gst::ClockTime::NONE) {
-        use gst::MessageView;
-        match msg.view() {
-            MessageView::Eos(..) => break,
-            MessageView::Error(err) => return Err(Box::new(err.error().clone())),
-            _ => (),
-        }
-    }
+    wait_for_eos(&pipeline, DEFAULT_PIPELINE_TIMEOUT)?;
 
     pipeline.set_state(gst::State::Null)?;
     Ok(())
@@ -88,6 +128,16 @@ pub fn trim_video_gst(
 /// * `input_files` - Slice of paths to the video files to concatenate (in order).
 /// * `output` - Path to the output concatenated video file.
 pub fn concat_videos_gst(input_files: &[&str], output: &str) -> Result<(), Box<dyn Error>> {
+    concat_videos_gst_with_timeout(input_files, output, DEFAULT_PIPELINE_TIMEOUT)
+}
+
+/// Concatenates video files like `concat_videos_gst`, failing with `Timeout` if the
+/// pipeline hasn't finished after `timeout`.
+pub fn concat_videos_gst_with_timeout(
+    input_files: &[&str],
+    output: &str,
+    timeout: Duration,
+) -> Result<(), Box<dyn Error>> {
     ensure_gst_init()?;
 
     let pipeline = gst::Pipeline::new();
@@ -138,16 +188,7 @@ pub fn concat_videos_gst(input_files: &[&str], output: &str) -> Result<(), Box<d
     }
 
     pipeline.set_state(gst::State::Playing)?;
-    let bus = pipeline.bus().unwrap();
-
-    for msg in bus.iter_timed(gst::ClockTime::NONE) {
-        use gst::MessageView;
-        match msg.view() {
-            MessageView::Eos(..) => break,
-            MessageView::Error(err) => return Err(Box::new(err.error().clone())),
-            _ => (),
-        }
-    }
+    wait_for_eos(&pipeline, timeout)?;
 
     pipeline.set_state(gst::State::Null)?;
     Ok(())
@@ -178,18 +219,7 @@ pub fn trim_audio_gst(
         .expect("Expected a gst::Pipeline");
 
     pipeline.set_state(gst::State::Paused)?;
-    let bus = pipeline.bus().unwrap();
-    loop {
-        use gst::MessageView;
-        match bus.timed_pop(gst::ClockTime::from_seconds(5)) {
-            Some(msg) => match msg.view() {
-                MessageView::AsyncDone(_) | MessageView::StateChanged(_) => break,
-                MessageView::Error(err) => return Err(Box::new(err.error().clone())),
-                _ => {}
-            },
-            None => break,
-        }
-    }
+    wait_for_preroll(&pipeline)?;
 
     let start_ns = (start * 1_000_000_000.0) as u64;
     let duration_ns = ((end - start) * 1_000_000_000.0) as u64;
@@ -203,18 +233,80 @@ pub fn trim_audio_gst(
     )?;
 
     pipeline.set_state(gst::State::Playing)?;
+    wait_for_eos(&pipeline, DEFAULT_PIPELINE_TIMEOUT)?;
+
+    pipeline.set_state(gst::State::Null)?;
+    Ok(())
+}
+
+/// Options for the final stage of an audio mix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MixOptions {
+    /// Gain applied to the mixed signal (1.0 = unchanged)
+    pub master_volume: f64,
+    /// Run the limiter after the master volume
+    pub apply_limiter: bool,
+    /// Level (0.0..1.0 of full scale) above which samples are attenuated
+    pub limiter_threshold: f32,
+}
 
-    for msg in bus.iter_timed(gst::ClockTime::NONE) {
-        use gst::MessageView;
-        match msg.view() {
-            MessageView::Eos(..) => break,
-            MessageView::Error(err) => return Err(Box::new(err.error().clone())),
-            _ => (),
+impl Default for MixOptions {
+    fn default() -> Self {
+        Self {
+            master_volume: 1.0,
+            apply_limiter: false,
+            limiter_threshold: 0.9,
         }
     }
+}
 
-    pipeline.set_state(gst::State::Null)?;
-    Ok(())
+/// Soft limiter curve: samples within `threshold` pass unchanged, louder ones are
+/// compressed into the remaining headroom so the output never exceeds full scale.
+pub fn limit_sample(sample: f32, threshold: f32) -> f32 {
+    let threshold = threshold.clamp(0.0, 1.0);
+    let level = sample.abs();
+    if level <= threshold {
+        return sample;
+    }
+    let headroom = 1.0 - threshold;
+    if headroom <= 0.0 {
+        return sample.signum() * threshold;
+    }
+    let limited = threshold + headroom * ((level - threshold) / headroom).tanh();
+    sample.signum() * limited
+}
+
+/// One file of an audio mix with its left/right gains
+#[derive(Debug, Clone, PartialEq)]
+pub struct MixInput {
+    pub path: String,
+    /// Left/right gain (1.0 = unchanged)
+    pub gains: (f64, f64),
+}
+
+impl MixInput {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            gains: (1.0, 1.0),
+        }
+    }
+
+    /// Input for `clip` with its gain, the track volume and the track pan applied
+    pub fn from_clip(track: &AudioTrack, clip: &AudioClip) -> Self {
+        Self {
+            path: clip.asset_path.clone(),
+            gains: track.channel_gains(clip),
+        }
+    }
+}
+
+/// Scale interleaved stereo samples by the left/right `gains`.
+pub fn apply_channel_gains(samples: &mut [f32], gains: (f64, f64)) {
+    for frame in samples.chunks_exact_mut(2) {
+        frame[0] *= gains.0 as f32;
+        frame[1] *= gains.1 as f32;
+    }
 }
 
 /// Mixes multiple audio files into one using GStreamer.
@@ -223,6 +315,31 @@ pub fn trim_audio_gst(
 /// * `inputs` - Slice of paths to the audio files to mix.
 /// * `output` - Path to the output mixed audio file.
 pub fn mix_audio_gst(inputs: &[&str], output: &str) -> Result<(), Box<dyn Error>> {
+    mix_audio_gst_with(inputs, output, &MixOptions::default())
+}
+
+/// Mixes multiple audio files into one, applying master volume and the optional limiter.
+///
+/// # Arguments
+/// * `inputs` - Slice of paths to the audio files to mix.
+/// * `output` - Path to the output mixed audio file.
+/// * `options` - Master volume and limiter settings.
+pub fn mix_audio_gst_with(
+    inputs: &[&str],
+    output: &str,
+    options: &MixOptions,
+) -> Result<(), Box<dyn Error>> {
+    let inputs: Vec<MixInput> = inputs.iter().map(|path| MixInput::new(path)).collect();
+    mix_audio_inputs(&inputs, output, options)
+}
+
+/// Mixes audio files with per-input channel gains (clip gain, track volume and
+/// pan), then applies master volume and the optional limiter.
+pub fn mix_audio_inputs(
+    inputs: &[MixInput],
+    output: &str,
+    options: &MixOptions,
+) -> Result<(), Box<dyn Error>> {
     ensure_gst_init()?;
 
     let pipeline = gst::Pipeline::new();
@@ -244,12 +361,54 @@ pub fn mix_audio_gst(inputs: &[&str], output: &str) -> Result<(), Box<dyn Error>
         .build()
         .expect("Failed to create filesink");
 
-    pipeline.add_many(&[&mixer, &audioconvert, &encoder, &wavenc, &sink])?;
-    gst::Element::link_many(&[&mixer, &audioconvert, &encoder, &wavenc, &sink])?;
+    let master_volume = gst::ElementFactory::make("volume")
+        .property("volume", options.master_volume)
+        .build()
+        .expect("Failed to create volume");
+
+    let mut chain = vec![mixer.clone(), master_volume];
+    if options.apply_limiter {
+        // Limit float samples in place on the way out of the capsfilter
+        let limiter_convert = gst::ElementFactory::make("audioconvert")
+            .build()
+            .expect("Failed to create audioconvert");
+        let limiter_caps = gst::ElementFactory::make("capsfilter")
+            .property(
+                "caps",
+                gst::Caps::builder("audio/x-raw")
+                    .field("format", "F32LE")
+                    .build(),
+            )
+            .build()
+            .expect("Failed to create capsfilter");
+        let threshold = options.limiter_threshold;
+        limiter_caps.static_pad("src").unwrap().add_probe(
+            gst::PadProbeType::BUFFER,
+            move |_pad, info| {
+                if let Some(gst::PadProbeData::Buffer(ref mut buffer)) = info.data {
+                    let buffer = buffer.make_mut();
+                    if let Ok(mut map) = buffer.map_writable() {
+                        for bytes in map.as_mut_slice().chunks_exact_mut(4) {
+                            let sample =
+                                f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                            bytes.copy_from_slice(&limit_sample(sample, threshold).to_le_bytes());
+                        }
+                    }
+                }
+                gst::PadProbeReturn::Ok
+            },
+        );
+        chain.push(limiter_convert);
+        chain.push(limiter_caps);
+    }
+    chain.extend([audioconvert, encoder, wavenc, sink]);
+
+    pipeline.add_many(&chain)?;
+    gst::Element::link_many(&chain)?;
 
     for input in inputs {
         let src = gst::ElementFactory::make("filesrc")
-            .property("location", input)
+            .property("location", &input.path)
             .build()
             .expect("Failed to create filesrc");
         let decode = gst::ElementFactory::make("decodebin")
@@ -265,12 +424,47 @@ pub fn mix_audio_gst(inputs: &[&str], output: &str) -> Result<(), Box<dyn Error>
             .build()
             .expect("Failed to create queue");
 
-        pipeline.add_many(&[&src, &decode, &convert, &resample, &queue])?;
+        // Stereo float samples, scaled in place by the input's channel gains
+        let gains = input.gains;
+        let gain_caps = gst::ElementFactory::make("capsfilter")
+            .property(
+                "caps",
+                gst::Caps::builder("audio/x-raw")
+                    .field("format", "F32LE")
+                    .field("channels", 2)
+                    .field("layout", "interleaved")
+                    .build(),
+            )
+            .build()
+            .expect("Failed to create capsfilter");
+        gain_caps.static_pad("src").unwrap().add_probe(
+            gst::PadProbeType::BUFFER,
+            move |_pad, info| {
+                if let Some(gst::PadProbeData::Buffer(ref mut buffer)) = info.data {
+                    let buffer = buffer.make_mut();
+                    if let Ok(mut map) = buffer.map_writable() {
+                        let mut samples: Vec<f32> = map
+                            .as_slice()
+                            .chunks_exact(4)
+                            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                            .collect();
+                        apply_channel_gains(&mut samples, gains);
+                        for (bytes, sample) in map.as_mut_slice().chunks_exact_mut(4).zip(samples) {
+                            bytes.copy_from_slice(&sample.to_le_bytes());
+                        }
+                    }
+                }
+                gst::PadProbeReturn::Ok
+            },
+        );
+
+        pipeline.add_many(&[&src, &decode, &convert, &resample, &gain_caps, &queue])?;
         gst::Element::link_many(&[&src, &decode])?;
 
         let mixer_clone = mixer.clone();
         let convert_clone = convert.clone();
         let resample_clone = resample.clone();
+        let gain_caps_clone = gain_caps.clone();
         let queue_clone = queue.clone();
         decode.connect_pad_added(move |_dbin, src_pad| {
             let sink_pad = convert_clone.static_pad("sink").unwrap();
@@ -278,6 +472,7 @@ pub fn mix_audio_gst(inputs: &[&str], output: &str) -> Result<(), Box<dyn Error>
                 let _ = gst::Element::link_many(&[
                     &convert_clone,
                     &resample_clone,
+                    &gain_caps_clone,
                     &queue_clone,
                     &mixer_clone,
                 ]);
@@ -286,16 +481,7 @@ pub fn mix_audio_gst(inputs: &[&str], output: &str) -> Result<(), Box<dyn Error>
     }
 
     pipeline.set_state(gst::State::Playing)?;
-    let bus = pipeline.bus().unwrap();
-
-    for msg in bus.iter_timed(gst::ClockTime::NONE) {
-        use gst::MessageView;
-        match msg.view() {
-            MessageView::Eos(..) => break,
-            MessageView::Error(err) => return Err(Box::new(err.error().clone())),
-            _ => (),
-        }
-    }
+    wait_for_eos(&pipeline, DEFAULT_PIPELINE_TIMEOUT)?;
 
     pipeline.set_state(gst::State::Null)?;
     Ok(())
@@ -308,6 +494,17 @@ pub fn mix_audio_gst(inputs: &[&str], output: &str) -> Result<(), Box<dyn Error>
 /// * `audio` - Path to the audio file.
 /// * `output` - Path to the output muxed file.
 pub fn mux_audio_video_gst(video: &str, audio: &str, output: &str) -> Result<(), Box<dyn Error>> {
+    mux_audio_video_gst_with_timeout(video, audio, output, DEFAULT_PIPELINE_TIMEOUT)
+}
+
+/// Muxes a video and an audio file like `mux_audio_video_gst`, failing with `Timeout`
+/// if the pipeline hasn't finished after `timeout`.
+pub fn mux_audio_video_gst_with_timeout(
+    video: &str,
+    audio: &str,
+    output: &str,
+    timeout: Duration,
+) -> Result<(), Box<dyn Error>> {
     ensure_gst_init()?;
 
     let pipeline_str = format!(
@@ -322,16 +519,7 @@ pub fn mux_audio_video_gst(video: &str, audio: &str, output: &str) -> Result<(),
         .expect("Expected a gst::Pipeline");
 
     pipeline.set_state(gst::State::Playing)?;
-    let bus = pipeline.bus().unwrap();
-
-    for msg in bus.iter_timed(gst::ClockTime::NONE) {
-        use gst::MessageView;
-        match msg.view() {
-            MessageView::Eos(..) => break,
-            MessageView::Error(err) => return Err(Box::new(err.error().clone())),
-            _ => (),
-        }
-    }
+    wait_for_eos(&pipeline, timeout)?;
 
     pipeline.set_state(gst::State::Null)?;
     Ok(())
@@ -340,6 +528,7 @@ pub fn mux_audio_video_gst(video: &str, audio: &str, output: &str) -> Result<(),
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::time::Instant;
 
     // These tests require GStreamer and valid test files.
     // Update the paths to valid files on your system to run.
@@ -402,6 +591,64 @@ mod tests {
         let _ = std::fs::remove_file(output_str);
     }
 
+    #[test]
+    fn test_channel_gains_in_mix() {
+        let track = AudioTrack {
+            id: "at1".to_string(),
+            name: "Audio 1".to_string(),
+            clips: Vec::new(),
+            muted: false,
+            sync_locked: false,
+            volume_db: 6.0,
+            pan: -0.5,
+        };
+        let clip = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "a.wav".to_string(),
+            in_point: 0.0,
+            out_point: 1.0,
+            start_time: 0.0,
+            duration: 1.0,
+            enabled: true,
+            gain_db: -6.0,
+            group_id: None,
+            metadata: crate::types::media::AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+        let input = MixInput::from_clip(&track, &clip);
+        assert_eq!(input.path, "a.wav");
+
+        // Unity gain after clip + track, then panned left: right channel halved
+        let mut samples = vec![0.8, 0.8, -0.4, -0.4];
+        apply_channel_gains(&mut samples, input.gains);
+        let expected = [0.8, 0.4, -0.4, -0.2];
+        for (sample, expected) in samples.iter().zip(expected) {
+            assert!((sample - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_limit_sample() {
+        let threshold = 0.8;
+        // Below the threshold: unchanged
+        for sample in [0.0, 0.25, -0.5, 0.8, -0.8] {
+            assert_eq!(limit_sample(sample, threshold), sample);
+        }
+        // Above the threshold: attenuated, sign kept, never past full scale
+        for sample in [0.9, 1.0, 1.5, 4.0] {
+            let limited = limit_sample(sample, threshold);
+            assert!(limited < sample);
+            assert!(limited > threshold && limited <= 1.0);
+            assert_eq!(limit_sample(-sample, threshold), -limited);
+        }
+        // Louder input stays louder
+        assert!(limit_sample(0.9, threshold) < limit_sample(1.5, threshold));
+    }
+
     #[test]
     fn test_mux_audio_video_gst() {
         let video = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata/sample.mp4");
@@ -416,4 +663,26 @@ mod tests {
         assert!(std::path::Path::new(output_str).exists());
         let _ = std::fs::remove_file(output_str);
     }
+
+    #[test]
+    fn test_wait_for_eos_times_out() {
+        if ensure_gst_init().is_err() {
+            return;
+        }
+        // A live source never reaches EOS on its own
+        let Ok(pipeline) = gst::parse::launch("videotestsrc is-live=true ! fakesink") else {
+            return;
+        };
+        let pipeline = pipeline
+            .downcast::<gst::Pipeline>()
+            .expect("Expected a gst::Pipeline");
+        pipeline.set_state(gst::State::Playing).unwrap();
+
+        let limit = Duration::from_millis(300);
+        let started = Instant::now();
+        let err = wait_for_eos(&pipeline, limit).unwrap_err();
+        assert_eq!(err.downcast_ref::<Timeout>(), Some(&Timeout { limit }));
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert_eq!(pipeline.current_state(), gst::State::Null);
+    }
 }
@@ -0,0 +1,552 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+// GStreamer imports for audio peak extraction
+use gst::prelude::*;
+use gstreamer as gst;
+use gstreamer_app as gst_app;
+
+use crate::renderer::timeline_renderer::{
+    ColorConversion, DecodeBackend, SeekMode, TimelineRenderer,
+};
+
+/// The kind of analysis requested for an asset
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnalysisKind {
+    /// Peak array for drawing a waveform
+    Waveform,
+    /// Single decoded RGBA frame for drawing a thumbnail
+    Thumbnail,
+    /// Length of the source, so edits don't probe it on the UI thread
+    Duration,
+}
+
+/// Parameters of an analysis request. Part of the cache key, so only integer fields.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AnalysisParams {
+    /// Number of peak buckets (waveforms)
+    pub buckets: u32,
+    /// Source time in milliseconds (thumbnail frame, or waveform range start)
+    pub time_ms: u64,
+    /// Source range length in milliseconds (waveforms, 0 = until the end)
+    pub duration_ms: u64,
+    /// Output width in pixels (thumbnails)
+    pub width: u32,
+    /// Output height in pixels (thumbnails)
+    pub height: u32,
+}
+
+impl AnalysisParams {
+    /// Peaks of the whole source
+    pub fn waveform(buckets: u32) -> Self {
+        Self {
+            buckets,
+            ..Default::default()
+        }
+    }
+
+    /// Peaks of the source range `[start, start + duration)`
+    pub fn waveform_range(start: f64, duration: f64, buckets: u32) -> Self {
+        Self {
+            buckets,
+            time_ms: (start.max(0.0) * 1000.0) as u64,
+            duration_ms: (duration.max(0.0) * 1000.0) as u64,
+            ..Default::default()
+        }
+    }
+
+    pub fn thumbnail(time: f64, width: u32, height: u32) -> Self {
+        Self {
+            time_ms: (time.max(0.0) * 1000.0) as u64,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+}
+
+/// Result payload of an analysis
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalysisData {
+    /// Normalized peaks (0.0..=1.0), one per bucket
+    Waveform(Vec<f32>),
+    /// RGBA pixel data
+    Thumbnail {
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    },
+    /// Source length in seconds
+    Duration(f64),
+    /// The asset could not be analyzed
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AnalysisRequest {
+    pub kind: AnalysisKind,
+    pub path: String,
+    pub params: AnalysisParams,
+}
+
+#[derive(Debug, Clone)]
+pub struct AnalysisResult {
+    pub request: AnalysisRequest,
+    pub data: AnalysisData,
+}
+
+/// Analysis results kept before the oldest are dropped (and analyzed again if asked for)
+pub const RESULT_CACHE_CAPACITY: usize = 512;
+
+/// Background asset analysis service shared by the waveform and thumbnail features.
+///
+/// Requests are processed on a worker thread so the UI thread never blocks on
+/// GStreamer. Results are cached by (kind, path, params) and each request is only
+/// submitted once while its result is cached.
+pub struct AssetAnalyzer {
+    request_tx: Sender<AnalysisRequest>,
+    result_rx: Receiver<AnalysisResult>,
+    pending: HashSet<AnalysisRequest>,
+    cache: HashMap<AnalysisRequest, AnalysisData>,
+    /// Cached requests, oldest first
+    cache_order: VecDeque<AnalysisRequest>,
+}
+
+impl AssetAnalyzer {
+    /// Create an analyzer backed by GStreamer decoding.
+    pub fn new() -> Self {
+        let mut samples = SampleCache::new(SAMPLE_CACHE_SAMPLES);
+        Self::with_worker(move |request| analyze(request, &mut samples))
+    }
+
+    /// Create an analyzer with a custom analysis function (runs on the worker thread).
+    pub fn with_worker<F>(mut worker: F) -> Self
+    where
+        F: FnMut(&AnalysisRequest) -> AnalysisData + Send + 'static,
+    {
+        let (request_tx, request_rx) = mpsc::channel::<AnalysisRequest>();
+        let (result_tx, result_rx) = mpsc::channel();
+        thread::spawn(move || {
+            for request in request_rx {
+                let data = worker(&request);
+                if result_tx.send(AnalysisResult { request, data }).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            request_tx,
+            result_rx,
+            pending: HashSet::new(),
+            cache: HashMap::new(),
+            cache_order: VecDeque::new(),
+        }
+    }
+
+    /// Submit an analysis request. Returns false if it is already cached or in flight.
+    pub fn request(&mut self, kind: AnalysisKind, path: &str, params: AnalysisParams) -> bool {
+        let request = AnalysisRequest {
+            kind,
+            path: path.to_string(),
+            params,
+        };
+        if self.cache.contains_key(&request) || self.pending.contains(&request) {
+            return false;
+        }
+        if self.request_tx.send(request.clone()).is_err() {
+            return false;
+        }
+        self.pending.insert(request);
+        true
+    }
+
+    /// Look up a cached result.
+    pub fn get(
+        &self,
+        kind: AnalysisKind,
+        path: &str,
+        params: AnalysisParams,
+    ) -> Option<&AnalysisData> {
+        self.cache.get(&AnalysisRequest {
+            kind,
+            path: path.to_string(),
+            params,
+        })
+    }
+
+    /// Collect finished results from the worker. Each result is delivered once and cached,
+    /// dropping the oldest cached results past `RESULT_CACHE_CAPACITY`.
+    pub fn poll(&mut self) -> Vec<AnalysisResult> {
+        let mut results = Vec::new();
+        while let Ok(result) = self.result_rx.try_recv() {
+            self.pending.remove(&result.request);
+            if self
+                .cache
+                .insert(result.request.clone(), result.data.clone())
+                .is_none()
+            {
+                self.cache_order.push_back(result.request.clone());
+            }
+            results.push(result);
+        }
+        while self.cache_order.len() > RESULT_CACHE_CAPACITY {
+            if let Some(oldest) = self.cache_order.pop_front() {
+                self.cache.remove(&oldest);
+            }
+        }
+        results
+    }
+
+    /// Whether any requests are still being processed
+    pub fn has_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+}
+
+impl Default for AssetAnalyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Decoded samples kept for waveforms across all sources (30 minutes at `PEAK_SAMPLE_RATE`)
+const SAMPLE_CACHE_SAMPLES: usize = 30 * 60 * PEAK_SAMPLE_RATE as usize;
+
+/// Decoded samples of recently analyzed sources, so each waveform range of a clip
+/// doesn't decode the whole file again
+struct SampleCache {
+    /// Sources by path, least recently used first
+    sources: VecDeque<(String, Vec<f32>)>,
+    max_samples: usize,
+}
+
+impl SampleCache {
+    fn new(max_samples: usize) -> Self {
+        Self {
+            sources: VecDeque::new(),
+            max_samples,
+        }
+    }
+
+    /// Samples of `path`, decoded with `decode` the first time they are asked for.
+    /// Least recently used sources are dropped once more than `max_samples` are kept,
+    /// but never the one just asked for.
+    fn get_or_decode(
+        &mut self,
+        path: &str,
+        decode: impl FnOnce(&str) -> Option<Vec<f32>>,
+    ) -> Option<&[f32]> {
+        if let Some(index) = self.sources.iter().position(|(p, _)| p == path) {
+            let source = self.sources.remove(index)?;
+            self.sources.push_back(source);
+        } else {
+            self.sources.push_back((path.to_string(), decode(path)?));
+            let mut total: usize = self.sources.iter().map(|(_, s)| s.len()).sum();
+            while total > self.max_samples && self.sources.len() > 1 {
+                if let Some((_, dropped)) = self.sources.pop_front() {
+                    total -= dropped.len();
+                }
+            }
+        }
+        self.sources.back().map(|(_, samples)| samples.as_slice())
+    }
+}
+
+/// Default analysis function used by `AssetAnalyzer::new`.
+fn analyze(request: &AnalysisRequest, samples: &mut SampleCache) -> AnalysisData {
+    match request.kind {
+        AnalysisKind::Waveform => {
+            let params = request.params;
+            samples
+                .get_or_decode(&request.path, extract_samples)
+                .map(|samples| {
+                    let range = sample_range(samples.len(), params.time_ms, params.duration_ms);
+                    AnalysisData::Waveform(compute_peaks(&samples[range], params.buckets as usize))
+                })
+                .unwrap_or(AnalysisData::Failed)
+        }
+        AnalysisKind::Thumbnail => {
+            let params = request.params;
+            TimelineRenderer::decode_video_frame(
+                &request.path,
+                params.time_ms as f64 / 1000.0,
+                params.width,
+                params.height,
+                SeekMode::Accurate,
+                DecodeBackend::default(),
+                ColorConversion::default(),
+            )
+            .map(|data| AnalysisData::Thumbnail {
+                width: params.width,
+                height: params.height,
+                data,
+            })
+            .unwrap_or(AnalysisData::Failed)
+        }
+        AnalysisKind::Duration => crate::ui::timeline_widget::get_media_duration(&request.path)
+            .map(AnalysisData::Duration)
+            .unwrap_or(AnalysisData::Failed),
+    }
+}
+
+/// Peak buckets of the waveform preview on media library cards
+pub const LIBRARY_WAVEFORM_BUCKETS: u32 = 32;
+
+/// Sample rate used for peak extraction
+const PEAK_SAMPLE_RATE: u64 = 8000;
+
+/// Map a millisecond source range to sample indices, clamped to the available samples.
+fn sample_range(len: usize, start_ms: u64, duration_ms: u64) -> std::ops::Range<usize> {
+    let start = ((start_ms * PEAK_SAMPLE_RATE / 1000) as usize).min(len);
+    let end = if duration_ms == 0 {
+        len
+    } else {
+        (((start_ms + duration_ms) * PEAK_SAMPLE_RATE / 1000) as usize).min(len)
+    };
+    start..end
+}
+
+/// Reduce samples to `buckets` absolute peaks.
+pub fn compute_peaks(samples: &[f32], buckets: usize) -> Vec<f32> {
+    if buckets == 0 || samples.is_empty() {
+        return Vec::new();
+    }
+    let bucket_len = samples.len().div_ceil(buckets);
+    samples
+        .chunks(bucket_len)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0.0f32, |peak, s| peak.max(s.abs()))
+                .min(1.0)
+        })
+        .collect()
+}
+
+/// Decode an asset's audio to mono f32 samples at a low rate suitable for peaks.
+fn extract_samples(path: &str) -> Option<Vec<f32>> {
+    let _ = gst::init(); // Safe to call multiple times
+
+    if !std::path::Path::new(path).exists() {
+        return None;
+    }
+
+    let pipeline_str = format!(
+        "filesrc location=\"{}\" ! decodebin ! audioconvert ! audioresample ! audio/x-raw,format=F32LE,channels=1,rate={} ! appsink name=sink sync=false",
+        path, PEAK_SAMPLE_RATE
+    );
+    let pipeline = gst::parse::launch(&pipeline_str)
+        .ok()?
+        .downcast::<gst::Pipeline>()
+        .ok()?;
+    let sink = pipeline
+        .by_name("sink")?
+        .downcast::<gst_app::AppSink>()
+        .ok()?;
+
+    if pipeline.set_state(gst::State::Playing).is_err() {
+        pipeline.set_state(gst::State::Null).ok();
+        return None;
+    }
+
+    // pull_sample returns an error on EOS or when the pipeline fails
+    let mut samples = Vec::new();
+    while let Ok(sample) = sink.pull_sample() {
+        if let Some(buffer) = sample.buffer() {
+            if let Ok(map) = buffer.map_readable() {
+                samples.extend(
+                    map.as_slice()
+                        .chunks_exact(4)
+                        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                );
+            }
+        }
+    }
+
+    pipeline.set_state(gst::State::Null).ok();
+
+    if samples.is_empty() {
+        None
+    } else {
+        Some(samples)
+    }
+}
+
+/// Write `samples` as a mono 16-bit PCM WAV at `rate` Hz, for tests that decode audio
+#[cfg(test)]
+pub(crate) fn write_test_wav(path: &std::path::Path, rate: u32, samples: &[i16]) {
+    let data: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    let mut wav = Vec::new();
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&rate.to_le_bytes());
+    wav.extend_from_slice(&(rate * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    wav.extend_from_slice(&data);
+    std::fs::write(path, wav).unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::{Duration, Instant};
+
+    fn poll_until(analyzer: &mut AssetAnalyzer, count: usize) -> Vec<AnalysisResult> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut results = Vec::new();
+        while results.len() < count && Instant::now() < deadline {
+            results.extend(analyzer.poll());
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        results
+    }
+
+    #[test]
+    fn test_requests_are_deduplicated_and_delivered_once() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let worker_calls = calls.clone();
+        let mut analyzer = AssetAnalyzer::with_worker(move |_req| {
+            worker_calls.fetch_add(1, Ordering::SeqCst);
+            AnalysisData::Waveform(vec![0.5; 4])
+        });
+
+        let params = AnalysisParams::waveform(4);
+        assert!(analyzer.request(AnalysisKind::Waveform, "a.wav", params));
+        // Same request while in flight is ignored
+        assert!(!analyzer.request(AnalysisKind::Waveform, "a.wav", params));
+        // Different kind or params is a separate request
+        assert!(analyzer.request(AnalysisKind::Waveform, "a.wav", AnalysisParams::waveform(8)));
+
+        let results = poll_until(&mut analyzer, 2);
+        assert_eq!(results.len(), 2);
+        assert!(!analyzer.has_pending());
+
+        // Cached now: no new request, nothing delivered again
+        assert!(!analyzer.request(AnalysisKind::Waveform, "a.wav", params));
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(analyzer.poll().is_empty());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            analyzer.get(AnalysisKind::Waveform, "a.wav", params),
+            Some(&AnalysisData::Waveform(vec![0.5; 4]))
+        );
+    }
+
+    #[test]
+    fn test_oldest_results_are_dropped_past_capacity() {
+        let mut analyzer = AssetAnalyzer::with_worker(|_req| AnalysisData::Duration(1.0));
+        let at = |time_ms| AnalysisParams {
+            time_ms,
+            ..Default::default()
+        };
+        for time_ms in 0..=RESULT_CACHE_CAPACITY as u64 {
+            analyzer.request(AnalysisKind::Thumbnail, "a.mp4", at(time_ms));
+        }
+        assert_eq!(
+            poll_until(&mut analyzer, RESULT_CACHE_CAPACITY + 1).len(),
+            RESULT_CACHE_CAPACITY + 1
+        );
+
+        let last = at(RESULT_CACHE_CAPACITY as u64);
+        assert!(
+            analyzer
+                .get(AnalysisKind::Thumbnail, "a.mp4", at(0))
+                .is_none()
+        );
+        assert!(
+            analyzer
+                .get(AnalysisKind::Thumbnail, "a.mp4", last)
+                .is_some()
+        );
+        // A dropped result can be asked for again
+        assert!(analyzer.request(AnalysisKind::Thumbnail, "a.mp4", at(0)));
+    }
+
+    #[test]
+    fn test_sources_are_decoded_once_per_path() {
+        let mut decodes = Vec::new();
+        let mut cache = SampleCache::new(10);
+        let mut decode = |path: &str| {
+            decodes.push(path.to_string());
+            Some(vec![0.5; 4])
+        };
+        assert_eq!(
+            cache.get_or_decode("a.wav", &mut decode),
+            Some(&[0.5; 4][..])
+        );
+        assert!(cache.get_or_decode("a.wav", &mut decode).is_some());
+        assert!(cache.get_or_decode("b.wav", &mut decode).is_some());
+        // "a" was used last of the two, so the third source drops "b"
+        assert!(cache.get_or_decode("a.wav", &mut decode).is_some());
+        assert!(cache.get_or_decode("c.wav", &mut decode).is_some());
+        assert!(cache.get_or_decode("a.wav", &mut decode).is_some());
+        assert!(cache.get_or_decode("b.wav", &mut decode).is_some());
+        assert_eq!(decodes, ["a.wav", "b.wav", "c.wav", "b.wav"]);
+
+        // Failures are not kept
+        assert!(cache.get_or_decode("bad.wav", |_| None).is_none());
+        assert_eq!(cache.sources.len(), 2);
+    }
+
+    #[test]
+    fn test_compute_peaks() {
+        let samples = [0.1, -0.8, 0.3, 0.2, -0.4, 0.0, 1.5];
+        let peaks = compute_peaks(&samples, 3);
+        assert_eq!(peaks, vec![0.8, 0.4, 1.0]);
+        assert!(compute_peaks(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_library_waveform_bucket_count() {
+        // 2s at the peak sample rate splits evenly into the preview buckets
+        let samples = vec![0.5f32; 2 * PEAK_SAMPLE_RATE as usize];
+        let peaks = compute_peaks(&samples, LIBRARY_WAVEFORM_BUCKETS as usize);
+        assert_eq!(peaks.len(), LIBRARY_WAVEFORM_BUCKETS as usize);
+
+        // Needs GStreamer's WAV demuxer
+        if gst::init().is_err() || gst::ElementFactory::find("wavparse").is_none() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tone.wav");
+        // 2s of a 2Hz sawtooth
+        let rate = PEAK_SAMPLE_RATE as u32;
+        let samples: Vec<i16> = (0..rate * 2)
+            .map(|i| ((i % (rate / 2)) as i32 * 2 * i16::MAX as i32 / rate as i32) as i16)
+            .collect();
+        write_test_wav(&path, rate, &samples);
+        let request = AnalysisRequest {
+            kind: AnalysisKind::Waveform,
+            path: path.to_string_lossy().into_owned(),
+            params: AnalysisParams::waveform(LIBRARY_WAVEFORM_BUCKETS),
+        };
+        match analyze(&request, &mut SampleCache::new(SAMPLE_CACHE_SAMPLES)) {
+            AnalysisData::Waveform(peaks) => {
+                assert_eq!(peaks.len(), LIBRARY_WAVEFORM_BUCKETS as usize);
+                assert!(peaks.iter().all(|p| (0.0..=1.0).contains(p)));
+                assert!(peaks.iter().any(|p| *p > 0.5));
+            }
+            other => panic!("expected a waveform, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sample_range() {
+        // 2s at 8kHz
+        assert_eq!(sample_range(16000, 0, 0), 0..16000);
+        assert_eq!(sample_range(16000, 500, 1000), 4000..12000);
+        // Clamped past the end
+        assert_eq!(sample_range(16000, 1500, 5000), 12000..16000);
+        assert_eq!(sample_range(16000, 3000, 0), 16000..16000);
+    }
+}
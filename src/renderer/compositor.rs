@@ -0,0 +1,174 @@
+use crate::types::media::CropRect;
+
+/// Composite an RGBA layer over `dst` in place using the "source over" operator.
+///
+/// Both buffers hold straight (non-premultiplied) alpha, as decoded by GStreamer, and
+/// must have the same size. Pixels of `src` with alpha 0 leave `dst` untouched.
+pub fn blend_over(dst: &mut [u8], src: &[u8]) {
+    for (d, s) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+        let src_alpha = s[3] as f32 / 255.0;
+        if src_alpha >= 1.0 {
+            d.copy_from_slice(s);
+            continue;
+        }
+        if src_alpha <= 0.0 {
+            continue;
+        }
+        let dst_alpha = d[3] as f32 / 255.0;
+        let out_alpha = src_alpha + dst_alpha * (1.0 - src_alpha);
+        for (dc, sc) in d[..3].iter_mut().zip(&s[..3]) {
+            let color =
+                (*sc as f32 * src_alpha + *dc as f32 * dst_alpha * (1.0 - src_alpha)) / out_alpha;
+            *dc = color.round().clamp(0.0, 255.0) as u8;
+        }
+        d[3] = (out_alpha * 255.0).round() as u8;
+    }
+}
+
+/// Multiply the alpha of every pixel by `opacity` (0.0..=1.0).
+pub fn scale_alpha(data: &mut [u8], opacity: f32) {
+    let opacity = opacity.clamp(0.0, 1.0);
+    if opacity >= 1.0 {
+        return;
+    }
+    for pixel in data.chunks_exact_mut(4) {
+        pixel[3] = (pixel[3] as f32 * opacity).round() as u8;
+    }
+}
+
+/// Darken every pixel toward black, keeping `level` (0.0..=1.0) of its color.
+/// Alpha is untouched, so a faded layer hides what is below it as it goes dark.
+pub fn fade_to_black(data: &mut [u8], level: f32) {
+    let level = level.clamp(0.0, 1.0);
+    if level >= 1.0 {
+        return;
+    }
+    for pixel in data.chunks_exact_mut(4) {
+        for channel in &mut pixel[..3] {
+            *channel = (*channel as f32 * level).round() as u8;
+        }
+    }
+}
+
+/// Pixel region `(x, y, width, height)` of a `width`x`height` frame covered by a
+/// normalized `(left, top, right, bottom)` crop. Edges are clamped to the frame and
+/// the region is always at least one pixel.
+pub fn crop_region(crop: CropRect, width: u32, height: u32) -> (u32, u32, u32, u32) {
+    let (left, top, right, bottom) = crop;
+    let span = |from: f32, to: f32, size: u32| {
+        let size_f = size as f32;
+        let start = (from.clamp(0.0, 1.0) * size_f).floor() as u32;
+        let start = start.min(size.saturating_sub(1));
+        let end = (to.clamp(0.0, 1.0) * size_f).ceil() as u32;
+        (start, end.clamp(start + 1, size.max(start + 1)) - start)
+    };
+    let (x, w) = span(left, right, width);
+    let (y, h) = span(top, bottom, height);
+    (x, y, w, h)
+}
+
+/// Crop a `width`x`height` RGBA frame to `crop` and scale the region back up to the
+/// full frame (nearest neighbor). A full-frame crop leaves the data untouched.
+pub fn apply_crop(data: &mut [u8], width: u32, height: u32, crop: CropRect) {
+    let (x, y, w, h) = crop_region(crop, width, height);
+    if (x, y, w, h) == (0, 0, width, height) || data.len() != (width * height * 4) as usize {
+        return;
+    }
+    let source = data.to_vec();
+    for (row, out_row) in data.chunks_exact_mut(width as usize * 4).enumerate() {
+        let src_y = y + (row as u64 * h as u64 / height as u64) as u32;
+        for (col, pixel) in out_row.chunks_exact_mut(4).enumerate() {
+            let src_x = x + (col as u64 * w as u64 / width as u64) as u32;
+            let offset = ((src_y * width + src_x) * 4) as usize;
+            pixel.copy_from_slice(&source[offset..offset + 4]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(pixels: usize, rgba: [u8; 4]) -> Vec<u8> {
+        rgba.repeat(pixels)
+    }
+
+    #[test]
+    fn test_transparent_region_shows_track_below() {
+        // 4x1 frame: opaque red underlying track
+        let mut frame = fill(4, [255, 0, 0, 255]);
+        // Overlay: transparent, transparent, half-transparent blue, opaque blue
+        let overlay = [
+            [0, 0, 255, 0],
+            [10, 20, 30, 0],
+            [0, 0, 255, 128],
+            [0, 0, 255, 255],
+        ]
+        .concat();
+
+        blend_over(&mut frame, &overlay);
+
+        assert_eq!(&frame[0..4], &[255, 0, 0, 255]);
+        assert_eq!(&frame[4..8], &[255, 0, 0, 255]);
+        assert_eq!(&frame[8..12], &[127, 0, 128, 255]);
+        assert_eq!(&frame[12..16], &[0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_blend_over_transparent_background() {
+        let mut frame = fill(1, [0, 0, 0, 0]);
+        blend_over(&mut frame, &[200, 100, 50, 128]);
+        // Nothing underneath: the color is kept, alpha is the overlay's
+        assert_eq!(frame, vec![200, 100, 50, 128]);
+    }
+
+    #[test]
+    fn test_scale_alpha() {
+        let mut frame = [[10, 20, 30, 255], [10, 20, 30, 100]].concat();
+        scale_alpha(&mut frame, 0.5);
+        assert_eq!(frame, vec![10, 20, 30, 128, 10, 20, 30, 50]);
+        scale_alpha(&mut frame, 2.0);
+        assert_eq!(frame, vec![10, 20, 30, 128, 10, 20, 30, 50]);
+    }
+
+    #[test]
+    fn test_fade_to_black_keeps_alpha() {
+        let mut frame = [[200, 100, 50, 255], [10, 20, 30, 100]].concat();
+        fade_to_black(&mut frame, 0.5);
+        assert_eq!(frame, vec![100, 50, 25, 255, 5, 10, 15, 100]);
+        fade_to_black(&mut frame, 0.0);
+        assert_eq!(frame, vec![0, 0, 0, 255, 0, 0, 0, 100]);
+    }
+
+    #[test]
+    fn test_crop_region_to_pixels() {
+        assert_eq!(crop_region((0.25, 0.5, 0.75, 1.0), 8, 4), (2, 2, 4, 2));
+        // Fractional edges round outward
+        assert_eq!(crop_region((0.1, 0.0, 0.6, 0.3), 10, 10), (1, 0, 5, 3));
+        // Out-of-range and inverted edges are clamped to at least one pixel
+        assert_eq!(crop_region((-1.0, 0.0, 2.0, 1.0), 8, 4), (0, 0, 8, 4));
+        assert_eq!(crop_region((0.5, 1.0, 0.25, 1.0), 8, 4), (4, 3, 1, 1));
+    }
+
+    #[test]
+    fn test_full_frame_crop_is_a_no_op() {
+        let original: Vec<u8> = (0..4 * 2 * 4).map(|i| i as u8).collect();
+        let mut frame = original.clone();
+        apply_crop(&mut frame, 4, 2, (0.0, 0.0, 1.0, 1.0));
+        assert_eq!(frame, original);
+    }
+
+    #[test]
+    fn test_crop_scales_region_to_frame() {
+        // 2x2 frame: red, green / blue, white. Keep the top-left quarter.
+        let mut frame = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255, 255, 255, 255],
+        ]
+        .concat();
+        apply_crop(&mut frame, 2, 2, (0.0, 0.0, 0.5, 0.5));
+        assert_eq!(frame, fill(4, [255, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,107 @@
+use crate::types::media::Effect;
+
+/// Apply a clip's effects chain, in order, to an RGBA buffer in place.
+/// Alpha is left untouched.
+pub fn apply_effects(data: &mut [u8], effects: &[Effect]) {
+    for effect in effects {
+        match *effect {
+            Effect::Brightness(amount) => apply_brightness(data, amount),
+            Effect::Contrast(factor) => apply_contrast(data, factor),
+            Effect::Saturation(factor) => apply_saturation(data, factor),
+            // Not implemented yet: needs neighbouring pixels, not per-pixel math
+            Effect::GaussianBlur(_) => {}
+        }
+    }
+}
+
+fn to_channel(value: f32) -> u8 {
+    value.round().clamp(0.0, 255.0) as u8
+}
+
+/// Add `amount` (-1.0..=1.0 of full scale) to each color channel.
+pub fn apply_brightness(data: &mut [u8], amount: f32) {
+    let offset = amount * 255.0;
+    for pixel in data.chunks_exact_mut(4) {
+        for c in &mut pixel[..3] {
+            *c = to_channel(*c as f32 + offset);
+        }
+    }
+}
+
+/// Scale each color channel's distance from mid gray by `factor`.
+pub fn apply_contrast(data: &mut [u8], factor: f32) {
+    for pixel in data.chunks_exact_mut(4) {
+        for c in &mut pixel[..3] {
+            *c = to_channel((*c as f32 - 128.0) * factor + 128.0);
+        }
+    }
+}
+
+/// Scale each color channel's distance from the pixel's luma by `factor`.
+pub fn apply_saturation(data: &mut [u8], factor: f32) {
+    for pixel in data.chunks_exact_mut(4) {
+        let luma = 0.299 * pixel[0] as f32 + 0.587 * pixel[1] as f32 + 0.114 * pixel[2] as f32;
+        for c in &mut pixel[..3] {
+            *c = to_channel(luma + (*c as f32 - luma) * factor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two RGBA pixels: a mid-dark color and a near-white one
+    fn sample() -> Vec<u8> {
+        vec![64, 128, 192, 255, 250, 240, 10, 100]
+    }
+
+    #[test]
+    fn test_brightness() {
+        let mut data = sample();
+        apply_brightness(&mut data, 0.1);
+        // +25.5 rounds per channel, clamped at 255, alpha unchanged
+        assert_eq!(data, vec![90, 154, 218, 255, 255, 255, 36, 100]);
+
+        let mut data = sample();
+        apply_brightness(&mut data, -1.0);
+        assert_eq!(data, vec![0, 0, 0, 255, 0, 0, 0, 100]);
+    }
+
+    #[test]
+    fn test_contrast() {
+        let mut data = sample();
+        apply_contrast(&mut data, 2.0);
+        assert_eq!(data, vec![0, 128, 255, 255, 255, 255, 0, 100]);
+
+        let mut data = sample();
+        apply_contrast(&mut data, 0.5);
+        assert_eq!(data, vec![96, 128, 160, 255, 189, 184, 69, 100]);
+
+        let mut data = sample();
+        apply_contrast(&mut data, 1.0);
+        assert_eq!(data, sample());
+    }
+
+    #[test]
+    fn test_saturation_zero_is_grayscale() {
+        let mut data = sample();
+        apply_saturation(&mut data, 0.0);
+        assert_eq!(data[0], data[1]);
+        assert_eq!(data[1], data[2]);
+        assert_eq!(data[3], 255);
+    }
+
+    #[test]
+    fn test_effects_apply_in_order() {
+        let mut a = sample();
+        apply_effects(&mut a, &[Effect::Brightness(0.1), Effect::Contrast(2.0)]);
+        let mut b = sample();
+        apply_effects(&mut b, &[Effect::Contrast(2.0), Effect::Brightness(0.1)]);
+        assert_ne!(a, b);
+
+        let mut c = sample();
+        apply_effects(&mut c, &[Effect::GaussianBlur(3.0)]);
+        assert_eq!(c, sample());
+    }
+}
@@ -1,2 +1,6 @@
+pub mod asset_analyzer;
+pub mod compositor;
+pub mod effects;
+pub mod text;
 pub mod time_player_bridge;
 pub mod timeline_renderer;
@@ -0,0 +1,173 @@
+use crate::renderer::compositor::blend_over;
+use crate::types::media::{TextAlign, TextClip};
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+
+/// Rasterize a text clip onto a transparent `width` x `height` RGBA frame,
+/// ready to be composited over video with `blend_over`.
+pub fn render_text_frame(clip: &TextClip, width: u32, height: u32) -> Vec<u8> {
+    let mut frame = vec![0u8; (width * height * 4) as usize];
+    let font =
+        FontRef::try_from_slice(epaint_default_fonts::UBUNTU_LIGHT).expect("bundled font is valid");
+    let font = font.as_scaled(PxScale::from(clip.font_size.max(1.0)));
+
+    let lines: Vec<&str> = clip.text.lines().collect();
+    let line_widths: Vec<f32> = lines.iter().map(|line| line_width(&font, line)).collect();
+    let block_width = line_widths.iter().copied().fold(0.0, f32::max);
+    let line_height = font.height() + font.line_gap();
+    let block_height = line_height * lines.len() as f32;
+
+    // Top-left of the text block
+    let anchor_x = clip.position.0 * width as f32;
+    let left = match clip.align {
+        TextAlign::Left => anchor_x,
+        TextAlign::Center => anchor_x - block_width / 2.0,
+        TextAlign::Right => anchor_x - block_width,
+    };
+    let top = clip.position.1 * height as f32 - block_height / 2.0;
+
+    if let Some(background) = clip.background {
+        let padding = clip.font_size * 0.25;
+        fill_rect(
+            &mut frame,
+            width,
+            height,
+            (left - padding, top - padding),
+            (left + block_width + padding, top + block_height + padding),
+            background,
+        );
+    }
+
+    for (i, (line, line_width)) in lines.iter().zip(&line_widths).enumerate() {
+        let mut x = match clip.align {
+            TextAlign::Left => left,
+            TextAlign::Center => left + (block_width - line_width) / 2.0,
+            TextAlign::Right => left + block_width - line_width,
+        };
+        let baseline = top + line_height * i as f32 + font.ascent();
+        let mut previous = None;
+        for c in line.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                x += font.kern(previous, id);
+            }
+            previous = Some(id);
+            let glyph = id.with_scale_and_position(font.scale(), point(x, baseline));
+            x += font.h_advance(id);
+
+            let Some(outlined) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outlined.px_bounds();
+            outlined.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i64 + gx as i64;
+                let py = bounds.min.y as i64 + gy as i64;
+                if px < 0 || py < 0 || px >= width as i64 || py >= height as i64 {
+                    return;
+                }
+                let alpha = (clip.color[3] as f32 * coverage.clamp(0.0, 1.0)).round() as u8;
+                let [r, g, b, _] = clip.color;
+                let i = ((py as u32 * width + px as u32) * 4) as usize;
+                blend_over(&mut frame[i..i + 4], &[r, g, b, alpha]);
+            });
+        }
+    }
+    frame
+}
+
+fn line_width<F: Font, S: ScaleFont<F>>(font: &S, line: &str) -> f32 {
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in line.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, id);
+        }
+        width += font.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Fill the pixels between `min` and `max` (clipped to the frame) with `color`.
+fn fill_rect(
+    frame: &mut [u8],
+    width: u32,
+    height: u32,
+    min: (f32, f32),
+    max: (f32, f32),
+    color: [u8; 4],
+) {
+    let x0 = min.0.round().clamp(0.0, width as f32) as u32;
+    let x1 = max.0.round().clamp(0.0, width as f32) as u32;
+    let y0 = min.1.round().clamp(0.0, height as f32) as u32;
+    let y1 = max.1.round().clamp(0.0, height as f32) as u32;
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let i = ((y * width + x) * 4) as usize;
+            frame[i..i + 4].copy_from_slice(&color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alpha_at(frame: &[u8], width: u32, x: u32, y: u32) -> u8 {
+        frame[((y * width + x) * 4 + 3) as usize]
+    }
+
+    fn opaque_pixels(frame: &[u8]) -> usize {
+        frame.chunks_exact(4).filter(|p| p[3] > 0).count()
+    }
+
+    #[test]
+    fn test_text_is_drawn_at_position() {
+        let (width, height) = (320, 180);
+        let mut clip = TextClip::new("t1".to_string(), "Hello".to_string(), 0.0, 5.0);
+        clip.font_size = 40.0;
+        clip.position = (0.25, 0.5);
+        let frame = render_text_frame(&clip, width, height);
+
+        assert!(opaque_pixels(&frame) > 50);
+        // Every drawn pixel is near the anchor, in the left half of the frame
+        for (i, pixel) in frame.chunks_exact(4).enumerate() {
+            if pixel[3] > 0 {
+                let (x, y) = (i as u32 % width, i as u32 / width);
+                assert!(x < width / 2, "stray pixel at ({}, {})", x, y);
+                assert!((50..130).contains(&y), "stray pixel at ({}, {})", x, y);
+                assert_eq!(&pixel[..3], &[255, 255, 255]);
+            }
+        }
+        // Corners stay transparent
+        assert_eq!(alpha_at(&frame, width, 0, 0), 0);
+        assert_eq!(alpha_at(&frame, width, width - 1, height - 1), 0);
+    }
+
+    #[test]
+    fn test_background_box_and_alignment() {
+        let (width, height) = (320, 180);
+        let mut clip = TextClip::new("t1".to_string(), "Title".to_string(), 0.0, 5.0);
+        clip.font_size = 30.0;
+        clip.background = Some([0, 0, 0, 255]);
+        let boxed = render_text_frame(&clip, width, height);
+        // The anchor (frame center) is inside the box
+        assert_eq!(alpha_at(&boxed, width, width / 2, height / 2), 255);
+        assert_eq!(alpha_at(&boxed, width, 0, 0), 0);
+
+        // Right-aligned text ends at the anchor
+        clip.background = None;
+        clip.align = TextAlign::Right;
+        let right = render_text_frame(&clip, width, height);
+        assert!(opaque_pixels(&right) > 0);
+        for (i, pixel) in right.chunks_exact(4).enumerate() {
+            if pixel[3] > 0 {
+                assert!(i as u32 % width <= width / 2 + 2);
+            }
+        }
+
+        // Nothing to draw
+        clip.text = String::new();
+        assert_eq!(opaque_pixels(&render_text_frame(&clip, width, height)), 0);
+    }
+}
@@ -1,14 +1,23 @@
 use crate::renderer::timeline_renderer::{AudioBuffer, TimelineRenderer, VideoFrame};
 use crate::types::playback_state::PlaybackState;
 use crate::types::timeline::Timeline;
+use std::collections::VecDeque;
 use std::time::Instant;
 
+/// Number of frames kept rendered ahead of the playhead by default
+pub const DEFAULT_BUFFER_DEPTH: usize = 4;
+
 pub struct TimelinePlayerBridge<'a> {
     pub timeline: &'a Timeline,
     pub renderer: &'a mut TimelineRenderer,
     pub playback_state: PlaybackState,
     pub last_update: Instant,
-    pub video_buffer: Vec<VideoFrame>,
+    /// Frames from the playhead onwards, oldest first
+    pub video_buffer: VecDeque<VideoFrame>,
+    /// Maximum number of frames in `video_buffer` (at least 1)
+    pub buffer_depth: usize,
+    /// Renderer cache generation `video_buffer` was rendered in
+    pub buffer_generation: u64,
     // pub audio_buffer: Vec<AudioBuffer>, // Uncomment if you have audio
 }
 
@@ -23,7 +32,9 @@ impl<'a> TimelinePlayerBridge<'a> {
             renderer,
             playback_state,
             last_update: Instant::now(),
-            video_buffer: Vec::new(),
+            video_buffer: VecDeque::new(),
+            buffer_depth: DEFAULT_BUFFER_DEPTH,
+            buffer_generation: 0,
             // audio_buffer: Vec::new(),
         }
     }
@@ -41,18 +52,66 @@ impl<'a> TimelinePlayerBridge<'a> {
         let max_time = self.timeline.duration.max(1.0);
         self.playback_state.playhead = self.playback_state.playhead.clamp(0.0, max_time);
 
-        // Render and buffer the current frame
-        let frame = self.renderer.render_frame(self.playback_state.playhead);
-        self.video_buffer.clear();
-        self.video_buffer.push(frame);
+        self.fill_buffer(max_time);
         // Do the same for audio if needed
     }
 
+    /// Drop frames the playhead has passed and render ahead up to `buffer_depth` frames.
+    fn fill_buffer(&mut self, max_time: f64) {
+        let playhead = self.playback_state.playhead;
+        let frame_duration = 1.0 / self.renderer.frame_rate.max(1.0);
+
+        // Discard the whole buffer if the playhead left its range (seek or rewind) or the
+        // renderer dropped its cached frames since (an edit or a changed setting)
+        let in_range = match (self.video_buffer.front(), self.video_buffer.back()) {
+            (Some(first), Some(last)) => {
+                playhead >= first.timestamp && playhead < last.timestamp + frame_duration
+            }
+            _ => false,
+        };
+        if !in_range || self.buffer_generation != self.renderer.cache_generation {
+            self.video_buffer.clear();
+            self.buffer_generation = self.renderer.cache_generation;
+        }
+
+        // Pop frames once the next one is due
+        while self.video_buffer.len() > 1 && self.video_buffer[1].timestamp <= playhead {
+            self.video_buffer.pop_front();
+        }
+
+        if self.video_buffer.is_empty() {
+            let frame = self.renderer.render_frame(playhead);
+            self.video_buffer.push_back(frame);
+        }
+
+        while self.video_buffer.len() < self.buffer_depth.max(1) {
+            let next_time = self.video_buffer.back().unwrap().timestamp + frame_duration;
+            if next_time > max_time {
+                break;
+            }
+            let frame = self.renderer.render_frame(next_time);
+            self.video_buffer.push_back(frame);
+        }
+    }
+
+    /// Set how many frames are rendered ahead of the playhead (minimum 1).
+    pub fn set_buffer_depth(&mut self, depth: usize) {
+        self.buffer_depth = depth.max(1);
+        self.video_buffer.truncate(self.buffer_depth);
+    }
+
     pub fn seek(&mut self, time: f64) {
         self.playback_state.playhead = time.clamp(0.0, self.timeline.duration.max(1.0));
+        // Frames already rendered ahead are kept while the playhead stays among them
         self.update();
     }
 
+    /// Forget every rendered frame so the next update draws the timeline again
+    pub fn invalidate(&mut self) {
+        self.video_buffer.clear();
+        self.renderer.clear_cache();
+    }
+
     pub fn play(&mut self) {
         self.playback_state.is_playing = true;
         self.last_update = Instant::now();
@@ -63,8 +122,86 @@ impl<'a> TimelinePlayerBridge<'a> {
     }
 
     pub fn current_frame(&self) -> Option<&VideoFrame> {
-        self.video_buffer.last()
+        self.video_buffer.front()
     }
 
     // Add audio methods, stats, etc. as needed
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, RwLock};
+
+    #[test]
+    fn test_buffer_renders_ahead_of_playhead() {
+        let mut timeline = Timeline::new();
+        timeline.duration = 10.0;
+        let mut renderer =
+            TimelineRenderer::new(Arc::new(RwLock::new(timeline.clone())), 4, 4, 30.0);
+        let mut bridge = TimelinePlayerBridge::new(&timeline, &mut renderer, PlaybackState::new());
+        bridge.set_buffer_depth(5);
+        let frame_duration = 1.0 / 30.0;
+
+        for step in 0..6 {
+            bridge.playback_state.playhead = step as f64 * 0.05;
+            bridge.update();
+
+            let playhead = bridge.playback_state.playhead;
+            let timestamps: Vec<f64> = bridge.video_buffer.iter().map(|f| f.timestamp).collect();
+            assert_eq!(timestamps.len(), 5);
+            assert!(timestamps.windows(2).all(|w| w[0] < w[1]));
+            // Current frame covers the playhead, the rest is lookahead
+            assert!(timestamps[0] <= playhead && playhead < timestamps[0] + frame_duration);
+            assert!((timestamps[4] - timestamps[0] - 4.0 * frame_duration).abs() < 1e-9);
+        }
+
+        // Lookahead stops at the end of the timeline
+        bridge.seek(10.0 - frame_duration * 1.5);
+        assert_eq!(bridge.video_buffer.len(), 2);
+    }
+
+    #[test]
+    fn test_seeking_forward_uses_the_lookahead() {
+        let mut timeline = Timeline::new();
+        timeline.duration = 10.0;
+        let mut renderer =
+            TimelineRenderer::new(Arc::new(RwLock::new(timeline.clone())), 4, 4, 30.0);
+        let mut bridge = TimelinePlayerBridge::new(&timeline, &mut renderer, PlaybackState::new());
+        let frame_duration = 1.0 / 30.0;
+        bridge.seek(0.0);
+        assert_eq!(bridge.video_buffer.len(), DEFAULT_BUFFER_DEPTH);
+
+        // The frame rendered ahead is shown as is, not rendered again
+        bridge.video_buffer[1].data[0] = 7;
+        bridge.seek(frame_duration * 1.5);
+        assert_eq!(bridge.video_buffer.len(), DEFAULT_BUFFER_DEPTH);
+        assert!((bridge.current_frame().unwrap().timestamp - frame_duration).abs() < 1e-9);
+        assert_eq!(bridge.current_frame().unwrap().data[0], 7);
+
+        // Until the renderer's cache is cleared
+        bridge.renderer.clear_cache();
+        bridge.seek(frame_duration * 1.5);
+        assert_eq!(bridge.current_frame().unwrap().data[0], 0);
+
+        // Jumping elsewhere starts over at the new position
+        bridge.seek(5.0);
+        assert!((bridge.current_frame().unwrap().timestamp - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_invalidate_drops_rendered_frames() {
+        let mut timeline = Timeline::new();
+        timeline.duration = 10.0;
+        let mut renderer =
+            TimelineRenderer::new(Arc::new(RwLock::new(timeline.clone())), 4, 4, 30.0);
+        let mut bridge = TimelinePlayerBridge::new(&timeline, &mut renderer, PlaybackState::new());
+        bridge.update();
+        assert!(!bridge.video_buffer.is_empty());
+        assert!(!bridge.renderer.frame_cache.is_empty());
+
+        bridge.invalidate();
+        assert!(bridge.video_buffer.is_empty());
+        assert!(bridge.renderer.frame_cache.is_empty());
+    }
+}
@@ -1,5 +1,12 @@
-use crate::types::timeline::Timeline;
-use std::collections::HashMap;
+use crate::ops::export::output_timestamps;
+use crate::ops::gst_util::drain_bus_until;
+use crate::renderer::compositor::{apply_crop, blend_over, fade_to_black, scale_alpha};
+use crate::renderer::effects::apply_effects;
+use crate::renderer::text::render_text_frame;
+use crate::types::media::{ColorSpace, VideoClip};
+use crate::types::timeline::{GapFade, Timeline};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
@@ -7,7 +14,8 @@ use std::time::Duration;
 use gst::prelude::*;
 use gstreamer as gst;
 use gstreamer_app as gst_app;
-// use gstreamer_video as gst_video; // Unused import
+use gstreamer_pbutils as gst_pbutils;
+use gstreamer_video as gst_video;
 
 #[derive(Debug, Clone)]
 pub struct VideoFrame {
@@ -19,6 +27,47 @@ pub struct VideoFrame {
     // Add more fields as needed (e.g., format, color space)
 }
 
+impl VideoFrame {
+    /// Write the frame to `path` as an RGBA PNG
+    pub fn save_png(&self, path: &Path) -> image::ImageResult<()> {
+        image::save_buffer(
+            path,
+            &self.data,
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+        )
+    }
+}
+
+/// File name of frame `index` of an image sequence: the run of `#` in `pattern` is
+/// replaced by the index, zero-padded to the run's length (`frame_#####.png` ->
+/// `frame_00001.png`). Without a `#`, the index goes before the extension.
+pub fn sequence_file_name(pattern: &str, index: usize) -> String {
+    match pattern.find('#') {
+        Some(start) => {
+            let width = pattern[start..].chars().take_while(|c| *c == '#').count();
+            format!(
+                "{}{:0width$}{}",
+                &pattern[..start],
+                index,
+                &pattern[start + width..],
+                width = width
+            )
+        }
+        None => {
+            let path = Path::new(pattern);
+            let stem = path
+                .file_stem()
+                .map_or(pattern.into(), |s| s.to_string_lossy());
+            match path.extension() {
+                Some(ext) => format!("{}_{:05}.{}", stem, index, ext.to_string_lossy()),
+                None => format!("{}_{:05}", stem, index),
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AudioBuffer {
     pub data: Vec<f32>, // Raw audio data (e.g., PCM)
@@ -28,13 +77,260 @@ pub struct AudioBuffer {
     // Add more fields as needed (e.g., format, channel count)
 }
 
+/// Trade-off between seek precision and speed when decoding a source frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SeekMode {
+    /// Decode forward from the previous keyframe to the exact frame
+    #[default]
+    Accurate,
+    /// Show the nearest keyframe; much faster, for scrubbing
+    Fast,
+}
+
+/// GStreamer seek flags used for `mode`
+pub fn seek_flags_for(mode: SeekMode) -> gst::SeekFlags {
+    match mode {
+        SeekMode::Accurate => gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+        SeekMode::Fast => gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT,
+    }
+}
+
+/// Which decoders `decodebin` should use for source video
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodeBackend {
+    #[default]
+    Software,
+    /// VA-API (Linux, Intel/AMD)
+    VaApi,
+    /// Direct3D 11 (Windows)
+    D3d11,
+    /// NVIDIA NVDEC
+    NvDec,
+}
+
+impl DecodeBackend {
+    /// Hardware decoder elements provided by the backend (empty for software)
+    pub fn decoder_elements(self) -> &'static [&'static str] {
+        match self {
+            DecodeBackend::Software => &[],
+            DecodeBackend::VaApi => &[
+                "vah264dec",
+                "vah265dec",
+                "vavp9dec",
+                "vaav1dec",
+                "vaapih264dec",
+                "vaapih265dec",
+            ],
+            DecodeBackend::D3d11 => &["d3d11h264dec", "d3d11h265dec", "d3d11vp9dec", "d3d11av1dec"],
+            DecodeBackend::NvDec => &["nvh264dec", "nvh265dec", "nvvp9dec", "nvav1dec"],
+        }
+    }
+
+    /// Name shown in the preview toolbar
+    pub fn label(self) -> &'static str {
+        match self {
+            DecodeBackend::Software => "Software",
+            DecodeBackend::VaApi => "VA-API",
+            DecodeBackend::D3d11 => "Direct3D 11",
+            DecodeBackend::NvDec => "NVDEC",
+        }
+    }
+
+    /// The backend's decoders `decodebin` can pick: installed and ranked for autoplugging
+    fn autoplug_decoders(self) -> Vec<gst::ElementFactory> {
+        self.decoder_elements()
+            .iter()
+            .filter_map(|name| gst::ElementFactory::find(name))
+            .filter(|factory| factory.rank() >= gst::Rank::MARGINAL)
+            .collect()
+    }
+}
+
+/// Make `decodebin` decode video with `backend`, in this pipeline only. Software decoding
+/// is forced (the property needs GStreamer 1.18); a hardware backend's decoders are picked
+/// over every other decoder for the streams they handle.
+fn select_decoders(decodebin: &gst::Element, backend: DecodeBackend) {
+    if backend == DecodeBackend::Software {
+        if decodebin.find_property("force-sw-decoders").is_some() {
+            decodebin.set_property("force-sw-decoders", true);
+        }
+        return;
+    }
+    let Some(result_type) = gst::glib::Type::from_name("GstAutoplugSelectResult") else {
+        return;
+    };
+    let preferred = backend.autoplug_decoders();
+    decodebin.connect("autoplug-select", false, move |values| {
+        // Arguments are the decodebin, the pad, its caps and the candidate factory
+        let skip = match (
+            values[2].get::<gst::Caps>(),
+            values[3].get::<gst::ElementFactory>(),
+        ) {
+            (Ok(caps), Ok(factory)) => {
+                factory.klass().contains("Decoder")
+                    && !preferred.contains(&factory)
+                    && preferred
+                        .iter()
+                        .any(|decoder| decoder.can_sink_any_caps(&caps))
+            }
+            _ => false,
+        };
+        // GstAutoplugSelectResult: 0 tries the factory, 2 skips it
+        gst::glib::EnumClass::with_type(result_type)?.to_value(if skip { 2 } else { 0 })
+    });
+}
+
+/// Color spaces a decoded frame is converted between
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ColorConversion {
+    /// Space the source declares; sources that don't are taken to be in the working space
+    pub source: Option<ColorSpace>,
+    /// The project's working space
+    pub working: ColorSpace,
+}
+
+impl ColorConversion {
+    /// Whether the source's transfer curve and primaries have to be remapped
+    pub fn needs_remap(&self) -> bool {
+        self.source.is_some_and(|source| source != self.working)
+    }
+
+    /// Pipeline segment turning decoded video into `width`x`height` RGBA frames in the
+    /// working space
+    pub fn rgba_pipeline(&self, width: u32, height: u32) -> String {
+        // videoconvert keeps gamma and primaries as they are unless asked to remap
+        let convert = if self.needs_remap() {
+            "videoconvert gamma-mode=remap primaries-mode=fast"
+        } else {
+            "videoconvert"
+        };
+        format!(
+            "{} ! videoscale ! video/x-raw,format=RGBA,width={},height={},colorimetry={}",
+            convert,
+            width,
+            height,
+            self.working.colorimetry()
+        )
+    }
+}
+
+/// The backend that will actually decode: `requested` if GStreamer has any of
+/// its decoders, otherwise `Software`.
+pub fn resolve_decode_backend(requested: DecodeBackend) -> DecodeBackend {
+    if requested == DecodeBackend::Software || gst::init().is_err() {
+        return DecodeBackend::Software;
+    }
+    if !requested.autoplug_decoders().is_empty() {
+        requested
+    } else {
+        log::warn!(
+            "No {:?} decoders available, falling back to software decoding",
+            requested
+        );
+        DecodeBackend::Software
+    }
+}
+
+/// Copy `height` rows of `width` RGBA pixels out of a frame whose rows start `stride`
+/// bytes apart, dropping any row padding. None if the frame is too short.
+pub fn pack_rgba_rows(data: &[u8], width: usize, height: usize, stride: usize) -> Option<Vec<u8>> {
+    let row = width * 4;
+    if stride < row || height == 0 || data.len() < stride * (height - 1) + row {
+        return None;
+    }
+    if stride == row {
+        return Some(data[..row * height].to_vec());
+    }
+    let mut packed = Vec::with_capacity(row * height);
+    for y in 0..height {
+        packed.extend_from_slice(&data[y * stride..y * stride + row]);
+    }
+    Some(packed)
+}
+
+/// Tightly packed RGBA pixels of an appsink sample, using the stride from its caps.
+/// Samples without usable video caps are returned as they are.
+fn sample_rgba(sample: &gst::Sample) -> Option<Vec<u8>> {
+    let buffer = sample.buffer()?;
+    let map = buffer.map_readable().ok()?;
+    let info = sample
+        .caps()
+        .and_then(|caps| gst_video::VideoInfo::from_caps(caps).ok());
+    let Some(info) = info else {
+        log::warn!("Decoded sample has no video caps, assuming packed RGBA");
+        return Some(map.as_slice().to_vec());
+    };
+    let stride = info.stride()[0].max(0) as usize;
+    pack_rgba_rows(
+        map.as_slice(),
+        info.width() as usize,
+        info.height() as usize,
+        stride,
+    )
+}
+
+/// Run a decode step, turning a panic inside it (e.g. from a corrupt file tripping up
+/// the GStreamer bindings) into `None` so one bad frame doesn't take the editor down
+pub fn catch_decode_panic<T>(what: &str, decode: impl FnOnce() -> Option<T>) -> Option<T> {
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(decode)) {
+        Ok(result) => result,
+        Err(panic) => {
+            let message = panic
+                .downcast_ref::<&str>()
+                .map(|s| s.to_string())
+                .or_else(|| panic.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string());
+            log::error!(
+                "Recovered from a panic while decoding {}: {}",
+                what,
+                message
+            );
+            None
+        }
+    }
+}
+
+/// Source time to seek to for `time`, kept within a source `media_duration` seconds
+/// long: times past the end show its last frame, one `frame_duration` before the end.
+/// None for times before the start of the source, which are drawn as black.
+pub fn clamp_seek_time(time: f64, media_duration: Option<f64>, frame_duration: f64) -> Option<f64> {
+    if time < 0.0 {
+        return None;
+    }
+    Some(match media_duration {
+        Some(duration) => time.min((duration - frame_duration).max(0.0)),
+        None => time,
+    })
+}
+
 pub struct TimelineRenderer {
     pub timeline: Arc<RwLock<Timeline>>,
     pub width: u32,
     pub height: u32,
     pub frame_rate: f64,
     pub frame_cache: HashMap<u64, VideoFrame>, // Frame cache keyed by frame number
-                                               // Add more fields as needed (e.g., caches, effect processors)
+    /// Bumped whenever `frame_cache` is cleared, so frames kept elsewhere can tell they are stale
+    pub cache_generation: u64,
+    /// How source frames are seeked; see `set_seek_mode`
+    pub seek_mode: SeekMode,
+    /// Decoders used for source video; see `set_decode_backend`
+    pub decode_backend: DecodeBackend,
+    /// Rendering for export rather than the preview; picks which subtitle setting applies
+    pub exporting: bool,
+    /// Working color space clips are converted to before compositing
+    pub color_space: ColorSpace,
+    /// Skip every clip's effects (for A/B comparing); see `set_effects_bypass`
+    pub bypass_effects: bool,
+    /// Clips whose effects are skipped, leaving their effect data as is
+    pub bypassed_clips: HashSet<String>,
+    /// Probed source lengths by path (None if probing failed)
+    pub media_durations: HashMap<String, Option<f64>>,
+    /// Skip all video decoding and render black frames; see `set_video_disabled`
+    pub video_disabled: bool,
+    /// Source frames asked of the decoder so far
+    pub decode_attempts: u64,
+    /// Dissolve clips to and from black across short gaps; see `set_gap_fade`
+    pub gap_fade: Option<GapFade>,
 }
 
 impl TimelineRenderer {
@@ -45,9 +341,89 @@ impl TimelineRenderer {
             height,
             frame_rate,
             frame_cache: HashMap::new(),
+            cache_generation: 0,
+            seek_mode: SeekMode::default(),
+            decode_backend: DecodeBackend::default(),
+            exporting: false,
+            color_space: ColorSpace::default(),
+            bypass_effects: false,
+            bypassed_clips: HashSet::new(),
+            media_durations: HashMap::new(),
+            video_disabled: false,
+            decode_attempts: 0,
+            gap_fade: None,
+        }
+    }
+
+    /// Turn video decoding off (every frame renders black) or back on; cached frames
+    /// are dropped when that changes
+    pub fn set_video_disabled(&mut self, disabled: bool) {
+        if disabled != self.video_disabled {
+            self.clear_cache();
+            self.video_disabled = disabled;
+        }
+    }
+
+    /// Fade clips to and from black across short gaps (`None` cuts straight to black);
+    /// cached frames are dropped when that changes
+    pub fn set_gap_fade(&mut self, fade: Option<GapFade>) {
+        if fade != self.gap_fade {
+            self.clear_cache();
+            self.gap_fade = fade;
+        }
+    }
+
+    /// Skip the effects of every clip (`all`) or of the clips in `clips`;
+    /// cached frames are dropped when that changes
+    pub fn set_effects_bypass(&mut self, all: bool, clips: &HashSet<String>) {
+        if all != self.bypass_effects || *clips != self.bypassed_clips {
+            self.clear_cache();
+            self.bypass_effects = all;
+            self.bypassed_clips = clips.clone();
         }
     }
 
+    /// Crop, effects (unless bypassed) and opacity of `clip`, applied to its decoded frame
+    fn finish_clip_frame(&self, clip: &VideoClip, frame_data: &mut [u8], time: f64) {
+        if let Some(crop) = clip.crop {
+            apply_crop(frame_data, self.width, self.height, crop);
+        }
+        if !self.bypass_effects && !self.bypassed_clips.contains(&clip.id) {
+            apply_effects(frame_data, &clip.effects);
+        }
+        if let Some(opacity) = clip.opacity_envelope.value_at(time - clip.start_time) {
+            scale_alpha(frame_data, opacity);
+        }
+    }
+
+    /// Composite in `space`; cached frames from another space are dropped
+    pub fn set_color_space(&mut self, space: ColorSpace) {
+        if space != self.color_space {
+            self.clear_cache();
+        }
+        self.color_space = space;
+    }
+
+    /// Request a decode backend, falling back to software when its decoders are
+    /// missing. Returns the backend actually in use; only this renderer's decodes use it.
+    pub fn set_decode_backend(&mut self, requested: DecodeBackend) -> DecodeBackend {
+        let backend = resolve_decode_backend(requested);
+        if backend != self.decode_backend {
+            self.clear_cache();
+        }
+        self.decode_backend = backend;
+        backend
+    }
+
+    /// Switch seek mode. Going back to `Accurate` drops the cache so frames
+    /// decoded at keyframes while scrubbing are rendered again exactly.
+    pub fn set_seek_mode(&mut self, mode: SeekMode) {
+        if mode == SeekMode::Accurate && self.seek_mode != SeekMode::Accurate {
+            self.clear_cache();
+        }
+        self.seek_mode = mode;
+    }
+
     /// Render a video frame at the given time (in seconds), with stub compositing and caching.
     pub fn render_frame(&mut self, time: f64) -> VideoFrame {
         let frame_number = (time * self.frame_rate) as u64;
@@ -57,53 +433,101 @@ impl TimelineRenderer {
             return frame.clone();
         }
 
+        if self.video_disabled {
+            return VideoFrame {
+                data: vec![0u8; (self.width * self.height * 4) as usize],
+                width: self.width,
+                height: self.height,
+                timestamp: time,
+                frame_number,
+            };
+        }
+
         // 2. Lock the timeline and find active video clips
         let timeline = self.timeline.read().unwrap();
 
-        // Debug print: show all tracks and their clips
-        println!("--- Timeline Debug ---");
-        println!("Timeline has {} tracks", timeline.tracks.len());
-        for (i, track) in timeline.tracks.iter().enumerate() {
-            println!("Track {}: {:?}", i, track);
-        }
-
-        // Get all active clips (video and audio) at this time
-        let active_clips = timeline.active_clips_at(time);
+        // Get all enabled clips (video and audio) active at this time; muted tracks are skipped
+        let active_clips = timeline.renderable_clips_at(time);
 
-        // Debug print: show active clips at this time
-        println!("Active clips at time {}: {:?}", time, active_clips);
+        log::trace!("Active clips at time {}: {:?}", time, active_clips);
 
-        // 3. Composite the clips (real decoding for first active video clip)
+        // 3. Composite the clips: video tracks later in the list are drawn over earlier
+        // ones, honoring each decoded frame's alpha
         let mut data = vec![0u8; (self.width * self.height * 4) as usize];
 
-        // Find the first active video clip and decode it
-        if let Some(crate::types::timeline::ActiveClip::Video(clip)) = active_clips
-            .iter()
-            .find(|c| matches!(c, crate::types::timeline::ActiveClip::Video(_)))
-        {
+        for clip in active_clips.iter().filter_map(|c| match c {
+            crate::types::timeline::ActiveClip::Video(clip) => Some(clip),
+            _ => None,
+        }) {
             let path = &clip.asset_path;
-            let clip_in_point = clip.in_point;
             let clip_start_time = clip.start_time;
-            // Calculate the timestamp in the source video
-            let local_time = time - clip_start_time + clip_in_point;
-            if let Some(frame_data) =
-                Self::decode_video_frame(path, local_time, self.width, self.height)
-            {
+            // Calculate the timestamp in the source video, staying inside the source
+            let media_duration = *self
+                .media_durations
+                .entry(path.clone())
+                .or_insert_with(|| Self::get_video_duration(path));
+            let Some(local_time) = clamp_seek_time(
+                clip.source_time_at(time),
+                media_duration,
+                1.0 / self.frame_rate,
+            ) else {
+                continue;
+            };
+            self.decode_attempts += 1;
+            let colors = ColorConversion {
+                source: clip.metadata.color_space,
+                working: self.color_space,
+            };
+            if let Some(mut frame_data) = Self::decode_video_frame(
+                path,
+                local_time,
+                self.width,
+                self.height,
+                self.seek_mode,
+                self.decode_backend,
+                colors,
+            ) {
                 if frame_data.len() == data.len() {
-                    data.copy_from_slice(&frame_data);
+                    self.finish_clip_frame(clip, &mut frame_data, time);
+                    if let Some(fade) = self.gap_fade {
+                        fade_to_black(&mut frame_data, timeline.gap_fade_at(&clip.id, time, fade));
+                    }
+                    blend_over(&mut data, &frame_data);
                 } else {
-                    println!(
+                    log::warn!(
                         "Decoded frame size mismatch: got {}, expected {}",
                         frame_data.len(),
                         data.len()
                     );
                 }
             } else {
-                println!("Failed to decode video frame for clip at {}", local_time);
+                log::warn!("Failed to decode video frame for clip at {}", local_time);
             }
         }
 
-        println!("Compositing {} clips at time {}", active_clips.len(), time);
+        // Titles go over all video tracks
+        for text_clip in timeline.active_text_clips_at(time) {
+            blend_over(
+                &mut data,
+                &render_text_frame(text_clip, self.width, self.height),
+            );
+        }
+
+        // Captions go over everything
+        if let Some(subtitles) = timeline
+            .subtitles
+            .as_ref()
+            .filter(|s| s.is_drawn(self.exporting))
+        {
+            for cue in subtitles.cues_at(time) {
+                blend_over(
+                    &mut data,
+                    &render_text_frame(&cue.to_text_clip(), self.width, self.height),
+                );
+            }
+        }
+
+        log::trace!("Compositing {} clips at time {}", active_clips.len(), time);
 
         let output = VideoFrame {
             data,
@@ -119,40 +543,99 @@ impl TimelineRenderer {
         output
     }
 
+    /// Render the frame at `time` and write it to `path` as a PNG
+    pub fn export_frame_png(&mut self, time: f64, path: &Path) -> image::ImageResult<()> {
+        self.render_frame(time).save_png(path)
+    }
+
+    /// Render `range` (in seconds) at the renderer's frame rate and write each frame
+    /// to `dir` as a PNG named by `pattern` (see `sequence_file_name`), numbered from 1.
+    /// Returns the number of frames written.
+    pub fn export_image_sequence(
+        &mut self,
+        dir: &Path,
+        range: std::ops::Range<f64>,
+        pattern: &str,
+    ) -> image::ImageResult<usize> {
+        std::fs::create_dir_all(dir)?;
+        let timestamps = output_timestamps(range.end - range.start, self.frame_rate);
+        for (i, offset) in timestamps.iter().enumerate() {
+            // Keep only the frame being written in memory
+            self.clear_cache();
+            let path = dir.join(sequence_file_name(pattern, i + 1));
+            self.export_frame_png(range.start + offset, &path)?;
+        }
+        self.clear_cache();
+        Ok(timestamps.len())
+    }
+
     /// Optionally, clear the cache (e.g., when timeline changes)
     pub fn clear_cache(&mut self) {
         self.frame_cache.clear();
+        self.cache_generation += 1;
     }
 
     /// Decode a single video frame from a file at a given timestamp using GStreamer.
-    /// Returns RGBA pixel data if successful.
-    fn decode_video_frame(path: &str, timestamp: f64, width: u32, height: u32) -> Option<Vec<u8>> {
+    /// Returns RGBA pixel data if successful; a panic while decoding counts as failure.
+    pub(crate) fn decode_video_frame(
+        path: &str,
+        timestamp: f64,
+        width: u32,
+        height: u32,
+        seek_mode: SeekMode,
+        backend: DecodeBackend,
+        colors: ColorConversion,
+    ) -> Option<Vec<u8>> {
+        catch_decode_panic(path, || {
+            Self::decode_video_frame_unguarded(
+                path, timestamp, width, height, seek_mode, backend, colors,
+            )
+        })
+    }
+
+    fn decode_video_frame_unguarded(
+        path: &str,
+        timestamp: f64,
+        width: u32,
+        height: u32,
+        seek_mode: SeekMode,
+        backend: DecodeBackend,
+        colors: ColorConversion,
+    ) -> Option<Vec<u8>> {
         let _ = gst::init(); // Safe to call multiple times
 
         // Debug: Check file existence and print seek time
         if !std::path::Path::new(path).exists() {
-            println!("Video file does not exist: {}", path);
+            log::warn!("Video file does not exist: {}", path);
             return None;
         }
 
-        println!(
+        log::debug!(
             "Decoding frame from {} at timestamp {} (width {}, height {})",
-            path, timestamp, width, height
+            path,
+            timestamp,
+            width,
+            height
         );
 
         let pipeline_str = format!(
-            "filesrc location=\"{}\" ! decodebin ! videoconvert ! videoscale ! video/x-raw,format=RGBA,width={},height={} ! appsink name=sink sync=false",
-            path, width, height
+            "filesrc location=\"{}\" ! decodebin name=dec ! {} ! appsink name=sink sync=false",
+            path,
+            colors.rgba_pipeline(width, height)
         );
 
         let pipeline = match gst::parse::launch(&pipeline_str) {
             Ok(pipeline) => pipeline.downcast::<gst::Pipeline>().ok()?,
             Err(e) => {
-                println!("Failed to create pipeline: {}", e);
+                log::warn!("Failed to create pipeline: {}", e);
                 return None;
             }
         };
 
+        if let Some(decodebin) = pipeline.by_name("dec") {
+            select_decoders(&decodebin, backend);
+        }
+
         let sink = pipeline
             .by_name("sink")?
             .clone()
@@ -166,7 +649,7 @@ impl TimelineRenderer {
 
         // Set pipeline to PAUSED and wait for state change
         if let Err(e) = pipeline.set_state(gst::State::Paused) {
-            println!("Failed to set pipeline to PAUSED: {}", e);
+            log::warn!("Failed to set pipeline to PAUSED: {}", e);
             return None;
         }
 
@@ -175,12 +658,14 @@ impl TimelineRenderer {
             pipeline.state(Some(gst::ClockTime::from_seconds(5)));
         match (state_change_result, state, pending) {
             (Ok(gst::StateChangeSuccess::Success), gst::State::Paused, _) => {
-                println!("Pipeline reached PAUSED state");
+                log::debug!("Pipeline reached PAUSED state");
             }
             (result, state, pending) => {
-                println!(
+                log::warn!(
                     "Pipeline failed to reach PAUSED state: {:?}, current state: {:?}, pending: {:?}",
-                    result, state, pending
+                    result,
+                    state,
+                    pending
                 );
                 pipeline.set_state(gst::State::Null).ok();
                 return None;
@@ -189,22 +674,22 @@ impl TimelineRenderer {
 
         // Perform seek
         let seek_time_ns = (timestamp * 1_000_000_000.0) as u64;
-        println!("Seeking to {} ns ({} seconds)", seek_time_ns, timestamp);
+        log::debug!("Seeking to {} ns ({} seconds)", seek_time_ns, timestamp);
 
         let seek_result = pipeline.seek_simple(
-            gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+            seek_flags_for(seek_mode),
             gst::ClockTime::from_nseconds(seek_time_ns),
         );
 
         if let Err(e) = seek_result {
-            println!("Seek failed: {}", e);
+            log::warn!("Seek failed: {}", e);
             pipeline.set_state(gst::State::Null).ok();
             return None;
         }
 
         // Set to PLAYING and wait for state change
         if let Err(e) = pipeline.set_state(gst::State::Playing) {
-            println!("Failed to set pipeline to PLAYING: {}", e);
+            log::warn!("Failed to set pipeline to PLAYING: {}", e);
             pipeline.set_state(gst::State::Null).ok();
             return None;
         }
@@ -214,12 +699,14 @@ impl TimelineRenderer {
             pipeline.state(Some(gst::ClockTime::from_seconds(2)));
         match (state_change_result, state, pending) {
             (Ok(gst::StateChangeSuccess::Success), gst::State::Playing, _) => {
-                println!("Pipeline reached PLAYING state");
+                log::debug!("Pipeline reached PLAYING state");
             }
             (result, state, pending) => {
-                println!(
+                log::warn!(
                     "Pipeline failed to reach PLAYING state: {:?}, current state: {:?}, pending: {:?}",
-                    result, state, pending
+                    result,
+                    state,
+                    pending
                 );
                 pipeline.set_state(gst::State::Null).ok();
                 return None;
@@ -230,7 +717,7 @@ impl TimelineRenderer {
         let sample = match Self::pull_sample_with_timeout(&sink, Duration::from_secs(5)) {
             Some(sample) => sample,
             None => {
-                println!("Failed to pull sample from appsink");
+                log::warn!("Failed to pull sample from appsink");
                 pipeline.set_state(gst::State::Null).ok();
                 return None;
             }
@@ -240,11 +727,9 @@ impl TimelineRenderer {
         pipeline.set_state(gst::State::Null).ok();
 
         // Extract buffer data
-        let buffer = sample.buffer()?;
-        let map = buffer.map_readable().ok()?;
-        let data = map.as_slice().to_vec();
+        let data = sample_rgba(&sample)?;
 
-        println!("Successfully decoded frame buffer size: {}", data.len());
+        log::debug!("Successfully decoded frame buffer size: {}", data.len());
         Some(data)
     }
 
@@ -260,7 +745,7 @@ impl TimelineRenderer {
 
             // Check if we've timed out
             if start_time.elapsed() > timeout {
-                println!("Timeout waiting for sample");
+                log::warn!("Timeout waiting for sample");
                 return None;
             }
 
@@ -275,20 +760,24 @@ impl TimelineRenderer {
         timestamp: f64,
         width: u32,
         height: u32,
+        seek_mode: SeekMode,
+        colors: ColorConversion,
     ) -> Option<Vec<u8>> {
         if !std::path::Path::new(path).exists() {
-            println!("Video file does not exist: {}", path);
+            log::warn!("Video file does not exist: {}", path);
             return None;
         }
 
-        println!(
+        log::debug!(
             "Decoding frame from {} at timestamp {} using bus method",
-            path, timestamp
+            path,
+            timestamp
         );
 
         let pipeline_str = format!(
-            "filesrc location=\"{}\" ! decodebin ! videoconvert ! videoscale ! video/x-raw,format=RGBA,width={},height={} ! appsink name=sink sync=false",
-            path, width, height
+            "filesrc location=\"{}\" ! decodebin ! {} ! appsink name=sink sync=false",
+            path,
+            colors.rgba_pipeline(width, height)
         );
 
         let pipeline = gst::parse::launch(&pipeline_str)
@@ -304,56 +793,41 @@ impl TimelineRenderer {
 
         // Set to PAUSED
         if let Err(e) = pipeline.set_state(gst::State::Paused) {
-            println!("Failed to set pipeline to PAUSED: {}", e);
+            log::warn!("Failed to set pipeline to PAUSED: {}", e);
             return None;
         }
 
         // Wait for ASYNC_DONE message
-        let mut preroll_complete = false;
-        for msg in bus.iter_timed(gst::ClockTime::from_seconds(5)) {
-            match msg.view() {
-                gst::MessageView::AsyncDone(_) => {
-                    println!("Pipeline preroll complete");
-                    preroll_complete = true;
-                    break;
-                }
-                gst::MessageView::Error(err) => {
-                    println!("Pipeline error during preroll: {}", err.error());
-                    pipeline.set_state(gst::State::Null).ok();
-                    return None;
-                }
-                gst::MessageView::Warning(warn) => {
-                    println!("Pipeline warning: {}", warn.error());
-                }
-                _ => {}
-            }
-        }
-
-        if !preroll_complete {
-            println!("Pipeline preroll timed out");
+        if let Err(e) = drain_bus_until(
+            &bus,
+            |msg| matches!(msg.view(), gst::MessageView::AsyncDone(_)),
+            Some(Duration::from_secs(5)),
+        ) {
+            log::warn!("Pipeline preroll failed: {}", e);
             pipeline.set_state(gst::State::Null).ok();
             return None;
         }
+        log::debug!("Pipeline preroll complete");
 
         // Seek
         let seek_time_ns = (timestamp * 1_000_000_000.0) as u64;
-        println!("Seeking to {} ns ({} seconds)", seek_time_ns, timestamp);
+        log::debug!("Seeking to {} ns ({} seconds)", seek_time_ns, timestamp);
 
         if pipeline
             .seek_simple(
-                gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE,
+                seek_flags_for(seek_mode),
                 gst::ClockTime::from_nseconds(seek_time_ns),
             )
             .is_err()
         {
-            println!("Seek failed");
+            log::warn!("Seek failed");
             pipeline.set_state(gst::State::Null).ok();
             return None;
         }
 
         // Set to PLAYING
         if let Err(e) = pipeline.set_state(gst::State::Playing) {
-            println!("Failed to set pipeline to PLAYING: {}", e);
+            log::warn!("Failed to set pipeline to PLAYING: {}", e);
             pipeline.set_state(gst::State::Null).ok();
             return None;
         }
@@ -365,7 +839,7 @@ impl TimelineRenderer {
         let sample = match Self::pull_sample_with_timeout(&sink, Duration::from_secs(3)) {
             Some(sample) => sample,
             None => {
-                println!("Failed to pull sample using bus method");
+                log::warn!("Failed to pull sample using bus method");
                 pipeline.set_state(gst::State::Null).ok();
                 return None;
             }
@@ -373,46 +847,22 @@ impl TimelineRenderer {
 
         pipeline.set_state(gst::State::Null).ok();
 
-        let buffer = sample.buffer()?;
-        let map = buffer.map_readable().ok()?;
-        let data = map.as_slice().to_vec();
+        let data = sample_rgba(&sample)?;
 
-        println!(
+        log::debug!(
             "Successfully decoded frame using bus method, buffer size: {}",
             data.len()
         );
         Some(data)
     }
 
-    /// Utility method to get video file duration
+    /// Length of a video file in seconds, probed with the GStreamer discoverer
     fn get_video_duration(path: &str) -> Option<f64> {
         let _ = gst::init();
-
-        if !std::path::Path::new(path).exists() {
-            return None;
-        }
-
-        let pipeline_str = format!("filesrc location=\"{}\" ! decodebin ! fakesink", path);
-
-        let pipeline = gst::parse::launch(&pipeline_str)
-            .ok()?
-            .downcast::<gst::Pipeline>()
-            .ok()?;
-
-        pipeline.set_state(gst::State::Paused).ok()?;
-
-        // Wait for state change
-        let (state_change_result, _state, _pending) =
-            pipeline.state(Some(gst::ClockTime::from_seconds(5)));
-        match state_change_result {
-            Ok(gst::StateChangeSuccess::Success) => {}
-            _ => return None,
-        }
-
-        let duration = pipeline.query_duration::<gst::ClockTime>()?;
-        pipeline.set_state(gst::State::Null).ok();
-
-        Some(duration.seconds() as f64)
+        let uri = gst::glib::filename_to_uri(std::fs::canonicalize(path).ok()?, None).ok()?;
+        let discoverer = gst_pbutils::Discoverer::new(gst::ClockTime::from_seconds(5)).ok()?;
+        let duration = discoverer.discover_uri(&uri).ok()?.duration()?;
+        Some(duration.nseconds() as f64 / 1e9)
     }
 
     /// Validate that the timestamp is within the video duration
@@ -430,30 +880,342 @@ impl TimelineRenderer {
         timestamp: f64,
         width: u32,
         height: u32,
+        seek_mode: SeekMode,
+        backend: DecodeBackend,
+        colors: ColorConversion,
     ) -> Option<Vec<u8>> {
-        println!("Enhanced decode attempt for {} at {}", path, timestamp);
+        log::debug!("Enhanced decode attempt for {} at {}", path, timestamp);
 
         // Validate timestamp
         if !Self::validate_timestamp(path, timestamp) {
-            println!("Invalid timestamp {} for video {}", timestamp, path);
+            log::warn!("Invalid timestamp {} for video {}", timestamp, path);
             return None;
         }
 
         // Try primary method first
-        if let Some(data) = Self::decode_video_frame(path, timestamp, width, height) {
+        if let Some(data) =
+            Self::decode_video_frame(path, timestamp, width, height, seek_mode, backend, colors)
+        {
             return Some(data);
         }
 
-        println!("Primary decode failed, trying bus method");
+        log::warn!("Primary decode failed, trying bus method");
 
         // Fallback to bus method
-        if let Some(data) = Self::decode_video_frame_with_bus(path, timestamp, width, height) {
+        if let Some(data) =
+            Self::decode_video_frame_with_bus(path, timestamp, width, height, seek_mode, colors)
+        {
             return Some(data);
         }
 
-        println!("All decode methods failed for {} at {}", path, timestamp);
+        log::warn!("All decode methods failed for {} at {}", path, timestamp);
         None
     }
 
     // Add audio rendering, effect processing, etc. as needed
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seek_time_stays_inside_the_source() {
+        let frame = 1.0 / 25.0;
+        assert_eq!(clamp_seek_time(3.0, Some(10.0), frame), Some(3.0));
+        // Past the end: the last frame, also well past the old one-hour cap
+        assert_eq!(clamp_seek_time(12.0, Some(10.0), frame), Some(10.0 - frame));
+        assert_eq!(clamp_seek_time(5400.0, Some(7200.0), frame), Some(5400.0));
+        // Before the start of the source: nothing to show
+        assert_eq!(clamp_seek_time(-0.5, Some(10.0), frame), None);
+        // Unknown length: seek as asked
+        assert_eq!(clamp_seek_time(42.0, None, frame), Some(42.0));
+    }
+
+    #[test]
+    fn test_seek_past_the_end_of_the_sample_clamps_to_its_length() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/sample.mp4");
+        // Needs GStreamer's MP4 demuxer
+        let Some(duration) = TimelineRenderer::get_video_duration(path) else {
+            return;
+        };
+        assert!((duration - 13.696).abs() < 0.05, "{}", duration);
+        let frame = 1.0 / 30.0;
+        assert_eq!(
+            clamp_seek_time(20.0, Some(duration), frame),
+            Some(duration - frame)
+        );
+    }
+
+    #[test]
+    fn test_decode_panic_returns_fallback() {
+        let decoded: Option<Vec<u8>> = catch_decode_panic("broken.mp4", || {
+            panic!("corrupt frame");
+        });
+        assert_eq!(decoded, None);
+        assert_eq!(
+            catch_decode_panic("good.mp4", || Some(vec![1u8])),
+            Some(vec![1])
+        );
+        // Still fine after recovering
+        assert_eq!(
+            catch_decode_panic("broken.mp4", || -> Option<u8> { panic!("{}", 42) }),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pack_rgba_rows_drops_padding() {
+        // 2x3 frame with 4 bytes of padding after each 8-byte row
+        let padded: Vec<u8> = (0..3u8)
+            .flat_map(|y| {
+                let mut row: Vec<u8> = (0..8).map(|x| y * 10 + x).collect();
+                row.extend([0xEE; 4]);
+                row
+            })
+            .collect();
+        let packed = pack_rgba_rows(&padded, 2, 3, 12).unwrap();
+        assert_eq!(packed.len(), 2 * 3 * 4);
+        assert_eq!(&packed[..8], &[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(&packed[16..], &[20, 21, 22, 23, 24, 25, 26, 27]);
+        assert!(!packed.contains(&0xEE));
+
+        // The last row needs no padding after it
+        assert_eq!(pack_rgba_rows(&padded[..32], 2, 3, 12), Some(packed));
+        // Too short, or a stride narrower than a row
+        assert_eq!(pack_rgba_rows(&padded[..30], 2, 3, 12), None);
+        assert_eq!(pack_rgba_rows(&padded, 4, 3, 12), None);
+        // Already packed
+        assert_eq!(pack_rgba_rows(&[1; 16], 2, 2, 8), Some(vec![1; 16]));
+    }
+
+    #[test]
+    fn test_seek_flags_for_mode() {
+        let accurate = seek_flags_for(SeekMode::Accurate);
+        assert!(accurate.contains(gst::SeekFlags::FLUSH | gst::SeekFlags::ACCURATE));
+        assert!(!accurate.contains(gst::SeekFlags::KEY_UNIT));
+
+        let fast = seek_flags_for(SeekMode::Fast);
+        assert!(fast.contains(gst::SeekFlags::FLUSH | gst::SeekFlags::KEY_UNIT));
+        assert!(!fast.contains(gst::SeekFlags::ACCURATE));
+    }
+
+    #[test]
+    fn test_returning_to_accurate_clears_cache() {
+        let mut renderer =
+            TimelineRenderer::new(Arc::new(RwLock::new(Timeline::new())), 4, 4, 30.0);
+        assert_eq!(renderer.seek_mode, SeekMode::Accurate);
+        renderer.set_seek_mode(SeekMode::Fast);
+        renderer.render_frame(0.0);
+        assert_eq!(renderer.frame_cache.len(), 1);
+
+        renderer.set_seek_mode(SeekMode::Fast);
+        assert_eq!(renderer.frame_cache.len(), 1);
+        renderer.set_seek_mode(SeekMode::Accurate);
+        assert!(renderer.frame_cache.is_empty());
+    }
+
+    #[test]
+    fn test_effects_bypass_keeps_clip_effects() {
+        use crate::types::media::Effect;
+        let mut renderer =
+            TimelineRenderer::new(Arc::new(RwLock::new(Timeline::new())), 2, 1, 30.0);
+        let mut clip: VideoClip = serde_json::from_str(
+            r#"{ "id": "v1", "asset_path": "v.mp4", "in_point": 0.0, "out_point": 2.0,
+                 "start_time": 0.0, "duration": 2.0,
+                 "metadata": { "resolution": [2, 1], "frame_rate": 30.0, "codec": "h264" } }"#,
+        )
+        .unwrap();
+        clip.effects = vec![Effect::Brightness(0.25)];
+        let source = vec![40, 80, 120, 255, 10, 20, 30, 255];
+        let finish = |renderer: &TimelineRenderer| {
+            let mut frame = source.clone();
+            renderer.finish_clip_frame(&clip, &mut frame, 0.5);
+            frame
+        };
+        assert_ne!(finish(&renderer), source);
+
+        renderer.render_frame(0.0);
+        renderer.set_effects_bypass(true, &HashSet::new());
+        assert!(renderer.frame_cache.is_empty());
+        assert_eq!(finish(&renderer), source);
+
+        // Just this clip
+        let only_v1 = ["v1".to_string()].into_iter().collect();
+        renderer.set_effects_bypass(false, &only_v1);
+        assert_eq!(finish(&renderer), source);
+        renderer.set_effects_bypass(false, &HashSet::new());
+        assert_ne!(finish(&renderer), source);
+        assert_eq!(clip.effects, vec![Effect::Brightness(0.25)]);
+    }
+
+    #[test]
+    fn test_disabled_video_renders_black_without_decoding() {
+        use crate::types::track::{Track, VideoTrack};
+        let clip: VideoClip = serde_json::from_str(
+            r#"{ "id": "v1", "asset_path": "/no/such/video.mp4", "in_point": 0.0,
+                 "out_point": 2.0, "start_time": 0.0, "duration": 2.0,
+                 "metadata": { "resolution": [2, 1], "frame_rate": 30.0, "codec": "h264" } }"#,
+        )
+        .unwrap();
+        let mut timeline = Timeline::new();
+        timeline.tracks_mut().push(Track::Video(VideoTrack {
+            id: "v".to_string(),
+            name: "V".to_string(),
+            clips: vec![clip],
+            muted: false,
+            sync_locked: false,
+        }));
+        timeline.invalidate_index();
+        let mut renderer = TimelineRenderer::new(Arc::new(RwLock::new(timeline)), 2, 1, 30.0);
+
+        renderer.set_video_disabled(true);
+        let frame = renderer.render_frame(0.5);
+        assert_eq!(renderer.decode_attempts, 0);
+        assert_eq!(frame.data, vec![0u8; 8]);
+
+        // Back on, the clip is decoded again
+        renderer.set_video_disabled(false);
+        renderer.render_frame(0.5);
+        assert_eq!(renderer.decode_attempts, 1);
+    }
+
+    #[test]
+    fn test_unavailable_decode_backend_falls_back_to_software() {
+        if gst::init().is_err() {
+            return;
+        }
+        let mut renderer =
+            TimelineRenderer::new(Arc::new(RwLock::new(Timeline::new())), 4, 4, 30.0);
+        assert_eq!(renderer.decode_backend, DecodeBackend::Software);
+        for backend in [
+            DecodeBackend::VaApi,
+            DecodeBackend::D3d11,
+            DecodeBackend::NvDec,
+        ] {
+            let expected = if !backend.autoplug_decoders().is_empty() {
+                backend
+            } else {
+                DecodeBackend::Software
+            };
+            assert_eq!(renderer.set_decode_backend(backend), expected);
+            assert_eq!(renderer.decode_backend, expected);
+        }
+        assert_eq!(
+            renderer.set_decode_backend(DecodeBackend::Software),
+            DecodeBackend::Software
+        );
+
+        // Decoding still works after the fallback (needs an encoder to make a source)
+        if !crate::ops::plugins::check_plugins().all_available()
+            || gst::ElementFactory::find("videotestsrc").is_none()
+        {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("source.mp4");
+        let pipeline = gst::parse::launch(&format!(
+            "videotestsrc num-buffers=15 ! video/x-raw,width=64,height=48,framerate=30/1 ! x264enc ! mp4mux ! filesink location=\"{}\"",
+            path.display()
+        ))
+        .unwrap();
+        pipeline.set_state(gst::State::Playing).unwrap();
+        let bus = pipeline.bus().unwrap();
+        bus.timed_pop_filtered(
+            gst::ClockTime::from_seconds(10),
+            &[gst::MessageType::Eos, gst::MessageType::Error],
+        );
+        pipeline.set_state(gst::State::Null).unwrap();
+
+        let backend = resolve_decode_backend(DecodeBackend::NvDec);
+        let frame = TimelineRenderer::decode_video_frame(
+            &path.to_string_lossy(),
+            0.2,
+            32,
+            24,
+            SeekMode::Accurate,
+            backend,
+            ColorConversion::default(),
+        )
+        .expect("frame decodes after falling back");
+        assert_eq!(frame.len(), 32 * 24 * 4);
+    }
+
+    #[test]
+    fn test_color_conversion_pipeline() {
+        let into_srgb = |source| ColorConversion {
+            source,
+            working: ColorSpace::Srgb,
+        };
+        // Same or undeclared space: plain conversion, tagged with the working space
+        for colors in [into_srgb(Some(ColorSpace::Srgb)), into_srgb(None)] {
+            assert!(!colors.needs_remap());
+            assert_eq!(
+                colors.rgba_pipeline(64, 48),
+                "videoconvert ! videoscale ! \
+                 video/x-raw,format=RGBA,width=64,height=48,colorimetry=sRGB"
+            );
+        }
+        // BT.709 footage in an sRGB project has its transfer curve remapped
+        let colors = into_srgb(Some(ColorSpace::Bt709));
+        assert!(colors.needs_remap());
+        assert_eq!(
+            colors.rgba_pipeline(64, 48),
+            "videoconvert gamma-mode=remap primaries-mode=fast ! videoscale ! \
+             video/x-raw,format=RGBA,width=64,height=48,colorimetry=sRGB"
+        );
+        let colors = ColorConversion {
+            source: Some(ColorSpace::Srgb),
+            working: ColorSpace::Bt709,
+        };
+        assert!(colors.rgba_pipeline(8, 8).ends_with("colorimetry=bt709"));
+
+        assert_eq!(ColorSpace::from_name("sRGB"), Some(ColorSpace::Srgb));
+        assert_eq!(ColorSpace::from_name("Rec.709"), Some(ColorSpace::Bt709));
+        assert_eq!(ColorSpace::from_name("ACEScg"), None);
+        assert_eq!(
+            ColorSpace::from_colorimetry("bt709"),
+            Some(ColorSpace::Bt709)
+        );
+        assert_eq!(ColorSpace::from_colorimetry("bt2020"), None);
+    }
+
+    #[test]
+    fn test_sequence_file_name() {
+        assert_eq!(sequence_file_name("frame_#####.png", 1), "frame_00001.png");
+        assert_eq!(sequence_file_name("shot##_v1.png", 7), "shot07_v1.png");
+        // Numbers wider than the run aren't truncated
+        assert_eq!(sequence_file_name("f#.png", 12), "f12.png");
+        assert_eq!(sequence_file_name("plate.png", 3), "plate_00003.png");
+    }
+
+    #[test]
+    fn test_export_image_sequence_writes_numbered_frames() {
+        let mut timeline = Timeline::new();
+        timeline.text_clips.push(crate::types::media::TextClip::new(
+            "title".to_string(),
+            "Hi".to_string(),
+            0.0,
+            5.0,
+        ));
+        let mut renderer = TimelineRenderer::new(Arc::new(RwLock::new(timeline)), 32, 18, 10.0);
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("frames");
+
+        let count = renderer
+            .export_image_sequence(&out, 2.0..3.0, "frame_#####.png")
+            .unwrap();
+
+        assert_eq!(count, 10);
+        let mut names: Vec<String> = std::fs::read_dir(&out)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        let expected: Vec<String> = (1..=10).map(|i| format!("frame_{:05}.png", i)).collect();
+        assert_eq!(names, expected);
+        let image = image::open(out.join("frame_00010.png")).unwrap();
+        assert_eq!((image.width(), image.height()), (32, 18));
+        assert!(renderer.frame_cache.is_empty());
+    }
+}
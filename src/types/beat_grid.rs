@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+/// A musical tempo grid for snapping edits to beats
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BeatGrid {
+    /// Beats per minute
+    pub bpm: f64,
+    /// Time of the first beat in seconds
+    pub offset: f64,
+}
+
+impl BeatGrid {
+    pub fn new(bpm: f64, offset: f64) -> Self {
+        BeatGrid { bpm, offset }
+    }
+
+    /// Length of one beat in seconds (0.0 for a non-positive tempo)
+    pub fn beat_duration(&self) -> f64 {
+        if self.bpm > 0.0 { 60.0 / self.bpm } else { 0.0 }
+    }
+
+    /// Time of the beat closest to `time`. Returns `time` for a non-positive tempo.
+    pub fn nearest_beat(&self, time: f64) -> f64 {
+        let beat = self.beat_duration();
+        if beat <= 0.0 {
+            return time;
+        }
+        self.offset + ((time - self.offset) / beat).round() * beat
+    }
+
+    /// Beat times within `start..=end`, in order
+    pub fn beats_in(&self, start: f64, end: f64) -> Vec<f64> {
+        let beat = self.beat_duration();
+        if beat <= 0.0 || end < start {
+            return Vec::new();
+        }
+        let first = ((start - self.offset) / beat).ceil() as i64;
+        let last = ((end - self.offset) / beat).floor() as i64;
+        (first..=last)
+            .map(|i| self.offset + i as f64 * beat)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_beat_120_bpm() {
+        let grid = BeatGrid::new(120.0, 0.0);
+        assert_eq!(grid.beat_duration(), 0.5);
+        assert_eq!(grid.nearest_beat(0.0), 0.0);
+        assert_eq!(grid.nearest_beat(0.2), 0.0);
+        assert_eq!(grid.nearest_beat(0.3), 0.5);
+        assert_eq!(grid.nearest_beat(10.74), 10.5);
+        assert_eq!(grid.nearest_beat(10.76), 11.0);
+    }
+
+    #[test]
+    fn test_nearest_beat_with_offset() {
+        let grid = BeatGrid::new(120.0, 0.1);
+        assert!((grid.nearest_beat(0.3) - 0.1).abs() < 1e-9);
+        assert!((grid.nearest_beat(0.4) - 0.6).abs() < 1e-9);
+        assert!((grid.nearest_beat(5.0) - 5.1).abs() < 1e-9);
+        // The grid extends back before the first beat
+        assert!((grid.nearest_beat(-0.3) - -0.4).abs() < 1e-9);
+
+        let beats = grid.beats_in(0.0, 1.6);
+        assert_eq!(beats.len(), 4);
+        assert!((beats[0] - 0.1).abs() < 1e-9 && (beats[3] - 1.6).abs() < 1e-9);
+
+        // No tempo, no grid
+        let none = BeatGrid::new(0.0, 0.0);
+        assert_eq!(none.nearest_beat(1.23), 1.23);
+        assert!(none.beats_in(0.0, 10.0).is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+
+/// A keyframe: `value` at `time` seconds from the start of the owning clip
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnvelopePoint {
+    pub time: f64,
+    pub value: f32,
+}
+
+/// A parameter curve over a clip, linearly interpolated between keyframes.
+/// Points are kept sorted by time.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Envelope {
+    points: Vec<EnvelopePoint>,
+}
+
+impl Envelope {
+    pub fn new() -> Self {
+        Envelope { points: Vec::new() }
+    }
+
+    pub fn points(&self) -> &[EnvelopePoint] {
+        &self.points
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Insert a keyframe, replacing any existing one at the same time.
+    pub fn add_point(&mut self, time: f64, value: f32) {
+        let point = EnvelopePoint { time, value };
+        match self.points.binary_search_by(|p| p.time.total_cmp(&time)) {
+            Ok(i) => self.points[i] = point,
+            Err(i) => self.points.insert(i, point),
+        }
+    }
+
+    /// Value at `time`, holding the first/last value outside the keyframes.
+    /// None if the envelope has no points.
+    pub fn value_at(&self, time: f64) -> Option<f32> {
+        let after = self.points.partition_point(|p| p.time <= time);
+        match (
+            after.checked_sub(1).map(|i| self.points[i]),
+            self.points.get(after),
+        ) {
+            (Some(a), Some(b)) => {
+                let t = ((time - a.time) / (b.time - a.time)) as f32;
+                Some(a.value + (b.value - a.value) * t)
+            }
+            (Some(a), None) => Some(a.value),
+            (None, Some(b)) => Some(b.value),
+            (None, None) => None,
+        }
+    }
+
+    /// Move every keyframe `offset` seconds earlier, for a clip whose start moved
+    /// `offset` seconds into its old length
+    pub fn shift(&mut self, offset: f64) {
+        for point in &mut self.points {
+            point.time -= offset;
+        }
+    }
+
+    /// First keyframe strictly after `time`
+    pub fn next_point_after(&self, time: f64) -> Option<&EnvelopePoint> {
+        self.points
+            .get(self.points.partition_point(|p| p.time <= time))
+    }
+
+    /// Last keyframe strictly before `time`
+    pub fn prev_point_before(&self, time: f64) -> Option<&EnvelopePoint> {
+        let i = self.points.partition_point(|p| p.time < time);
+        i.checked_sub(1).map(|i| &self.points[i])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope() -> Envelope {
+        let mut envelope = Envelope::new();
+        // Added out of order
+        envelope.add_point(4.0, 0.0);
+        envelope.add_point(1.0, 1.0);
+        envelope.add_point(2.5, 0.5);
+        envelope
+    }
+
+    #[test]
+    fn test_next_and_prev_point() {
+        let envelope = envelope();
+        assert_eq!(envelope.next_point_after(0.0).unwrap().time, 1.0);
+        assert_eq!(envelope.next_point_after(1.7).unwrap().time, 2.5);
+        assert_eq!(envelope.next_point_after(4.5), None);
+        assert_eq!(envelope.prev_point_before(3.0).unwrap().time, 2.5);
+        assert_eq!(envelope.prev_point_before(10.0).unwrap().time, 4.0);
+        assert_eq!(envelope.prev_point_before(0.5), None);
+    }
+
+    #[test]
+    fn test_next_and_prev_point_exactly_on_a_point() {
+        let envelope = envelope();
+        // Sitting on a keyframe moves past it, so repeated jumps make progress
+        assert_eq!(envelope.next_point_after(2.5).unwrap().time, 4.0);
+        assert_eq!(envelope.prev_point_before(2.5).unwrap().time, 1.0);
+        assert_eq!(envelope.next_point_after(4.0), None);
+        assert_eq!(envelope.prev_point_before(1.0), None);
+    }
+
+    #[test]
+    fn test_value_at_interpolates() {
+        let mut envelope = envelope();
+        assert_eq!(Envelope::new().value_at(1.0), None);
+        assert_eq!(envelope.value_at(0.0), Some(1.0));
+        assert_eq!(envelope.value_at(1.75), Some(0.75));
+        assert_eq!(envelope.value_at(4.0), Some(0.0));
+        assert_eq!(envelope.value_at(9.0), Some(0.0));
+
+        // Same time replaces the keyframe
+        envelope.add_point(2.5, 0.2);
+        assert_eq!(envelope.points().len(), 3);
+        assert_eq!(envelope.value_at(2.5), Some(0.2));
+    }
+}
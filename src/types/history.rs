@@ -0,0 +1,267 @@
+use crate::types::timeline::Timeline;
+
+/// Undo steps kept by default
+pub const DEFAULT_MAX_UNDO_STEPS: usize = 100;
+/// Default budget for the undo steps' snapshots, in serialized bytes
+pub const DEFAULT_MAX_UNDO_BYTES: usize = 64 * 1024 * 1024;
+
+/// A timeline state in the history and its serialized size
+#[derive(Debug, Clone)]
+struct Snapshot {
+    timeline: Timeline,
+    bytes: usize,
+}
+
+impl Snapshot {
+    fn new(timeline: Timeline) -> Self {
+        let bytes = serialized_size(&timeline);
+        Self { timeline, bytes }
+    }
+}
+
+fn serialized_size(timeline: &Timeline) -> usize {
+    serde_json::to_vec(timeline).map_or(0, |json| json.len())
+}
+
+/// Undo/redo history of timeline snapshots.
+///
+/// Call `record` after each edit. Continuous edits such as drags go inside
+/// `begin_transaction`/`commit`: everything recorded in between becomes a single
+/// undo step, taken when the transaction is committed. The oldest steps are dropped
+/// once there are more than `max_steps` or their snapshots exceed `max_bytes`.
+#[derive(Debug, Clone)]
+pub struct EditHistory {
+    undo_stack: Vec<Snapshot>,
+    redo_stack: Vec<Snapshot>,
+    /// Timeline as of the last recorded step
+    current: Snapshot,
+    /// Open transactions; they nest, and only the outermost commit records
+    transaction_depth: usize,
+    max_steps: usize,
+    max_bytes: usize,
+}
+
+impl EditHistory {
+    /// Start a history whose oldest state is `timeline`
+    pub fn new(timeline: &Timeline) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            current: Snapshot::new(timeline.clone()),
+            transaction_depth: 0,
+            max_steps: DEFAULT_MAX_UNDO_STEPS,
+            max_bytes: DEFAULT_MAX_UNDO_BYTES,
+        }
+    }
+
+    /// Forget all steps and start over from `timeline` (e.g. after opening a project),
+    /// keeping the limits
+    pub fn reset(&mut self, timeline: &Timeline) {
+        *self = Self {
+            max_steps: self.max_steps,
+            max_bytes: self.max_bytes,
+            ..Self::new(timeline)
+        };
+    }
+
+    /// Keep at most `max_steps` undo steps whose snapshots add up to at most
+    /// `max_bytes` serialized bytes, dropping the oldest ones right away if needed
+    pub fn set_limits(&mut self, max_steps: usize, max_bytes: usize) {
+        self.max_steps = max_steps;
+        self.max_bytes = max_bytes;
+        self.enforce_limits();
+    }
+
+    /// Drop the oldest undo steps until the stack fits the limits
+    fn enforce_limits(&mut self) {
+        let mut evicted = self.undo_stack.len().saturating_sub(self.max_steps);
+        let mut bytes: usize = self.undo_stack[evicted..].iter().map(|s| s.bytes).sum();
+        while bytes > self.max_bytes && evicted < self.undo_stack.len() {
+            bytes -= self.undo_stack[evicted].bytes;
+            evicted += 1;
+        }
+        self.undo_stack.drain(..evicted);
+    }
+
+    /// Record the timeline after an edit as a new undo step. Does nothing inside a
+    /// transaction or when the timeline didn't actually change.
+    pub fn record(&mut self, timeline: &Timeline) {
+        if self.in_transaction() || *timeline == self.current.timeline {
+            return;
+        }
+        let previous = std::mem::replace(&mut self.current, Snapshot::new(timeline.clone()));
+        self.undo_stack.push(previous);
+        self.redo_stack.clear();
+        self.enforce_limits();
+    }
+
+    /// Start coalescing edits into one step
+    pub fn begin_transaction(&mut self) {
+        self.transaction_depth += 1;
+    }
+
+    /// Close the transaction opened by `begin_transaction`. Closing the outermost one
+    /// records `timeline` as a single step.
+    pub fn commit(&mut self, timeline: &Timeline) {
+        self.transaction_depth = self.transaction_depth.saturating_sub(1);
+        self.record(timeline);
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.transaction_depth > 0
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Apply `f` to every timeline kept in the history, e.g. to rewrite media paths
+    /// after the project moved
+    pub fn map_timelines(&mut self, mut f: impl FnMut(&mut Timeline)) {
+        let snapshots = self.undo_stack.iter_mut().chain(&mut self.redo_stack);
+        for snapshot in snapshots.chain(std::iter::once(&mut self.current)) {
+            f(&mut snapshot.timeline);
+            snapshot.bytes = serialized_size(&snapshot.timeline);
+        }
+        self.enforce_limits();
+    }
+
+    /// Step back; returns the timeline to restore
+    pub fn undo(&mut self) -> Option<Timeline> {
+        let previous = self.undo_stack.pop()?;
+        let undone = std::mem::replace(&mut self.current, previous);
+        self.redo_stack.push(undone);
+        Some(self.current.timeline.clone())
+    }
+
+    /// Re-apply the last undone step; returns the timeline to restore
+    pub fn redo(&mut self) -> Option<Timeline> {
+        let next = self.redo_stack.pop()?;
+        let redone = std::mem::replace(&mut self.current, next);
+        self.undo_stack.push(redone);
+        Some(self.current.timeline.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::media::TextClip;
+
+    fn with_title_at(start: f64) -> Timeline {
+        let mut timeline = Timeline::new();
+        timeline.text_clips.push(TextClip::new(
+            "title".to_string(),
+            "Title".to_string(),
+            start,
+            2.0,
+        ));
+        timeline
+    }
+
+    #[test]
+    fn test_drag_transaction_is_one_undo_step() {
+        let before = with_title_at(1.0);
+        let mut history = EditHistory::new(&before);
+
+        history.begin_transaction();
+        for step in 1..=20 {
+            history.record(&with_title_at(1.0 + step as f64 * 0.1));
+        }
+        assert!(!history.can_undo());
+        let after = with_title_at(3.0);
+        history.commit(&after);
+        assert!(!history.in_transaction());
+
+        assert_eq!(history.undo(), Some(before.clone()));
+        assert!(!history.can_undo());
+        assert_eq!(history.redo(), Some(after));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_record_outside_transaction() {
+        let mut history = EditHistory::new(&with_title_at(0.0));
+        history.record(&with_title_at(1.0));
+        // No change, no step
+        history.record(&with_title_at(1.0));
+        history.record(&with_title_at(2.0));
+
+        assert_eq!(history.undo(), Some(with_title_at(1.0)));
+        // A new edit drops the redo branch
+        history.record(&with_title_at(5.0));
+        assert!(!history.can_redo());
+        assert_eq!(history.undo(), Some(with_title_at(1.0)));
+        assert_eq!(history.undo(), Some(with_title_at(0.0)));
+        assert_eq!(history.undo(), None);
+
+        // Committing an unchanged transaction adds nothing
+        let mut history = EditHistory::new(&with_title_at(0.0));
+        history.begin_transaction();
+        history.begin_transaction();
+        history.commit(&with_title_at(4.0));
+        assert!(history.in_transaction());
+        history.commit(&with_title_at(0.0));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn test_map_timelines_reaches_every_step() {
+        let mut history = EditHistory::new(&with_title_at(0.0));
+        history.record(&with_title_at(1.0));
+        history.record(&with_title_at(2.0));
+        history.undo();
+
+        history.map_timelines(|timeline| timeline.text_clips[0].start_time += 10.0);
+        assert_eq!(history.redo(), Some(with_title_at(12.0)));
+        assert_eq!(history.undo(), Some(with_title_at(11.0)));
+        assert_eq!(history.undo(), Some(with_title_at(10.0)));
+    }
+
+    #[test]
+    fn test_step_cap_drops_oldest_states() {
+        let mut history = EditHistory::new(&with_title_at(0.0));
+        history.set_limits(3, DEFAULT_MAX_UNDO_BYTES);
+        for start in 1..=5 {
+            history.record(&with_title_at(start as f64));
+        }
+        // States 0 and 1 were dropped; undo reaches back to 2
+        for start in [4.0, 3.0, 2.0] {
+            assert_eq!(history.undo(), Some(with_title_at(start)));
+        }
+        assert!(!history.can_undo());
+        assert_eq!(history.redo(), Some(with_title_at(3.0)));
+
+        // Tightening the limits drops steps at once
+        history.set_limits(0, DEFAULT_MAX_UNDO_BYTES);
+        assert!(!history.can_undo());
+        assert!(history.can_redo());
+    }
+
+    #[test]
+    fn test_byte_budget_drops_oldest_states() {
+        let snapshot_bytes = Snapshot::new(with_title_at(1.0)).bytes;
+        let mut history = EditHistory::new(&with_title_at(1.0));
+        // Room for two snapshots of this size
+        history.set_limits(DEFAULT_MAX_UNDO_STEPS, snapshot_bytes * 2);
+        for start in 2..=4 {
+            history.record(&with_title_at(start as f64));
+        }
+        assert_eq!(history.undo(), Some(with_title_at(3.0)));
+        assert_eq!(history.undo(), Some(with_title_at(2.0)));
+        assert_eq!(history.undo(), None);
+
+        // A new history after reset keeps the budget
+        history.reset(&with_title_at(1.0));
+        for start in 2..=4 {
+            history.record(&with_title_at(start as f64));
+        }
+        history.undo();
+        history.undo();
+        assert!(!history.can_undo());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::types::envelope::Envelope;
 use serde::{Deserialize, Serialize};
 
 pub trait Clip {
@@ -7,6 +8,97 @@ pub trait Clip {
     fn out_point(&self) -> f64;
     fn start_time(&self) -> f64;
     fn duration(&self) -> f64;
+
+    /// Whether the clip plays at `time`: from its start, up to but not including its end
+    fn is_active_at(&self, time: f64) -> bool {
+        time >= self.start_time() && time < self.start_time() + self.duration()
+    }
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+/// Keep a clip's source range inside `0.0..=source_duration`, with `duration`
+/// following the range at `speed`. Trimming the head (the out point end when
+/// `reversed`) moves the start so the clip's end stays put.
+fn clamp_times_to_source(
+    start_time: &mut f64,
+    in_point: &mut f64,
+    out_point: &mut f64,
+    duration: &mut f64,
+    speed: f64,
+    reversed: bool,
+    source_duration: f64,
+) {
+    let speed = if speed > 0.0 { speed } else { 1.0 };
+    let cut_in = (-*in_point).max(0.0);
+    *in_point = in_point.max(0.0);
+    let cut_out = (*out_point - source_duration).max(0.0);
+    *out_point = out_point.min(source_duration).max(*in_point);
+    *start_time += if reversed { cut_out } else { cut_in } / speed;
+    *duration = (*out_point - *in_point) / speed;
+}
+
+/// A per-clip video effect
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Effect {
+    /// Offset added to each color channel, -1.0..=1.0
+    Brightness(f32),
+    /// Contrast factor around mid gray (1.0 = unchanged)
+    Contrast(f32),
+    /// Saturation factor (0.0 = grayscale, 1.0 = unchanged)
+    Saturation(f32),
+    /// Blur radius in pixels
+    GaussianBlur(f32),
+}
+
+impl Effect {
+    /// Whether the effect is a color correction (as opposed to e.g. a blur)
+    pub fn is_color(&self) -> bool {
+        matches!(
+            self,
+            Effect::Brightness(_) | Effect::Contrast(_) | Effect::Saturation(_)
+        )
+    }
+}
+
+/// Clip properties to copy onto other clips; `None` fields are left untouched
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClipPropsPatch {
+    /// Constant opacity (0.0..=1.0), replacing any opacity keyframes
+    pub opacity: Option<f32>,
+    /// Playback speed; the clip's duration follows its source range
+    pub speed: Option<f64>,
+    /// Color corrections replacing the clip's current ones; other effects are kept
+    pub color: Option<Vec<Effect>>,
+}
+
+impl ClipPropsPatch {
+    /// Patch carrying all of `clip`'s properties. Opacity is only included when
+    /// it is constant over the clip.
+    pub fn from_clip(clip: &VideoClip) -> Self {
+        let opacity = match clip.opacity_envelope.points() {
+            [] => Some(1.0),
+            [point] => Some(point.value),
+            _ => None,
+        };
+        ClipPropsPatch {
+            opacity,
+            speed: Some(clip.speed),
+            color: Some(
+                clip.effects
+                    .iter()
+                    .copied()
+                    .filter(Effect::is_color)
+                    .collect(),
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -14,8 +106,50 @@ pub struct VideoMetadata {
     pub resolution: (u32, u32),
     pub frame_rate: f64,
     pub codec: String,
+    /// Color space reported by the source, if it declares one
+    #[serde(default)]
+    pub color_space: Option<ColorSpace>,
 }
 
+/// RGB color space footage is encoded in or composited in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    Bt709,
+}
+
+impl ColorSpace {
+    /// Parse a project setting such as "sRGB" or "BT.709"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "srgb" => Some(ColorSpace::Srgb),
+            "bt709" | "bt.709" | "rec709" | "rec.709" => Some(ColorSpace::Bt709),
+            _ => None,
+        }
+    }
+
+    /// Parse the `colorimetry` field of GStreamer video caps
+    pub fn from_colorimetry(colorimetry: &str) -> Option<Self> {
+        match colorimetry {
+            "sRGB" | "srgb" => Some(ColorSpace::Srgb),
+            "bt709" => Some(ColorSpace::Bt709),
+            _ => None,
+        }
+    }
+
+    /// Value for the `colorimetry` field of GStreamer video caps
+    pub fn colorimetry(self) -> &'static str {
+        match self {
+            ColorSpace::Srgb => "sRGB",
+            ColorSpace::Bt709 => "bt709",
+        }
+    }
+}
+
+/// Normalized `(left, top, right, bottom)` crop edges; `(0.0, 0.0, 1.0, 1.0)` is the whole frame
+pub type CropRect = (f32, f32, f32, f32);
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VideoClip {
     pub id: String,
@@ -24,9 +158,142 @@ pub struct VideoClip {
     pub out_point: f64,
     pub start_time: f64,
     pub duration: f64,
+    /// Disabled clips stay on the track but are skipped when rendering
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Effects applied by the renderer, in order
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+    /// Keyframed opacity (0.0..=1.0); fully opaque when empty
+    #[serde(default)]
+    pub opacity_envelope: Envelope,
+    /// Freeze frame: holds the source frame at `in_point` for the whole duration
+    /// (`out_point == in_point`)
+    #[serde(default)]
+    pub freeze: bool,
+    /// Playback speed of the source (2.0 = twice as fast);
+    /// `duration` is `(out_point - in_point) / speed`
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+    /// Speed ramp as (clip-local time, source time) keyframes, interpolated linearly
+    /// in between and held past either end; overrides `speed` and `reversed` when set
+    #[serde(default)]
+    pub time_map: Option<Vec<(f64, f64)>>,
+    /// Play the source range backwards, from `out_point` down to `in_point`
+    #[serde(default)]
+    pub reversed: bool,
+    /// Leave the clip's embedded audio out of the mix, keeping its picture
+    #[serde(default)]
+    pub mute_audio: bool,
+    /// Source region to show, scaled up to fill the frame; `None` shows the whole frame
+    #[serde(default)]
+    pub crop: Option<CropRect>,
+    /// Clips sharing a group id (such as the picture and sound of one file) move together
+    #[serde(default)]
+    pub group_id: Option<String>,
     pub metadata: VideoMetadata,
 }
 
+impl VideoClip {
+    /// Source timestamp shown at timeline time `time`
+    pub fn source_time_at(&self, time: f64) -> f64 {
+        if self.freeze {
+            self.in_point
+        } else {
+            self.source_time_remapped(time - self.start_time)
+        }
+    }
+
+    /// Source timestamp shown `local` seconds into the clip, following `time_map` when
+    /// it has at least two keyframes and the constant `speed` otherwise
+    pub fn source_time_remapped(&self, local: f64) -> f64 {
+        let Some(map) = self.time_map.as_deref().filter(|map| map.len() >= 2) else {
+            return if self.reversed {
+                self.out_point - local * self.speed
+            } else {
+                self.in_point + local * self.speed
+            };
+        };
+        let (first, last) = (map[0], map[map.len() - 1]);
+        if local <= first.0 {
+            return first.1;
+        }
+        if local >= last.0 {
+            return last.1;
+        }
+        // Keyframes are sorted by local time; interpolate between the pair around `local`
+        let next = map.partition_point(|(t, _)| *t <= local);
+        let ((t0, s0), (t1, s1)) = (map[next - 1], map[next]);
+        s0 + (local - t0) / (t1 - t0) * (s1 - s0)
+    }
+
+    /// Source times shown at the clip's start and end on the timeline;
+    /// the first is the later one when the clip is reversed
+    pub fn source_span(&self) -> (f64, f64) {
+        (
+            self.source_time_at(self.start_time),
+            self.source_time_at(self.start_time + self.duration),
+        )
+    }
+
+    /// Apply the set fields of `patch` to this clip
+    pub fn apply_patch(&mut self, patch: &ClipPropsPatch) {
+        if let Some(opacity) = patch.opacity {
+            self.opacity_envelope = Envelope::new();
+            if opacity < 1.0 {
+                self.opacity_envelope
+                    .add_point(0.0, opacity.clamp(0.0, 1.0));
+            }
+        }
+        if let Some(speed) = patch.speed.filter(|s| *s > 0.0) {
+            self.speed = speed;
+            if !self.freeze {
+                self.duration = (self.out_point - self.in_point) / speed;
+            }
+        }
+        if let Some(color) = &patch.color {
+            self.effects.retain(|e| !e.is_color());
+            self.effects.extend(color.iter().copied());
+        }
+    }
+
+    /// Fraction of the source used by the clip: `(in_point / source_duration,
+    /// out_point / source_duration)`. Points past the end of the source are
+    /// clamped to 1.0; check `exceeds_source` to flag them.
+    pub fn source_coverage(&self, source_duration: f64) -> (f64, f64) {
+        if source_duration <= 0.0 {
+            return (0.0, 0.0);
+        }
+        let fraction = |t: f64| (t / source_duration).clamp(0.0, 1.0);
+        (fraction(self.in_point), fraction(self.out_point))
+    }
+
+    /// Whether the clip's out point lies past the end of a source of `source_duration` seconds
+    pub fn exceeds_source(&self, source_duration: f64) -> bool {
+        self.out_point > source_duration + 1e-9
+    }
+
+    /// Clamp the in/out points to a source of `source_duration` seconds,
+    /// shortening the clip as needed.
+    pub fn clamp_to_source(&mut self, source_duration: f64) {
+        if self.freeze {
+            // A held frame's length doesn't depend on the source
+            self.in_point = self.in_point.clamp(0.0, source_duration);
+            self.out_point = self.in_point;
+            return;
+        }
+        clamp_times_to_source(
+            &mut self.start_time,
+            &mut self.in_point,
+            &mut self.out_point,
+            &mut self.duration,
+            self.speed,
+            self.reversed,
+            source_duration,
+        );
+    }
+}
+
 impl Clip for VideoClip {
     fn id(&self) -> &str {
         &self.id
@@ -61,9 +328,34 @@ pub struct AudioClip {
     pub out_point: f64,
     pub start_time: f64,
     pub duration: f64,
+    /// Disabled clips stay on the track but are skipped when rendering
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Clip gain in dB, applied before the track volume
+    #[serde(default)]
+    pub gain_db: f64,
+    /// Clips sharing a group id (such as the picture and sound of one file) move together
+    #[serde(default)]
+    pub group_id: Option<String>,
     pub metadata: AudioMetadata,
 }
 
+impl AudioClip {
+    /// Clamp the in/out points to a source of `source_duration` seconds,
+    /// shortening the clip as needed.
+    pub fn clamp_to_source(&mut self, source_duration: f64) {
+        clamp_times_to_source(
+            &mut self.start_time,
+            &mut self.in_point,
+            &mut self.out_point,
+            &mut self.duration,
+            1.0,
+            false,
+            source_duration,
+        );
+    }
+}
+
 impl Clip for AudioClip {
     fn id(&self) -> &str {
         &self.id
@@ -90,6 +382,59 @@ impl Clip for AudioClip {
     }
 }
 
+/// Horizontal alignment of a text clip's lines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum TextAlign {
+    Left,
+    #[default]
+    Center,
+    Right,
+}
+
+/// A title drawn by the renderer over the video tracks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextClip {
+    pub id: String,
+    /// Text to draw; `\n` starts a new line
+    pub text: String,
+    /// Font size in output pixels
+    pub font_size: f32,
+    /// RGBA text color
+    pub color: [u8; 4],
+    /// RGBA fill of a box behind the text, if any
+    pub background: Option<[u8; 4]>,
+    /// Anchor point as a fraction of the frame size (0.5, 0.5 = center).
+    /// The text block is centered vertically on it and aligned horizontally per `align`.
+    pub position: (f32, f32),
+    pub align: TextAlign,
+    pub start_time: f64,
+    pub duration: f64,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+impl TextClip {
+    /// White, centered 48px text with no background box
+    pub fn new(id: String, text: String, start_time: f64, duration: f64) -> Self {
+        TextClip {
+            id,
+            text,
+            font_size: 48.0,
+            color: [255, 255, 255, 255],
+            background: None,
+            position: (0.5, 0.5),
+            align: TextAlign::Center,
+            start_time,
+            duration,
+            enabled: true,
+        }
+    }
+
+    pub fn is_active_at(&self, time: f64) -> bool {
+        time >= self.start_time && time < self.start_time + self.duration
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AudioMetadata {
     pub sample_rate: u32,
@@ -97,3 +442,154 @@ pub struct AudioMetadata {
     pub codec: String,
     pub bitrate: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn video_clip(in_point: f64, out_point: f64) -> VideoClip {
+        VideoClip {
+            id: "v1".to_string(),
+            asset_path: "video.mp4".to_string(),
+            in_point,
+            out_point,
+            start_time: 0.0,
+            duration: out_point - in_point,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_source_coverage_fraction() {
+        let clip = video_clip(15.0, 45.0);
+        assert_eq!(clip.source_coverage(60.0), (0.25, 0.75));
+        assert!(!clip.exceeds_source(60.0));
+        assert_eq!(video_clip(0.0, 60.0).source_coverage(60.0), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_source_coverage_overflow_is_clamped() {
+        let clip = video_clip(30.0, 80.0);
+        assert_eq!(clip.source_coverage(60.0), (0.5, 1.0));
+        assert!(clip.exceeds_source(60.0));
+
+        // Entirely past the end
+        assert_eq!(video_clip(70.0, 80.0).source_coverage(60.0), (1.0, 1.0));
+        // Unknown source duration
+        assert_eq!(clip.source_coverage(0.0), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_clamp_to_source() {
+        let mut clip = video_clip(30.0, 80.0);
+        clip.start_time = 5.0;
+        clip.clamp_to_source(60.0);
+        assert_eq!((clip.in_point, clip.out_point), (30.0, 60.0));
+        assert_eq!((clip.start_time, clip.duration), (5.0, 30.0));
+
+        // A negative in point keeps the clip's end where it was
+        let mut clip = video_clip(-2.0, 4.0);
+        clip.start_time = 10.0;
+        clip.clamp_to_source(60.0);
+        assert_eq!((clip.in_point, clip.out_point), (0.0, 4.0));
+        assert_eq!((clip.start_time, clip.duration), (12.0, 4.0));
+    }
+
+    #[test]
+    fn test_clamp_to_source_at_double_speed() {
+        // 50s of source over 25s, 20s of it past the end of a 60s source
+        let mut clip = video_clip(30.0, 80.0);
+        clip.speed = 2.0;
+        clip.duration = 25.0;
+        clip.clamp_to_source(60.0);
+        assert_eq!((clip.in_point, clip.out_point), (30.0, 60.0));
+        assert_eq!((clip.start_time, clip.duration), (0.0, 15.0));
+
+        // 2s of missing source before 0 is 1s of timeline
+        let mut clip = video_clip(-2.0, 4.0);
+        clip.speed = 2.0;
+        clip.start_time = 10.0;
+        clip.clamp_to_source(60.0);
+        assert_eq!((clip.start_time, clip.duration), (11.0, 2.0));
+
+        // Reversed, the head shows the end of the range
+        let mut clip = video_clip(50.0, 64.0);
+        clip.speed = 2.0;
+        clip.reversed = true;
+        clip.start_time = 10.0;
+        clip.clamp_to_source(60.0);
+        assert_eq!((clip.in_point, clip.out_point), (50.0, 60.0));
+        assert_eq!((clip.start_time, clip.duration), (12.0, 5.0));
+    }
+
+    #[test]
+    fn test_clip_is_active_from_start_until_end() {
+        let mut video = video_clip(0.0, 2.0);
+        video.start_time = 3.0;
+        let audio = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "audio.wav".to_string(),
+            in_point: 0.0,
+            out_point: 2.0,
+            start_time: 3.0,
+            duration: 2.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+        let clips: [&dyn Clip; 2] = [&video, &audio];
+        for clip in clips {
+            assert!(!clip.is_active_at(2.999));
+            assert!(clip.is_active_at(3.0));
+            assert!(clip.is_active_at(4.999));
+            assert!(!clip.is_active_at(5.0));
+        }
+    }
+
+    #[test]
+    fn test_two_segment_speed_ramp() {
+        // Normal speed for 2s, then half speed for 4s
+        let mut clip = video_clip(10.0, 14.0);
+        clip.start_time = 5.0;
+        clip.duration = 6.0;
+        clip.time_map = Some(vec![(0.0, 10.0), (2.0, 12.0), (6.0, 14.0)]);
+
+        assert_eq!(clip.source_time_remapped(0.0), 10.0);
+        assert_eq!(clip.source_time_remapped(1.0), 11.0);
+        assert_eq!(clip.source_time_remapped(2.0), 12.0);
+        assert_eq!(clip.source_time_remapped(4.0), 13.0);
+        assert_eq!(clip.source_time_remapped(6.0), 14.0);
+        // Held past either end
+        assert_eq!(clip.source_time_remapped(-1.0), 10.0);
+        assert_eq!(clip.source_time_remapped(7.0), 14.0);
+        // Timeline times go through the map too
+        assert_eq!(clip.source_time_at(9.0), 13.0);
+        assert_eq!(clip.source_span(), (10.0, 14.0));
+
+        // Without a map the constant speed applies
+        clip.time_map = None;
+        clip.speed = 0.5;
+        assert_eq!(clip.source_time_remapped(4.0), 12.0);
+    }
+}
@@ -12,6 +12,22 @@ pub enum MediaItem {
     // ImageProp(String),
 }
 
+impl MediaItem {
+    pub fn file_descriptor(&self) -> &FileDescriptor {
+        match self {
+            MediaItem::AudioItem(a) => &a.file_descriptor,
+            MediaItem::VideoItem(v) => &v.file_descriptor,
+        }
+    }
+
+    pub fn file_descriptor_mut(&mut self) -> &mut FileDescriptor {
+        match self {
+            MediaItem::AudioItem(a) => &mut a.file_descriptor,
+            MediaItem::VideoItem(v) => &mut v.file_descriptor,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AudioProp {
     pub file_descriptor: FileDescriptor,
@@ -29,6 +45,9 @@ pub struct FileDescriptor {
     pub path: String,
     pub size: u64,
     pub mime_type: String,
+    /// Content signature used to find the file again after it is moved or renamed
+    #[serde(default)]
+    pub fingerprint: Option<Fingerprint>,
 }
 
 impl FileDescriptor {
@@ -38,10 +57,39 @@ impl FileDescriptor {
             path,
             size,
             mime_type,
+            fingerprint: None,
         }
     }
 }
 
+/// Number of leading bytes hashed into a `Fingerprint`
+pub const FINGERPRINT_HEAD_BYTES: usize = 64 * 1024;
+
+/// Cheap content signature: file size plus a hash of its first `FINGERPRINT_HEAD_BYTES`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Fingerprint {
+    pub size: u64,
+    pub head_hash: u64,
+}
+
+impl Fingerprint {
+    pub fn of_file(path: &std::path::Path) -> std::io::Result<Fingerprint> {
+        use std::io::Read;
+
+        let file = std::fs::File::open(path)?;
+        let size = file.metadata()?.len();
+        let mut head = Vec::with_capacity(FINGERPRINT_HEAD_BYTES);
+        file.take(FINGERPRINT_HEAD_BYTES as u64)
+            .read_to_end(&mut head)?;
+
+        // FNV-1a: stable across builds, unlike std's hasher
+        let head_hash = head.iter().fold(0xcbf29ce484222325u64, |hash, &byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+        Ok(Fingerprint { size, head_hash })
+    }
+}
+
 impl MediaLibrary {
     pub fn new() -> Self {
         MediaLibrary { items: Vec::new() }
@@ -55,12 +103,17 @@ impl MediaLibrary {
         self.items.push(MediaItem::VideoItem(prop));
     }
 
+    pub fn all_items_mut(&mut self) -> &mut Vec<MediaItem> {
+        &mut self.items
+    }
+
     pub fn all_items(&self) -> &Vec<MediaItem> {
         &self.items
     }
 
     /// Add a file (audio or video) to the media library, inferring type from extension.
-    pub fn add_file(&mut self, path: &std::path::Path) {
+    /// Returns the new item, or None for unsupported files.
+    pub fn add_file(&mut self, path: &std::path::Path) -> Option<&MediaItem> {
         use std::fs;
         use std::process::Command;
         let file_name = path
@@ -81,7 +134,8 @@ impl MediaLibrary {
             _ => "unknown".to_string(),
         };
 
-        let fd = FileDescriptor::new(file_name, path_str.clone(), size, mime_type.clone());
+        let mut fd = FileDescriptor::new(file_name, path_str.clone(), size, mime_type.clone());
+        fd.fingerprint = Fingerprint::of_file(path).ok();
         if mime_type == "audio" {
             self.add_audio(AudioProp {
                 file_descriptor: fd,
@@ -101,7 +155,7 @@ impl MediaLibrary {
                     );
                     let pipeline = match gst::parse::launch(&pipeline_str) {
                         Ok(p) => p,
-                        Err(_) => return,
+                        Err(_) => return None,
                     };
                     let pipeline = pipeline
                         .downcast::<gst::Pipeline>()
@@ -117,20 +171,16 @@ impl MediaLibrary {
                     pipeline.set_state(gst::State::Playing).ok();
 
                     let bus = pipeline.bus().unwrap();
-                    let mut success = false;
-                    for msg in bus.iter_timed(gst::ClockTime::from_seconds(5)) {
-                        use gst::MessageView;
-                        match msg.view() {
-                            MessageView::Eos(..) => {
-                                success = true;
-                                break;
-                            }
-                            MessageView::Error(_) => break,
-                            _ => (),
-                        }
+                    let finished = crate::ops::gst_util::drain_bus_until(
+                        &bus,
+                        |msg| matches!(msg.view(), gst::MessageView::Eos(..)),
+                        Some(std::time::Duration::from_secs(5)),
+                    );
+                    if let Err(e) = &finished {
+                        log::warn!("No thumbnail for {}: {}", path_str, e);
                     }
                     pipeline.set_state(gst::State::Null).ok();
-                    success
+                    finished.is_ok()
                 };
                 if gst_status && std::path::Path::new(&thumb_path).exists() {
                     Some(thumb_path)
@@ -142,8 +192,24 @@ impl MediaLibrary {
                 file_descriptor: fd,
                 thumbnail_path,
             });
+        } else {
+            // Ignore unknown types for now
+            return None;
+        }
+        self.items.last()
+    }
+
+    /// Move the item at `from` so it ends up at index `to`, shifting the items in between.
+    /// Returns false (and leaves the library untouched) if either index is out of range.
+    pub fn move_item(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.items.len() || to >= self.items.len() {
+            return false;
+        }
+        if from != to {
+            let item = self.items.remove(from);
+            self.items.insert(to, item);
         }
-        // Ignore unknown types for now
+        true
     }
 
     pub fn find_by_filename(&self, name: &str) -> Option<&MediaItem> {
@@ -153,6 +219,46 @@ impl MediaLibrary {
         })
     }
 
+    pub fn find_by_fingerprint(&self, fingerprint: &Fingerprint) -> Option<&MediaItem> {
+        self.items
+            .iter()
+            .find(|item| item.file_descriptor().fingerprint.as_ref() == Some(fingerprint))
+    }
+
+    /// Paths of library files that no longer exist, in library order; relative paths
+    /// are looked up in `base_dir`
+    pub fn check_missing(&self, base_dir: &std::path::Path) -> Vec<String> {
+        self.items
+            .iter()
+            .map(|item| &item.file_descriptor().path)
+            .filter(|path| !base_dir.join(path).exists())
+            .cloned()
+            .collect()
+    }
+
+    /// Point a library item whose file has gone missing at `candidate` if their
+    /// contents match. Relative item paths are relative to `base_dir`. Returns the
+    /// item's previous path.
+    pub fn relink_by_fingerprint(
+        &mut self,
+        candidate: &std::path::Path,
+        base_dir: &std::path::Path,
+    ) -> Option<String> {
+        let fingerprint = Fingerprint::of_file(candidate).ok()?;
+        let fd = self
+            .items
+            .iter_mut()
+            .map(MediaItem::file_descriptor_mut)
+            .find(|fd| fd.fingerprint == Some(fingerprint) && !base_dir.join(&fd.path).exists())?;
+        let old_path = std::mem::replace(&mut fd.path, candidate.to_string_lossy().into_owned());
+        fd.file_name = candidate
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        Some(old_path)
+    }
+
     pub fn remove_by_filename(&mut self, name: &str) -> Option<MediaItem> {
         let idx = self.items.iter().position(|item| match item {
             MediaItem::AudioItem(a) => a.file_descriptor.file_name == name,
@@ -270,4 +376,98 @@ mod tests {
         let items = lib.all_items();
         assert_eq!(items.len(), 2);
     }
+
+    fn library_with(names: &[&str]) -> MediaLibrary {
+        let mut lib = MediaLibrary::new();
+        for name in names {
+            lib.add_audio(AudioProp {
+                file_descriptor: FileDescriptor::new(
+                    name.to_string(),
+                    format!("/audio/{}", name),
+                    1024,
+                    "audio/wav".to_string(),
+                ),
+            });
+        }
+        lib
+    }
+
+    fn names(lib: &MediaLibrary) -> Vec<String> {
+        lib.all_items()
+            .iter()
+            .map(|item| item.file_descriptor().file_name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_move_item_forward() {
+        let mut lib = library_with(&["a", "b", "c", "d"]);
+        assert!(lib.move_item(0, 2));
+        assert_eq!(names(&lib), vec!["b", "c", "a", "d"]);
+    }
+
+    #[test]
+    fn test_move_item_backward() {
+        let mut lib = library_with(&["a", "b", "c", "d"]);
+        assert!(lib.move_item(3, 1));
+        assert_eq!(names(&lib), vec!["a", "d", "b", "c"]);
+    }
+
+    #[test]
+    fn test_move_item_out_of_range_is_noop() {
+        let mut lib = library_with(&["a", "b", "c"]);
+        assert!(!lib.move_item(3, 0));
+        assert!(!lib.move_item(0, 3));
+        assert!(lib.move_item(1, 1));
+        assert_eq!(names(&lib), vec!["a", "b", "c"]);
+    }
+    #[test]
+    fn test_fingerprint_matches_same_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("interview.mp4");
+        let renamed = dir.path().join("interview_final.mp4");
+        let other = dir.path().join("broll.mp4");
+        let content: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&original, &content).unwrap();
+        std::fs::write(&renamed, &content).unwrap();
+        let mut different = content.clone();
+        different[10] ^= 0xff;
+        std::fs::write(&other, &different).unwrap();
+
+        let fingerprint = Fingerprint::of_file(&original).unwrap();
+        assert_eq!(Fingerprint::of_file(&renamed).unwrap(), fingerprint);
+        assert_ne!(Fingerprint::of_file(&other).unwrap(), fingerprint);
+
+        let mut lib = MediaLibrary::new();
+        lib.add_video(VideoProp {
+            file_descriptor: FileDescriptor {
+                fingerprint: Some(fingerprint),
+                ..FileDescriptor::new(
+                    "interview.mp4".to_string(),
+                    original.to_string_lossy().to_string(),
+                    content.len() as u64,
+                    "video".to_string(),
+                )
+            },
+            thumbnail_path: None,
+        });
+        assert!(lib.find_by_fingerprint(&fingerprint).is_some());
+        assert!(
+            lib.find_by_fingerprint(&Fingerprint::of_file(&other).unwrap())
+                .is_none()
+        );
+
+        // Nothing to relink while the original is still there
+        assert_eq!(lib.relink_by_fingerprint(&renamed, dir.path()), None);
+
+        std::fs::remove_file(&original).unwrap();
+        assert_eq!(lib.relink_by_fingerprint(&other, dir.path()), None);
+        assert_eq!(
+            lib.relink_by_fingerprint(&renamed, dir.path()),
+            Some(original.to_string_lossy().to_string())
+        );
+        let fd = lib.all_items()[0].file_descriptor();
+        assert_eq!(fd.path, renamed.to_string_lossy());
+        assert_eq!(fd.file_name, "interview_final.mp4");
+    }
 }
@@ -1,6 +1,11 @@
+pub mod beat_grid;
+pub mod envelope;
+pub mod history;
 pub mod media;
 pub mod media_library;
 pub mod playback_state;
 pub mod project;
+pub mod subtitle;
 pub mod timeline;
 pub mod track;
+pub mod ui_state;
@@ -1,11 +1,62 @@
-use crate::types::media_library::MediaLibrary;
-use crate::types::timeline::Timeline;
+use crate::types::media_library::{MediaItem, MediaLibrary};
+use crate::types::timeline::{SanitizeChange, SanitizeReport, Timeline};
+use crate::types::track::{AudioTrack, Track, VideoTrack};
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Version of the project file format written by this build
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Why a project file could not be loaded
+#[derive(Debug)]
+pub enum ProjectLoadError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    /// The file was written by a newer version of the app
+    UnsupportedVersion {
+        found: u32,
+        max_supported: u32,
+    },
+}
+
+impl std::fmt::Display for ProjectLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectLoadError::Io(e) => write!(f, "could not read project: {}", e),
+            ProjectLoadError::Parse(e) => write!(f, "invalid project file: {}", e),
+            ProjectLoadError::UnsupportedVersion {
+                found,
+                max_supported,
+            } => write!(
+                f,
+                "project uses format version {}, but this version of cutio only supports up to {}",
+                found, max_supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ProjectLoadError {}
+
+impl From<std::io::Error> for ProjectLoadError {
+    fn from(e: std::io::Error) -> Self {
+        ProjectLoadError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for ProjectLoadError {
+    fn from(e: serde_json::Error) -> Self {
+        ProjectLoadError::Parse(e)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Project {
+    /// File format version; files from before versioning load as 0
+    #[serde(default)]
+    pub schema_version: u32,
     pub name: String,
     pub description: Option<String>,
     pub project_file_path: String,
@@ -16,6 +67,9 @@ pub struct Project {
     pub cache_dir: String,
     pub render_output_dir: String,
     pub settings: ProjectSettings,
+    /// Timeline zoom and scroll when the project was last saved
+    #[serde(default)]
+    pub view_state: Option<ViewState>,
 }
 
 impl Project {
@@ -26,13 +80,445 @@ impl Project {
         file.write_all(json.as_bytes())
     }
 
+    /// Save the project to `new_path` and make that its file from now on.
+    ///
+    /// Relative asset paths are relative to the project file's folder, so they are
+    /// rewritten to reach the same files from the new folder. Absolute ones are kept.
+    pub fn save_as(&mut self, new_path: &str) -> std::io::Result<()> {
+        let rebase = path_rebaser(&self.project_file_path, new_path)?;
+
+        // Left untouched if writing fails
+        let mut saved = self.clone();
+        rebase_clip_paths(&mut saved.timeline, &rebase);
+        for item in saved.media_library.all_items_mut() {
+            rebase(&mut item.file_descriptor_mut().path);
+            if let MediaItem::VideoItem(video) = item {
+                video.thumbnail_path.iter_mut().for_each(&rebase);
+            }
+        }
+
+        saved.project_file_path = new_path.to_string();
+        saved.last_modified = timestamp_now();
+        saved.save_to_file(new_path)?;
+        *self = saved;
+        Ok(())
+    }
+
     /// Load a project from a JSON file at the given path.
-    pub fn load_from_file(path: &str) -> std::io::Result<Project> {
+    ///
+    /// Files written by a newer format version are rejected before their fields
+    /// are interpreted; older ones are upgraded to `CURRENT_SCHEMA_VERSION`.
+    /// Invalid clip timings are repaired with `Timeline::sanitize`.
+    pub fn load_from_file(path: &str) -> Result<Project, ProjectLoadError> {
+        let (project, report) = Self::load_and_sanitize(path)?;
+        for change in report.changes {
+            log::warn!("Fixed invalid clip in {}: {:?}", path, change);
+        }
+        Ok(project)
+    }
+
+    /// Load a project like `load_from_file` and check it over, returning the problems
+    /// that don't stop it from opening: clips repaired while loading, media relinked to
+    /// renamed files, media files that can't be found and video clips whose frame rate
+    /// differs from the project's.
+    pub fn open_and_check(path: &str) -> Result<(Project, Vec<ProjectWarning>), ProjectLoadError> {
+        let (mut project, report) = Self::load_and_sanitize(path)?;
+        let mut warnings: Vec<ProjectWarning> = report
+            .changes
+            .into_iter()
+            .map(ProjectWarning::FixedClip)
+            .collect();
+
+        // Relative media paths are relative to the project file's folder
+        let dir = project_dir(path)?;
+        warnings.extend(project.relink_missing(&dir));
+        let mut missing = project.media_library.check_missing(&dir);
+        for track in project.timeline.tracks() {
+            let clip_paths: Vec<&String> = match track {
+                Track::Video(v) => v.clips.iter().map(|c| &c.asset_path).collect(),
+                Track::Audio(a) => a.clips.iter().map(|c| &c.asset_path).collect(),
+            };
+            for clip_path in clip_paths {
+                if !missing.contains(clip_path) && !dir.join(clip_path).exists() {
+                    missing.push(clip_path.clone());
+                }
+            }
+        }
+        warnings.extend(
+            missing
+                .into_iter()
+                .map(|path| ProjectWarning::MissingMedia { path }),
+        );
+
+        warnings.extend(project.conformance_warnings());
+        Ok((project, warnings))
+    }
+
+    /// Point library items and clips whose media has gone missing at files in `dir`
+    /// with the same contents, e.g. after the media was renamed. Returns the relinks made.
+    pub fn relink_missing(&mut self, dir: &Path) -> Vec<ProjectWarning> {
+        if self.media_library.check_missing(dir).is_empty() {
+            return Vec::new();
+        }
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut relinked = Vec::new();
+        for candidate in entries.filter_map(Result::ok).map(|e| e.path()) {
+            if !candidate.is_file() {
+                continue;
+            }
+            if let Some(from) = self.media_library.relink_by_fingerprint(&candidate, dir) {
+                let to = candidate.to_string_lossy().into_owned();
+                self.timeline.relink_asset(&from, &to);
+                relinked.push(ProjectWarning::Relinked { from, to });
+            }
+        }
+        relinked
+    }
+
+    /// Video clips whose source frame rate differs from the project's
+    pub fn conformance_warnings(&self) -> Vec<ProjectWarning> {
+        let frame_rate = self.settings.frame_rate;
+        self.timeline
+            .tracks()
+            .iter()
+            .filter_map(|track| match track {
+                Track::Video(v) => Some(&v.clips),
+                Track::Audio(_) => None,
+            })
+            .flatten()
+            .filter(|clip| (clip.metadata.frame_rate - frame_rate).abs() > 0.01)
+            .map(|clip| ProjectWarning::FrameRateMismatch {
+                clip_id: clip.id.clone(),
+                frame_rate: clip.metadata.frame_rate,
+            })
+            .collect()
+    }
+
+    fn load_and_sanitize(path: &str) -> Result<(Project, SanitizeReport), ProjectLoadError> {
         let mut file = File::open(path)?;
         let mut json = String::new();
         file.read_to_string(&mut json)?;
-        let project: Project = serde_json::from_str(&json).unwrap();
-        Ok(project)
+        let value: serde_json::Value = serde_json::from_str(&json)?;
+        let found = value
+            .get("schema_version")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        if found > CURRENT_SCHEMA_VERSION as u64 {
+            return Err(ProjectLoadError::UnsupportedVersion {
+                found: found.try_into().unwrap_or(u32::MAX),
+                max_supported: CURRENT_SCHEMA_VERSION,
+            });
+        }
+        let mut project: Project = serde_json::from_value(value)?;
+        project.schema_version = CURRENT_SCHEMA_VERSION;
+        let report = project.timeline.sanitize();
+        // Older files may carry a stale or placeholder duration
+        project.timeline.recompute_duration();
+        Ok((project, report))
+    }
+}
+
+/// A problem found by `Project::open_and_check` that doesn't stop the project opening
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProjectWarning {
+    /// A clip with invalid timing was repaired or removed while loading
+    FixedClip(SanitizeChange),
+    /// A media file used by the library or a clip can't be found
+    MissingMedia { path: String },
+    /// Missing media was found under a new name with the same contents
+    Relinked { from: String, to: String },
+    /// A video clip's source runs at a different frame rate than the project
+    FrameRateMismatch { clip_id: String, frame_rate: f64 },
+}
+
+impl std::fmt::Display for ProjectWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProjectWarning::FixedClip(change) => write!(f, "Repaired clip: {:?}", change),
+            ProjectWarning::MissingMedia { path } => write!(f, "Missing media: {}", path),
+            ProjectWarning::Relinked { from, to } => write!(f, "Relinked {} to {}", from, to),
+            ProjectWarning::FrameRateMismatch {
+                clip_id,
+                frame_rate,
+            } => write!(
+                f,
+                "Clip {} runs at {:.3} fps, unlike the project",
+                clip_id, frame_rate
+            ),
+        }
+    }
+}
+
+/// Aggregate numbers about a project's timeline and media
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectStats {
+    pub track_count: usize,
+    /// (track name, clip count) in track order
+    pub clips_per_track: Vec<(String, usize)>,
+    pub total_clips: usize,
+    /// End of the last clip on any track, in seconds
+    pub total_duration: f64,
+    /// Distinct source files referenced by the timeline or the media library
+    pub unique_media: usize,
+    /// Sum of the sizes of the distinct source files, in bytes
+    pub total_media_size: u64,
+}
+
+impl Project {
+    /// Compute statistics over the timeline and media library.
+    /// Each source file is counted once, however many clips reference it.
+    pub fn stats(&self) -> ProjectStats {
+        use std::collections::HashMap;
+
+        // path -> known size (from the library), if any
+        let mut media: HashMap<&str, Option<u64>> = HashMap::new();
+        for item in self.media_library.all_items() {
+            let fd = match item {
+                MediaItem::AudioItem(a) => &a.file_descriptor,
+                MediaItem::VideoItem(v) => &v.file_descriptor,
+            };
+            media.insert(fd.path.as_str(), Some(fd.size));
+        }
+
+        let mut clips_per_track = Vec::new();
+        let mut total_duration: f64 = 0.0;
+        for track in self.timeline.tracks() {
+            let (name, clips): (&str, Vec<(&str, f64)>) = match track {
+                Track::Video(v) => (
+                    v.name.as_str(),
+                    v.clips
+                        .iter()
+                        .map(|c| (c.asset_path.as_str(), c.start_time + c.duration))
+                        .collect(),
+                ),
+                Track::Audio(a) => (
+                    a.name.as_str(),
+                    a.clips
+                        .iter()
+                        .map(|c| (c.asset_path.as_str(), c.start_time + c.duration))
+                        .collect(),
+                ),
+            };
+            for (path, end) in &clips {
+                media.entry(*path).or_insert(None);
+                total_duration = total_duration.max(*end);
+            }
+            clips_per_track.push((name.to_string(), clips.len()));
+        }
+
+        let total_media_size = media
+            .iter()
+            .map(|(path, size)| {
+                size.unwrap_or_else(|| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0))
+            })
+            .sum();
+
+        ProjectStats {
+            track_count: self.timeline.tracks().len(),
+            total_clips: clips_per_track.iter().map(|(_, n)| n).sum(),
+            clips_per_track,
+            total_duration,
+            unique_media: media.len(),
+            total_media_size,
+        }
+    }
+}
+
+impl Project {
+    /// Copy every source file used by the timeline into `dest_dir` and point
+    /// clips (and matching media library entries) at the copies.
+    ///
+    /// Each source is copied once however many clips use it. Files with the same
+    /// name from different folders get a numeric suffix (`clip_1.mp4`).
+    pub fn collect_files(&mut self, dest_dir: &Path) -> std::io::Result<()> {
+        use std::collections::{HashMap, HashSet};
+
+        std::fs::create_dir_all(dest_dir)?;
+
+        let mut used: Vec<String> = Vec::new();
+        for track in self.timeline.tracks() {
+            let paths: Vec<&String> = match track {
+                Track::Video(v) => v.clips.iter().map(|c| &c.asset_path).collect(),
+                Track::Audio(a) => a.clips.iter().map(|c| &c.asset_path).collect(),
+            };
+            for path in paths {
+                if !used.contains(path) {
+                    used.push(path.clone());
+                }
+            }
+        }
+
+        // original path -> collected path
+        let mut collected: HashMap<String, String> = HashMap::new();
+        let mut taken: HashSet<PathBuf> = HashSet::new();
+        for path in used {
+            let source = Path::new(&path);
+            if source.parent() == Some(dest_dir) {
+                // Already collected
+                taken.insert(source.to_path_buf());
+                collected.insert(path.clone(), path);
+                continue;
+            }
+            let target = unique_destination(dest_dir, source, &taken);
+            std::fs::copy(source, &target)?;
+            collected.insert(path, target.to_string_lossy().into_owned());
+            taken.insert(target);
+        }
+
+        for track in self.timeline.tracks_mut() {
+            match track {
+                Track::Video(v) => {
+                    for clip in &mut v.clips {
+                        clip.asset_path = collected[&clip.asset_path].clone();
+                    }
+                }
+                Track::Audio(a) => {
+                    for clip in &mut a.clips {
+                        clip.asset_path = collected[&clip.asset_path].clone();
+                    }
+                }
+            }
+        }
+        for item in self.media_library.all_items_mut() {
+            let fd = item.file_descriptor_mut();
+            if let Some(new_path) = collected.get(&fd.path) {
+                fd.path = new_path.clone();
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites a media path relative to the folder of the project file `old_project` so
+/// it reaches the same file from the folder of `new_project`. Absolute paths are kept.
+pub fn path_rebaser(
+    old_project: &str,
+    new_project: &str,
+) -> std::io::Result<impl Fn(&mut String) + use<>> {
+    let old_dir = project_dir(old_project)?;
+    let new_dir = project_dir(new_project)?;
+    Ok(move |path: &mut String| {
+        if Path::new(path.as_str()).is_absolute() {
+            return;
+        }
+        if let Some(rebased) = relative_path(&old_dir.join(path.as_str()), &new_dir) {
+            *path = rebased.to_string_lossy().into_owned();
+        }
+    })
+}
+
+/// Apply `rebase` to the media path of every clip in `timeline`
+pub fn rebase_clip_paths(timeline: &mut Timeline, rebase: &impl Fn(&mut String)) {
+    for track in timeline.tracks_mut() {
+        match track {
+            Track::Video(v) => v.clips.iter_mut().for_each(|c| rebase(&mut c.asset_path)),
+            Track::Audio(a) => a.clips.iter_mut().for_each(|c| rebase(&mut c.asset_path)),
+        }
+    }
+    timeline.invalidate_index();
+}
+
+/// Absolute folder of the project file at `path`; an unsaved project (empty path)
+/// resolves relative paths against the working directory
+fn project_dir(path: &str) -> std::io::Result<PathBuf> {
+    if path.is_empty() {
+        return std::env::current_dir();
+    }
+    let file = std::path::absolute(path)?;
+    Ok(file.parent().map(Path::to_path_buf).unwrap_or(file))
+}
+
+/// `path` relative to the folder `base`, both absolute, e.g. `/a/b/c.mp4` from `/a/d`
+/// is `../b/c.mp4`. None when they are on different roots (Windows drives).
+fn relative_path(path: &Path, base: &Path) -> Option<PathBuf> {
+    let path: Vec<_> = path.components().collect();
+    let base: Vec<_> = base.components().collect();
+    if path.first() != base.first() {
+        return None;
+    }
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    relative.extend(&path[common..]);
+    Some(relative)
+}
+
+/// Current UTC time in RFC 3339 form, e.g. `2024-06-09T12:00:00Z`
+fn timestamp_now() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+    // Civil date from days since 1970-01-01 (proleptic Gregorian)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
+
+/// First path in `dest_dir` named after `source` that is neither in `taken`
+/// nor already on disk, adding `_1`, `_2`, ... before the extension as needed.
+fn unique_destination(
+    dest_dir: &Path,
+    source: &Path,
+    taken: &std::collections::HashSet<PathBuf>,
+) -> PathBuf {
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "media".to_string());
+    let extension = source.extension().map(|e| e.to_string_lossy().into_owned());
+    let name = |suffix: Option<usize>| {
+        let stem = match suffix {
+            Some(n) => format!("{}_{}", stem, n),
+            None => stem.clone(),
+        };
+        match &extension {
+            Some(ext) => format!("{}.{}", stem, ext),
+            None => stem,
+        }
+    };
+
+    let mut candidate = dest_dir.join(name(None));
+    let mut n = 1;
+    while taken.contains(&candidate) || candidate.exists() {
+        candidate = dest_dir.join(name(Some(n)));
+        n += 1;
+    }
+    candidate
+}
+
+/// Timeline zoom (pixels per second) and horizontal scroll (pixels)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ViewState {
+    pub zoom: f32,
+    pub scroll_x: f32,
+}
+
+impl Default for ViewState {
+    fn default() -> Self {
+        Self {
+            zoom: 100.0,
+            scroll_x: 0.0,
+        }
     }
 }
 
@@ -41,9 +527,30 @@ pub struct ProjectSettings {
     pub resolution: (u32, u32),
     pub frame_rate: f64,
     pub color_space: String,
+    /// Length given to new stills and titles, and to sources whose length can't be
+    /// probed, in seconds
+    #[serde(default = "default_still_duration")]
+    pub default_still_duration: f64,
     // Add more as needed
 }
 
+/// Still duration for new projects and projects saved without one
+pub const DEFAULT_STILL_DURATION: f64 = 5.0;
+
+fn default_still_duration() -> f64 {
+    DEFAULT_STILL_DURATION
+}
+
+impl ProjectSettings {
+    /// Color space clips are composited in; unrecognized names fall back to sRGB
+    pub fn working_color_space(&self) -> crate::types::media::ColorSpace {
+        crate::types::media::ColorSpace::from_name(&self.color_space).unwrap_or_else(|| {
+            log::warn!("Unsupported color space {:?}, using sRGB", self.color_space);
+            crate::types::media::ColorSpace::Srgb
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,6 +558,7 @@ mod tests {
     #[test]
     fn test_save_and_load_project() {
         let project = Project {
+            schema_version: CURRENT_SCHEMA_VERSION,
             name: "Test Project".to_string(),
             description: Some("A test project".to_string()),
             project_file_path: "/tmp/test_project.json".to_string(),
@@ -64,7 +572,9 @@ mod tests {
                 resolution: (1920, 1080),
                 frame_rate: 30.0,
                 color_space: "sRGB".to_string(),
+                default_still_duration: DEFAULT_STILL_DURATION,
             },
+            view_state: None,
         };
         let path = "/tmp/test_project.json";
         project.save_to_file(path).unwrap();
@@ -73,6 +583,539 @@ mod tests {
         assert_eq!(project.settings.resolution, loaded.settings.resolution);
         let _ = std::fs::remove_file(path);
     }
+
+    fn project_json_with_version(version: u32) -> String {
+        let project = Project::new(
+            "Versioned".to_string(),
+            "/tmp/versioned.json".to_string(),
+            "/tmp/cache".to_string(),
+            "/tmp/render".to_string(),
+            ProjectSettings {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                color_space: "sRGB".to_string(),
+                default_still_duration: DEFAULT_STILL_DURATION,
+            },
+        );
+        let mut value = serde_json::to_value(&project).unwrap();
+        value["schema_version"] = version.into();
+        value.to_string()
+    }
+
+    #[test]
+    fn test_load_rejects_newer_schema_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("future.json");
+        let mut json: serde_json::Value =
+            serde_json::from_str(&project_json_with_version(CURRENT_SCHEMA_VERSION + 1)).unwrap();
+        // A future format may also change fields in ways this build can't parse
+        json["timeline"] = "something new".into();
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        match Project::load_from_file(path.to_str().unwrap()) {
+            Err(ProjectLoadError::UnsupportedVersion {
+                found,
+                max_supported,
+            }) => {
+                assert_eq!(found, CURRENT_SCHEMA_VERSION + 1);
+                assert_eq!(max_supported, CURRENT_SCHEMA_VERSION);
+            }
+            other => panic!("Expected UnsupportedVersion, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_load_supported_schema_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("current.json");
+        std::fs::write(&path, project_json_with_version(CURRENT_SCHEMA_VERSION)).unwrap();
+        let loaded = Project::load_from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.name, "Versioned");
+
+        // Files from before versioning have no field at all and are upgraded
+        let mut json: serde_json::Value =
+            serde_json::from_str(&project_json_with_version(0)).unwrap();
+        json.as_object_mut().unwrap().remove("schema_version");
+        std::fs::write(&path, json.to_string()).unwrap();
+        let loaded = Project::load_from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.schema_version, CURRENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_load_sanitizes_timeline() {
+        use crate::types::envelope::Envelope;
+        use crate::types::media::{VideoClip, VideoMetadata};
+        use crate::types::track::VideoTrack;
+
+        let mut project = Project::new(
+            "Sanitize".to_string(),
+            "/tmp/test_sanitize_project.json".to_string(),
+            "/tmp/cache".to_string(),
+            "/tmp/render".to_string(),
+            ProjectSettings {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                color_space: "sRGB".to_string(),
+                default_still_duration: DEFAULT_STILL_DURATION,
+            },
+        );
+        *project.timeline.tracks_mut() = vec![Track::Video(VideoTrack {
+            id: "vt1".to_string(),
+            name: "Video Track 1".to_string(),
+            clips: vec![VideoClip {
+                id: "v1".to_string(),
+                asset_path: "/media/movie.mp4".to_string(),
+                in_point: 3.0,
+                out_point: 1.0,
+                start_time: -1.0,
+                duration: 2.0,
+                enabled: true,
+                effects: Vec::new(),
+                opacity_envelope: Envelope::new(),
+                freeze: false,
+                speed: 1.0,
+                time_map: None,
+                reversed: false,
+                mute_audio: false,
+                crop: None,
+                group_id: None,
+                metadata: VideoMetadata {
+                    resolution: (1920, 1080),
+                    frame_rate: 30.0,
+                    codec: "h264".to_string(),
+                    color_space: None,
+                },
+            }],
+            muted: false,
+            sync_locked: false,
+        })];
+
+        let path = "/tmp/test_sanitize_project.json";
+        project.save_to_file(path).unwrap();
+        let loaded = Project::load_from_file(path).unwrap();
+        let _ = std::fs::remove_file(path);
+
+        match &loaded.timeline.tracks()[0] {
+            Track::Video(v) => {
+                assert_eq!(v.clips[0].start_time, 0.0);
+                assert_eq!((v.clips[0].in_point, v.clips[0].out_point), (1.0, 3.0));
+            }
+            _ => panic!("Expected video track"),
+        }
+    }
+
+    #[test]
+    fn test_project_stats_counts_duplicates_once() {
+        use crate::types::envelope::Envelope;
+        use crate::types::media::{AudioClip, AudioMetadata, VideoClip, VideoMetadata};
+        use crate::types::media_library::{AudioProp, FileDescriptor, VideoProp};
+        use crate::types::track::{AudioTrack, VideoTrack};
+
+        let video_clip = |id: &str, start_time: f64| VideoClip {
+            id: id.to_string(),
+            asset_path: "/media/movie.mp4".to_string(),
+            in_point: 0.0,
+            out_point: 4.0,
+            start_time,
+            duration: 4.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        };
+        let audio_clip = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "/media/song.wav".to_string(),
+            in_point: 0.0,
+            out_point: 12.0,
+            start_time: 1.0,
+            duration: 12.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+
+        let mut project = Project::new(
+            "Stats".to_string(),
+            "/tmp/stats.json".to_string(),
+            "/tmp/cache".to_string(),
+            "/tmp/render".to_string(),
+            ProjectSettings {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                color_space: "sRGB".to_string(),
+                default_still_duration: DEFAULT_STILL_DURATION,
+            },
+        );
+        project.media_library.add_video(VideoProp {
+            file_descriptor: FileDescriptor::new(
+                "movie.mp4".to_string(),
+                "/media/movie.mp4".to_string(),
+                1000,
+                "video".to_string(),
+            ),
+            thumbnail_path: None,
+        });
+        project.media_library.add_audio(AudioProp {
+            file_descriptor: FileDescriptor::new(
+                "song.wav".to_string(),
+                "/media/song.wav".to_string(),
+                250,
+                "audio".to_string(),
+            ),
+        });
+        *project.timeline.tracks_mut() = vec![
+            Track::Video(VideoTrack {
+                id: "vt1".to_string(),
+                name: "Video Track 1".to_string(),
+                clips: vec![video_clip("v1", 0.0), video_clip("v2", 4.0)],
+                muted: false,
+                sync_locked: false,
+            }),
+            Track::Audio(AudioTrack {
+                id: "at1".to_string(),
+                name: "Audio Track 1".to_string(),
+                clips: vec![audio_clip],
+                muted: false,
+                sync_locked: false,
+                volume_db: 0.0,
+                pan: 0.0,
+            }),
+        ];
+
+        let stats = project.stats();
+        assert_eq!(stats.track_count, 2);
+        assert_eq!(
+            stats.clips_per_track,
+            vec![
+                ("Video Track 1".to_string(), 2),
+                ("Audio Track 1".to_string(), 1)
+            ]
+        );
+        assert_eq!(stats.total_clips, 3);
+        assert_eq!(stats.total_duration, 13.0);
+        // movie.mp4 is used by two clips but counted once
+        assert_eq!(stats.unique_media, 2);
+        assert_eq!(stats.total_media_size, 1250);
+    }
+
+    #[test]
+    fn test_collect_files_renames_colliding_names() {
+        use crate::types::media::{AudioClip, AudioMetadata};
+        use crate::types::track::AudioTrack;
+
+        let dir = tempfile::tempdir().unwrap();
+        let (first_dir, second_dir) = (dir.path().join("day1"), dir.path().join("day2"));
+        std::fs::create_dir_all(&first_dir).unwrap();
+        std::fs::create_dir_all(&second_dir).unwrap();
+        let first = first_dir.join("take.wav");
+        let second = second_dir.join("take.wav");
+        std::fs::write(&first, b"first").unwrap();
+        std::fs::write(&second, b"second").unwrap();
+
+        let clip = |id: &str, path: &Path| AudioClip {
+            id: id.to_string(),
+            asset_path: path.to_string_lossy().into_owned(),
+            in_point: 0.0,
+            out_point: 1.0,
+            start_time: 0.0,
+            duration: 1.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+        let mut project = Project::new(
+            "Collect".to_string(),
+            "/tmp/collect.json".to_string(),
+            "/tmp/cache".to_string(),
+            "/tmp/render".to_string(),
+            ProjectSettings {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                color_space: "sRGB".to_string(),
+                default_still_duration: DEFAULT_STILL_DURATION,
+            },
+        );
+        *project.timeline.tracks_mut() = vec![Track::Audio(AudioTrack {
+            id: "at1".to_string(),
+            name: "Audio Track 1".to_string(),
+            clips: vec![clip("a1", &first), clip("a2", &second), clip("a3", &first)],
+            muted: false,
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        })];
+
+        let dest = dir.path().join("collected");
+        project.collect_files(&dest).unwrap();
+
+        let paths: Vec<String> = match &project.timeline.tracks()[0] {
+            Track::Audio(a) => a.clips.iter().map(|c| c.asset_path.clone()).collect(),
+            _ => panic!("Expected audio track"),
+        };
+        let first_copy = dest.join("take.wav");
+        let second_copy = dest.join("take_1.wav");
+        assert_eq!(paths[0], first_copy.to_string_lossy());
+        assert_eq!(paths[1], second_copy.to_string_lossy());
+        // Reused source is copied once and shares the collected path
+        assert_eq!(paths[2], paths[0]);
+        assert_eq!(std::fs::read(&first_copy).unwrap(), b"first");
+        assert_eq!(std::fs::read(&second_copy).unwrap(), b"second");
+        assert_eq!(std::fs::read_dir(&dest).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_save_as_rebases_relative_asset_paths() {
+        use crate::types::media_library::{AudioProp, FileDescriptor};
+
+        let dir = tempfile::tempdir().unwrap();
+        let (old_dir, new_dir) = (dir.path().join("old"), dir.path().join("new/nested"));
+        std::fs::create_dir_all(old_dir.join("media")).unwrap();
+        std::fs::create_dir_all(&new_dir).unwrap();
+        std::fs::write(old_dir.join("media/voice.wav"), b"voice").unwrap();
+
+        let mut project = Project::from_template("1080p30");
+        project.project_file_path = old_dir.join("edit.json").to_string_lossy().into_owned();
+        project.media_library.add_audio(AudioProp {
+            file_descriptor: FileDescriptor::new(
+                "voice.wav".to_string(),
+                "media/voice.wav".to_string(),
+                5,
+                "audio".to_string(),
+            ),
+        });
+        let json = r#"{ "id": "a1", "asset_path": "media/voice.wav", "in_point": 0.0,
+                        "out_point": 1.0, "start_time": 0.0, "duration": 1.0,
+                        "metadata": { "sample_rate": 48000, "channels": 2, "codec": "pcm",
+                                      "bitrate": 1536 } }"#;
+        let absolute = "/elsewhere/music.wav";
+        if let Track::Audio(a) = &mut project.timeline.tracks_mut()[1] {
+            a.clips.push(serde_json::from_str(json).unwrap());
+            let mut music = a.clips[0].clone();
+            music.asset_path = absolute.to_string();
+            a.clips.push(music);
+        }
+
+        let new_path = new_dir.join("edit.json");
+        project.save_as(&new_path.to_string_lossy()).unwrap();
+
+        assert_eq!(project.project_file_path, new_path.to_string_lossy());
+        assert_ne!(project.last_modified, project.created_at);
+        let Track::Audio(a) = &project.timeline.tracks()[1] else {
+            panic!("Expected audio track");
+        };
+        assert_eq!(a.clips[0].asset_path, "../../old/media/voice.wav");
+        assert_eq!(a.clips[1].asset_path, absolute);
+        // The rewritten path still reaches the file from the new folder
+        assert_eq!(
+            std::fs::read(new_dir.join(&a.clips[0].asset_path)).unwrap(),
+            b"voice"
+        );
+        let library_path = &project.media_library.all_items()[0].file_descriptor().path;
+        assert_eq!(library_path, "../../old/media/voice.wav");
+
+        // What was written is the rebased project
+        let loaded = Project::load_from_file(&new_path.to_string_lossy()).unwrap();
+        assert_eq!(loaded.project_file_path, project.project_file_path);
+    }
+
+    #[test]
+    fn test_open_and_check_reports_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.wav"), b"audio").unwrap();
+        let clip_json = |path: &str, in_point: f64, out_point: f64| {
+            format!(
+                r#"{{ "id": "{path}", "asset_path": "{path}", "in_point": {in_point},
+                      "out_point": {out_point}, "start_time": 0.0, "duration": 1.0,
+                      "metadata": {{ "sample_rate": 48000, "channels": 2, "codec": "pcm",
+                                    "bitrate": 1536 }} }}"#
+            )
+        };
+        let save_with = |name: &str, clips: &[String]| {
+            let mut project = Project::from_template("1080p30");
+            let audio = project
+                .timeline
+                .tracks_mut()
+                .iter_mut()
+                .find_map(|t| match t {
+                    Track::Audio(a) => Some(a),
+                    _ => None,
+                })
+                .unwrap();
+            for json in clips {
+                audio.clips.push(serde_json::from_str(json).unwrap());
+            }
+            let path = dir.path().join(name).to_string_lossy().into_owned();
+            project.save_to_file(&path).unwrap();
+            path
+        };
+
+        let clean = save_with("clean.json", &[clip_json("a.wav", 0.0, 1.0)]);
+        let (_, warnings) = Project::open_and_check(&clean).unwrap();
+        assert!(warnings.is_empty());
+
+        let broken = save_with(
+            "broken.json",
+            &[
+                clip_json("missing.wav", 0.0, 1.0),
+                clip_json("a.wav", 3.0, 1.0),
+            ],
+        );
+        let (project, warnings) = Project::open_and_check(&broken).unwrap();
+        assert_eq!(
+            warnings,
+            vec![
+                ProjectWarning::FixedClip(SanitizeChange::SwappedInOut {
+                    clip_id: "a.wav".to_string()
+                }),
+                ProjectWarning::MissingMedia {
+                    path: "missing.wav".to_string()
+                },
+            ]
+        );
+        // The repaired clip is what was loaded
+        let Track::Audio(a) = &project.timeline.tracks()[1] else {
+            panic!("Expected audio track");
+        };
+        assert_eq!((a.clips[1].in_point, a.clips[1].out_point), (1.0, 3.0));
+    }
+
+    #[test]
+    fn test_open_relinks_renamed_media() {
+        use crate::types::media_library::{AudioProp, FileDescriptor, Fingerprint};
+
+        let dir = tempfile::tempdir().unwrap();
+        let renamed = dir.path().join("take2_final.wav");
+        std::fs::write(&renamed, b"the only take worth keeping").unwrap();
+
+        let mut project = Project::from_template("1080p30");
+        project.media_library.add_audio(AudioProp {
+            file_descriptor: FileDescriptor {
+                fingerprint: Fingerprint::of_file(&renamed).ok(),
+                ..FileDescriptor::new(
+                    "take2.wav".to_string(),
+                    "take2.wav".to_string(),
+                    27,
+                    "audio/wav".to_string(),
+                )
+            },
+        });
+        let Track::Audio(a) = &mut project.timeline.tracks_mut()[1] else {
+            panic!("Expected audio track");
+        };
+        a.clips.push(
+            serde_json::from_str(
+                r#"{ "id": "a1", "asset_path": "take2.wav", "in_point": 0.0, "out_point": 1.0,
+                     "start_time": 0.0, "duration": 1.0,
+                     "metadata": { "sample_rate": 48000, "channels": 2, "codec": "pcm",
+                                   "bitrate": 1536 } }"#,
+            )
+            .unwrap(),
+        );
+        let path = dir.path().join("p.json").to_string_lossy().into_owned();
+        project.save_to_file(&path).unwrap();
+
+        let (project, warnings) = Project::open_and_check(&path).unwrap();
+        let to = renamed.to_string_lossy().into_owned();
+        assert_eq!(
+            warnings,
+            vec![ProjectWarning::Relinked {
+                from: "take2.wav".to_string(),
+                to: to.clone(),
+            }]
+        );
+        assert_eq!(
+            project.media_library.all_items()[0].file_descriptor().path,
+            to
+        );
+        assert_eq!(project.timeline.clip_asset_path("a1"), Some(to.as_str()));
+        // The clip pushed above never updated the saved duration
+        assert_eq!(project.timeline.duration, 1.0);
+    }
+
+    #[test]
+    fn test_timestamp_format() {
+        let now = timestamp_now();
+        assert_eq!(now.len(), "2024-06-09T12:00:00Z".len());
+        assert!(now.ends_with('Z') && now.as_bytes()[10] == b'T');
+        assert!(now.as_str() > "2024-01-01");
+    }
+
+    #[test]
+    fn test_project_templates() {
+        let kinds = |project: &Project| -> Vec<(bool, String)> {
+            project
+                .timeline
+                .tracks()
+                .iter()
+                .map(|t| match t {
+                    Track::Video(v) => (true, v.name.clone()),
+                    Track::Audio(a) => (false, a.name.clone()),
+                })
+                .collect()
+        };
+        let av = vec![
+            (true, "Video Track 1".to_string()),
+            (false, "Audio Track 1".to_string()),
+        ];
+        let cases = [
+            ("1080p30", (1920, 1080), 30.0, av.clone()),
+            ("1080p60", (1920, 1080), 60.0, av.clone()),
+            ("4K30", (3840, 2160), 30.0, av.clone()),
+            ("Vertical 1080x1920", (1080, 1920), 30.0, av.clone()),
+            (
+                "Audio-only",
+                (1920, 1080),
+                30.0,
+                vec![
+                    (false, "Audio Track 1".to_string()),
+                    (false, "Audio Track 2".to_string()),
+                ],
+            ),
+        ];
+        assert_eq!(cases.len(), PROJECT_TEMPLATES.len());
+        for (name, resolution, frame_rate, tracks) in cases {
+            let project = Project::from_template(name);
+            assert_eq!(project.settings.resolution, resolution, "{}", name);
+            assert_eq!(project.settings.frame_rate, frame_rate, "{}", name);
+            assert_eq!(project.timeline.resolution, resolution, "{}", name);
+            assert_eq!(project.timeline.frame_rate, frame_rate, "{}", name);
+            assert_eq!(kinds(&project), tracks, "{}", name);
+            assert!(project.timeline.tracks().iter().all(|t| match t {
+                Track::Video(v) => v.clips.is_empty(),
+                Track::Audio(a) => a.clips.is_empty(),
+            }));
+        }
+
+        // Unknown names get the default template
+        let project = Project::from_template("8K120");
+        assert_eq!(project.settings.resolution, (1920, 1080));
+        assert_eq!(project.settings.frame_rate, 30.0);
+    }
 }
 
 impl Project {
@@ -85,6 +1128,7 @@ impl Project {
     ) -> Self {
         let now = "2024-06-09T12:00:00Z".to_string(); // Placeholder, use chrono for real
         Project {
+            schema_version: CURRENT_SCHEMA_VERSION,
             name,
             description: None,
             project_file_path,
@@ -95,6 +1139,107 @@ impl Project {
             cache_dir,
             render_output_dir,
             settings,
+            view_state: None,
         }
     }
+
+    /// New untitled project from the built-in template called `name` (see
+    /// `PROJECT_TEMPLATES`), with its settings and empty starting tracks.
+    /// Unknown names fall back to the first template.
+    pub fn from_template(name: &str) -> Project {
+        let template = PROJECT_TEMPLATES
+            .iter()
+            .find(|t| t.name == name)
+            .unwrap_or_else(|| {
+                log::warn!("Unknown project template '{}', using 1080p30", name);
+                &PROJECT_TEMPLATES[0]
+            });
+        let mut project = Project::new(
+            "Untitled Project".to_string(),
+            String::new(),
+            String::new(),
+            String::new(),
+            ProjectSettings {
+                resolution: template.resolution,
+                frame_rate: template.frame_rate,
+                color_space: "sRGB".to_string(),
+                default_still_duration: DEFAULT_STILL_DURATION,
+            },
+        );
+        project.timeline.resolution = template.resolution;
+        project.timeline.frame_rate = template.frame_rate;
+        for n in 1..=template.video_tracks {
+            let id = project.timeline.tracks().len() + 1;
+            project.timeline.tracks_mut().push(Track::Video(VideoTrack {
+                id: format!("track_{}", id),
+                name: format!("Video Track {}", n),
+                clips: Vec::new(),
+                muted: false,
+                sync_locked: false,
+            }));
+        }
+        for n in 1..=template.audio_tracks {
+            let id = project.timeline.tracks().len() + 1;
+            project.timeline.tracks_mut().push(Track::Audio(AudioTrack {
+                id: format!("track_{}", id),
+                name: format!("Audio Track {}", n),
+                clips: Vec::new(),
+                muted: false,
+                sync_locked: false,
+                volume_db: 0.0,
+                pan: 0.0,
+            }));
+        }
+        project
+    }
+}
+
+/// Settings and starting track layout for a new project
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProjectTemplate {
+    pub name: &'static str,
+    pub resolution: (u32, u32),
+    pub frame_rate: f64,
+    pub video_tracks: usize,
+    pub audio_tracks: usize,
 }
+
+/// Templates offered in the New Project dialog. The first one is the default.
+pub const PROJECT_TEMPLATES: &[ProjectTemplate] = &[
+    ProjectTemplate {
+        name: "1080p30",
+        resolution: (1920, 1080),
+        frame_rate: 30.0,
+        video_tracks: 1,
+        audio_tracks: 1,
+    },
+    ProjectTemplate {
+        name: "1080p60",
+        resolution: (1920, 1080),
+        frame_rate: 60.0,
+        video_tracks: 1,
+        audio_tracks: 1,
+    },
+    ProjectTemplate {
+        name: "4K30",
+        resolution: (3840, 2160),
+        frame_rate: 30.0,
+        video_tracks: 1,
+        audio_tracks: 1,
+    },
+    ProjectTemplate {
+        name: "Vertical 1080x1920",
+        resolution: (1080, 1920),
+        frame_rate: 30.0,
+        video_tracks: 1,
+        audio_tracks: 1,
+    },
+    // Resolution only matters for the preview
+    ProjectTemplate {
+        name: "Audio-only",
+        resolution: (1920, 1080),
+        frame_rate: 30.0,
+        video_tracks: 0,
+        audio_tracks: 2,
+    },
+];
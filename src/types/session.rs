@@ -1,24 +1,6 @@
+use crate::types::playback_state::PlaybackState;
 use crate::types::project::Project;
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct PlaybackState {
-    pub playhead: f64, // Current time in seconds or frames
-    pub is_playing: bool,
-    pub loop_start: Option<f64>,
-    pub loop_end: Option<f64>,
-}
-
-impl PlaybackState {
-    pub fn new() -> Self {
-        PlaybackState {
-            playhead: 0.0,
-            is_playing: false,
-            loop_start: None,
-            loop_end: None,
-        }
-    }
-}
-
 /// ProjectSession groups a Project (persistent data) and PlaybackState (ephemeral UI state).
 /// Only the Project should be serialized; PlaybackState is for runtime use only.
 #[derive(Debug, Clone)]
@@ -35,3 +17,24 @@ impl ProjectSession {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_playback_state_has_loop_points_and_rate() {
+        let mut session = ProjectSession::new(Project::from_template("1080p30"));
+        let state = &mut session.playback_state;
+        assert_eq!((state.playhead, state.is_playing), (0.0, false));
+        assert_eq!((state.volume, state.playback_rate), (1.0, 1.0));
+
+        state.loop_start = Some(1.0);
+        state.loop_end = Some(4.0);
+        state.playback_rate = 2.0;
+        assert_eq!(
+            (state.loop_start, state.loop_end, state.playback_rate),
+            (Some(1.0), Some(4.0), 2.0)
+        );
+    }
+}
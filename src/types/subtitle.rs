@@ -0,0 +1,116 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::media::{TextAlign, TextClip};
+
+/// One caption, shown from `start` to `end` seconds on the timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    /// Caption text; `\n` starts a new line
+    pub text: String,
+}
+
+impl Cue {
+    pub fn is_active_at(&self, time: f64) -> bool {
+        time >= self.start && time < self.end
+    }
+
+    /// The caption as a title: bottom-centered white text on a dark box
+    pub fn to_text_clip(&self) -> TextClip {
+        TextClip {
+            id: format!("cue@{}", self.start),
+            text: self.text.clone(),
+            font_size: 36.0,
+            color: [255, 255, 255, 255],
+            background: Some([0, 0, 0, 160]),
+            position: (0.5, 0.88),
+            align: TextAlign::Center,
+            start_time: self.start,
+            duration: self.end - self.start,
+            enabled: true,
+        }
+    }
+}
+
+fn default_show_in_preview() -> bool {
+    true
+}
+
+/// Captions loaded from a sidecar subtitle file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubtitleTrack {
+    /// File the cues were loaded from
+    pub path: String,
+    pub cues: Vec<Cue>,
+    /// Draw the captions over the preview
+    #[serde(default = "default_show_in_preview")]
+    pub show_in_preview: bool,
+    /// Draw the captions into exported video
+    #[serde(default)]
+    pub burn_in: bool,
+}
+
+impl SubtitleTrack {
+    /// Shown in the preview, not burned into exports
+    pub fn new(path: String, cues: Vec<Cue>) -> Self {
+        SubtitleTrack {
+            path,
+            cues,
+            show_in_preview: true,
+            burn_in: false,
+        }
+    }
+
+    /// Whether captions are drawn when rendering for export or for the preview
+    pub fn is_drawn(&self, exporting: bool) -> bool {
+        if exporting {
+            self.burn_in
+        } else {
+            self.show_in_preview
+        }
+    }
+
+    /// Cues shown at `time`, in file order
+    pub fn cues_at(&self, time: f64) -> Vec<&Cue> {
+        self.cues.iter().filter(|c| c.is_active_at(time)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cues_at_and_visibility() {
+        let cue = |start: f64, end: f64, text: &str| Cue {
+            start,
+            end,
+            text: text.to_string(),
+        };
+        let mut track = SubtitleTrack::new(
+            "movie.srt".to_string(),
+            vec![
+                cue(1.0, 3.0, "Hello"),
+                cue(2.5, 4.0, "Overlap"),
+                cue(5.0, 6.0, "Bye"),
+            ],
+        );
+
+        let texts = |track: &SubtitleTrack, time: f64| -> Vec<String> {
+            track.cues_at(time).iter().map(|c| c.text.clone()).collect()
+        };
+        assert!(texts(&track, 0.5).is_empty());
+        assert_eq!(texts(&track, 2.7), vec!["Hello", "Overlap"]);
+        // End is exclusive
+        assert_eq!(texts(&track, 3.0), vec!["Overlap"]);
+        assert!(texts(&track, 4.5).is_empty());
+
+        assert!(track.is_drawn(false));
+        assert!(!track.is_drawn(true));
+        track.burn_in = true;
+        track.show_in_preview = false;
+        assert!(track.is_drawn(true));
+        assert!(!track.is_drawn(false));
+    }
+}
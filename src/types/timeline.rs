@@ -1,31 +1,226 @@
-use crate::ops::clip_ops::cut_clip_at;
+use crate::ops::clip_ops::{ClipSplit, cut_clip_at};
+use crate::types::beat_grid::BeatGrid;
+use crate::types::subtitle::SubtitleTrack;
 
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Timeline {
-    pub tracks: Vec<Track>,
+    /// Read with `tracks` and edit with `tracks_mut`, so the clip index never goes stale
+    tracks: Vec<Track>,
     pub duration: f64,
     pub frame_rate: f64,
     pub resolution: (u32, u32),
+    /// Titles drawn over all video tracks
+    #[serde(default)]
+    pub text_clips: Vec<TextClip>,
+    /// Tempo grid used for snapping when set
+    #[serde(default)]
+    pub beat_grid: Option<BeatGrid>,
+    /// Global sync lock switch; when off, ripple edits only move the edited track
+    #[serde(default = "default_sync_lock")]
+    pub sync_lock_enabled: bool,
+    /// Sidecar captions, drawn over the preview and optionally burned into exports
+    #[serde(default)]
+    pub subtitles: Option<SubtitleTrack>,
+    /// Named points in time, sorted by time
+    #[serde(default)]
+    pub markers: Vec<Marker>,
+    /// Lazily built clip lookup index; call `invalidate_index` after editing clips
+    #[serde(skip)]
+    clip_index: OnceLock<ClipIndex>,
 }
 
-impl Timeline {
-    /// Returns all active video clips at a specific time.
-    pub fn active_video_clips_at(&self, time: f64) -> Vec<&VideoClip> {
-        self.tracks
+/// A named point on the timeline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Marker {
+    pub time: f64,
+    #[serde(default)]
+    pub name: String,
+}
+
+fn default_sync_lock() -> bool {
+    true
+}
+
+// The clip index is a cache, so it takes no part in equality
+impl PartialEq for Timeline {
+    fn eq(&self, other: &Self) -> bool {
+        let Timeline {
+            tracks,
+            duration,
+            frame_rate,
+            resolution,
+            text_clips,
+            beat_grid,
+            sync_lock_enabled,
+            subtitles,
+            markers,
+            clip_index: _,
+        } = self;
+        *tracks == other.tracks
+            && *duration == other.duration
+            && *frame_rate == other.frame_rate
+            && *resolution == other.resolution
+            && *text_clips == other.text_clips
+            && *beat_grid == other.beat_grid
+            && *sync_lock_enabled == other.sync_lock_enabled
+            && *subtitles == other.subtitles
+            && *markers == other.markers
+    }
+}
+
+/// Per-track clips sorted by start time, for binary-searched time lookups.
+#[derive(Debug, Clone, Default)]
+struct ClipIndex {
+    tracks: Vec<TrackIndex>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct TrackIndex {
+    /// Sorted by `start`
+    entries: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    start: f64,
+    end: f64,
+    /// Position of the clip in its track's `clips`
+    clip_idx: usize,
+    /// Largest `end` among this entry and all entries before it
+    max_end: f64,
+}
+
+impl ClipIndex {
+    fn build(tracks: &[Track]) -> Self {
+        let tracks = tracks
             .iter()
-            .filter_map(|track| match track {
-                Track::Video(video_track) => Some(video_track),
-                _ => None,
+            .map(|track| {
+                let spans: Vec<(f64, f64)> = match track {
+                    Track::Video(v) => v
+                        .clips
+                        .iter()
+                        .map(|c| (c.start_time, c.start_time + c.duration))
+                        .collect(),
+                    Track::Audio(a) => a
+                        .clips
+                        .iter()
+                        .map(|c| (c.start_time, c.start_time + c.duration))
+                        .collect(),
+                };
+                TrackIndex::build(&spans)
             })
-            .flat_map(|video_track| {
-                video_track
-                    .clips
-                    .iter()
-                    .filter(move |clip| clip.is_active_at(time))
+            .collect();
+        ClipIndex { tracks }
+    }
+}
+
+impl TrackIndex {
+    fn build(spans: &[(f64, f64)]) -> Self {
+        let mut entries: Vec<IndexEntry> = spans
+            .iter()
+            .enumerate()
+            .map(|(clip_idx, &(start, end))| IndexEntry {
+                start,
+                end,
+                clip_idx,
+                max_end: end,
             })
-            .collect()
+            .collect();
+        entries.sort_by(|a, b| a.start.total_cmp(&b.start));
+        let mut max_end = f64::NEG_INFINITY;
+        for entry in &mut entries {
+            max_end = max_end.max(entry.end);
+            entry.max_end = max_end;
+        }
+        TrackIndex { entries }
+    }
+
+    /// Clip positions (ascending) overlapping `from..to`, i.e. `end > from` and
+    /// `start < to` (or `start <= to` when `inclusive`, which with `from == to`
+    /// gives the half-open point test `start <= t < end`).
+    /// `comparisons` counts the entries examined.
+    fn overlapping(
+        &self,
+        from: f64,
+        to: f64,
+        inclusive: bool,
+        comparisons: &mut usize,
+    ) -> Vec<usize> {
+        let upper = self.entries.partition_point(|e| {
+            *comparisons += 1;
+            if inclusive {
+                e.start <= to
+            } else {
+                e.start < to
+            }
+        });
+        let mut found = Vec::new();
+        for entry in self.entries[..upper].iter().rev() {
+            *comparisons += 1;
+            if entry.max_end <= from {
+                break;
+            }
+            if entry.end > from {
+                found.push(entry.clip_idx);
+            }
+        }
+        found.sort_unstable();
+        found
+    }
+}
+
+impl Timeline {
+    /// Tracks from top to bottom
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    /// Tracks for editing. Drops the clip index, which is rebuilt on the next lookup.
+    pub fn tracks_mut(&mut self) -> &mut Vec<Track> {
+        self.invalidate_index();
+        &mut self.tracks
+    }
+
+    /// Drop the clip lookup index so it is rebuilt on next use.
+    /// Must be called after adding, removing, moving or resizing clips.
+    pub fn invalidate_index(&mut self) {
+        self.clip_index = OnceLock::new();
+    }
+
+    /// The clip index, built on first use after an edit
+    fn index(&self) -> &ClipIndex {
+        self.clip_index
+            .get_or_init(|| ClipIndex::build(&self.tracks))
+    }
+
+    /// Positions of the clips on track `track_idx` overlapping `from..to` (see `TrackIndex::overlapping`).
+    fn track_clips_overlapping(
+        &self,
+        track_idx: usize,
+        from: f64,
+        to: f64,
+        inclusive: bool,
+        comparisons: &mut usize,
+    ) -> Vec<usize> {
+        self.index().tracks[track_idx].overlapping(from, to, inclusive, comparisons)
+    }
+
+    /// Returns all active video clips at a specific time.
+    pub fn active_video_clips_at(&self, time: f64) -> Vec<&VideoClip> {
+        let mut comparisons = 0;
+        let mut result = Vec::new();
+        for (track_idx, track) in self.tracks.iter().enumerate() {
+            if let Track::Video(video_track) = track {
+                for i in self.track_clips_overlapping(track_idx, time, time, true, &mut comparisons)
+                {
+                    result.push(&video_track.clips[i]);
+                }
+            }
+        }
+        result
     }
 }
 
@@ -45,6 +240,7 @@ impl Timeline {
                                 video_track.clips.remove(i);
                                 video_track.clips.insert(i, right);
                                 video_track.clips.insert(i, left);
+                                self.clip_index = OnceLock::new();
                                 return true;
                             }
                         }
@@ -59,6 +255,7 @@ impl Timeline {
                                 audio_track.clips.remove(i);
                                 audio_track.clips.insert(i, right);
                                 audio_track.clips.insert(i, left);
+                                self.clip_index = OnceLock::new();
                                 return true;
                             }
                         }
@@ -69,10 +266,54 @@ impl Timeline {
         }
         false
     }
+
+    /// Split the clips in `clip_ids` that the playhead is inside of, on every track;
+    /// other clips are left alone. Returns how many clips were split.
+    pub fn split_selected_at(
+        &mut self,
+        clip_ids: &std::collections::HashSet<String>,
+        playhead: f64,
+    ) -> usize {
+        fn split_in<T: ClipSplit + Clone>(
+            clips: &mut Vec<T>,
+            clip_ids: &std::collections::HashSet<String>,
+            playhead: f64,
+        ) -> usize {
+            let mut split = 0;
+            let mut i = 0;
+            while i < clips.len() {
+                if let Some((left, right)) = Some(&clips[i])
+                    .filter(|clip| clip_ids.contains(clip.id()))
+                    .and_then(|clip| cut_clip_at(clip, playhead))
+                {
+                    clips.splice(i..=i, [left, right]);
+                    split += 1;
+                    i += 1;
+                }
+                i += 1;
+            }
+            split
+        }
+
+        let split = self
+            .tracks
+            .iter_mut()
+            .map(|track| match track {
+                Track::Video(video_track) => split_in(&mut video_track.clips, clip_ids, playhead),
+                Track::Audio(audio_track) => split_in(&mut audio_track.clips, clip_ids, playhead),
+            })
+            .sum();
+        if split > 0 {
+            self.invalidate_index();
+        }
+        split
+    }
 }
 
-use crate::types::media::{AudioClip, VideoClip};
-use crate::types::track::Track;
+use crate::types::media::{
+    AudioClip, AudioMetadata, ClipPropsPatch, CropRect, TextClip, VideoClip, VideoMetadata,
+};
+use crate::types::track::{AudioTrack, Track, VideoTrack};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ActiveClip {
@@ -80,6 +321,15 @@ pub enum ActiveClip {
     Audio(AudioClip),
 }
 
+impl ActiveClip {
+    pub fn is_enabled(&self) -> bool {
+        match self {
+            ActiveClip::Video(v) => v.enabled,
+            ActiveClip::Audio(a) => a.enabled,
+        }
+    }
+}
+
 impl Timeline {
     pub fn new() -> Self {
         Timeline {
@@ -87,473 +337,3520 @@ impl Timeline {
             duration: 0.0,
             frame_rate: 30.0,
             resolution: (1920, 1080),
+            text_clips: Vec::new(),
+            beat_grid: None,
+            sync_lock_enabled: true,
+            subtitles: None,
+            markers: Vec::new(),
+            clip_index: OnceLock::new(),
         }
     }
 
     /// Returns all clips (audio and video) active at a specific time.
     pub fn active_clips_at(&self, time: f64) -> Vec<ActiveClip> {
+        let mut comparisons = 0;
+        self.collect_overlapping(time, time, true, false, &mut comparisons)
+    }
+
+    /// `active_clips_at` without the clips on muted tracks, for playback and export
+    pub fn active_unmuted_clips_at(&self, time: f64) -> Vec<ActiveClip> {
+        let mut comparisons = 0;
+        self.collect_overlapping(time, time, true, true, &mut comparisons)
+    }
+
+    /// Returns all clips (audio and video) that overlap with a given time range.
+    pub fn clips_in_range(&self, start: f64, end: f64) -> Vec<ActiveClip> {
+        let mut comparisons = 0;
+        self.collect_overlapping(start, end, false, false, &mut comparisons)
+    }
+
+    fn collect_overlapping(
+        &self,
+        from: f64,
+        to: f64,
+        inclusive: bool,
+        skip_muted: bool,
+        comparisons: &mut usize,
+    ) -> Vec<ActiveClip> {
         let mut result = Vec::new();
-        for track in &self.tracks {
+        for (track_idx, track) in self.tracks.iter().enumerate() {
+            if skip_muted && track.is_muted() {
+                continue;
+            }
+            let hits = self.track_clips_overlapping(track_idx, from, to, inclusive, comparisons);
             match track {
                 Track::Video(video_track) => {
-                    for clip in &video_track.clips {
-                        if clip.start_time <= time && time < clip.start_time + clip.duration {
-                            result.push(ActiveClip::Video(clip.clone()));
-                        }
-                    }
+                    result.extend(
+                        hits.into_iter()
+                            .map(|i| ActiveClip::Video(video_track.clips[i].clone())),
+                    );
                 }
                 Track::Audio(audio_track) => {
-                    for clip in &audio_track.clips {
-                        if clip.start_time <= time && time < clip.start_time + clip.duration {
-                            result.push(ActiveClip::Audio(clip.clone()));
-                        }
-                    }
+                    result.extend(
+                        hits.into_iter()
+                            .map(|i| ActiveClip::Audio(audio_track.clips[i].clone())),
+                    );
                 }
             }
         }
         result
     }
 
-    /// Returns all clips (audio and video) that overlap with a given time range.
-    pub fn clips_in_range(&self, start: f64, end: f64) -> Vec<ActiveClip> {
-        let mut result = Vec::new();
-        for track in &self.tracks {
+    /// Enabled text clips shown at a specific time, in drawing order.
+    pub fn active_text_clips_at(&self, time: f64) -> Vec<&TextClip> {
+        self.text_clips
+            .iter()
+            .filter(|c| c.enabled && c.is_active_at(time))
+            .collect()
+    }
+
+    /// Returns the clips that should be rendered at a specific time: active, enabled
+    /// and on an unmuted track.
+    pub fn renderable_clips_at(&self, time: f64) -> Vec<ActiveClip> {
+        self.active_unmuted_clips_at(time)
+            .into_iter()
+            .filter(ActiveClip::is_enabled)
+            .collect()
+    }
+
+    /// Clips whose sound is mixed at a specific time: enabled audio clips, and
+    /// enabled video clips whose embedded audio isn't muted.
+    pub fn audible_clips_at(&self, time: f64) -> Vec<ActiveClip> {
+        self.renderable_clips_at(time)
+            .into_iter()
+            .filter(|clip| match clip {
+                ActiveClip::Video(v) => !v.mute_audio,
+                ActiveClip::Audio(_) => true,
+            })
+            .collect()
+    }
+
+    /// Structural flatten before export: every enabled video clip on one video track and
+    /// every enabled audio clip on one audio track, each track's volume folded into its
+    /// clips' gain (track pan is dropped). Muted tracks are left out, as in playback.
+    /// Clips are ordered by track priority, then start time, so clips active at the same
+    /// time stay in compositing order (later on top).
+    pub fn flatten_for_export(&self) -> Timeline {
+        let mut video = Vec::new();
+        let mut audio = Vec::new();
+        for track in self.tracks.iter().filter(|t| !t.is_muted()) {
             match track {
-                Track::Video(video_track) => {
-                    for clip in &video_track.clips {
-                        let clip_start = clip.start_time;
-                        let clip_end = clip.start_time + clip.duration;
-                        if clip_end > start && clip_start < end {
-                            result.push(ActiveClip::Video(clip.clone()));
-                        }
+                Track::Video(v) => {
+                    let mut clips: Vec<VideoClip> =
+                        v.clips.iter().filter(|c| c.enabled).cloned().collect();
+                    clips.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+                    video.extend(clips);
+                }
+                Track::Audio(a) => {
+                    let mut clips: Vec<AudioClip> =
+                        a.clips.iter().filter(|c| c.enabled).cloned().collect();
+                    clips.sort_by(|a, b| a.start_time.total_cmp(&b.start_time));
+                    audio.extend(clips.into_iter().map(|clip| AudioClip {
+                        gain_db: clip.gain_db + a.volume_db,
+                        ..clip
+                    }));
+                }
+            }
+        }
 
-                        impl Track {
-                            pub fn is_video(&self) -> bool {
-                                matches!(self, Track::Video(_))
-                            }
-                        }
+        let mut flat = self.clone();
+        flat.tracks = Vec::new();
+        if !video.is_empty() {
+            flat.tracks.push(Track::Video(VideoTrack {
+                id: "flattened_video".to_string(),
+                name: "Video".to_string(),
+                clips: video,
+                muted: false,
+                sync_locked: false,
+            }));
+        }
+        if !audio.is_empty() {
+            flat.tracks.push(Track::Audio(AudioTrack {
+                id: "flattened_audio".to_string(),
+                name: "Audio".to_string(),
+                clips: audio,
+                muted: false,
+                sync_locked: false,
+                volume_db: 0.0,
+                pan: 0.0,
+            }));
+        }
+        flat.invalidate_index();
+        flat
+    }
 
-                        impl VideoClip {
-                            pub fn is_active_at(&self, time: f64) -> bool {
-                                time >= self.start_time && time < self.start_time + self.duration
-                            }
-                        }
+    /// Flips the embedded-audio mute of the video clip with the given id.
+    /// Returns the new state, or None if no such video clip exists.
+    pub fn toggle_clip_audio_muted(&mut self, clip_id: &str) -> Option<bool> {
+        self.tracks.iter_mut().find_map(|track| match track {
+            Track::Video(v) => v.clips.iter_mut().find(|c| c.id == clip_id).map(|clip| {
+                clip.mute_audio = !clip.mute_audio;
+                clip.mute_audio
+            }),
+            Track::Audio(_) => None,
+        })
+    }
+
+    /// Set the source crop of the video clip with the given id; a full-frame crop
+    /// is stored as `None`. Returns false if no such video clip exists.
+    pub fn set_clip_crop(&mut self, clip_id: &str, crop: Option<CropRect>) -> bool {
+        let crop = crop.filter(|c| *c != (0.0, 0.0, 1.0, 1.0));
+        self.tracks.iter_mut().any(|track| match track {
+            Track::Video(v) => v
+                .clips
+                .iter_mut()
+                .find(|c| c.id == clip_id)
+                .is_some_and(|clip| {
+                    clip.crop = crop;
+                    true
+                }),
+            Track::Audio(_) => false,
+        })
+    }
+
+    /// Flips the enabled flag of the clip with the given id.
+    /// Returns the new state, or None if no such clip exists.
+    pub fn toggle_clip_enabled(&mut self, clip_id: &str) -> Option<bool> {
+        for track in &mut self.tracks {
+            match track {
+                Track::Video(v) => {
+                    if let Some(clip) = v.clips.iter_mut().find(|c| c.id == clip_id) {
+                        clip.enabled = !clip.enabled;
+                        return Some(clip.enabled);
                     }
                 }
-                Track::Audio(audio_track) => {
-                    for clip in &audio_track.clips {
-                        let clip_start = clip.start_time;
-                        let clip_end = clip.start_time + clip.duration;
-                        if clip_end > start && clip_start < end {
-                            result.push(ActiveClip::Audio(clip.clone()));
-                        }
+                Track::Audio(a) => {
+                    if let Some(clip) = a.clips.iter_mut().find(|c| c.id == clip_id) {
+                        clip.enabled = !clip.enabled;
+                        return Some(clip.enabled);
                     }
                 }
             }
         }
-        result
+        None
     }
 
-    /// Returns all clips on a specific track by track id.
-    pub fn clips_on_track(&self, track_id: &str) -> Option<Vec<ActiveClip>> {
-        self.tracks
-            .iter()
-            .find(|t| match t {
-                Track::Video(v) => v.id == track_id,
-                Track::Audio(a) => a.id == track_id,
-            })
-            .map(|track| match track {
-                Track::Video(v) => v.clips.iter().cloned().map(ActiveClip::Video).collect(),
-                Track::Audio(a) => a.clips.iter().cloned().map(ActiveClip::Audio).collect(),
-            })
+    /// Source file of the clip with the given id.
+    pub fn clip_asset_path(&self, clip_id: &str) -> Option<&str> {
+        self.tracks.iter().find_map(|track| match track {
+            Track::Video(v) => v
+                .clips
+                .iter()
+                .find(|c| c.id == clip_id)
+                .map(|c| c.asset_path.as_str()),
+            Track::Audio(a) => a
+                .clips
+                .iter()
+                .find(|c| c.id == clip_id)
+                .map(|c| c.asset_path.as_str()),
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::ActiveClip;
-    use super::*;
-    use crate::types::media::{AudioClip, AudioMetadata, VideoClip, VideoMetadata};
-    use crate::types::track::{AudioTrack, Track, VideoTrack};
-    #[test]
-    fn test_split_clip_at_playhead_video() {
-        let video_clip = VideoClip {
-            id: "v1".to_string(),
-            asset_path: "video.mp4".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: VideoMetadata {
-                resolution: (1920, 1080),
-                frame_rate: 30.0,
-                codec: "h264".to_string(),
-            },
-        };
-        let video_track = VideoTrack {
-            id: "vt1".to_string(),
-            name: "Video Track 1".to_string(),
-            clips: vec![video_clip.clone()],
-            muted: false,
-        };
-        let mut timeline = Timeline {
-            tracks: vec![Track::Video(video_track)],
-            duration: 10.0,
-            frame_rate: 30.0,
-            resolution: (1920, 1080),
+    /// Clips whose id or source file name contains `query` (ignoring case), as
+    /// (track_idx, clip_id, start_time) in track order, then by start time
+    pub fn find_clips(&self, query: &str) -> Vec<(usize, String, f64)> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let matches = |id: &str, asset_path: &str| {
+            let file_name = std::path::Path::new(asset_path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            id.to_lowercase().contains(&query) || file_name.contains(&query)
         };
-        let split = timeline.split_clip_at_playhead("vt1", 4.0);
-        assert!(split);
-        if let Track::Video(ref vt) = timeline.tracks[0] {
-            assert_eq!(vt.clips.len(), 2);
-            assert_eq!(vt.clips[0].start_time, 0.0);
-            assert_eq!(vt.clips[0].duration, 4.0);
-            assert_eq!(vt.clips[1].start_time, 4.0);
-            assert_eq!(vt.clips[1].duration, 6.0);
-            assert_eq!(vt.clips[0].id, "v1_left");
-            assert_eq!(vt.clips[1].id, "v1_right");
-        } else {
-            panic!("Expected video track");
+        let mut found: Vec<(usize, String, f64)> = Vec::new();
+        for (track_idx, track) in self.tracks.iter().enumerate() {
+            let mut on_track: Vec<(usize, String, f64)> = match track {
+                Track::Video(v) => v
+                    .clips
+                    .iter()
+                    .filter(|c| matches(&c.id, &c.asset_path))
+                    .map(|c| (track_idx, c.id.clone(), c.start_time))
+                    .collect(),
+                Track::Audio(a) => a
+                    .clips
+                    .iter()
+                    .filter(|c| matches(&c.id, &c.asset_path))
+                    .map(|c| (track_idx, c.id.clone(), c.start_time))
+                    .collect(),
+            };
+            on_track.sort_by(|a, b| a.2.total_cmp(&b.2));
+            found.extend(on_track);
         }
+        found
     }
 
-    #[test]
-    fn test_split_clip_at_playhead_audio() {
-        let audio_clip = AudioClip {
-            id: "a1".to_string(),
-            asset_path: "audio.wav".to_string(),
-            in_point: 0.0,
-            out_point: 8.0,
-            start_time: 2.0,
-            duration: 8.0,
-            metadata: AudioMetadata {
-                sample_rate: 48000,
-                channels: 2,
-                codec: "pcm".to_string(),
-                bitrate: 1536,
+    /// Index of the track holding the clip `clip_id`
+    pub fn clip_track_index(&self, clip_id: &str) -> Option<usize> {
+        self.clip_span(clip_id).map(|(track_idx, _, _)| track_idx)
+    }
+
+    /// Start time of the clip `clip_id`
+    pub fn clip_start_time(&self, clip_id: &str) -> Option<f64> {
+        self.clip_span(clip_id).map(|(_, start, _)| start)
+    }
+
+    /// Trim a clip to a new start time and duration, moving its in/out points
+    /// with the edges. The source range is kept within `0..=source_duration`
+    /// (or just non-negative when the source length is unknown).
+    /// Returns false if no such clip exists.
+    pub fn resize_clip(
+        &mut self,
+        clip_id: &str,
+        new_start_time: f64,
+        new_duration: f64,
+        source_duration: Option<f64>,
+    ) -> bool {
+        let source_duration = source_duration.unwrap_or(f64::INFINITY);
+        let resized = self.tracks.iter_mut().any(|track| match track {
+            Track::Video(v) => match v.clips.iter_mut().find(|c| c.id == clip_id) {
+                // A freeze frame keeps showing the same source frame at any length
+                Some(clip) if clip.freeze => {
+                    clip.opacity_envelope
+                        .shift(new_start_time - clip.start_time);
+                    clip.start_time = new_start_time;
+                    clip.duration = new_duration;
+                    clip.clamp_to_source(source_duration);
+                    true
+                }
+                Some(clip) => {
+                    // Timeline seconds cover `speed` seconds of source
+                    let head = (new_start_time - clip.start_time) * clip.speed;
+                    let tail = (new_start_time + new_duration - (clip.start_time + clip.duration))
+                        * clip.speed;
+                    if clip.reversed {
+                        clip.out_point -= head;
+                        clip.in_point -= tail;
+                    } else {
+                        clip.in_point += head;
+                        clip.out_point += tail;
+                    }
+                    let old_start = clip.start_time;
+                    clip.start_time = new_start_time;
+                    clip.clamp_to_source(source_duration);
+                    // Keyframes and the speed ramp stay at their timeline position
+                    clip.shift_local_times(clip.start_time - old_start);
+                    true
+                }
+                None => false,
+            },
+            Track::Audio(a) => match a.clips.iter_mut().find(|c| c.id == clip_id) {
+                Some(clip) => {
+                    clip.in_point += new_start_time - clip.start_time;
+                    clip.out_point +=
+                        new_start_time + new_duration - (clip.start_time + clip.duration);
+                    clip.start_time = new_start_time;
+                    clip.clamp_to_source(source_duration);
+                    true
+                }
+                None => false,
             },
+        });
+        if resized {
+            self.invalidate_index();
+        }
+        resized
+    }
+
+    /// Move a clip to start at `new_start_time` (clamped to 0), keeping its length and
+    /// source range. Clips grouped with it move by the same amount, so none of them goes
+    /// before 0. Returns false if no such clip exists.
+    pub fn move_clip(&mut self, clip_id: &str, new_start_time: f64) -> bool {
+        let Some(old_start) = self.clip_start_time(clip_id) else {
+            return false;
         };
-        let audio_track = AudioTrack {
-            id: "at1".to_string(),
-            name: "Audio Track 1".to_string(),
-            clips: vec![audio_clip.clone()],
-            muted: false,
+        let group = self.clip_group_id(clip_id).map(str::to_string);
+        let moves = |id: &str, group_id: &Option<String>| {
+            id == clip_id || (group.is_some() && *group_id == group)
         };
-        let mut timeline = Timeline {
-            tracks: vec![Track::Audio(audio_track)],
-            duration: 10.0,
-            frame_rate: 30.0,
-            resolution: (1920, 1080),
+        let earliest = self
+            .tracks
+            .iter()
+            .flat_map(|track| match track {
+                Track::Video(v) => v
+                    .clips
+                    .iter()
+                    .filter(|c| moves(&c.id, &c.group_id))
+                    .map(|c| c.start_time)
+                    .collect::<Vec<_>>(),
+                Track::Audio(a) => a
+                    .clips
+                    .iter()
+                    .filter(|c| moves(&c.id, &c.group_id))
+                    .map(|c| c.start_time)
+                    .collect(),
+            })
+            .fold(old_start, f64::min);
+        let delta = (new_start_time - old_start).max(-earliest);
+        for track in &mut self.tracks {
+            match track {
+                Track::Video(v) => {
+                    for clip in v.clips.iter_mut().filter(|c| moves(&c.id, &c.group_id)) {
+                        clip.start_time += delta;
+                    }
+                }
+                Track::Audio(a) => {
+                    for clip in a.clips.iter_mut().filter(|c| moves(&c.id, &c.group_id)) {
+                        clip.start_time += delta;
+                    }
+                }
+            }
+        }
+        self.invalidate_index();
+        true
+    }
+
+    /// Group id of the clip `clip_id`, if it has one
+    pub fn clip_group_id(&self, clip_id: &str) -> Option<&str> {
+        self.tracks.iter().find_map(|track| match track {
+            Track::Video(v) => v
+                .clips
+                .iter()
+                .find(|c| c.id == clip_id)
+                .map(|c| c.group_id.as_deref()),
+            Track::Audio(a) => a
+                .clips
+                .iter()
+                .find(|c| c.id == clip_id)
+                .map(|c| c.group_id.as_deref()),
+        })?
+    }
+
+    /// Place `video_path` at `at_time` as a video clip and a clip of its sound, sharing a
+    /// new group id. Each goes on the first track of its kind with room over
+    /// `at_time..at_time + duration`, or on a new track when none has. The video clip's
+    /// embedded audio is muted, as the audio clip now carries it.
+    /// Returns the ids of the video and audio clips.
+    pub fn add_av_clip(
+        &mut self,
+        video_path: &str,
+        at_time: f64,
+        duration: f64,
+        video: VideoMetadata,
+        audio: AudioMetadata,
+    ) -> (String, String) {
+        let at_time = at_time.max(0.0);
+        let group = (1..)
+            .map(|n| format!("av_{}", n))
+            .find(|group| {
+                !self.tracks.iter().any(|track| match track {
+                    Track::Video(v) => v.clips.iter().any(|c| c.group_id.as_ref() == Some(group)),
+                    Track::Audio(a) => a.clips.iter().any(|c| c.group_id.as_ref() == Some(group)),
+                })
+            })
+            .unwrap();
+        let (video_id, audio_id) = (format!("{}_video", group), format!("{}_audio", group));
+
+        let fits = |track: &Track| track.has_room(at_time, at_time + duration);
+        let video_idx = match self.tracks.iter().position(|t| t.is_video() && fits(t)) {
+            Some(idx) => idx,
+            None => self.push_track(true),
         };
-        let split = timeline.split_clip_at_playhead("at1", 6.0);
-        assert!(split);
-        if let Track::Audio(ref at) = timeline.tracks[0] {
-            assert_eq!(at.clips.len(), 2);
-            assert_eq!(at.clips[0].start_time, 2.0);
-            assert_eq!(at.clips[0].duration, 4.0);
-            assert_eq!(at.clips[1].start_time, 6.0);
-            assert_eq!(at.clips[1].duration, 4.0);
-            assert_eq!(at.clips[0].id, "a1_left");
-            assert_eq!(at.clips[1].id, "a1_right");
-        } else {
-            panic!("Expected audio track");
+        let audio_idx = match self.tracks.iter().position(|t| t.is_audio() && fits(t)) {
+            Some(idx) => idx,
+            None => self.push_track(false),
+        };
+        if let Track::Video(v) = &mut self.tracks[video_idx] {
+            v.clips.push(VideoClip {
+                id: video_id.clone(),
+                asset_path: video_path.to_string(),
+                in_point: 0.0,
+                out_point: duration,
+                start_time: at_time,
+                duration,
+                enabled: true,
+                effects: Vec::new(),
+                opacity_envelope: crate::types::envelope::Envelope::new(),
+                freeze: false,
+                speed: 1.0,
+                time_map: None,
+                reversed: false,
+                mute_audio: true,
+                crop: None,
+                group_id: Some(group.clone()),
+                metadata: video,
+            });
         }
+        if let Track::Audio(a) = &mut self.tracks[audio_idx] {
+            a.clips.push(AudioClip {
+                id: audio_id.clone(),
+                asset_path: video_path.to_string(),
+                in_point: 0.0,
+                out_point: duration,
+                start_time: at_time,
+                duration,
+                enabled: true,
+                gain_db: 0.0,
+                group_id: Some(group),
+                metadata: audio,
+            });
+        }
+        self.invalidate_index();
+        (video_id, audio_id)
     }
 
-    #[test]
-    fn test_split_clip_at_playhead_no_split() {
-        let video_clip = VideoClip {
-            id: "v1".to_string(),
-            asset_path: "video.mp4".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
+    /// Append an empty video (or audio) track and return its index
+    fn push_track(&mut self, video: bool) -> usize {
+        let n = self.tracks.len() + 1;
+        self.tracks.push(if video {
+            Track::Video(VideoTrack {
+                id: format!("track_{}", n),
+                name: format!("Video Track {}", n),
+                clips: vec![],
+                muted: false,
+                sync_locked: false,
+            })
+        } else {
+            Track::Audio(AudioTrack {
+                id: format!("track_{}", n),
+                name: format!("Audio Track {}", n),
+                clips: vec![],
+                muted: false,
+                sync_locked: false,
+                volume_db: 0.0,
+                pan: 0.0,
+            })
+        });
+        self.invalidate_index();
+        n - 1
+    }
+
+    /// Split the video clip `clip_id` at `playhead` and insert a freeze frame of the
+    /// source frame at the playhead, held for `hold_duration` seconds. The right part
+    /// and everything after it on the track (and on sync-locked tracks, see `ripple`)
+    /// move right by `hold_duration`.
+    /// Returns false if the clip isn't on the track or the playhead isn't inside it.
+    pub fn insert_freeze(
+        &mut self,
+        track_id: &str,
+        clip_id: &str,
+        playhead: f64,
+        hold_duration: f64,
+    ) -> bool {
+        if hold_duration <= 0.0 {
+            return false;
+        }
+        let Some(track_idx) = self
+            .tracks
+            .iter()
+            .position(|t| t.is_video() && t.id() == track_id)
+        else {
+            return false;
+        };
+        let Track::Video(video_track) = &self.tracks[track_idx] else {
+            return false;
+        };
+        let Some(i) = video_track.clips.iter().position(|c| c.id == clip_id) else {
+            return false;
+        };
+        let Some((left, mut right)) = cut_clip_at(&video_track.clips[i], playhead) else {
+            return false;
+        };
+        // The frame under the playhead, wherever it lies in a reversed or ramped range
+        let frame = video_track.clips[i].source_time_at(playhead);
+
+        let mut freeze = left.clone();
+        freeze.id = format!("{}_freeze", clip_id);
+        freeze.in_point = frame;
+        freeze.out_point = frame;
+        freeze.shift_local_times(playhead - left.start_time);
+        freeze.start_time = playhead;
+        freeze.duration = hold_duration;
+        freeze.freeze = true;
+        right.start_time += hold_duration;
+
+        // Push later content right to make room for the held frame
+        self.ripple(track_idx, playhead, hold_duration);
+        if let Track::Video(video_track) = &mut self.tracks[track_idx] {
+            video_track.clips.splice(i..=i, [left, freeze, right]);
+        }
+        self.invalidate_index();
+        true
+    }
+
+    /// Move content at or after `time` by `delta` seconds on track `track_idx` and,
+    /// when that track is sync-locked and sync lock is on, on every other
+    /// sync-locked track.
+    fn ripple(&mut self, track_idx: usize, time: f64, delta: f64) {
+        let follow = self.sync_lock_enabled && self.tracks[track_idx].is_sync_locked();
+        for (i, track) in self.tracks.iter_mut().enumerate() {
+            if i == track_idx || (follow && track.is_sync_locked()) {
+                track.shift_clips_from(time, delta);
+            }
+        }
+        self.invalidate_index();
+    }
+
+    /// Track index, start and end of the clip `clip_id`
+    fn clip_span(&self, clip_id: &str) -> Option<(usize, f64, f64)> {
+        self.tracks
+            .iter()
+            .enumerate()
+            .find_map(|(track_idx, track)| {
+                let (start, duration) = match track {
+                    Track::Video(v) => v
+                        .clips
+                        .iter()
+                        .find(|c| c.id == clip_id)
+                        .map(|c| (c.start_time, c.duration))?,
+                    Track::Audio(a) => a
+                        .clips
+                        .iter()
+                        .find(|c| c.id == clip_id)
+                        .map(|c| (c.start_time, c.duration))?,
+                };
+                Some((track_idx, start, start + duration))
+            })
+    }
+
+    /// Remove a clip and close the gap it leaves by pulling later content left
+    /// (on sync-locked tracks too, see `ripple`). Returns false if no such clip exists.
+    pub fn ripple_delete(&mut self, clip_id: &str) -> bool {
+        let Some((track_idx, start, end)) = self.clip_span(clip_id) else {
+            return false;
+        };
+        match &mut self.tracks[track_idx] {
+            Track::Video(v) => v.clips.retain(|c| c.id != clip_id),
+            Track::Audio(a) => a.clips.retain(|c| c.id != clip_id),
+        }
+        self.ripple(track_idx, end, start - end);
+        true
+    }
+
+    /// Remove every clip in `clip_ids`, leaving gaps, or with `ripple` closing each gap on
+    /// its own track. Gaps close right to left so earlier ones don't move the clips still
+    /// to be removed; sync-locked tracks don't follow, as the selection already says what
+    /// leaves each track. Returns how many clips were removed.
+    pub fn delete_clips(
+        &mut self,
+        clip_ids: &std::collections::HashSet<String>,
+        ripple: bool,
+    ) -> usize {
+        let mut spans: Vec<(usize, f64, f64, &String)> = clip_ids
+            .iter()
+            .filter_map(|id| {
+                let (track_idx, start, end) = self.clip_span(id)?;
+                Some((track_idx, start, end, id))
+            })
+            .collect();
+        spans.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        for &(track_idx, start, end, clip_id) in &spans {
+            let track = &mut self.tracks[track_idx];
+            match track {
+                Track::Video(v) => v.clips.retain(|c| c.id != *clip_id),
+                Track::Audio(a) => a.clips.retain(|c| c.id != *clip_id),
+            }
+            if ripple {
+                track.shift_clips_from(end, start - end);
+            }
+        }
+        self.invalidate_index();
+        spans.len()
+    }
+
+    /// Remove a clip and leave a gap where it was. Returns false if no such clip exists.
+    pub fn remove_clip(&mut self, clip_id: &str) -> bool {
+        let Some((track_idx, _, _)) = self.clip_span(clip_id) else {
+            return false;
+        };
+        match &mut self.tracks[track_idx] {
+            Track::Video(v) => v.clips.retain(|c| c.id != clip_id),
+            Track::Audio(a) => a.clips.retain(|c| c.id != clip_id),
+        }
+        self.invalidate_index();
+        true
+    }
+
+    /// Make room for a clip just placed on its track by moving content that starts at
+    /// or after it right by its length (on sync-locked tracks too, see `ripple`).
+    /// Returns false if no such clip exists.
+    pub fn ripple_insert(&mut self, clip_id: &str) -> bool {
+        let Some((track_idx, start, end)) = self.clip_span(clip_id) else {
+            return false;
+        };
+        self.ripple(track_idx, start, end - start);
+        // The inserted clip itself stays put
+        self.move_clip(clip_id, start)
+    }
+
+    /// Trim the end of a clip to `new_duration` (clamped to its source, see
+    /// `resize_clip`) and move later content by the change in length.
+    /// Returns false if no such clip exists.
+    pub fn ripple_trim(
+        &mut self,
+        clip_id: &str,
+        new_duration: f64,
+        source_duration: Option<f64>,
+    ) -> bool {
+        let Some((track_idx, start, end)) = self.clip_span(clip_id) else {
+            return false;
+        };
+        self.resize_clip(clip_id, start, new_duration, source_duration);
+        let new_end = self
+            .clip_span(clip_id)
+            .map_or(end, |(_, _, new_end)| new_end);
+        // Content from the old end on follows the clip's new end
+        self.ripple(track_idx, end, new_end - end);
+        true
+    }
+
+    /// Play the video clip `clip_id` on track `track_id` at `speed` (see
+    /// `VideoClip::apply_patch`). With `ripple`, content after it on the track (and on
+    /// sync-locked tracks) moves by the change in length, so clips stay butted.
+    /// Returns false if the clip isn't on the track or `speed` isn't positive.
+    pub fn set_clip_speed(
+        &mut self,
+        track_id: &str,
+        clip_id: &str,
+        speed: f64,
+        ripple: bool,
+    ) -> bool {
+        let Some(track_idx) = self.tracks.iter().position(|t| t.id() == track_id) else {
+            return false;
+        };
+        let Track::Video(video_track) = &mut self.tracks[track_idx] else {
+            return false;
+        };
+        let Some(clip) = video_track
+            .clips
+            .iter_mut()
+            .find(|c| c.id == clip_id)
+            .filter(|_| speed > 0.0)
+        else {
+            return false;
+        };
+        let end = clip.start_time + clip.duration;
+        clip.apply_patch(&ClipPropsPatch {
+            speed: Some(speed),
+            ..ClipPropsPatch::default()
+        });
+        let new_end = clip.start_time + clip.duration;
+        if ripple {
+            self.ripple(track_idx, end, new_end - end);
+        }
+        self.invalidate_index();
+        true
+    }
+
+    /// Lengthen a clip so it ends where the next clip on its track starts, keeping its
+    /// source range within `source_duration` (see `resize_clip`). Returns false if the
+    /// clip isn't on the track, has no gap after it, or can't grow.
+    pub fn extend_to_next(
+        &mut self,
+        track_id: &str,
+        clip_id: &str,
+        source_duration: Option<f64>,
+    ) -> bool {
+        let Some(track) = self.tracks.iter().find(|t| t.id() == track_id) else {
+            return false;
+        };
+        let spans: Vec<(&str, f64, f64)> = match track {
+            Track::Video(v) => v
+                .clips
+                .iter()
+                .map(|c| (c.id.as_str(), c.start_time, c.start_time + c.duration))
+                .collect(),
+            Track::Audio(a) => a
+                .clips
+                .iter()
+                .map(|c| (c.id.as_str(), c.start_time, c.start_time + c.duration))
+                .collect(),
+        };
+        let Some(&(_, start, end)) = spans.iter().find(|(id, _, _)| *id == clip_id) else {
+            return false;
+        };
+        let Some(next_start) = spans
+            .iter()
+            .filter(|(id, next_start, _)| *id != clip_id && *next_start >= end)
+            .map(|(_, next_start, _)| *next_start)
+            .min_by(f64::total_cmp)
+        else {
+            return false;
+        };
+        if next_start - end < 1e-9 {
+            return false;
+        }
+
+        self.resize_clip(clip_id, start, next_start - start, source_duration);
+        self.clip_span(clip_id)
+            .is_some_and(|(_, _, new_end)| new_end > end + 1e-9)
+    }
+
+    /// Extend edit: move the clip edge on track `track_id` nearest to `playhead` onto it.
+    /// When two clips meet at that edge both are trimmed (a roll). Otherwise the lone edge
+    /// grows into the gap next to it, stopping at the neighboring clip, or trims its clip
+    /// when the playhead is inside it. Edges stop where a clip runs out of source, as
+    /// told by `source_duration` for an asset path (`None` when unknown). Returns false if
+    /// the track is missing or empty, or the edge can't move.
+    pub fn extend_edit(
+        &mut self,
+        track_id: &str,
+        playhead: f64,
+        mut source_duration: impl FnMut(&str) -> Option<f64>,
+    ) -> bool {
+        let Some(track) = self.tracks.iter().find(|t| t.id() == track_id) else {
+            return false;
+        };
+        let spans: Vec<(String, f64, f64)> = match track {
+            Track::Video(v) => v
+                .clips
+                .iter()
+                .map(|c| (c.id.clone(), c.start_time, c.start_time + c.duration))
+                .collect(),
+            Track::Audio(a) => a
+                .clips
+                .iter()
+                .map(|c| (c.id.clone(), c.start_time, c.start_time + c.duration))
+                .collect(),
+        };
+        let Some(edge) = spans
+            .iter()
+            .flat_map(|(_, start, end)| [*start, *end])
+            .min_by(|a, b| (a - playhead).abs().total_cmp(&(b - playhead).abs()))
+        else {
+            return false;
+        };
+        let ending = spans.iter().find(|(_, _, end)| (end - edge).abs() < 1e-9);
+        let starting = spans
+            .iter()
+            .find(|(_, start, _)| (start - edge).abs() < 1e-9);
+
+        // Neither clip may shrink away, and a lone edge stops at the next clip over
+        let mut low = ending.map_or(0.0, |(_, start, _)| *start);
+        let mut high = starting.map_or(f64::INFINITY, |(_, _, end)| *end);
+        if starting.is_none() {
+            high = spans
+                .iter()
+                .map(|(_, start, _)| *start)
+                .filter(|start| *start >= edge + 1e-9)
+                .fold(high, f64::min);
+        }
+        if ending.is_none() {
+            low = spans
+                .iter()
+                .map(|(_, _, end)| *end)
+                .filter(|end| *end <= edge - 1e-9)
+                .fold(low, f64::max);
+        }
+        // Nor may either clip grow past the ends of its source
+        let mut room = |clip_id: &str| {
+            let source = self.clip_asset_path(clip_id).and_then(&mut source_duration);
+            self.grow_room(clip_id, source)
+        };
+        if let Some((clip_id, _, end)) = ending {
+            high = high.min(end + room(clip_id).1);
+        }
+        if let Some((clip_id, start, _)) = starting {
+            low = low.max(start - room(clip_id).0);
+        }
+        let target = playhead.clamp(low, high);
+        let collapses = ending.is_some_and(|(_, start, _)| target - start < 1e-9)
+            || starting.is_some_and(|(_, _, end)| end - target < 1e-9);
+        if (target - edge).abs() < 1e-9 || collapses {
+            return false;
+        }
+
+        let (ending, starting) = (ending.cloned(), starting.cloned());
+        let mut moved = false;
+        if let Some((clip_id, start, end)) = ending {
+            self.resize_clip(&clip_id, start, target - start, None);
+            moved |= self
+                .clip_span(&clip_id)
+                .is_some_and(|(_, _, new_end)| (new_end - end).abs() > 1e-9);
+        }
+        if let Some((clip_id, start, end)) = starting {
+            self.resize_clip(&clip_id, target, end - target, None);
+            moved |= self
+                .clip_span(&clip_id)
+                .is_some_and(|(_, new_start, _)| (new_start - start).abs() > 1e-9);
+        }
+        moved
+    }
+
+    /// Timeline seconds the clip `clip_id` can grow at its head and at its tail before
+    /// running out of a source `source_duration` seconds long (unbounded when unknown)
+    fn grow_room(&self, clip_id: &str, source_duration: Option<f64>) -> (f64, f64) {
+        let source = source_duration.unwrap_or(f64::INFINITY);
+        let (head, tail) = self
+            .tracks
+            .iter()
+            .find_map(|track| match track {
+                Track::Video(v) => v.clips.iter().find(|c| c.id == clip_id).map(|c| {
+                    if c.freeze {
+                        return (f64::INFINITY, f64::INFINITY);
+                    }
+                    let (before, after) = (c.in_point / c.speed, (source - c.out_point) / c.speed);
+                    if c.reversed {
+                        (after, before)
+                    } else {
+                        (before, after)
+                    }
+                }),
+                Track::Audio(a) => a
+                    .clips
+                    .iter()
+                    .find(|c| c.id == clip_id)
+                    .map(|c| (c.in_point, source - c.out_point)),
+            })
+            .unwrap_or((f64::INFINITY, f64::INFINITY));
+        (head.max(0.0), tail.max(0.0))
+    }
+
+    /// Point a clip at a different source file, keeping its place on the timeline and
+    /// its in/out points. When the new source is `source_duration` seconds long the
+    /// source range is clamped to it, shortening the clip if needed. `metadata` replaces
+    /// a video clip's metadata when given. Returns false if the clip isn't on the track
+    /// or its in point lies past the end of the new source.
+    pub fn replace_clip_source(
+        &mut self,
+        track_id: &str,
+        clip_id: &str,
+        new_path: &str,
+        source_duration: Option<f64>,
+        metadata: Option<VideoMetadata>,
+    ) -> bool {
+        let Some(track) = self.tracks.iter_mut().find(|t| t.id() == track_id) else {
+            return false;
+        };
+        let replaced = match track {
+            Track::Video(v) => v
+                .clips
+                .iter_mut()
+                .find(|c| c.id == clip_id)
+                .is_some_and(|clip| {
+                    if source_duration.is_some_and(|d| clip.in_point >= d) {
+                        return false;
+                    }
+                    clip.asset_path = new_path.to_string();
+                    if let Some(source_duration) = source_duration {
+                        clip.clamp_to_source(source_duration);
+                    }
+                    if let Some(metadata) = metadata {
+                        clip.metadata = metadata;
+                    }
+                    true
+                }),
+            Track::Audio(a) => a
+                .clips
+                .iter_mut()
+                .find(|c| c.id == clip_id)
+                .is_some_and(|clip| {
+                    if source_duration.is_some_and(|d| clip.in_point >= d) {
+                        return false;
+                    }
+                    clip.asset_path = new_path.to_string();
+                    if let Some(source_duration) = source_duration {
+                        clip.clamp_to_source(source_duration);
+                    }
+                    true
+                }),
+        };
+        if replaced {
+            self.invalidate_index();
+        }
+        replaced
+    }
+
+    /// Point every clip playing `old_path` at `new_path` instead. Returns how many
+    /// clips were changed.
+    pub fn relink_asset(&mut self, old_path: &str, new_path: &str) -> usize {
+        let mut relinked = 0;
+        for track in &mut self.tracks {
+            let paths: Vec<&mut String> = match track {
+                Track::Video(v) => v.clips.iter_mut().map(|c| &mut c.asset_path).collect(),
+                Track::Audio(a) => a.clips.iter_mut().map(|c| &mut c.asset_path).collect(),
+            };
+            for path in paths.into_iter().filter(|p| *p == old_path) {
+                *path = new_path.to_string();
+                relinked += 1;
+            }
+        }
+        if relinked > 0 {
+            self.invalidate_index();
+        }
+        relinked
+    }
+
+    /// Apply `props` to every video clip with the same source file as `clip_id`
+    /// (including that clip). Returns how many clips were patched.
+    pub fn apply_properties_to_same_source(
+        &mut self,
+        clip_id: &str,
+        props: ClipPropsPatch,
+    ) -> usize {
+        let Some(asset_path) = self.clip_asset_path(clip_id).map(str::to_string) else {
+            return 0;
+        };
+        let mut patched = 0;
+        for track in &mut self.tracks {
+            if let Track::Video(v) = track {
+                for clip in v.clips.iter_mut().filter(|c| c.asset_path == asset_path) {
+                    clip.apply_patch(&props);
+                    patched += 1;
+                }
+            }
+        }
+        if patched > 0 {
+            self.invalidate_index();
+        }
+        patched
+    }
+
+    /// Add a marker at `time`, keeping markers sorted. A marker already at that time
+    /// is kept instead. Returns false in that case.
+    pub fn add_marker(&mut self, time: f64, name: String) -> bool {
+        if self.markers.iter().any(|m| (m.time - time).abs() < 1e-9) {
+            return false;
+        }
+        let idx = self.markers.partition_point(|m| m.time < time);
+        self.markers.insert(idx, Marker { time, name });
+        true
+    }
+
+    /// Time of the first marker after `time`
+    pub fn next_marker(&self, time: f64) -> Option<f64> {
+        self.markers
+            .iter()
+            .map(|m| m.time)
+            .find(|t| *t > time + 1e-9)
+    }
+
+    /// Time of the last marker before `time`
+    pub fn previous_marker(&self, time: f64) -> Option<f64> {
+        self.markers
+            .iter()
+            .rev()
+            .map(|m| m.time)
+            .find(|t| *t < time - 1e-9)
+    }
+
+    /// Empty stretches on track `track_idx` as (start, end): before the first clip and
+    /// between clips. Space after the last clip isn't a gap.
+    pub fn find_gaps(&self, track_idx: usize) -> Vec<(f64, f64)> {
+        self.tracks
+            .get(track_idx)
+            .map(|track| gaps_between(&track.clip_spans()))
+            .unwrap_or_default()
+    }
+
+    /// The gap on track `track_idx` containing `time`
+    pub fn gap_at(&self, track_idx: usize, time: f64) -> Option<(f64, f64)> {
+        self.find_gaps(track_idx)
+            .into_iter()
+            .find(|&(start, end)| time >= start && time < end)
+    }
+
+    /// Opacity (0.0..=1.0) of the clip `clip_id` at `time` under `fade`: it fades up over
+    /// the first `fade.duration` seconds after a short gap before it, and down over the
+    /// last ones before a short gap after it. Clips without a short gap beside them,
+    /// and unknown clips, stay at 1.0.
+    pub fn gap_fade_at(&self, clip_id: &str, time: f64, fade: GapFade) -> f32 {
+        let Some((track_idx, start, end)) = self.clip_span(clip_id) else {
+            return 1.0;
+        };
+        let spans = self.tracks[track_idx].clip_spans();
+        let gap_before = spans
+            .iter()
+            .filter(|&&(_, e)| e <= start + 1e-9)
+            .map(|&(_, e)| start - e)
+            .reduce(f64::min);
+        let gap_after = spans
+            .iter()
+            .filter(|&&(s, _)| s >= end - 1e-9)
+            .map(|&(s, _)| s - end)
+            .reduce(f64::min);
+        let ramp = |edge_distance: f64, gap: Option<f64>| match gap {
+            Some(gap) if gap > 1e-9 && gap <= fade.max_gap && fade.duration > 0.0 => {
+                (edge_distance / fade.duration).clamp(0.0, 1.0)
+            }
+            _ => 1.0,
+        };
+        (ramp(time - start, gap_before) * ramp(end - time, gap_after)) as f32
+    }
+
+    /// Close the gap `start..end` on track `track_idx` by pulling the clips after it
+    /// left (on sync-locked tracks too, see `ripple`). Returns false if there is no
+    /// such gap.
+    pub fn close_gap(&mut self, track_idx: usize, start: f64, end: f64) -> bool {
+        let exists = self
+            .find_gaps(track_idx)
+            .iter()
+            .any(|&(s, e)| (s - start).abs() < 1e-9 && (e - end).abs() < 1e-9);
+        if !exists {
+            return false;
+        }
+        self.ripple(track_idx, end, start - end);
+        true
+    }
+
+    /// Close every gap on every track, so clips run back to back from 0. With sync lock
+    /// on, the sync-locked tracks close together: only stretches empty on all of them are
+    /// removed, keeping them in sync. Returns the number of stretches removed.
+    pub fn close_all_gaps(&mut self) -> usize {
+        let locked: Vec<usize> = (0..self.tracks.len())
+            .filter(|&i| self.sync_lock_enabled && self.tracks[i].is_sync_locked())
+            .collect();
+        // Each group of tracks shifts as one: all the locked tracks, then every other
+        // track on its own
+        let mut groups = vec![locked.clone()];
+        groups.extend(
+            (0..self.tracks.len())
+                .filter(|i| !locked.contains(i))
+                .map(|i| vec![i]),
+        );
+        let mut closed = 0;
+        for group in groups.into_iter().filter(|g| !g.is_empty()) {
+            let mut spans: Vec<(f64, f64)> = group
+                .iter()
+                .flat_map(|&i| self.tracks[i].clip_spans())
+                .collect();
+            spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+            // Right to left, so the gaps still to close keep their place
+            for (start, end) in gaps_between(&spans).into_iter().rev() {
+                for &i in &group {
+                    self.tracks[i].shift_clips_from(end - 1e-9, start - end);
+                }
+                closed += 1;
+            }
+        }
+        self.invalidate_index();
+        closed
+    }
+
+    /// Ids and start times of the clips on track `track_idx`, in start-time order
+    fn clips_in_order(&self, track_idx: usize) -> Vec<(&str, f64)> {
+        let mut clips: Vec<(&str, f64)> = match self.tracks.get(track_idx) {
+            Some(Track::Video(v)) => v
+                .clips
+                .iter()
+                .map(|c| (c.id.as_str(), c.start_time))
+                .collect(),
+            Some(Track::Audio(a)) => a
+                .clips
+                .iter()
+                .map(|c| (c.id.as_str(), c.start_time))
+                .collect(),
+            None => Vec::new(),
+        };
+        clips.sort_by(|a, b| a.1.total_cmp(&b.1));
+        clips
+    }
+
+    /// The clip after `after_clip_id` on track `track_idx` in start-time order,
+    /// wrapping around to the first. Without `after_clip_id` (or if it isn't on the
+    /// track) the first clip. None for an empty or missing track.
+    pub fn next_clip(&self, track_idx: usize, after_clip_id: Option<&str>) -> Option<String> {
+        let clips = self.clips_in_order(track_idx);
+        let next = after_clip_id
+            .and_then(|id| clips.iter().position(|(c, _)| *c == id))
+            .map_or(0, |i| (i + 1) % clips.len());
+        clips.get(next).map(|(id, _)| id.to_string())
+    }
+
+    /// Like `next_clip`, stepping backwards; wraps around to the last clip.
+    pub fn previous_clip(&self, track_idx: usize, before_clip_id: Option<&str>) -> Option<String> {
+        let clips = self.clips_in_order(track_idx);
+        let previous = before_clip_id
+            .and_then(|id| clips.iter().position(|(c, _)| *c == id))
+            .map_or(0, |i| (i + clips.len() - 1) % clips.len());
+        clips.get(previous).map(|(id, _)| id.to_string())
+    }
+
+    /// The clip on track `track_idx` starting closest to `time`
+    pub fn clip_nearest(&self, track_idx: usize, time: f64) -> Option<String> {
+        self.clips_in_order(track_idx)
+            .into_iter()
+            .min_by(|a, b| (a.1 - time).abs().total_cmp(&(b.1 - time).abs()))
+            .map(|(id, _)| id.to_string())
+    }
+
+    /// Track edits go to by default: the `target` track when it still exists and holds
+    /// video (`video`) or audio, otherwise the first track of that kind
+    pub fn target_track(&self, target: Option<&str>, video: bool) -> Option<usize> {
+        let fits = |track: &Track| track.is_video() == video;
+        target
+            .and_then(|id| {
+                self.tracks
+                    .iter()
+                    .position(|track| track.id() == id && fits(track))
+            })
+            .or_else(|| self.tracks.iter().position(fits))
+    }
+
+    /// Returns all clips on a specific track by track id.
+    pub fn clips_on_track(&self, track_id: &str) -> Option<Vec<ActiveClip>> {
+        self.tracks
+            .iter()
+            .find(|t| match t {
+                Track::Video(v) => v.id == track_id,
+                Track::Audio(a) => a.id == track_id,
+            })
+            .map(|track| match track {
+                Track::Video(v) => v.clips.iter().cloned().map(ActiveClip::Video).collect(),
+                Track::Audio(a) => a.clips.iter().cloned().map(ActiveClip::Audio).collect(),
+            })
+    }
+}
+
+/// Empty stretches before and between `spans` (sorted by start), as (start, end)
+fn gaps_between(spans: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut gaps = Vec::new();
+    let mut covered_to = 0.0_f64;
+    for &(start, end) in spans {
+        if start > covered_to + 1e-9 {
+            gaps.push((covered_to, start));
+        }
+        covered_to = covered_to.max(end);
+    }
+    gaps
+}
+
+/// Dissolve to and from black across short gaps on a track; see `Timeline::gap_fade_at`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GapFade {
+    /// Length of each fade, in seconds
+    pub duration: f64,
+    /// Longest gap that is faded across; longer gaps stay hard cuts to black
+    pub max_gap: f64,
+}
+
+impl Default for GapFade {
+    fn default() -> Self {
+        Self {
+            duration: 0.25,
+            max_gap: 2.0,
+        }
+    }
+}
+
+/// A fix applied to a clip by `Timeline::sanitize`
+#[derive(Debug, Clone, PartialEq)]
+pub enum SanitizeChange {
+    /// A negative time field was clamped to 0
+    ClampedNegative {
+        clip_id: String,
+        field: &'static str,
+        value: f64,
+    },
+    /// `in_point` was after `out_point`, so the two were swapped
+    SwappedInOut { clip_id: String },
+    /// The clip had no duration left and was removed
+    RemovedEmpty { clip_id: String },
+}
+
+/// Everything `Timeline::sanitize` changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SanitizeReport {
+    pub changes: Vec<SanitizeChange>,
+}
+
+impl SanitizeReport {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Fix one clip's timing fields in place. Returns false if the clip should be dropped.
+fn sanitize_clip_times(
+    clip_id: &str,
+    start_time: &mut f64,
+    in_point: &mut f64,
+    out_point: &mut f64,
+    duration: &mut f64,
+    changes: &mut Vec<SanitizeChange>,
+) -> bool {
+    for (field, value) in [
+        ("start_time", &mut *start_time),
+        ("in_point", &mut *in_point),
+        ("out_point", &mut *out_point),
+        ("duration", &mut *duration),
+    ] {
+        if *value < 0.0 {
+            changes.push(SanitizeChange::ClampedNegative {
+                clip_id: clip_id.to_string(),
+                field,
+                value: *value,
+            });
+            *value = 0.0;
+        }
+    }
+    if *in_point > *out_point {
+        std::mem::swap(in_point, out_point);
+        changes.push(SanitizeChange::SwappedInOut {
+            clip_id: clip_id.to_string(),
+        });
+    }
+    if *duration <= 0.0 {
+        changes.push(SanitizeChange::RemovedEmpty {
+            clip_id: clip_id.to_string(),
+        });
+        return false;
+    }
+    true
+}
+
+/// Trim the part of `clip` before time 0 off its head (see `cut_clip_at`). Returns false
+/// if nothing of it is left.
+fn trim_before_zero<T: ClipSplit + Clone>(clip: &mut T) -> bool {
+    if clip.start_time() + clip.duration() <= 1e-9 {
+        return false;
+    }
+    if let Some((_, mut right)) = cut_clip_at(clip, 0.0) {
+        right.set_id(clip.id().to_string());
+        right.set_group_id(clip.group_id().map(str::to_string));
+        *clip = right;
+    }
+    true
+}
+
+impl Timeline {
+    /// Keep every clip and title at or after time 0: anything starting earlier loses
+    /// the part before 0 from its head, and anything ending by 0 is removed. Run after
+    /// each edit (see `apply_timeline_event`). Returns the number of items changed.
+    pub fn enforce_non_negative(&mut self) -> usize {
+        let mut changed = 0;
+        for track in &mut self.tracks {
+            match track {
+                Track::Video(v) => v.clips.retain_mut(|c| {
+                    if c.start_time >= 0.0 {
+                        return true;
+                    }
+                    changed += 1;
+                    trim_before_zero(c)
+                }),
+                Track::Audio(a) => a.clips.retain_mut(|c| {
+                    if c.start_time >= 0.0 {
+                        return true;
+                    }
+                    changed += 1;
+                    trim_before_zero(c)
+                }),
+            }
+        }
+        self.text_clips.retain_mut(|t| {
+            if t.start_time >= 0.0 {
+                return true;
+            }
+            changed += 1;
+            t.duration += t.start_time;
+            t.start_time = 0.0;
+            t.duration > 1e-9
+        });
+        if changed > 0 {
+            self.invalidate_index();
+        }
+        changed
+    }
+
+    /// Set `duration` to the end of the last clip or title, or 0 when there are none.
+    /// Run after each edit so the ruler, playback and export see the current length.
+    pub fn recompute_duration(&mut self) {
+        let mut end: f64 = 0.0;
+        for track in &self.tracks {
+            match track {
+                Track::Video(v) => v
+                    .clips
+                    .iter()
+                    .for_each(|c| end = end.max(c.start_time + c.duration)),
+                Track::Audio(a) => a
+                    .clips
+                    .iter()
+                    .for_each(|c| end = end.max(c.start_time + c.duration)),
+            }
+        }
+        for title in &self.text_clips {
+            end = end.max(title.start_time + title.duration);
+        }
+        self.duration = end;
+    }
+
+    /// Repair clips that would break rendering (typically from a hand-edited or old
+    /// project file): negative times are clamped to 0, inverted in/out points are
+    /// swapped and clips without duration are removed.
+    pub fn sanitize(&mut self) -> SanitizeReport {
+        let mut changes = Vec::new();
+        for track in &mut self.tracks {
+            match track {
+                Track::Video(v) => v.clips.retain_mut(|c| {
+                    sanitize_clip_times(
+                        &c.id,
+                        &mut c.start_time,
+                        &mut c.in_point,
+                        &mut c.out_point,
+                        &mut c.duration,
+                        &mut changes,
+                    )
+                }),
+                Track::Audio(a) => a.clips.retain_mut(|c| {
+                    sanitize_clip_times(
+                        &c.id,
+                        &mut c.start_time,
+                        &mut c.in_point,
+                        &mut c.out_point,
+                        &mut c.duration,
+                        &mut changes,
+                    )
+                }),
+            }
+        }
+        if !changes.is_empty() {
+            self.invalidate_index();
+        }
+        SanitizeReport { changes }
+    }
+}
+
+impl Track {
+    pub fn is_video(&self) -> bool {
+        matches!(self, Track::Video(_))
+    }
+
+    pub fn is_audio(&self) -> bool {
+        matches!(self, Track::Audio(_))
+    }
+
+    pub fn id(&self) -> &str {
+        match self {
+            Track::Video(v) => &v.id,
+            Track::Audio(a) => &a.id,
+        }
+    }
+
+    pub fn is_sync_locked(&self) -> bool {
+        match self {
+            Track::Video(v) => v.sync_locked,
+            Track::Audio(a) => a.sync_locked,
+        }
+    }
+
+    pub fn is_muted(&self) -> bool {
+        match self {
+            Track::Video(v) => v.muted,
+            Track::Audio(a) => a.muted,
+        }
+    }
+
+    /// (start, end) of every clip, ordered by start time
+    fn clip_spans(&self) -> Vec<(f64, f64)> {
+        let mut spans: Vec<(f64, f64)> = match self {
+            Track::Video(v) => v
+                .clips
+                .iter()
+                .map(|c| (c.start_time, c.start_time + c.duration))
+                .collect(),
+            Track::Audio(a) => a
+                .clips
+                .iter()
+                .map(|c| (c.start_time, c.start_time + c.duration))
+                .collect(),
+        };
+        spans.sort_by(|a, b| a.0.total_cmp(&b.0));
+        spans
+    }
+
+    /// Whether no clip overlaps `from..to`
+    pub fn has_room(&self, from: f64, to: f64) -> bool {
+        self.clip_spans()
+            .iter()
+            .all(|&(start, end)| end <= from + 1e-9 || start >= to - 1e-9)
+    }
+
+    /// Move every clip starting at or after `time` by `delta` seconds
+    fn shift_clips_from(&mut self, time: f64, delta: f64) {
+        match self {
+            Track::Video(v) => {
+                for clip in v.clips.iter_mut().filter(|c| c.start_time >= time) {
+                    clip.start_time += delta;
+                }
+            }
+            Track::Audio(a) => {
+                for clip in a.clips.iter_mut().filter(|c| c.start_time >= time) {
+                    clip.start_time += delta;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ActiveClip;
+    use super::*;
+    use crate::types::envelope::Envelope;
+    use crate::types::media::{AudioClip, AudioMetadata, Clip, Effect, VideoClip, VideoMetadata};
+    use crate::types::track::{AudioTrack, Track, VideoTrack};
+    #[test]
+    fn test_split_clip_at_playhead_video() {
+        let video_clip = VideoClip {
+            id: "v1".to_string(),
+            asset_path: "video.mp4".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        };
+        let video_track = VideoTrack {
+            id: "vt1".to_string(),
+            name: "Video Track 1".to_string(),
+            clips: vec![video_clip.clone()],
+            muted: false,
+            sync_locked: false,
+        };
+        let mut timeline = Timeline {
+            tracks: vec![Track::Video(video_track)],
+            duration: 10.0,
+            frame_rate: 30.0,
+            resolution: (1920, 1080),
+            text_clips: Vec::new(),
+            beat_grid: None,
+            sync_lock_enabled: true,
+            subtitles: None,
+            markers: Vec::new(),
+            clip_index: OnceLock::new(),
+        };
+        let split = timeline.split_clip_at_playhead("vt1", 4.0);
+        assert!(split);
+        if let Track::Video(ref vt) = timeline.tracks[0] {
+            assert_eq!(vt.clips.len(), 2);
+            assert_eq!(vt.clips[0].start_time, 0.0);
+            assert_eq!(vt.clips[0].duration, 4.0);
+            assert_eq!(vt.clips[1].start_time, 4.0);
+            assert_eq!(vt.clips[1].duration, 6.0);
+            assert_eq!(vt.clips[0].id, "v1_left");
+            assert_eq!(vt.clips[1].id, "v1_right");
+        } else {
+            panic!("Expected video track");
+        }
+    }
+
+    #[test]
+    fn test_split_clip_at_playhead_audio() {
+        let audio_clip = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "audio.wav".to_string(),
+            in_point: 0.0,
+            out_point: 8.0,
+            start_time: 2.0,
+            duration: 8.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+        let audio_track = AudioTrack {
+            id: "at1".to_string(),
+            name: "Audio Track 1".to_string(),
+            clips: vec![audio_clip.clone()],
+            muted: false,
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        };
+        let mut timeline = Timeline {
+            tracks: vec![Track::Audio(audio_track)],
+            duration: 10.0,
+            frame_rate: 30.0,
+            resolution: (1920, 1080),
+            text_clips: Vec::new(),
+            beat_grid: None,
+            sync_lock_enabled: true,
+            subtitles: None,
+            markers: Vec::new(),
+            clip_index: OnceLock::new(),
+        };
+        let split = timeline.split_clip_at_playhead("at1", 6.0);
+        assert!(split);
+        if let Track::Audio(ref at) = timeline.tracks[0] {
+            assert_eq!(at.clips.len(), 2);
+            assert_eq!(at.clips[0].start_time, 2.0);
+            assert_eq!(at.clips[0].duration, 4.0);
+            assert_eq!(at.clips[1].start_time, 6.0);
+            assert_eq!(at.clips[1].duration, 4.0);
+            assert_eq!(at.clips[0].id, "a1_left");
+            assert_eq!(at.clips[1].id, "a1_right");
+        } else {
+            panic!("Expected audio track");
+        }
+    }
+
+    #[test]
+    fn test_split_clip_at_playhead_no_split() {
+        let video_clip = VideoClip {
+            id: "v1".to_string(),
+            asset_path: "video.mp4".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        };
+        let video_track = VideoTrack {
+            id: "vt1".to_string(),
+            name: "Video Track 1".to_string(),
+            clips: vec![video_clip.clone()],
+            muted: false,
+            sync_locked: false,
+        };
+        let mut timeline = Timeline {
+            tracks: vec![Track::Video(video_track)],
+            duration: 10.0,
+            frame_rate: 30.0,
+            resolution: (1920, 1080),
+            text_clips: Vec::new(),
+            beat_grid: None,
+            sync_lock_enabled: true,
+            subtitles: None,
+            markers: Vec::new(),
+            clip_index: OnceLock::new(),
+        };
+        // Playhead at start (should not split)
+        let split = timeline.split_clip_at_playhead("vt1", 0.0);
+        assert!(!split);
+        // Playhead at end (should not split)
+        let split = timeline.split_clip_at_playhead("vt1", 10.0);
+        assert!(!split);
+        // Playhead not on any clip (should not split)
+        let split = timeline.split_clip_at_playhead("vt1", 20.0);
+        assert!(!split);
+    }
+
+    #[test]
+    fn test_create_timeline_with_tracks() {
+        let video_clip = VideoClip {
+            id: "v1".to_string(),
+            asset_path: "video.mp4".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        };
+
+        let audio_clip = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "audio.wav".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+
+        let video_track = VideoTrack {
+            id: "vt1".to_string(),
+            name: "Video Track 1".to_string(),
+            clips: vec![video_clip.clone()],
+            muted: false,
+            sync_locked: false,
+        };
+
+        let audio_track = AudioTrack {
+            id: "at1".to_string(),
+            name: "Audio Track 1".to_string(),
+            clips: vec![audio_clip.clone()],
+            muted: false,
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        };
+
+        let timeline = Timeline {
+            tracks: vec![Track::Video(video_track), Track::Audio(audio_track)],
+            duration: 10.0,
+            frame_rate: 30.0,
+            resolution: (1920, 1080),
+            text_clips: Vec::new(),
+            beat_grid: None,
+            sync_lock_enabled: true,
+            subtitles: None,
+            markers: Vec::new(),
+            clip_index: OnceLock::new(),
+        };
+
+        assert_eq!(timeline.tracks.len(), 2);
+        assert_eq!(timeline.duration, 10.0);
+        assert_eq!(timeline.frame_rate, 30.0);
+        assert_eq!(timeline.resolution, (1920, 1080));
+    }
+
+    #[test]
+    fn test_active_clips_at() {
+        let video_clip = VideoClip {
+            id: "v1".to_string(),
+            asset_path: "video.mp4".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        };
+
+        let audio_clip = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "audio.wav".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+
+        let video_track = VideoTrack {
+            id: "vt1".to_string(),
+            name: "Video Track 1".to_string(),
+            clips: vec![video_clip.clone()],
+            muted: false,
+            sync_locked: false,
+        };
+
+        let audio_track = AudioTrack {
+            id: "at1".to_string(),
+            name: "Audio Track 1".to_string(),
+            clips: vec![audio_clip.clone()],
+            muted: false,
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        };
+
+        let timeline = Timeline {
+            tracks: vec![Track::Video(video_track), Track::Audio(audio_track)],
+            duration: 10.0,
+            frame_rate: 30.0,
+            resolution: (1920, 1080),
+            text_clips: Vec::new(),
+            beat_grid: None,
+            sync_lock_enabled: true,
+            subtitles: None,
+            markers: Vec::new(),
+            clip_index: OnceLock::new(),
+        };
+
+        // Both clips are active at time 5.0
+        let active = timeline.active_clips_at(5.0);
+        assert_eq!(active.len(), 2);
+
+        // No clips are active at time 11.0
+        let active = timeline.active_clips_at(11.0);
+        assert_eq!(active.len(), 0);
+
+        // Both clips are active at time 0.0
+        let active = timeline.active_clips_at(0.0);
+        assert!(active.iter().any(|c| matches!(c, ActiveClip::Video(_))));
+        assert!(active.iter().any(|c| matches!(c, ActiveClip::Audio(_))));
+    }
+
+    #[test]
+    fn test_clips_in_range() {
+        let video_clip = VideoClip {
+            id: "v1".to_string(),
+            asset_path: "video.mp4".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        };
+
+        let audio_clip = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "audio.wav".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+
+        let video_track = VideoTrack {
+            id: "vt1".to_string(),
+            name: "Video Track 1".to_string(),
+            clips: vec![video_clip.clone()],
+            muted: false,
+            sync_locked: false,
+        };
+
+        let audio_track = AudioTrack {
+            id: "at1".to_string(),
+            name: "Audio Track 1".to_string(),
+            clips: vec![audio_clip.clone()],
+            muted: false,
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        };
+
+        let timeline = Timeline {
+            tracks: vec![Track::Video(video_track), Track::Audio(audio_track)],
+            duration: 10.0,
+            frame_rate: 30.0,
+            resolution: (1920, 1080),
+            text_clips: Vec::new(),
+            beat_grid: None,
+            sync_lock_enabled: true,
+            subtitles: None,
+            markers: Vec::new(),
+            clip_index: OnceLock::new(),
+        };
+
+        // Both clips overlap with range 5.0..15.0
+        let in_range = timeline.clips_in_range(5.0, 15.0);
+        assert_eq!(in_range.len(), 2);
+
+        // Both clips overlap with range -5.0..1.0
+        let in_range = timeline.clips_in_range(-5.0, 1.0);
+        assert_eq!(in_range.len(), 2);
+
+        // No clips overlap with range 11.0..20.0
+        let in_range = timeline.clips_in_range(11.0, 20.0);
+        assert_eq!(in_range.len(), 0);
+    }
+
+    #[test]
+    fn test_clips_on_track() {
+        let video_clip = VideoClip {
+            id: "v1".to_string(),
+            asset_path: "video.mp4".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            effects: Vec::new(),
+            opacity_envelope: Envelope::new(),
+            freeze: false,
+            speed: 1.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: false,
+            crop: None,
+            group_id: None,
+            metadata: VideoMetadata {
+                resolution: (1920, 1080),
+                frame_rate: 30.0,
+                codec: "h264".to_string(),
+                color_space: None,
+            },
+        };
+
+        let audio_clip = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "audio.wav".to_string(),
+            in_point: 0.0,
+            out_point: 10.0,
+            start_time: 0.0,
+            duration: 10.0,
+            enabled: true,
+            gain_db: 0.0,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        };
+
+        let video_track = VideoTrack {
+            id: "vt1".to_string(),
+            name: "Video Track 1".to_string(),
+            clips: vec![video_clip.clone()],
+            muted: false,
+            sync_locked: false,
+        };
+
+        let audio_track = AudioTrack {
+            id: "at1".to_string(),
+            name: "Audio Track 1".to_string(),
+            clips: vec![audio_clip.clone()],
+            muted: false,
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        };
+
+        let timeline = Timeline {
+            tracks: vec![Track::Video(video_track), Track::Audio(audio_track)],
+            duration: 10.0,
+            frame_rate: 30.0,
+            resolution: (1920, 1080),
+            text_clips: Vec::new(),
+            beat_grid: None,
+            sync_lock_enabled: true,
+            subtitles: None,
+            markers: Vec::new(),
+            clip_index: OnceLock::new(),
+        };
+
+        let video_clips = timeline.clips_on_track("vt1").unwrap();
+        assert_eq!(video_clips.len(), 1);
+        match &video_clips[0] {
+            ActiveClip::Video(vc) => assert_eq!(vc.id, "v1"),
+            _ => panic!("Expected video clip"),
+        }
+
+        let audio_clips = timeline.clips_on_track("at1").unwrap();
+        assert_eq!(audio_clips.len(), 1);
+        match &audio_clips[0] {
+            ActiveClip::Audio(ac) => assert_eq!(ac.id, "a1"),
+            _ => panic!("Expected audio clip"),
+        }
+
+        // Non-existent track
+        assert!(timeline.clips_on_track("notrack").is_none());
+    }
+
+    /// Reference implementation: linear scan over every clip.
+    fn linear_active_ids(timeline: &Timeline, time: f64) -> Vec<String> {
+        let mut ids = Vec::new();
+        for track in &timeline.tracks {
+            match track {
+                Track::Video(v) => ids.extend(
+                    v.clips
+                        .iter()
+                        .filter(|c| c.is_active_at(time))
+                        .map(|c| c.id.clone()),
+                ),
+                Track::Audio(a) => ids.extend(
+                    a.clips
+                        .iter()
+                        .filter(|c| c.is_active_at(time))
+                        .map(|c| c.id.clone()),
+                ),
+            }
+        }
+        ids
+    }
+
+    fn linear_range_ids(timeline: &Timeline, start: f64, end: f64) -> Vec<String> {
+        let mut ids = Vec::new();
+        for track in &timeline.tracks {
+            match track {
+                Track::Video(v) => ids.extend(
+                    v.clips
+                        .iter()
+                        .filter(|c| c.start_time + c.duration > start && c.start_time < end)
+                        .map(|c| c.id.clone()),
+                ),
+                Track::Audio(a) => ids.extend(
+                    a.clips
+                        .iter()
+                        .filter(|c| c.start_time + c.duration > start && c.start_time < end)
+                        .map(|c| c.id.clone()),
+                ),
+            }
+        }
+        ids
+    }
+
+    fn active_ids(clips: &[ActiveClip]) -> Vec<String> {
+        clips
+            .iter()
+            .map(|c| match c {
+                ActiveClip::Video(v) => v.id.clone(),
+                ActiveClip::Audio(a) => a.id.clone(),
+            })
+            .collect()
+    }
+
+    /// Deterministic pseudo-random generator (no rand dependency)
+    struct Lcg(u64);
+
+    impl Lcg {
+        fn next_f64(&mut self) -> f64 {
+            self.0 = self
+                .0
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64
+        }
+    }
+
+    fn random_timeline(rng: &mut Lcg, tracks: usize, clips_per_track: usize) -> Timeline {
+        let mut timeline = Timeline::new();
+        for t in 0..tracks {
+            let mut video_clips = Vec::new();
+            let mut audio_clips = Vec::new();
+            for c in 0..clips_per_track {
+                // Unsorted, possibly overlapping clips
+                let start_time = (rng.next_f64() * 600.0 * 10.0).round() / 10.0;
+                let duration = (rng.next_f64() * 20.0 * 10.0).round() / 10.0 + 0.1;
+                if t % 2 == 0 {
+                    video_clips.push(VideoClip {
+                        id: format!("v{}_{}", t, c),
+                        asset_path: "video.mp4".to_string(),
+                        in_point: 0.0,
+                        out_point: duration,
+                        start_time,
+                        duration,
+                        enabled: true,
+                        effects: Vec::new(),
+                        opacity_envelope: Envelope::new(),
+                        freeze: false,
+                        speed: 1.0,
+                        time_map: None,
+                        reversed: false,
+                        mute_audio: false,
+                        crop: None,
+                        group_id: None,
+                        metadata: VideoMetadata {
+                            resolution: (1920, 1080),
+                            frame_rate: 30.0,
+                            codec: "h264".to_string(),
+                            color_space: None,
+                        },
+                    });
+                } else {
+                    audio_clips.push(AudioClip {
+                        id: format!("a{}_{}", t, c),
+                        asset_path: "audio.wav".to_string(),
+                        in_point: 0.0,
+                        out_point: duration,
+                        start_time,
+                        duration,
+                        enabled: true,
+                        gain_db: 0.0,
+                        group_id: None,
+                        metadata: AudioMetadata {
+                            sample_rate: 48000,
+                            channels: 2,
+                            codec: "pcm".to_string(),
+                            bitrate: 1536,
+                        },
+                    });
+                }
+            }
+            if t % 2 == 0 {
+                timeline.tracks.push(Track::Video(VideoTrack {
+                    id: format!("vt{}", t),
+                    name: format!("Video Track {}", t),
+                    clips: video_clips,
+                    muted: false,
+                    sync_locked: false,
+                }));
+            } else {
+                timeline.tracks.push(Track::Audio(AudioTrack {
+                    id: format!("at{}", t),
+                    name: format!("Audio Track {}", t),
+                    clips: audio_clips,
+                    muted: false,
+                    sync_locked: false,
+                    volume_db: 0.0,
+                    pan: 0.0,
+                }));
+            }
+        }
+        timeline
+    }
+
+    #[test]
+    fn test_indexed_lookups_match_linear_scan() {
+        let mut rng = Lcg(42);
+        for _ in 0..10 {
+            let timeline = random_timeline(&mut rng, 4, 50);
+            for _ in 0..50 {
+                let time = (rng.next_f64() * 650.0 * 10.0).round() / 10.0;
+                assert_eq!(
+                    active_ids(&timeline.active_clips_at(time)),
+                    linear_active_ids(&timeline, time)
+                );
+                let video: Vec<String> = timeline
+                    .active_video_clips_at(time)
+                    .iter()
+                    .map(|c| c.id.clone())
+                    .collect();
+                let expected: Vec<String> = linear_active_ids(&timeline, time)
+                    .into_iter()
+                    .filter(|id| id.starts_with('v'))
+                    .collect();
+                assert_eq!(video, expected);
+
+                let end = time + rng.next_f64() * 30.0;
+                assert_eq!(
+                    active_ids(&timeline.clips_in_range(time, end)),
+                    linear_range_ids(&timeline, time, end)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_index_is_rebuilt_after_invalidation() {
+        let mut rng = Lcg(7);
+        let mut timeline = random_timeline(&mut rng, 2, 20);
+        let _ = timeline.active_clips_at(10.0);
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.clips[0].start_time = 1000.0;
+        }
+        timeline.invalidate_index();
+        assert_eq!(
+            active_ids(&timeline.active_clips_at(1000.0)),
+            linear_active_ids(&timeline, 1000.0)
+        );
+        assert!(active_ids(&timeline.active_clips_at(1000.0)).contains(&"v0_0".to_string()));
+    }
+
+    #[test]
+    fn test_editing_through_tracks_mut_refreshes_the_index() {
+        let mut rng = Lcg(7);
+        let mut timeline = random_timeline(&mut rng, 2, 20);
+        let _ = timeline.active_clips_at(10.0);
+        // Same clip counts, so only a fresh index can find the moved clip
+        if let Track::Video(v) = &mut timeline.tracks_mut()[0] {
+            v.clips[0].start_time = 1000.0;
+        }
+        assert_eq!(
+            active_ids(&timeline.active_clips_at(1000.0)),
+            vec!["v0_0".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_index_reduces_comparisons() {
+        // 500 back-to-back one-second clips on a single track
+        let mut rng = Lcg(1);
+        let mut timeline = random_timeline(&mut rng, 1, 500);
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            for (i, clip) in v.clips.iter_mut().enumerate() {
+                clip.start_time = i as f64;
+                clip.duration = 1.0;
+            }
+        }
+        timeline.invalidate_index();
+
+        let mut indexed = 0;
+        let hits = timeline.track_clips_overlapping(0, 250.5, 250.5, true, &mut indexed);
+        assert_eq!(hits, vec![250]);
+
+        let linear = 500;
+        // Binary search (~log2(500) = 9) plus a couple of backward steps
+        assert!(indexed < 20, "indexed lookup used {} comparisons", indexed);
+        assert!(indexed * 10 < linear);
+    }
+
+    #[test]
+    fn test_disabled_clip_is_not_rendered() {
+        let mut rng = Lcg(3);
+        let mut timeline = random_timeline(&mut rng, 2, 1);
+        for track in &mut timeline.tracks {
+            match track {
+                Track::Video(v) => {
+                    v.clips[0].start_time = 0.0;
+                    v.clips[0].duration = 10.0;
+                }
+                Track::Audio(a) => {
+                    a.clips[0].start_time = 0.0;
+                    a.clips[0].duration = 10.0;
+                }
+            }
+        }
+        timeline.invalidate_index();
+
+        assert_eq!(timeline.renderable_clips_at(5.0).len(), 2);
+        assert_eq!(timeline.toggle_clip_enabled("v0_0"), Some(false));
+
+        // Still on the track and still found by plain lookups
+        assert_eq!(timeline.clips_on_track("vt0").unwrap().len(), 1);
+        assert_eq!(
+            active_ids(&timeline.active_clips_at(5.0)),
+            vec!["v0_0", "a1_0"]
+        );
+        // But skipped for rendering
+        assert_eq!(active_ids(&timeline.renderable_clips_at(5.0)), vec!["a1_0"]);
+
+        assert_eq!(timeline.toggle_clip_enabled("v0_0"), Some(true));
+        assert_eq!(timeline.renderable_clips_at(5.0).len(), 2);
+        assert_eq!(timeline.toggle_clip_enabled("missing"), None);
+    }
+
+    #[test]
+    fn test_muted_audio_video_clip_is_composited_but_not_mixed() {
+        let mut timeline = sync_lock_timeline();
+        let ids = |clips: Vec<ActiveClip>| -> Vec<String> {
+            clips
+                .iter()
+                .map(|c| match c {
+                    ActiveClip::Video(v) => v.id.clone(),
+                    ActiveClip::Audio(a) => a.id.clone(),
+                })
+                .collect()
+        };
+        assert_eq!(ids(timeline.audible_clips_at(1.0)), vec!["v1"]);
+
+        assert_eq!(timeline.toggle_clip_audio_muted("v1"), Some(true));
+        assert!(timeline.audible_clips_at(1.0).is_empty());
+        assert_eq!(ids(timeline.renderable_clips_at(1.0)), vec!["v1"]);
+        // Audio clips have no embedded audio to mute
+        assert_eq!(timeline.toggle_clip_audio_muted("a_locked"), None);
+        assert_eq!(
+            ids(timeline.audible_clips_at(6.5)),
+            vec!["a_locked", "a_free"]
+        );
+
+        assert_eq!(timeline.toggle_clip_audio_muted("v1"), Some(false));
+        assert_eq!(ids(timeline.audible_clips_at(1.0)), vec!["v1"]);
+    }
+
+    #[test]
+    fn test_enabled_defaults_to_true_when_missing() {
+        let json = r#"{
+            "id": "v1",
+            "asset_path": "video.mp4",
+            "in_point": 0.0,
+            "out_point": 5.0,
+            "start_time": 0.0,
+            "duration": 5.0,
+            "metadata": { "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" }
+        }"#;
+        let clip: VideoClip = serde_json::from_str(json).unwrap();
+        assert!(clip.enabled);
+    }
+
+    #[test]
+    fn test_sanitize_malformed_clips() {
+        let json = r#"{
+            "tracks": [
+                { "Video": { "id": "t1", "name": "Video 1", "muted": false, "clips": [
+                    { "id": "neg", "asset_path": "a.mp4", "in_point": 1.0, "out_point": 4.0,
+                      "start_time": -2.5, "duration": 3.0,
+                      "metadata": { "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" } },
+                    { "id": "inverted", "asset_path": "a.mp4", "in_point": 6.0, "out_point": 2.0,
+                      "start_time": 5.0, "duration": 4.0,
+                      "metadata": { "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" } },
+                    { "id": "ok", "asset_path": "a.mp4", "in_point": 0.0, "out_point": 2.0,
+                      "start_time": 10.0, "duration": 2.0,
+                      "metadata": { "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" } }
+                ] } },
+                { "Audio": { "id": "t2", "name": "Audio 1", "muted": false, "clips": [
+                    { "id": "empty", "asset_path": "a.wav", "in_point": 0.0, "out_point": 0.0,
+                      "start_time": 1.0, "duration": 0.0,
+                      "metadata": { "sample_rate": 48000, "channels": 2, "codec": "pcm", "bitrate": 0 } }
+                ] } }
+            ],
+            "duration": 20.0,
+            "frame_rate": 30.0,
+            "resolution": [1920, 1080]
+        }"#;
+        let mut timeline: Timeline = serde_json::from_str(json).unwrap();
+        let report = timeline.sanitize();
+
+        assert_eq!(
+            report.changes,
+            vec![
+                SanitizeChange::ClampedNegative {
+                    clip_id: "neg".to_string(),
+                    field: "start_time",
+                    value: -2.5,
+                },
+                SanitizeChange::SwappedInOut {
+                    clip_id: "inverted".to_string(),
+                },
+                SanitizeChange::RemovedEmpty {
+                    clip_id: "empty".to_string(),
+                },
+            ]
+        );
+
+        match &timeline.tracks[0] {
+            Track::Video(v) => {
+                assert_eq!(v.clips.len(), 3);
+                assert_eq!(v.clips[0].start_time, 0.0);
+                assert_eq!((v.clips[1].in_point, v.clips[1].out_point), (2.0, 6.0));
+                assert_eq!(v.clips[2].start_time, 10.0);
+            }
+            _ => panic!("Expected video track"),
+        }
+        match &timeline.tracks[1] {
+            Track::Audio(a) => assert!(a.clips.is_empty()),
+            _ => panic!("Expected audio track"),
+        }
+        assert_eq!(active_ids(&timeline.active_clips_at(0.5)), vec!["neg"]);
+
+        // Already clean: nothing to report
+        assert!(timeline.sanitize().is_empty());
+    }
+    #[test]
+    fn test_resize_is_clamped_to_source() {
+        let json = r#"{
+            "tracks": [
+                { "Video": { "id": "t1", "name": "Video 1", "muted": false, "clips": [
+                    { "id": "v1", "asset_path": "a.mp4", "in_point": 2.0, "out_point": 6.0,
+                      "start_time": 10.0, "duration": 4.0,
+                      "metadata": { "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" } }
+                ] } }
+            ],
+            "duration": 20.0,
+            "frame_rate": 30.0,
+            "resolution": [1920, 1080]
+        }"#;
+        let mut timeline: Timeline = serde_json::from_str(json).unwrap();
+        let clip = |timeline: &Timeline| match &timeline.tracks[0] {
+            Track::Video(v) => v.clips[0].clone(),
+            _ => panic!("Expected video track"),
+        };
+
+        // Dragging the right edge 10s out on an 8s source stops at the media end
+        assert!(timeline.resize_clip("v1", 10.0, 14.0, Some(8.0)));
+        let v = clip(&timeline);
+        assert_eq!((v.in_point, v.out_point), (2.0, 8.0));
+        assert_eq!((v.start_time, v.duration), (10.0, 6.0));
+        assert_eq!(timeline.clip_asset_path("v1"), Some("a.mp4"));
+
+        // Dragging the left edge past the source start stops at in_point 0
+        assert!(timeline.resize_clip("v1", 5.0, 11.0, Some(8.0)));
+        let v = clip(&timeline);
+        assert_eq!((v.in_point, v.out_point), (0.0, 8.0));
+        assert_eq!((v.start_time, v.duration), (8.0, 8.0));
+
+        // Shrinking within the source is untouched
+        assert!(timeline.resize_clip("v1", 9.0, 3.0, Some(8.0)));
+        let v = clip(&timeline);
+        assert_eq!((v.in_point, v.out_point), (1.0, 4.0));
+        assert_eq!((v.start_time, v.duration), (9.0, 3.0));
+
+        assert!(!timeline.resize_clip("missing", 0.0, 1.0, None));
+    }
+
+    #[test]
+    fn test_head_trim_keeps_opacity_keyframes_in_place() {
+        let mut timeline = sync_lock_timeline();
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.clips[0].opacity_envelope.add_point(0.0, 0.0);
+            v.clips[0].opacity_envelope.add_point(2.0, 1.0);
+        }
+        assert!(timeline.resize_clip("v1", 1.0, 1.0, None));
+        let Track::Video(v) = &timeline.tracks[0] else {
+            panic!("Expected video track");
+        };
+        // Timeline time 1s is still halfway up the fade
+        assert_eq!(v.clips[0].opacity_envelope.value_at(0.0), Some(0.5));
+        assert_eq!(v.clips[0].opacity_envelope.value_at(1.0), Some(1.0));
+    }
+
+    #[test]
+    fn test_head_trim_keeps_speed_ramp() {
+        let mut timeline = sync_lock_timeline();
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.clips[0].time_map = Some(vec![(0.0, 0.0), (1.0, 0.5), (2.0, 2.0)]);
+        }
+        let before: Vec<f64> = [1.0, 1.5, 1.9]
+            .iter()
+            .map(|t| timeline.active_video_clips_at(*t)[0].source_time_at(*t))
+            .collect();
+        assert!(timeline.resize_clip("v1", 1.0, 1.0, None));
+        // The frames left after the trim are the ones shown there before
+        for (t, source) in [1.0, 1.5, 1.9].iter().zip(before) {
+            assert_eq!(
+                timeline.active_video_clips_at(*t)[0].source_time_at(*t),
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_resize_at_double_speed_moves_source_twice_as_far() {
+        // 10s of source over 5s
+        let mut timeline = sync_lock_timeline();
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            let clip = &mut v.clips[0];
+            (clip.out_point, clip.duration, clip.speed) = (10.0, 5.0, 2.0);
+        }
+        let clip = |timeline: &Timeline| match &timeline.tracks[0] {
+            Track::Video(v) => v.clips[0].clone(),
+            _ => panic!("Expected video track"),
+        };
+
+        assert!(timeline.resize_clip("v1", 0.0, 6.0, None));
+        let v = clip(&timeline);
+        assert_eq!((v.in_point, v.out_point), (0.0, 12.0));
+        assert_eq!((v.start_time, v.duration), (0.0, 6.0));
+
+        // Trimming 1s off the head skips 2s of source
+        assert!(timeline.resize_clip("v1", 1.0, 5.0, None));
+        let v = clip(&timeline);
+        assert_eq!((v.in_point, v.out_point), (2.0, 12.0));
+        assert_eq!((v.start_time, v.duration), (1.0, 5.0));
+
+        // The media end is reached after 7s of timeline
+        assert!(timeline.resize_clip("v1", 1.0, 9.0, Some(16.0)));
+        let v = clip(&timeline);
+        assert_eq!((v.out_point, v.duration), (16.0, 7.0));
+
+        // Reversed, the head shows the end of the range
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.clips[0].reversed = true;
+        }
+        assert!(timeline.resize_clip("v1", 2.0, 6.0, Some(16.0)));
+        let v = clip(&timeline);
+        assert_eq!((v.in_point, v.out_point), (2.0, 14.0));
+        assert_eq!(v.source_time_at(2.0), 14.0);
+    }
+
+    #[test]
+    fn test_insert_freeze_layout() {
+        let json = r#"{
+            "tracks": [
+                { "Video": { "id": "t1", "name": "Video 1", "muted": false, "clips": [
+                    { "id": "v1", "asset_path": "a.mp4", "in_point": 2.0, "out_point": 12.0,
+                      "start_time": 0.0, "duration": 10.0,
+                      "metadata": { "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" } },
+                    { "id": "v2", "asset_path": "b.mp4", "in_point": 0.0, "out_point": 3.0,
+                      "start_time": 12.0, "duration": 3.0,
+                      "metadata": { "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" } }
+                ] } }
+            ],
+            "duration": 15.0,
+            "frame_rate": 30.0,
+            "resolution": [1920, 1080]
+        }"#;
+        let mut timeline: Timeline = serde_json::from_str(json).unwrap();
+
+        assert!(timeline.insert_freeze("t1", "v1", 4.0, 2.0));
+        let clips = match &timeline.tracks[0] {
+            Track::Video(v) => v.clips.clone(),
+            _ => panic!("Expected video track"),
+        };
+        let layout: Vec<(&str, f64, f64, f64, f64)> = clips
+            .iter()
+            .map(|c| {
+                (
+                    c.id.as_str(),
+                    c.start_time,
+                    c.duration,
+                    c.in_point,
+                    c.out_point,
+                )
+            })
+            .collect();
+        assert_eq!(
+            layout,
+            vec![
+                ("v1_left", 0.0, 4.0, 2.0, 6.0),
+                ("v1_freeze", 4.0, 2.0, 6.0, 6.0),
+                ("v1_right", 6.0, 6.0, 6.0, 12.0),
+                ("v2", 14.0, 3.0, 0.0, 3.0),
+            ]
+        );
+        assert!(clips[1].freeze && !clips[0].freeze && !clips[2].freeze);
+
+        // The held frame shows the source time at the cut for its whole length
+        let frozen = timeline.active_video_clips_at(5.5);
+        assert_eq!(frozen.len(), 1);
+        assert_eq!(frozen[0].source_time_at(4.0), 6.0);
+        assert_eq!(frozen[0].source_time_at(5.5), 6.0);
+        assert_eq!(
+            timeline.active_video_clips_at(6.5)[0].source_time_at(6.5),
+            6.5
+        );
+
+        // Resizing the freeze only changes how long it holds
+        assert!(timeline.resize_clip("v1_freeze", 4.0, 1.0, Some(20.0)));
+        assert_eq!(timeline.active_video_clips_at(4.5)[0].in_point, 6.0);
+        assert_eq!(timeline.active_video_clips_at(4.5)[0].duration, 1.0);
+
+        // Playhead outside the clip, unknown clip, wrong track kind
+        assert!(!timeline.insert_freeze("t1", "v2", 4.0, 1.0));
+        assert!(!timeline.insert_freeze("t1", "missing", 4.0, 1.0));
+        assert!(!timeline.insert_freeze("t2", "v2", 15.0, 1.0));
+        assert!(!timeline.insert_freeze("t1", "v2", 15.0, 0.0));
+    }
+
+    #[test]
+    fn test_insert_freeze_holds_the_frame_under_the_playhead() {
+        // v1 plays 0..2 of its source backwards over 0..2
+        let mut timeline = sync_lock_timeline();
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.clips[0].reversed = true;
+        }
+        assert!(timeline.insert_freeze("vt", "v1", 0.5, 1.0));
+        let frozen = timeline.active_video_clips_at(0.75);
+        assert_eq!(frozen[0].id, "v1_freeze");
+        assert_eq!(frozen[0].source_time_at(0.75), 1.5);
+
+        // A speed ramp: the frame at 1s is source 0.5
+        let mut timeline = sync_lock_timeline();
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.clips[0].time_map = Some(vec![(0.0, 0.0), (1.0, 0.5), (2.0, 2.0)]);
+        }
+        assert!(timeline.insert_freeze("vt", "v1", 1.0, 1.0));
+        assert_eq!(
+            timeline.active_video_clips_at(1.5)[0].source_time_at(1.5),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_apply_properties_to_same_source() {
+        let clip_json = |id: &str, path: &str, start: f64| {
+            format!(
+                r#"{{ "id": "{}", "asset_path": "{}", "in_point": 0.0, "out_point": 4.0,
+                     "start_time": {}, "duration": 4.0, "effects": [{{ "GaussianBlur": 2.0 }}],
+                     "metadata": {{ "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" }} }}"#,
+                id, path, start
+            )
+        };
+        let json = format!(
+            r#"{{
+                "tracks": [
+                    {{ "Video": {{ "id": "t1", "name": "Video 1", "muted": false, "clips": [{}, {}] }} }},
+                    {{ "Video": {{ "id": "t2", "name": "Video 2", "muted": false, "clips": [{}] }} }}
+                ],
+                "duration": 20.0,
+                "frame_rate": 30.0,
+                "resolution": [1920, 1080]
+            }}"#,
+            clip_json("a1", "a.mp4", 0.0),
+            clip_json("b1", "b.mp4", 4.0),
+            clip_json("a2", "a.mp4", 10.0)
+        );
+        let mut timeline: Timeline = serde_json::from_str(&json).unwrap();
+        let clip = |timeline: &Timeline, id: &str| {
+            timeline
+                .tracks
+                .iter()
+                .find_map(|t| match t {
+                    Track::Video(v) => v.clips.iter().find(|c| c.id == id).cloned(),
+                    _ => None,
+                })
+                .unwrap()
+        };
+
+        let patch = ClipPropsPatch {
+            opacity: Some(0.5),
+            speed: None,
+            color: Some(vec![Effect::Saturation(0.0)]),
+        };
+        assert_eq!(timeline.apply_properties_to_same_source("a1", patch), 2);
+        for id in ["a1", "a2"] {
+            let c = clip(&timeline, id);
+            assert_eq!(c.opacity_envelope.value_at(1.0), Some(0.5));
+            assert_eq!(
+                c.effects,
+                vec![Effect::GaussianBlur(2.0), Effect::Saturation(0.0)]
+            );
+            // Speed wasn't in the patch
+            assert_eq!((c.speed, c.duration), (1.0, 4.0));
+        }
+        // Different source is untouched
+        let b = clip(&timeline, "b1");
+        assert!(b.opacity_envelope.is_empty());
+        assert_eq!(b.effects, vec![Effect::GaussianBlur(2.0)]);
+
+        // Only speed: opacity and color stay as they were
+        let patch = ClipPropsPatch {
+            speed: Some(2.0),
+            ..Default::default()
+        };
+        assert_eq!(timeline.apply_properties_to_same_source("a2", patch), 2);
+        let a1 = clip(&timeline, "a1");
+        assert_eq!((a1.speed, a1.duration), (2.0, 2.0));
+        assert_eq!(a1.opacity_envelope.value_at(0.0), Some(0.5));
+        assert_eq!(a1.effects.len(), 2);
+        assert_eq!(clip(&timeline, "b1").speed, 1.0);
+
+        assert_eq!(
+            timeline.apply_properties_to_same_source("missing", ClipPropsPatch::default()),
+            0
+        );
+    }
+
+    #[test]
+    fn test_serde_round_trip_keeps_every_field() {
+        let mut opacity_envelope = Envelope::new();
+        opacity_envelope.add_point(0.0, 0.0);
+        opacity_envelope.add_point(0.75, 1.0);
+        let video_clip = VideoClip {
+            id: "v1".to_string(),
+            asset_path: "/media/a.mp4".to_string(),
+            in_point: 1.25,
+            out_point: 5.5,
+            start_time: 0.1 + 0.2,
+            duration: 2.125,
+            enabled: false,
+            effects: vec![
+                Effect::Brightness(-0.3),
+                Effect::Contrast(1.7),
+                Effect::Saturation(0.0),
+                Effect::GaussianBlur(2.5),
+            ],
+            opacity_envelope,
+            freeze: false,
+            speed: 2.0,
+            time_map: None,
+            reversed: false,
+            mute_audio: true,
+            crop: Some((0.125, 0.0, 0.875, 0.75)),
+            group_id: Some("av_1".to_string()),
             metadata: VideoMetadata {
-                resolution: (1920, 1080),
-                frame_rate: 30.0,
-                codec: "h264".to_string(),
+                resolution: (3840, 2160),
+                frame_rate: 29.97,
+                codec: "hevc".to_string(),
+                color_space: None,
             },
         };
-        let video_track = VideoTrack {
-            id: "vt1".to_string(),
-            name: "Video Track 1".to_string(),
-            clips: vec![video_clip.clone()],
-            muted: false,
+        let freeze_clip = VideoClip {
+            id: "v1_freeze".to_string(),
+            start_time: 2.425,
+            duration: 1.0 / 3.0,
+            out_point: 1.25,
+            freeze: true,
+            speed: 1.0,
+            time_map: None,
+            ..video_clip.clone()
         };
-        let mut timeline = Timeline {
-            tracks: vec![Track::Video(video_track)],
-            duration: 10.0,
-            frame_rate: 30.0,
-            resolution: (1920, 1080),
+        let audio_clip = AudioClip {
+            id: "a1".to_string(),
+            asset_path: "/media/b.wav".to_string(),
+            in_point: 0.0,
+            out_point: 8.0,
+            start_time: 2.0,
+            duration: 8.0,
+            enabled: true,
+            gain_db: -4.5,
+            group_id: Some("av_1".to_string()),
+            metadata: AudioMetadata {
+                sample_rate: 44100,
+                channels: 1,
+                codec: "pcm".to_string(),
+                bitrate: 705,
+            },
         };
-        // Playhead at start (should not split)
-        let split = timeline.split_clip_at_playhead("vt1", 0.0);
-        assert!(!split);
-        // Playhead at end (should not split)
-        let split = timeline.split_clip_at_playhead("vt1", 10.0);
-        assert!(!split);
-        // Playhead not on any clip (should not split)
-        let split = timeline.split_clip_at_playhead("vt1", 20.0);
-        assert!(!split);
+        let mut title = TextClip::new(
+            "text_1".to_string(),
+            "Line one\nLine two".to_string(),
+            1.5,
+            4.0,
+        );
+        title.font_size = 36.5;
+        title.color = [250, 10, 20, 200];
+        title.background = Some([0, 0, 0, 128]);
+        title.position = (0.25, 0.8);
+        title.align = crate::types::media::TextAlign::Right;
+        title.enabled = false;
+
+        let timeline = Timeline {
+            tracks: vec![
+                Track::Video(VideoTrack {
+                    id: "vt1".to_string(),
+                    name: "Video ✂".to_string(),
+                    clips: vec![video_clip, freeze_clip],
+                    muted: true,
+                    sync_locked: false,
+                }),
+                Track::Audio(AudioTrack {
+                    id: "at1".to_string(),
+                    name: "Audio".to_string(),
+                    clips: vec![audio_clip],
+                    muted: false,
+                    sync_locked: false,
+                    volume_db: 3.0,
+                    pan: -0.25,
+                }),
+            ],
+            duration: 12.345678901234,
+            frame_rate: 23.976,
+            resolution: (1280, 720),
+            text_clips: vec![title],
+            beat_grid: Some(crate::types::beat_grid::BeatGrid::new(128.0, 0.05)),
+            sync_lock_enabled: false,
+            subtitles: Some(crate::types::subtitle::SubtitleTrack {
+                path: "movie.srt".to_string(),
+                cues: vec![crate::types::subtitle::Cue {
+                    start: 1.5,
+                    end: 3.25,
+                    text: "Two\nlines".to_string(),
+                }],
+                show_in_preview: false,
+                burn_in: true,
+            }),
+            markers: vec![Marker {
+                time: 4.5,
+                name: "Chorus".to_string(),
+            }],
+            clip_index: OnceLock::new(),
+        };
+        // Populate the lookup cache; it must not affect the comparison
+        timeline.active_clips_at(1.0);
+
+        let json = serde_json::to_string(&timeline).unwrap();
+        let restored: Timeline = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, timeline);
+        // And stable on a second pass
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+    }
+
+    fn sync_lock_timeline() -> Timeline {
+        let video = |id: &str, start: f64| {
+            format!(
+                r#"{{ "id": "{}", "asset_path": "v.mp4", "in_point": 0.0, "out_point": 2.0,
+                     "start_time": {}, "duration": 2.0,
+                     "metadata": {{ "resolution": [1920, 1080], "frame_rate": 30.0, "codec": "h264" }} }}"#,
+                id, start
+            )
+        };
+        let audio = |id: &str, start: f64| {
+            format!(
+                r#"{{ "id": "{}", "asset_path": "a.wav", "in_point": 0.0, "out_point": 2.0,
+                     "start_time": {}, "duration": 2.0,
+                     "metadata": {{ "sample_rate": 48000, "channels": 2, "codec": "pcm", "bitrate": 1536 }} }}"#,
+                id, start
+            )
+        };
+        let json = format!(
+            r#"{{
+                "tracks": [
+                    {{ "Video": {{ "id": "vt", "name": "V", "muted": false, "sync_locked": true,
+                                  "clips": [{}, {}, {}] }} }},
+                    {{ "Audio": {{ "id": "locked", "name": "A1", "muted": false, "sync_locked": true,
+                                  "clips": [{}] }} }},
+                    {{ "Audio": {{ "id": "free", "name": "A2", "muted": false, "clips": [{}] }} }}
+                ],
+                "duration": 20.0,
+                "frame_rate": 30.0,
+                "resolution": [1920, 1080]
+            }}"#,
+            video("v1", 0.0),
+            video("v2", 2.0),
+            video("v3", 4.0),
+            audio("a_locked", 6.0),
+            audio("a_free", 6.0)
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn starts(timeline: &Timeline, track_idx: usize) -> Vec<f64> {
+        match &timeline.tracks[track_idx] {
+            Track::Video(v) => v.clips.iter().map(|c| c.start_time).collect(),
+            Track::Audio(a) => a.clips.iter().map(|c| c.start_time).collect(),
+        }
+    }
+
+    #[test]
+    fn test_ripple_delete_moves_sync_locked_tracks() {
+        let mut timeline = sync_lock_timeline();
+        assert!(timeline.sync_lock_enabled);
+
+        assert!(timeline.ripple_delete("v2"));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0]);
+        // The sync-locked audio track follows, the unlocked one stays put
+        assert_eq!(starts(&timeline, 1), vec![4.0]);
+        assert_eq!(starts(&timeline, 2), vec![6.0]);
+        assert!(!timeline.ripple_delete("v2"));
+    }
+
+    #[test]
+    fn test_ripple_with_sync_lock_off_or_unlocked_track() {
+        let mut timeline = sync_lock_timeline();
+        timeline.sync_lock_enabled = false;
+        assert!(timeline.ripple_delete("v1"));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0]);
+        assert_eq!(starts(&timeline, 1), vec![6.0]);
+
+        // Editing an unlocked track never moves the others
+        let mut timeline = sync_lock_timeline();
+        assert!(timeline.ripple_delete("a_free"));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0, 4.0]);
+        assert_eq!(starts(&timeline, 1), vec![6.0]);
+    }
+
+    #[test]
+    fn test_ripple_trim_and_freeze_follow_sync_lock() {
+        let mut timeline = sync_lock_timeline();
+        // Shorten v1 by 0.5s: everything after it on locked tracks moves left
+        assert!(timeline.ripple_trim("v1", 1.5, Some(10.0)));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 1.5, 3.5]);
+        assert_eq!(starts(&timeline, 1), vec![5.5]);
+        assert_eq!(starts(&timeline, 2), vec![6.0]);
+
+        // Inserting a freeze is a ripple insert
+        assert!(timeline.insert_freeze("vt", "v3", 4.0, 1.0));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 1.5, 3.5, 4.0, 5.0]);
+        assert_eq!(starts(&timeline, 1), vec![6.5]);
+        assert_eq!(starts(&timeline, 2), vec![6.0]);
+    }
+
+    #[test]
+    fn test_ripple_insert_keeps_inserted_clip() {
+        let mut timeline = sync_lock_timeline();
+        // Lifting v2 leaves its gap
+        assert!(timeline.remove_clip("v2"));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 4.0]);
+        assert!(!timeline.remove_clip("v2"));
+
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            let clip = VideoClip {
+                id: "ins".to_string(),
+                start_time: 4.0,
+                ..v.clips[0].clone()
+            };
+            v.clips.push(clip);
+        }
+        // Inserting where v3 starts pushes v3 and the locked track along
+        assert!(timeline.ripple_insert("ins"));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 6.0, 4.0]);
+        assert_eq!(starts(&timeline, 1), vec![8.0]);
+        assert_eq!(starts(&timeline, 2), vec![6.0]);
+        assert!(!timeline.ripple_insert("missing"));
+    }
+
+    #[test]
+    fn test_find_and_close_gap() {
+        let mut timeline = sync_lock_timeline();
+        assert!(timeline.find_gaps(0).is_empty());
+        assert_eq!(timeline.find_gaps(1), vec![(0.0, 6.0)]);
+        assert!(timeline.find_gaps(9).is_empty());
+
+        assert!(timeline.remove_clip("v2"));
+        assert_eq!(timeline.find_gaps(0), vec![(2.0, 4.0)]);
+        assert_eq!(timeline.gap_at(0, 3.0), Some((2.0, 4.0)));
+        assert_eq!(timeline.gap_at(0, 4.0), None);
+
+        assert!(!timeline.close_gap(0, 2.0, 3.0));
+        assert!(timeline.close_gap(0, 2.0, 4.0));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0]);
+        assert_eq!(starts(&timeline, 1), vec![4.0]);
+        assert_eq!(starts(&timeline, 2), vec![6.0]);
+        assert!(timeline.find_gaps(0).is_empty());
+    }
+
+    #[test]
+    fn test_set_clip_speed_ripples_later_clips() {
+        let mut timeline = sync_lock_timeline();
+        assert!(timeline.set_clip_speed("vt", "v2", 2.0, true));
+        let Track::Video(v) = &timeline.tracks[0] else {
+            panic!("Expected video track");
+        };
+        assert_eq!((v.clips[1].speed, v.clips[1].duration), (2.0, 1.0));
+        // Later clips are pulled left by the second it lost, on locked tracks too
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0, 3.0]);
+        assert_eq!(starts(&timeline, 1), vec![5.0]);
+        assert_eq!(starts(&timeline, 2), vec![6.0]);
+
+        // Without ripple only the clip changes
+        assert!(timeline.set_clip_speed("vt", "v2", 0.5, false));
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0, 3.0]);
+        assert_eq!(timeline.clip_span("v2"), Some((0, 2.0, 6.0)));
+
+        assert!(!timeline.set_clip_speed("vt", "v2", 0.0, true));
+        assert!(!timeline.set_clip_speed("locked", "a_locked", 2.0, true));
+        assert!(!timeline.set_clip_speed("vt", "missing", 2.0, true));
+    }
+
+    #[test]
+    fn test_enforce_non_negative_trims_heads() {
+        let mut timeline = sync_lock_timeline();
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            // As left behind by a slip past the start
+            v.clips[0].start_time = -0.5;
+            v.clips[1].speed = 2.0;
+            v.clips[1].out_point = 4.0;
+            v.clips[1].start_time = -1.5;
+            v.clips[2].start_time = -3.0;
+        }
+        timeline
+            .text_clips
+            .push(TextClip::new("t".to_string(), "Hi".to_string(), -1.0, 3.0));
+        assert_eq!(timeline.enforce_non_negative(), 4);
+
+        let Track::Video(v) = &timeline.tracks[0] else {
+            panic!("Expected video track");
+        };
+        // The part before 0 is trimmed from the head, at the clip's speed
+        assert_eq!(v.clips.len(), 2);
+        assert_eq!(
+            (
+                v.clips[0].start_time,
+                v.clips[0].in_point,
+                v.clips[0].duration
+            ),
+            (0.0, 0.5, 1.5)
+        );
+        assert_eq!(v.clips[1].id, "v2");
+        assert_eq!(
+            (
+                v.clips[1].start_time,
+                v.clips[1].in_point,
+                v.clips[1].duration
+            ),
+            (0.0, 3.0, 0.5)
+        );
+        // v3 ended before 0 and is gone
+        assert!(timeline.clip_span("v3").is_none());
+        assert_eq!(
+            (
+                timeline.text_clips[0].start_time,
+                timeline.text_clips[0].duration
+            ),
+            (0.0, 2.0)
+        );
+        assert_eq!(timeline.enforce_non_negative(), 0);
+    }
+
+    #[test]
+    fn test_close_all_gaps() {
+        let mut timeline = sync_lock_timeline();
+        assert!(timeline.remove_clip("v2"));
+        timeline.sync_lock_enabled = false;
+
+        assert_eq!(timeline.close_all_gaps(), 3);
+        for track_idx in 0..timeline.tracks.len() {
+            assert_eq!(starts(&timeline, track_idx)[0], 0.0);
+            assert!(timeline.find_gaps(track_idx).is_empty());
+        }
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0]);
+        assert_eq!(timeline.close_all_gaps(), 0);
+
+        // Sync-locked tracks only lose time that is empty on all of them
+        let mut timeline = sync_lock_timeline();
+        assert!(timeline.remove_clip("v2"));
+        assert_eq!(timeline.close_all_gaps(), 2);
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0]);
+        assert_eq!(starts(&timeline, 1), vec![4.0]);
+        assert_eq!(starts(&timeline, 2), vec![0.0]);
+    }
+
+    #[test]
+    fn test_flatten_for_export_keeps_compositing_order() {
+        let mut timeline = sync_lock_timeline();
+        // An upper video track overlapping v1 and v2, and a disabled v3
+        let mut upper = timeline.tracks[0].clone();
+        if let Track::Video(v) = &mut upper {
+            v.clips.truncate(1);
+            v.clips[0].id = "top".to_string();
+            v.clips[0].start_time = 1.0;
+        }
+        timeline.tracks.push(upper);
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.clips[2].enabled = false;
+            v.clips.swap(0, 1);
+        }
+        if let Track::Audio(a) = &mut timeline.tracks[2] {
+            a.volume_db = -6.0;
+        }
+
+        let flat = timeline.flatten_for_export();
+        assert_eq!(flat.tracks.len(), 2);
+        let ids: Vec<&str> = match &flat.tracks[0] {
+            Track::Video(v) => v.clips.iter().map(|c| c.id.as_str()).collect(),
+            Track::Audio(_) => panic!("video track first"),
+        };
+        assert_eq!(ids, vec!["v1", "v2", "top"]);
+        // The upper track's clip is still composited last where it overlaps
+        let at = |time: f64| -> Vec<String> {
+            flat.active_video_clips_at(time)
+                .iter()
+                .map(|c| c.id.clone())
+                .collect()
+        };
+        assert_eq!(at(1.5), vec!["v1", "top"]);
+        assert_eq!(at(2.5), vec!["v2", "top"]);
+
+        match &flat.tracks[1] {
+            Track::Audio(a) => {
+                let mixed: Vec<(&str, f64)> =
+                    a.clips.iter().map(|c| (c.id.as_str(), c.gain_db)).collect();
+                assert_eq!(mixed, vec![("a_locked", 0.0), ("a_free", -6.0)]);
+            }
+            Track::Video(_) => panic!("audio track second"),
+        }
+        // The source timeline is untouched
+        assert_eq!(timeline.tracks.len(), 4);
+    }
+
+    #[test]
+    fn test_delete_clips_lift_or_ripple() {
+        let ids = |ids: &[&str]| -> std::collections::HashSet<String> {
+            ids.iter().map(|id| id.to_string()).collect()
+        };
+
+        // Lift: v2 stays put between the two gaps
+        let mut timeline = sync_lock_timeline();
+        assert_eq!(timeline.delete_clips(&ids(&["v1", "v3", "gone"]), false), 2);
+        assert_eq!(starts(&timeline, 0), vec![2.0]);
+
+        // Ripple: both gaps close, right to left
+        let mut timeline = sync_lock_timeline();
+        assert_eq!(timeline.delete_clips(&ids(&["v1", "v3"]), true), 2);
+        assert_eq!(starts(&timeline, 0), vec![0.0]);
+        // Sync-locked tracks without selected clips stay put
+        assert_eq!(starts(&timeline, 1), vec![6.0]);
+
+        // Across tracks each track closes its own gap
+        let mut timeline = sync_lock_timeline();
+        assert_eq!(timeline.delete_clips(&ids(&["v2", "a_locked"]), true), 2);
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0]);
+        assert!(starts(&timeline, 1).is_empty());
+        assert_eq!(starts(&timeline, 2), vec![6.0]);
+        assert_eq!(timeline.active_clips_at(3.0).len(), 1);
+    }
+
+    #[test]
+    fn test_find_clips_by_id_or_file_name() {
+        let mut timeline = sync_lock_timeline();
+        if let Track::Audio(a) = &mut timeline.tracks[2] {
+            a.clips[0].asset_path = "/media/Interview Take2.WAV".to_string();
+        }
+        let found = |query: &str| -> Vec<(usize, String, f64)> { timeline.find_clips(query) };
+        // By id, across tracks
+        assert_eq!(
+            found("A_"),
+            vec![
+                (1, "a_locked".to_string(), 6.0),
+                (2, "a_free".to_string(), 6.0)
+            ]
+        );
+        assert_eq!(found("v2"), vec![(0, "v2".to_string(), 2.0)]);
+        // By source file name, not the directory
+        assert_eq!(found("take2"), vec![(2, "a_free".to_string(), 6.0)]);
+        assert_eq!(found("v.mp4").len(), 3);
+        assert!(found("media").is_empty());
+        assert!(found("  ").is_empty());
+    }
+
+    #[test]
+    fn test_split_selected_at() {
+        let ids = |ids: &[&str]| -> std::collections::HashSet<String> {
+            ids.iter().map(|id| id.to_string()).collect()
+        };
+        let mut timeline = sync_lock_timeline();
+        // v3 is selected but not under the playhead; a_free is under it but not selected
+        assert_eq!(
+            timeline.split_selected_at(&ids(&["v3", "a_locked"]), 7.0),
+            1
+        );
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0, 4.0]);
+        assert_eq!(starts(&timeline, 1), vec![6.0, 7.0]);
+        assert_eq!(starts(&timeline, 2), vec![6.0]);
+        assert_eq!(timeline.clip_track_index("v3"), Some(0));
+        assert_eq!(timeline.clip_track_index("a_locked_right"), Some(1));
+        assert_eq!(timeline.clip_start_time("a_locked_right"), Some(7.0));
+
+        // An edge is not inside the clip
+        assert_eq!(timeline.split_selected_at(&ids(&["v1", "v2"]), 2.0), 0);
     }
 
     #[test]
-    fn test_create_timeline_with_tracks() {
-        let video_clip = VideoClip {
-            id: "v1".to_string(),
-            asset_path: "video.mp4".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: VideoMetadata {
-                resolution: (1920, 1080),
-                frame_rate: 30.0,
-                codec: "h264".to_string(),
-            },
-        };
+    fn test_clip_traversal_order() {
+        let mut timeline = sync_lock_timeline();
+        // Out of order in the track, visited by start time
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.clips.swap(0, 2);
+        }
+        let next = |after: Option<&str>| timeline.next_clip(0, after);
+        assert_eq!(next(None).as_deref(), Some("v1"));
+        assert_eq!(next(Some("v1")).as_deref(), Some("v2"));
+        assert_eq!(next(Some("v2")).as_deref(), Some("v3"));
+        assert_eq!(next(Some("v3")).as_deref(), Some("v1"));
+        assert_eq!(next(Some("a_free")).as_deref(), Some("v1"));
 
-        let audio_clip = AudioClip {
-            id: "a1".to_string(),
-            asset_path: "audio.wav".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: AudioMetadata {
-                sample_rate: 48000,
-                channels: 2,
-                codec: "pcm".to_string(),
-                bitrate: 1536,
-            },
-        };
+        let previous = |before: Option<&str>| timeline.previous_clip(0, before);
+        assert_eq!(previous(Some("v1")).as_deref(), Some("v3"));
+        assert_eq!(previous(Some("v3")).as_deref(), Some("v2"));
 
-        let video_track = VideoTrack {
-            id: "vt1".to_string(),
-            name: "Video Track 1".to_string(),
-            clips: vec![video_clip.clone()],
-            muted: false,
-        };
+        // A single clip wraps onto itself
+        assert_eq!(
+            timeline.next_clip(1, Some("a_locked")).as_deref(),
+            Some("a_locked")
+        );
+        assert_eq!(timeline.clip_nearest(0, 2.9).as_deref(), Some("v2"));
 
-        let audio_track = AudioTrack {
-            id: "at1".to_string(),
-            name: "Audio Track 1".to_string(),
-            clips: vec![audio_clip.clone()],
+        // Empty and missing tracks
+        timeline.tracks.push(Track::Audio(AudioTrack {
+            id: "empty".to_string(),
+            name: "Empty".to_string(),
+            clips: vec![],
             muted: false,
-        };
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        }));
+        assert_eq!(timeline.next_clip(3, None), None);
+        assert_eq!(timeline.previous_clip(3, Some("v1")), None);
+        assert_eq!(timeline.clip_nearest(3, 0.0), None);
+        assert_eq!(timeline.next_clip(9, None), None);
+    }
 
-        let timeline = Timeline {
-            tracks: vec![Track::Video(video_track), Track::Audio(audio_track)],
-            duration: 10.0,
-            frame_rate: 30.0,
-            resolution: (1920, 1080),
+    #[test]
+    fn test_extend_edit_moves_nearest_edge_to_playhead() {
+        let mut timeline = sync_lock_timeline();
+        let span = |timeline: &Timeline, id: &str| {
+            let (_, start, end) = timeline.clip_span(id).unwrap();
+            (start, end)
+        };
+        let in_out = |timeline: &Timeline, track_idx: usize| match &timeline.tracks[track_idx] {
+            Track::Audio(a) => (a.clips[0].in_point, a.clips[0].out_point),
+            Track::Video(v) => (v.clips[0].in_point, v.clips[0].out_point),
         };
 
-        assert_eq!(timeline.tracks.len(), 2);
-        assert_eq!(timeline.duration, 10.0);
-        assert_eq!(timeline.frame_rate, 30.0);
-        assert_eq!(timeline.resolution, (1920, 1080));
+        // Out point to a later playhead in the gap after the clip
+        assert!(timeline.extend_edit("free", 9.0, |_| None));
+        assert_eq!(span(&timeline, "a_free"), (6.0, 9.0));
+        assert_eq!(in_out(&timeline, 2), (0.0, 3.0));
+
+        // In point to an earlier playhead, uncovering more of the source
+        if let Track::Audio(a) = &mut timeline.tracks[1] {
+            a.clips[0].in_point = 2.0;
+            a.clips[0].out_point = 4.0;
+        }
+        assert!(timeline.extend_edit("locked", 5.0, |_| None));
+        assert_eq!(span(&timeline, "a_locked"), (5.0, 8.0));
+        assert_eq!(in_out(&timeline, 1), (1.0, 4.0));
+
+        // Inside a clip its nearest edge is trimmed back to the playhead
+        assert!(timeline.extend_edit("vt", 5.4, |_| None));
+        assert_eq!(span(&timeline, "v3"), (4.0, 5.4));
+        // Where two clips meet, both sides roll
+        assert!(timeline.extend_edit("vt", 2.5, |_| None));
+        assert_eq!(span(&timeline, "v1"), (0.0, 2.5));
+        assert_eq!(span(&timeline, "v2"), (2.5, 4.0));
+
+        assert!(!timeline.extend_edit("vt", 2.5, |_| None));
+        assert!(!timeline.extend_edit("missing", 1.0, |_| None));
+        if let Track::Audio(a) = &mut timeline.tracks[2] {
+            a.clips.clear();
+        }
+        assert!(!timeline.extend_edit("free", 1.0, |_| None));
     }
 
     #[test]
-    fn test_active_clips_at() {
-        let video_clip = VideoClip {
-            id: "v1".to_string(),
-            asset_path: "video.mp4".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: VideoMetadata {
-                resolution: (1920, 1080),
-                frame_rate: 30.0,
-                codec: "h264".to_string(),
-            },
+    fn test_extend_edit_stops_at_the_end_of_the_source() {
+        let mut timeline = sync_lock_timeline();
+        let span = |timeline: &Timeline, id: &str| {
+            let (_, start, end) = timeline.clip_span(id).unwrap();
+            (start, end)
         };
+        // a_free plays 0..2 of a 2.5s file
+        assert!(timeline.extend_edit("free", 9.0, |_| Some(2.5)));
+        assert_eq!(span(&timeline, "a_free"), (6.0, 8.5));
+        // Nothing left to uncover on either side
+        assert!(!timeline.extend_edit("free", 9.0, |_| Some(2.5)));
+        assert!(!timeline.extend_edit("free", 5.0, |_| Some(2.5)));
+        assert_eq!(span(&timeline, "a_free"), (6.0, 8.5));
 
-        let audio_clip = AudioClip {
-            id: "a1".to_string(),
-            asset_path: "audio.wav".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: AudioMetadata {
-                sample_rate: 48000,
-                channels: 2,
-                codec: "pcm".to_string(),
-                bitrate: 1536,
-            },
+        // A roll stops where the earlier clip's source ends
+        assert!(timeline.extend_edit("vt", 2.5, |_| Some(2.2)));
+        assert_eq!(span(&timeline, "v1"), (0.0, 2.2));
+        assert_eq!(span(&timeline, "v2"), (2.2, 4.0));
+    }
+
+    #[test]
+    fn test_extend_to_next_fills_gap() {
+        let mut timeline = sync_lock_timeline();
+        // Open a 1s gap after v1 by shortening it in place
+        assert!(timeline.resize_clip("v1", 0.0, 1.0, Some(10.0)));
+
+        assert!(timeline.extend_to_next("vt", "v1", Some(10.0)));
+        let Track::Video(v) = &timeline.tracks[0] else {
+            panic!("Expected video track");
         };
+        assert_eq!((v.clips[0].duration, v.clips[0].out_point), (2.0, 2.0));
+        // Later clips don't move
+        assert_eq!(starts(&timeline, 0), vec![0.0, 2.0, 4.0]);
 
-        let video_track = VideoTrack {
-            id: "vt1".to_string(),
-            name: "Video Track 1".to_string(),
-            clips: vec![video_clip.clone()],
-            muted: false,
+        // No gap left, no clip after the last one, wrong track
+        assert!(!timeline.extend_to_next("vt", "v1", Some(10.0)));
+        assert!(!timeline.extend_to_next("vt", "v3", Some(10.0)));
+        assert!(!timeline.extend_to_next("locked", "v1", Some(10.0)));
+    }
+
+    #[test]
+    fn test_extend_to_next_is_clamped_by_source() {
+        let mut timeline = sync_lock_timeline();
+        assert!(timeline.ripple_delete("v2"));
+        assert!(timeline.resize_clip("v1", 0.0, 1.0, Some(10.0)));
+        // v3 now starts at 2.0; move it to leave a 3s gap after v1
+        assert!(timeline.resize_clip("v3", 4.0, 2.0, Some(10.0)));
+
+        // Only 2.5s of source exist, so v1 stops short of v3
+        assert!(timeline.extend_to_next("vt", "v1", Some(2.5)));
+        let Track::Video(v) = &timeline.tracks[0] else {
+            panic!("Expected video track");
         };
+        assert_eq!((v.clips[0].duration, v.clips[0].out_point), (2.5, 2.5));
 
-        let audio_track = AudioTrack {
-            id: "at1".to_string(),
-            name: "Audio Track 1".to_string(),
-            clips: vec![audio_clip.clone()],
-            muted: false,
+        // Already at the end of its source: nothing to extend
+        assert!(!timeline.extend_to_next("vt", "v1", Some(2.5)));
+    }
+
+    #[test]
+    fn test_replace_clip_source_keeps_position() {
+        let mut timeline = sync_lock_timeline();
+        let metadata = VideoMetadata {
+            resolution: (1280, 720),
+            frame_rate: 25.0,
+            codec: "vp9".to_string(),
+            color_space: None,
+        };
+        assert!(timeline.replace_clip_source("vt", "v2", "recut.mp4", Some(60.0), Some(metadata)));
+        let Track::Video(v) = &timeline.tracks[0] else {
+            panic!("Expected video track");
         };
+        let clip = &v.clips[1];
+        assert_eq!(clip.asset_path, "recut.mp4");
+        assert_eq!(
+            (
+                clip.start_time,
+                clip.duration,
+                clip.in_point,
+                clip.out_point
+            ),
+            (2.0, 2.0, 0.0, 2.0)
+        );
+        assert_eq!(clip.metadata.codec, "vp9");
+        assert_eq!(timeline.clip_asset_path("v2"), Some("recut.mp4"));
 
-        let timeline = Timeline {
-            tracks: vec![Track::Video(video_track), Track::Audio(audio_track)],
-            duration: 10.0,
-            frame_rate: 30.0,
-            resolution: (1920, 1080),
+        // Unknown clip, or a clip on another track
+        assert!(!timeline.replace_clip_source("vt", "nope", "x.mp4", None, None));
+        assert!(!timeline.replace_clip_source("locked", "v1", "x.mp4", None, None));
+    }
+
+    #[test]
+    fn test_replace_clip_source_clamps_to_shorter_source() {
+        let mut timeline = sync_lock_timeline();
+        assert!(timeline.replace_clip_source("vt", "v1", "short.mp4", Some(1.25), None));
+        assert!(timeline.replace_clip_source("locked", "a_locked", "short.wav", Some(0.5), None));
+        let Track::Video(v) = &timeline.tracks[0] else {
+            panic!("Expected video track");
+        };
+        let clip = &v.clips[0];
+        assert_eq!(
+            (
+                clip.start_time,
+                clip.in_point,
+                clip.out_point,
+                clip.duration
+            ),
+            (0.0, 0.0, 1.25, 1.25)
+        );
+        // Metadata is kept when none is given
+        assert_eq!(clip.metadata.codec, "h264");
+        let Track::Audio(a) = &timeline.tracks[1] else {
+            panic!("Expected audio track");
         };
+        assert_eq!((a.clips[0].start_time, a.clips[0].duration), (6.0, 0.5));
 
-        // Both clips are active at time 5.0
-        let active = timeline.active_clips_at(5.0);
-        assert_eq!(active.len(), 2);
+        // Nothing of the clip's source range would remain
+        let before = timeline.clone();
+        assert!(!timeline.replace_clip_source("vt", "v2", "tiny.mp4", Some(0.0), None));
+        assert_eq!(timeline, before);
+    }
 
-        // No clips are active at time 11.0
-        let active = timeline.active_clips_at(11.0);
-        assert_eq!(active.len(), 0);
+    #[test]
+    fn test_marker_navigation_jumps_to_marker_times() {
+        let mut timeline = Timeline::new();
+        assert!(timeline.add_marker(7.25, "Outro".to_string()));
+        assert!(timeline.add_marker(1.5, "Intro".to_string()));
+        assert!(timeline.add_marker(3.0, String::new()));
+        assert!(!timeline.add_marker(3.0, "Again".to_string()));
+        let times: Vec<f64> = timeline.markers.iter().map(|m| m.time).collect();
+        assert_eq!(times, vec![1.5, 3.0, 7.25]);
 
-        // Both clips are active at time 0.0
-        let active = timeline.active_clips_at(0.0);
-        assert!(active.iter().any(|c| matches!(c, ActiveClip::Video(_))));
-        assert!(active.iter().any(|c| matches!(c, ActiveClip::Audio(_))));
+        assert_eq!(timeline.next_marker(0.0), Some(1.5));
+        // Sitting on a marker moves on to the following one
+        assert_eq!(timeline.next_marker(1.5), Some(3.0));
+        assert_eq!(timeline.next_marker(3.1), Some(7.25));
+        assert_eq!(timeline.next_marker(7.25), None);
+        assert_eq!(timeline.previous_marker(7.25), Some(3.0));
+        assert_eq!(timeline.previous_marker(2.0), Some(1.5));
+        assert_eq!(timeline.previous_marker(1.5), None);
     }
 
     #[test]
-    fn test_clips_in_range() {
-        let video_clip = VideoClip {
-            id: "v1".to_string(),
-            asset_path: "video.mp4".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: VideoMetadata {
-                resolution: (1920, 1080),
-                frame_rate: 30.0,
-                codec: "h264".to_string(),
-            },
+    fn test_clip_activity_and_track_kinds() {
+        let timeline = sync_lock_timeline();
+        let (Track::Video(video), Track::Audio(audio)) = (&timeline.tracks[0], &timeline.tracks[1])
+        else {
+            panic!("expected a video track and an audio track");
         };
+        let (v1, a_locked) = (&video.clips[0], &audio.clips[0]);
+        assert!(!v1.is_active_at(-0.1));
+        assert!(v1.is_active_at(1.0));
+        assert!(!v1.is_active_at(2.5));
+        assert!(!a_locked.is_active_at(5.0));
+        assert!(a_locked.is_active_at(7.0));
+        assert!(!a_locked.is_active_at(9.0));
 
-        let audio_clip = AudioClip {
-            id: "a1".to_string(),
-            asset_path: "audio.wav".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: AudioMetadata {
-                sample_rate: 48000,
-                channels: 2,
-                codec: "pcm".to_string(),
-                bitrate: 1536,
-            },
+        assert!(timeline.tracks[0].is_video() && !timeline.tracks[0].is_audio());
+        assert!(timeline.tracks[2].is_audio() && !timeline.tracks[2].is_video());
+    }
+
+    #[test]
+    fn test_gap_fade_near_gap_edges() {
+        let mut timeline = sync_lock_timeline();
+        let fade = GapFade {
+            duration: 0.5,
+            max_gap: 2.0,
         };
+        // Back to back clips don't fade
+        assert_eq!(timeline.gap_fade_at("v1", 1.9, fade), 1.0);
 
-        let video_track = VideoTrack {
-            id: "vt1".to_string(),
-            name: "Video Track 1".to_string(),
-            clips: vec![video_clip.clone()],
-            muted: false,
+        assert!(timeline.remove_clip("v2"));
+        // v1 fades out over its last 0.5s before the 2s gap, v3 fades up after it
+        assert_eq!(timeline.gap_fade_at("v1", 1.0, fade), 1.0);
+        assert_eq!(timeline.gap_fade_at("v1", 1.75, fade), 0.5);
+        assert_eq!(timeline.gap_fade_at("v1", 2.0, fade), 0.0);
+        assert_eq!(timeline.gap_fade_at("v3", 4.0, fade), 0.0);
+        assert_eq!(timeline.gap_fade_at("v3", 4.25, fade), 0.5);
+        // Nothing before v1 or after v3 to fade across
+        assert_eq!(timeline.gap_fade_at("v1", 0.0, fade), 1.0);
+        assert_eq!(timeline.gap_fade_at("v3", 5.9, fade), 1.0);
+
+        // Longer gaps stay hard cuts
+        let short = GapFade {
+            max_gap: 1.0,
+            ..fade
         };
+        assert_eq!(timeline.gap_fade_at("v1", 1.75, short), 1.0);
+        assert_eq!(timeline.gap_fade_at("missing", 1.0, fade), 1.0);
+    }
 
-        let audio_track = AudioTrack {
-            id: "at1".to_string(),
-            name: "Audio Track 1".to_string(),
-            clips: vec![audio_clip.clone()],
-            muted: false,
+    #[test]
+    fn test_add_av_clip_pairs_picture_and_sound() {
+        let mut timeline = sync_lock_timeline();
+        let video = VideoMetadata {
+            resolution: (1280, 720),
+            frame_rate: 25.0,
+            codec: "h264".to_string(),
+            color_space: None,
+        };
+        let audio = AudioMetadata {
+            sample_rate: 48000,
+            channels: 2,
+            codec: "aac".to_string(),
+            bitrate: 128,
         };
 
-        let timeline = Timeline {
-            tracks: vec![Track::Video(video_track), Track::Audio(audio_track)],
-            duration: 10.0,
-            frame_rate: 30.0,
-            resolution: (1920, 1080),
+        // The video track is free from 6s, both audio tracks are taken
+        let (video_id, audio_id) = timeline.add_av_clip("interview.mp4", 6.0, 3.0, video, audio);
+        assert_eq!(timeline.clip_track_index(&video_id), Some(0));
+        assert_eq!(timeline.clip_track_index(&audio_id), Some(3));
+        assert!(timeline.tracks[3].is_audio());
+        assert_eq!(timeline.clip_start_time(&video_id), Some(6.0));
+        assert_eq!(timeline.clip_start_time(&audio_id), Some(6.0));
+        let group = timeline.clip_group_id(&video_id).map(str::to_string);
+        assert!(group.is_some());
+        assert_eq!(timeline.clip_group_id(&audio_id), group.as_deref());
+        let Track::Video(v) = &timeline.tracks[0] else {
+            panic!("Expected video track");
         };
+        assert!(v.clips[3].mute_audio);
 
-        // Both clips overlap with range 5.0..15.0
-        let in_range = timeline.clips_in_range(5.0, 15.0);
-        assert_eq!(in_range.len(), 2);
+        // Moving either clip keeps the pair aligned
+        assert!(timeline.move_clip(&audio_id, 8.5));
+        assert_eq!(timeline.clip_start_time(&video_id), Some(8.5));
+        assert!(timeline.move_clip(&video_id, -2.0));
+        assert_eq!(timeline.clip_start_time(&audio_id), Some(0.0));
+        // Ungrouped clips still move alone
+        assert!(timeline.move_clip("a_free", 1.0));
+        assert_eq!(starts(&timeline, 1), vec![6.0]);
+    }
 
-        // Both clips overlap with range -5.0..1.0
-        let in_range = timeline.clips_in_range(-5.0, 1.0);
-        assert_eq!(in_range.len(), 2);
+    #[test]
+    fn test_cutting_a_linked_pair_leaves_two_pairs() {
+        use crate::ops::clip_ops::split_ids;
+        let mut timeline = Timeline::new();
+        let video = VideoMetadata {
+            resolution: (1280, 720),
+            frame_rate: 25.0,
+            codec: "h264".to_string(),
+            color_space: None,
+        };
+        let audio = AudioMetadata {
+            sample_rate: 48000,
+            channels: 2,
+            codec: "aac".to_string(),
+            bitrate: 128,
+        };
+        let (video_id, audio_id) = timeline.add_av_clip("take.mp4", 0.0, 4.0, video, audio);
+        let selected = [video_id.clone(), audio_id.clone()].into_iter().collect();
+        assert_eq!(timeline.split_selected_at(&selected, 1.0), 2);
+        let (video_left, video_right) = split_ids(&video_id);
+        let (audio_left, audio_right) = split_ids(&audio_id);
 
-        // No clips overlap with range 11.0..20.0
-        let in_range = timeline.clips_in_range(11.0, 20.0);
-        assert_eq!(in_range.len(), 0);
+        // Moving the right half of the picture takes only the right half of the sound
+        assert!(timeline.move_clip(&video_right, 3.0));
+        assert_eq!(timeline.clip_start_time(&audio_right), Some(3.0));
+        assert_eq!(timeline.clip_start_time(&video_left), Some(0.0));
+        assert_eq!(timeline.clip_start_time(&audio_left), Some(0.0));
+        assert!(timeline.move_clip(&audio_left, 0.5));
+        assert_eq!(timeline.clip_start_time(&video_left), Some(0.5));
+        assert_eq!(timeline.clip_start_time(&video_right), Some(3.0));
     }
 
     #[test]
-    fn test_clips_on_track() {
-        let video_clip = VideoClip {
-            id: "v1".to_string(),
-            asset_path: "video.mp4".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: VideoMetadata {
-                resolution: (1920, 1080),
-                frame_rate: 30.0,
-                codec: "h264".to_string(),
-            },
-        };
+    fn test_recompute_duration_follows_clip_ends() {
+        let mut timeline = Timeline::new();
+        timeline.duration = 600.0;
+        timeline.recompute_duration();
+        assert_eq!(timeline.duration, 0.0);
 
-        let audio_clip = AudioClip {
-            id: "a1".to_string(),
-            asset_path: "audio.wav".to_string(),
-            in_point: 0.0,
-            out_point: 10.0,
-            start_time: 0.0,
-            duration: 10.0,
-            metadata: AudioMetadata {
-                sample_rate: 48000,
-                channels: 2,
-                codec: "pcm".to_string(),
-                bitrate: 1536,
-            },
-        };
+        // Clips overlap across tracks; the latest end wins
+        let mut timeline = sync_lock_timeline();
+        assert_eq!(timeline.duration, 20.0);
+        timeline.recompute_duration();
+        assert_eq!(timeline.duration, 8.0);
+        assert!(timeline.move_clip("a_free", 7.5));
+        timeline.recompute_duration();
+        assert_eq!(timeline.duration, 9.5);
 
-        let video_track = VideoTrack {
-            id: "vt1".to_string(),
-            name: "Video Track 1".to_string(),
-            clips: vec![video_clip.clone()],
-            muted: false,
-        };
+        // Shortening the last clip shortens the timeline
+        assert!(timeline.resize_clip("a_free", 7.5, 0.5, None));
+        timeline.recompute_duration();
+        assert_eq!(timeline.duration, 8.0);
+    }
 
-        let audio_track = AudioTrack {
-            id: "at1".to_string(),
-            name: "Audio Track 1".to_string(),
-            clips: vec![audio_clip.clone()],
-            muted: false,
-        };
+    #[test]
+    fn test_target_track_defaults_to_first_of_its_kind() {
+        // vt is the only video track; locked and free hold audio
+        let timeline = sync_lock_timeline();
+        assert_eq!(timeline.target_track(None, true), Some(0));
+        assert_eq!(timeline.target_track(None, false), Some(1));
 
-        let timeline = Timeline {
-            tracks: vec![Track::Video(video_track), Track::Audio(audio_track)],
-            duration: 10.0,
-            frame_rate: 30.0,
-            resolution: (1920, 1080),
+        // An explicit target wins while it exists and holds the right kind of media
+        assert_eq!(timeline.target_track(Some("free"), false), Some(2));
+        assert_eq!(timeline.target_track(Some("free"), true), Some(0));
+        assert_eq!(timeline.target_track(Some("deleted"), false), Some(1));
+        assert_eq!(Timeline::new().target_track(Some("vt"), true), None);
+    }
+
+    #[test]
+    fn test_muted_tracks_are_not_rendered() {
+        let mut timeline = sync_lock_timeline();
+        let ids = |clips: Vec<ActiveClip>| -> Vec<String> {
+            clips
+                .iter()
+                .map(|c| match c {
+                    ActiveClip::Video(v) => v.id.clone(),
+                    ActiveClip::Audio(a) => a.id.clone(),
+                })
+                .collect()
         };
+        assert_eq!(ids(timeline.renderable_clips_at(1.0)), vec!["v1"]);
 
-        let video_clips = timeline.clips_on_track("vt1").unwrap();
-        assert_eq!(video_clips.len(), 1);
-        match &video_clips[0] {
-            ActiveClip::Video(vc) => assert_eq!(vc.id, "v1"),
-            _ => panic!("Expected video clip"),
+        if let Track::Video(v) = &mut timeline.tracks[0] {
+            v.muted = true;
         }
-
-        let audio_clips = timeline.clips_on_track("at1").unwrap();
-        assert_eq!(audio_clips.len(), 1);
-        match &audio_clips[0] {
-            ActiveClip::Audio(ac) => assert_eq!(ac.id, "a1"),
-            _ => panic!("Expected audio clip"),
+        if let Track::Audio(a) = &mut timeline.tracks[2] {
+            a.muted = true;
         }
+        assert!(timeline.active_unmuted_clips_at(1.0).is_empty());
+        assert!(timeline.renderable_clips_at(1.0).is_empty());
+        // Editing still sees every clip
+        assert_eq!(ids(timeline.active_clips_at(1.0)), vec!["v1"]);
+        // Only the unmuted audio track is mixed
+        assert_eq!(ids(timeline.audible_clips_at(6.5)), vec!["a_locked"]);
 
-        // Non-existent track
-        assert!(timeline.clips_on_track("notrack").is_none());
+        // Exports leave them out too
+        let flat = timeline.flatten_for_export();
+        assert_eq!(flat.tracks.len(), 1);
+        assert!(flat.renderable_clips_at(1.0).is_empty());
+        assert_eq!(ids(flat.audible_clips_at(6.5)), vec!["a_locked"]);
     }
 }
@@ -1,29 +1,126 @@
 use crate::types::media::{AudioClip, Clip, VideoClip};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Track {
     Video(VideoTrack),
     Audio(AudioTrack),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct VideoTrack {
     pub id: String,
     pub name: String,
     pub clips: Vec<VideoClip>,
     pub muted: bool,
+    /// Follows ripple edits made on other sync-locked tracks
+    #[serde(default)]
+    pub sync_locked: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AudioTrack {
     pub id: String,
     pub name: String,
     pub clips: Vec<AudioClip>,
     pub muted: bool,
+    /// Follows ripple edits made on other sync-locked tracks
+    #[serde(default)]
+    pub sync_locked: bool,
+    /// Track volume in dB, applied after each clip's gain
+    #[serde(default)]
+    pub volume_db: f64,
+    /// Balance from -1.0 (left) to 1.0 (right)
+    #[serde(default)]
+    pub pan: f64,
+}
+
+/// Linear amplitude factor for a gain in dB
+pub fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+impl AudioTrack {
+    /// Left/right gain for `clip` on this track: clip gain and track volume add up
+    /// first, then the pan attenuates the opposite channel.
+    pub fn channel_gains(&self, clip: &AudioClip) -> (f64, f64) {
+        let gain = db_to_gain(clip.gain_db + self.volume_db);
+        let pan = self.pan.clamp(-1.0, 1.0);
+        (gain * (1.0 - pan).min(1.0), gain * (1.0 + pan).min(1.0))
+    }
 }
 
 enum TrackType {
     Video,
     Audio,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::media::AudioMetadata;
+
+    fn clip(gain_db: f64) -> AudioClip {
+        AudioClip {
+            id: "a1".to_string(),
+            asset_path: "a.wav".to_string(),
+            in_point: 0.0,
+            out_point: 1.0,
+            start_time: 0.0,
+            duration: 1.0,
+            enabled: true,
+            gain_db,
+            group_id: None,
+            metadata: AudioMetadata {
+                sample_rate: 48000,
+                channels: 2,
+                codec: "pcm".to_string(),
+                bitrate: 1536,
+            },
+        }
+    }
+
+    fn track(volume_db: f64, pan: f64) -> AudioTrack {
+        AudioTrack {
+            id: "at1".to_string(),
+            name: "Audio 1".to_string(),
+            clips: Vec::new(),
+            muted: false,
+            sync_locked: false,
+            volume_db,
+            pan,
+        }
+    }
+
+    fn assert_gains(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9,
+            "{:?} != {:?}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_clip_gain_and_track_volume_add_up() {
+        assert_gains(track(0.0, 0.0).channel_gains(&clip(0.0)), (1.0, 1.0));
+        // -6 dB on the clip is undone by +6 dB on the track
+        assert_gains(track(6.0, 0.0).channel_gains(&clip(-6.0)), (1.0, 1.0));
+        let half = db_to_gain(-6.0);
+        assert!((half - 0.501187).abs() < 1e-6);
+        assert_gains(track(-3.0, 0.0).channel_gains(&clip(-3.0)), (half, half));
+    }
+
+    #[test]
+    fn test_pan_applies_after_combined_gain() {
+        let gain = db_to_gain(-6.0);
+        // Panning right halves the left channel of the already-attenuated signal
+        assert_gains(
+            track(-6.0, 0.5).channel_gains(&clip(0.0)),
+            (gain * 0.5, gain),
+        );
+        assert_gains(track(0.0, -1.0).channel_gains(&clip(-6.0)), (gain, 0.0));
+        // Out-of-range pan is clamped
+        assert_gains(track(0.0, 3.0).channel_gains(&clip(0.0)), (0.0, 1.0));
+    }
+}
@@ -0,0 +1,159 @@
+use crate::types::project::ViewState;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// What a click on a timeline clip does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ToolMode {
+    /// Select the clip
+    #[default]
+    Select,
+    /// Split the clip at the pointer
+    Razor,
+}
+
+/// UI preferences kept per user, across projects and sessions.
+/// Fields missing from the file take their default.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiState {
+    pub tool: ToolMode,
+    /// Whether dragged clips and the playhead snap
+    pub snap: bool,
+    pub auto_ripple: bool,
+    /// Inner size of the main window at the last exit
+    pub window_size: Option<(f32, f32)>,
+    /// Zoom and scroll for projects that don't carry their own
+    pub view: ViewState,
+}
+
+impl Default for UiState {
+    fn default() -> Self {
+        Self {
+            tool: ToolMode::Select,
+            snap: true,
+            auto_ripple: false,
+            window_size: None,
+            view: ViewState::default(),
+        }
+    }
+}
+
+impl UiState {
+    /// `cutio/ui_state.json` in the user's config folder, if there is one
+    pub fn default_path() -> Option<PathBuf> {
+        config_dir().map(|dir| dir.join("cutio").join("ui_state.json"))
+    }
+
+    /// Load the state saved at `path`; a missing or unreadable file gives the defaults
+    pub fn load(path: &Path) -> Self {
+        let json = match std::fs::read_to_string(path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                log::warn!("Could not read UI state {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        serde_json::from_str(&json).unwrap_or_else(|e| {
+            log::warn!("Ignoring invalid UI state {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Write the state to `path`, creating its folder if needed
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        std::fs::write(path, json)
+    }
+}
+
+/// Settings in effect once the user's and the project's state are combined
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResolvedView {
+    pub tool: ToolMode,
+    pub snap: bool,
+    pub auto_ripple: bool,
+    pub view: ViewState,
+}
+
+/// Combine user and project state: the project's zoom and scroll win when it has
+/// them, while the tool, snapping and auto-ripple always follow the user
+pub fn resolve_view(user: &UiState, project: Option<&ViewState>) -> ResolvedView {
+    ResolvedView {
+        tool: user.tool,
+        snap: user.snap,
+        auto_ripple: user.auto_ripple,
+        view: project.copied().unwrap_or(user.view),
+    }
+}
+
+/// Per-user configuration folder (`%APPDATA%` on Windows, XDG elsewhere)
+fn config_dir() -> Option<PathBuf> {
+    let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    if cfg!(windows) {
+        non_empty("APPDATA").map(PathBuf::from)
+    } else {
+        non_empty("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| non_empty("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_view_wins_over_user_view() {
+        let user = UiState {
+            tool: ToolMode::Razor,
+            snap: false,
+            auto_ripple: true,
+            window_size: Some((1280.0, 720.0)),
+            view: ViewState {
+                zoom: 40.0,
+                scroll_x: 300.0,
+            },
+        };
+        let project = ViewState {
+            zoom: 250.0,
+            scroll_x: 12.0,
+        };
+
+        let resolved = resolve_view(&user, Some(&project));
+        assert_eq!(resolved.view, project);
+        // The project has no say over tool and snapping
+        assert_eq!(resolved.tool, ToolMode::Razor);
+        assert!(!resolved.snap);
+        assert!(resolved.auto_ripple);
+
+        // Without a saved project view the user's last one is used
+        assert_eq!(resolve_view(&user, None).view, user.view);
+    }
+
+    #[test]
+    fn test_ui_state_round_trip_and_fallbacks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config").join("ui_state.json");
+        assert_eq!(UiState::load(&path), UiState::default());
+
+        let state = UiState {
+            tool: ToolMode::Razor,
+            window_size: Some((1600.0, 900.0)),
+            ..UiState::default()
+        };
+        state.save(&path).unwrap();
+        assert_eq!(UiState::load(&path), state);
+
+        // Missing fields take their defaults, garbage gives the defaults
+        std::fs::write(&path, r#"{ "auto_ripple": true }"#).unwrap();
+        let partial = UiState::load(&path);
+        assert!(partial.auto_ripple && partial.snap);
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(UiState::load(&path), UiState::default());
+    }
+}
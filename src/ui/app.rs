@@ -1,11 +1,13 @@
 use std::ops::Sub;
 
+use crate::renderer::asset_analyzer::AssetAnalyzer;
 use crate::types::playback_state::PlaybackState;
 use crate::types::project::Project;
 use crate::types::timeline::{self, Timeline};
 use eframe::egui;
 use std::sync::{Arc, RwLock};
 
+use crate::ui::log_panel::LogPanel;
 use crate::ui::medialib::medialib_panel;
 use crate::ui::timeline_widget::{TimelineState, TimelineWidget};
 
@@ -15,6 +17,77 @@ pub struct AppState {
     pub video_player: crate::ui::video_player::VideoPlayer,
     pub timeline: Arc<RwLock<Timeline>>,
     pub timeline_state: TimelineState,
+    pub asset_analyzer: AssetAnalyzer,
+    /// Frames cached for hover scrubbing in the media library
+    pub scrub_previews: crate::ui::medialib::ScrubPreviews,
+    pub log_panel: LogPanel,
+    pub show_stats: bool,
+    /// Numbers shown in the Project Info dialog, computed when it opens and again after
+    /// the project changes
+    pub project_stats: Option<crate::types::project::ProjectStats>,
+    /// Text of the open "Go To" timecode prompt, if any
+    pub goto_input: Option<String>,
+    /// The project has changes that have not been saved
+    pub dirty: bool,
+    /// The "unsaved changes" dialog is open, for what wants to close the project
+    pub close_prompt: Option<CloseReason>,
+    /// Template selected in the open New Project dialog, if any
+    pub new_project_template: Option<&'static str>,
+    pub render_queue: crate::ops::render_queue::RenderQueue,
+    pub show_render_queue: bool,
+    /// Undo/redo steps for the timeline
+    pub history: crate::types::history::EditHistory,
+    /// Per-user UI preferences, saved on exit
+    pub ui_state: crate::types::ui_state::UiState,
+    /// Problems found in the last opened project, shown until dismissed
+    pub open_warnings: Option<Vec<crate::types::project::ProjectWarning>>,
+    /// Id of the track video edits go to; the first video track when unset or gone
+    pub target_video_track: Option<String>,
+    /// Id of the track audio edits go to; the first audio track when unset or gone
+    pub target_audio_track: Option<String>,
+}
+
+/// Answer to the "unsaved changes" dialog
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+/// What wants to close the current project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseReason {
+    /// The window is closing
+    Exit,
+    /// Another project is about to be opened
+    OpenProject,
+}
+
+/// What to do with a request to close the project
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloseAction {
+    /// Nothing to lose: close right away
+    Close,
+    /// Ask the user first
+    Prompt,
+    /// Save, and close only if saving succeeded
+    SaveAndClose,
+    /// Keep the project open
+    KeepOpen,
+}
+
+/// Decide how to handle a close request, given unsaved changes and the user's answer (if asked).
+pub fn close_decision(dirty: bool, user_choice: Option<CloseChoice>) -> CloseAction {
+    if !dirty {
+        return CloseAction::Close;
+    }
+    match user_choice {
+        None => CloseAction::Prompt,
+        Some(CloseChoice::Save) => CloseAction::SaveAndClose,
+        Some(CloseChoice::Discard) => CloseAction::Close,
+        Some(CloseChoice::Cancel) => CloseAction::KeepOpen,
+    }
 }
 
 pub struct CutioApp {
@@ -23,7 +96,151 @@ pub struct CutioApp {
 
 impl CutioApp {
     pub fn new(state: AppState) -> Self {
-        Self { state }
+        let mut app = Self { state };
+        app.apply_view_state();
+        app.apply_color_space();
+        app
+    }
+
+    /// Preview in the project's working color space. Resolved when a project is loaded,
+    /// so an unsupported name is only reported once.
+    fn apply_color_space(&mut self) {
+        let color_space = self.state.project.settings.working_color_space();
+        self.state.video_player.set_color_space(color_space);
+    }
+
+    /// Apply the user's preferences and the project's saved view to the timeline
+    fn apply_view_state(&mut self) {
+        let resolved = crate::types::ui_state::resolve_view(
+            &self.state.ui_state,
+            self.state.project.view_state.as_ref(),
+        );
+        let timeline_state = &mut self.state.timeline_state;
+        timeline_state.tool = resolved.tool;
+        timeline_state.auto_ripple = resolved.auto_ripple;
+        timeline_state.zoom = resolved.view.zoom;
+        timeline_state.scroll_to_x = Some(resolved.view.scroll_x);
+        self.state.ui_state.snap = resolved.snap;
+    }
+
+    /// Current zoom and scroll of the timeline
+    fn view_state(&self) -> crate::types::project::ViewState {
+        crate::types::project::ViewState {
+            zoom: self.state.timeline_state.zoom,
+            scroll_x: self.state.timeline_state.scroll_x,
+        }
+    }
+
+    /// Write the project to its file, asking for a path if it has none yet.
+    /// Returns true if the project was saved.
+    fn save_project(&mut self) -> bool {
+        if self.state.project.project_file_path.is_empty() {
+            return self.save_project_as();
+        }
+        // The timeline being edited lives behind the shared lock; mirror it into the project
+        self.state.project.timeline = self.state.timeline.read().unwrap().clone();
+        self.state.project.view_state = Some(self.view_state());
+        let path = self.state.project.project_file_path.clone();
+        match self.state.project.save_to_file(&path) {
+            Ok(()) => {
+                self.state.dirty = false;
+                log::info!("Saved project to {}", path);
+                true
+            }
+            Err(e) => {
+                log::error!("Failed to save project to {}: {}", path, e);
+                false
+            }
+        }
+    }
+
+    /// Ask for a new project file and save there, keeping relative media paths valid.
+    /// Returns true if the project was saved.
+    fn save_project_as(&mut self) -> bool {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Cutio Project", &["json"])
+            .save_file()
+        else {
+            return false;
+        };
+        let path = path.to_string_lossy().to_string();
+        let old_path = self.state.project.project_file_path.clone();
+        self.state.project.timeline = self.state.timeline.read().unwrap().clone();
+        self.state.project.view_state = Some(self.view_state());
+        match self.state.project.save_as(&path) {
+            Ok(()) => {
+                // Relative clip paths may have been rewritten for the new folder, in the
+                // undo steps too so undoing doesn't bring back the old ones
+                *self.state.timeline.write().unwrap() = self.state.project.timeline.clone();
+                if let Ok(rebase) = crate::types::project::path_rebaser(&old_path, &path) {
+                    self.state.history.map_timelines(|timeline| {
+                        crate::types::project::rebase_clip_paths(timeline, &rebase)
+                    });
+                }
+                self.state.dirty = false;
+                log::info!("Saved project as {}", path);
+                true
+            }
+            Err(e) => {
+                log::error!("Failed to save project as {}: {}", path, e);
+                false
+            }
+        }
+    }
+
+    /// Ask for a project file and open it, listing any problems found in it
+    fn open_project(&mut self) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("Cutio Project", &["json"])
+            .pick_file()
+        else {
+            return;
+        };
+        let path = path.to_string_lossy().to_string();
+        match Project::open_and_check(&path) {
+            Ok((mut project, warnings)) => {
+                project.project_file_path = path.clone();
+                *self.state.timeline.write().unwrap() = project.timeline.clone();
+                self.state.history.reset(&project.timeline);
+                self.state.project = project;
+                self.state.playback_state.playhead = 0.0;
+                // Cached frames belong to the previous project
+                self.state.video_player.invalidate_frames();
+                self.state.project_stats = None;
+                // Relinked media is only kept once the project is saved again
+                self.state.dirty = warnings
+                    .iter()
+                    .any(|w| matches!(w, crate::types::project::ProjectWarning::Relinked { .. }));
+                self.apply_view_state();
+                self.apply_color_space();
+                log::info!("Opened {} with {} warnings", path, warnings.len());
+                self.state.open_warnings = (!warnings.is_empty()).then_some(warnings);
+            }
+            Err(e) => log::error!("Failed to open {}: {}", path, e),
+        }
+    }
+
+    /// Go ahead with what the project was closed for
+    fn finish_close(&mut self, ctx: &egui::Context, reason: CloseReason) {
+        match reason {
+            CloseReason::Exit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+            CloseReason::OpenProject => self.open_project(),
+        }
+    }
+
+    /// Undo the last timeline edit, or redo the last undone one
+    fn step_history(&mut self, redo: bool) {
+        let restored = if redo {
+            self.state.history.redo()
+        } else {
+            self.state.history.undo()
+        };
+        if let Some(timeline) = restored {
+            *self.state.timeline.write().unwrap() = timeline;
+            self.state.video_player.invalidate_frames();
+            self.state.project_stats = None;
+            self.state.dirty = true;
+        }
     }
 }
 
@@ -57,8 +274,7 @@ impl eframe::App for CutioApp {
             });
 
             if elapsed > 0.0 {
-                let timeline = &self.state.project.timeline;
-                let max_time = timeline.duration.max(999.0);
+                let max_time = self.state.timeline.read().unwrap().duration;
                 self.state.playback_state.playhead +=
                     elapsed * self.state.playback_state.playback_rate;
                 self.state.playback_state.playhead =
@@ -69,20 +285,57 @@ impl eframe::App for CutioApp {
             }
         }
 
+        // Pick up finished analyses (source lengths go to the timeline) and keep repainting
+        // while any are pending
+        let analyzed = self.state.asset_analyzer.poll();
+        self.state.timeline_state.store_durations(&analyzed);
+        if !analyzed.is_empty() || self.state.asset_analyzer.has_pending() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+
+        // Advance the render queue and keep its progress on screen while it works
+        if self.state.render_queue.poll() || self.state.render_queue.is_busy() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+        }
+
+        // Rate-limit preview seeks while the pointer is dragging the playhead
+        let pointer_down = ctx.input(|i| i.pointer.primary_down());
+        self.state.video_player.scrubbing = pointer_down && !self.state.playback_state.is_playing;
+        // Seek exactly again once the pointer is released after scrubbing
+        if !pointer_down {
+            self.state
+                .video_player
+                .set_seek_mode(crate::renderer::timeline_renderer::SeekMode::Accurate);
+        }
+
+        // Hold backslash to see the preview without any effects (A/B compare)
+        let bypass_all = ctx.memory(|m| m.focused().is_none())
+            && ctx.input(|i| i.key_down(egui::Key::Backslash));
+        self.state
+            .video_player
+            .set_effects_bypass(bypass_all, &self.state.timeline_state.bypassed_effects);
+
         // Always update the video player to reflect the current playhead
         self.state
             .video_player
             .set_playhead(self.state.playback_state.playhead, ctx);
 
+        // Bottom-most: collapsible console
+        self.state.log_panel.show(ctx);
+
         // Left: Media Library
         egui::SidePanel::left("media_panel").show(ctx, |ui| {
             medialib_panel(
                 ui,
                 &mut self.state.project.media_library,
+                &mut self.state.asset_analyzer,
+                &mut self.state.scrub_previews,
                 |_medialib| {
                     // TODO: Implement import logic (e.g., file picker)
                 },
                 |medialib, idx| {
+                    self.state.dirty = true;
+                    self.state.project_stats = None;
                     // Clone file name before mutable borrow for removal
                     let file_name = if let Some(item) = medialib.all_items().get(idx) {
                         match item {
@@ -142,7 +395,7 @@ impl eframe::App for CutioApp {
                             self.state.playback_state.playhead =
                                 (self.state.playback_state.playhead - 1.0).max(0.0);
                             let timeline = self.state.timeline.read().unwrap();
-                            let max_time = timeline.duration.max(999.0);
+                            let max_time = timeline.duration;
                             self.state.playback_state.playhead =
                                 self.state.playback_state.playhead.clamp(0.0, max_time);
                             self.state
@@ -152,13 +405,45 @@ impl eframe::App for CutioApp {
                         if ui.button(">>").clicked() {
                             self.state.playback_state.playhead += 1.0;
                             let timeline = self.state.timeline.read().unwrap();
-                            let max_time = timeline.duration.max(999.0);
+                            let max_time = timeline.duration;
                             self.state.playback_state.playhead =
                                 self.state.playback_state.playhead.clamp(0.0, max_time);
                             self.state
                                 .video_player
                                 .set_playhead(self.state.playback_state.playhead, ctx);
                         }
+                        ui.separator();
+                        let undo = ui.add_enabled(
+                            self.state.history.can_undo(),
+                            egui::Button::new("Undo"),
+                        );
+                        let redo = ui.add_enabled(
+                            self.state.history.can_redo(),
+                            egui::Button::new("Redo"),
+                        );
+                        if undo.clicked() || redo.clicked() {
+                            self.step_history(redo.clicked());
+                        }
+                        ui.separator();
+                        ui.toggle_value(&mut self.state.ui_state.snap, "Snap");
+                        ui.toggle_value(&mut self.state.log_panel.open, "Console");
+                        ui.toggle_value(&mut self.state.show_stats, "Project Info");
+                        ui.toggle_value(&mut self.state.show_render_queue, "Render Queue");
+                        if ui.button("Open…").clicked() {
+                            match close_decision(self.state.dirty, None) {
+                                CloseAction::Prompt => {
+                                    self.state.close_prompt = Some(CloseReason::OpenProject);
+                                }
+                                _ => self.open_project(),
+                            }
+                        }
+                        if ui.button("Save As…").clicked() {
+                            self.save_project_as();
+                        }
+                        if ui.button("New Project…").clicked() {
+                            self.state.new_project_template =
+                                Some(crate::types::project::PROJECT_TEMPLATES[0].name);
+                        }
                     });
 
                     // Timeline and track view
@@ -170,28 +455,535 @@ impl eframe::App for CutioApp {
                             &mut self.state.timeline_state,
                             self.state.playback_state.playhead,
                         )
+                        .show_waveforms(true)
+                        .default_still_duration(
+                            self.state.project.settings.default_still_duration,
+                        )
+                        .snap_enabled(self.state.ui_state.snap)
+                        .analyzer(&mut self.state.asset_analyzer)
+                        .target_tracks(
+                            self.state.target_video_track.as_deref(),
+                            self.state.target_audio_track.as_deref(),
+                        )
                         .show(ui)
                     };
 
-                    // Handle timeline events (e.g., playhead moved)
+                    // Handle timeline events: the playhead is ours, edits go through the dispatcher
+                    let mut files_placed = false;
                     for event in timeline_events {
-                        match event {
-                            crate::ui::timeline_widget::TimelineEvent::PlayheadMoved(new_time) => {
-                                let timeline = self.state.timeline.read().unwrap();
-                                let max_time = timeline.duration.max(999.0);
-                                self.state.playback_state.playhead = new_time.clamp(0.0, max_time);
-                                self.state
-                                    .video_player
-                                    .set_playhead(self.state.playback_state.playhead, ctx);
+                        if let crate::ui::timeline_widget::TimelineEvent::FilesDropped {
+                            paths,
+                            time,
+                            track_idx,
+                        } = &event
+                        {
+                            let mut timeline = self.state.timeline.write().unwrap();
+                            crate::ui::file_drop::handle_dropped_files(
+                                paths,
+                                *time,
+                                Some(*track_idx),
+                                &mut self.state.project.media_library,
+                                &mut timeline,
+                                &mut self.state.timeline_state,
+                            );
+                            self.state.dirty = true;
+                            self.state.history.record(&timeline);
+                            self.state.video_player.invalidate_frames();
+                            self.state.project_stats = None;
+                            files_placed = true;
+                            continue;
+                        }
+                        if let crate::ui::timeline_widget::TimelineEvent::TrackTargeted {
+                            track_idx,
+                        } = event
+                        {
+                            let timeline = self.state.timeline.read().unwrap();
+                            if let Some(track) = timeline.tracks().get(track_idx) {
+                                let target = if track.is_video() {
+                                    &mut self.state.target_video_track
+                                } else {
+                                    &mut self.state.target_audio_track
+                                };
+                                *target = Some(track.id().to_string());
                             }
-                            // Handle other events as needed
-                            _ => {}
+                            continue;
+                        }
+                        if let crate::ui::timeline_widget::TimelineEvent::PlayheadMoved(new_time) =
+                            event
+                        {
+                            let timeline = self.state.timeline.read().unwrap();
+                            let max_time = timeline.duration;
+                            self.state.playback_state.playhead = new_time.clamp(0.0, max_time);
+                            // Keyframe-only seeks keep playhead drags responsive
+                            let seek_mode = if ctx.input(|i| i.pointer.primary_down()) {
+                                crate::renderer::timeline_renderer::SeekMode::Fast
+                            } else {
+                                crate::renderer::timeline_renderer::SeekMode::Accurate
+                            };
+                            self.state.video_player.set_seek_mode(seek_mode);
+                            self.state
+                                .video_player
+                                .set_playhead(self.state.playback_state.playhead, ctx);
+                            continue;
                         }
+                        let mut timeline = self.state.timeline.write().unwrap();
+                        if let Some(record) = crate::ui::edit_dispatch::apply_timeline_event(
+                            &mut timeline,
+                            &mut self.state.timeline_state,
+                            event,
+                        ) {
+                            log::debug!("Timeline edit: {:?}", record);
+                            self.state.dirty = true;
+                            // No-op while a drag transaction is open
+                            self.state.history.record(&timeline);
+                            // Cached preview frames show the timeline before the edit
+                            self.state.video_player.invalidate_frames();
+                            self.state.project_stats = None;
+                        }
+                    }
+
+                    // Files dropped anywhere else only go into the media library
+                    let dropped_files: Vec<std::path::PathBuf> = ctx.input(|i| {
+                        i.raw
+                            .dropped_files
+                            .iter()
+                            .filter_map(|file| file.path.clone())
+                            .collect()
+                    });
+                    if !files_placed && !dropped_files.is_empty() {
+                        crate::ui::file_drop::handle_dropped_files(
+                            &dropped_files,
+                            0.0,
+                            None,
+                            &mut self.state.project.media_library,
+                            &mut self.state.timeline.write().unwrap(),
+                            &mut self.state.timeline_state,
+                        );
+                        self.state.dirty = true;
+                        self.state.project_stats = None;
+                    }
+
+                    // A drag is one undo step, taken when it is released
+                    let dragging = self.state.timeline_state.drag_state.is_some();
+                    if dragging && !self.state.history.in_transaction() {
+                        self.state.history.begin_transaction();
+                    } else if !dragging && self.state.history.in_transaction() {
+                        self.state
+                            .history
+                            .commit(&self.state.timeline.read().unwrap());
                     }
                 });
             });
 
+        // Ctrl+Z / Ctrl+Shift+Z: undo and redo timeline edits
+        let (undo, redo) = ctx.input(|i| {
+            let z = i.modifiers.command && i.key_pressed(egui::Key::Z);
+            (z && !i.modifiers.shift, z && i.modifiers.shift)
+        });
+        if undo || redo {
+            self.step_history(redo);
+        }
+
+        // Ctrl+G: jump the playhead to a typed time
+        let goto_opened = ctx.input(|i| i.modifiers.command && i.key_pressed(egui::Key::G));
+        if goto_opened {
+            self.state.goto_input = Some(String::new());
+        }
+        if let Some(input) = &mut self.state.goto_input {
+            let mut close = false;
+            let mut target = None;
+            egui::Window::new("Go To")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    ui.label("Time (seconds, MM:SS.mmm or HH:MM:SS:FF)");
+                    let response = ui.text_edit_singleline(input);
+                    // Only on opening, so clicking elsewhere can still take focus
+                    if goto_opened {
+                        response.request_focus();
+                    }
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        let frame_rate = self.state.timeline.read().unwrap().frame_rate;
+                        target = crate::ui::timeline_widget::parse_time_input(input, frame_rate);
+                        if target.is_none() {
+                            log::warn!("Could not parse time input '{}'", input);
+                        }
+                        close = true;
+                    }
+                    if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                        close = true;
+                    }
+                });
+            if let Some(time) = target {
+                let max_time = self.state.timeline.read().unwrap().duration;
+                self.state.playback_state.playhead = time.clamp(0.0, max_time);
+                self.state
+                    .video_player
+                    .set_playhead(self.state.playback_state.playhead, ctx);
+            }
+            if close {
+                self.state.goto_input = None;
+            }
+        }
+
+        // New Project dialog: pick a template, then replace the open project
+        if let Some(selected) = &mut self.state.new_project_template {
+            let mut create = None;
+            let mut cancel = false;
+            egui::Window::new("New Project")
+                .collapsible(false)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    for template in crate::types::project::PROJECT_TEMPLATES {
+                        ui.radio_value(
+                            selected,
+                            template.name,
+                            format!(
+                                "{}  ({}×{}, {} fps)",
+                                template.name,
+                                template.resolution.0,
+                                template.resolution.1,
+                                template.frame_rate
+                            ),
+                        );
+                    }
+                    if self.state.dirty {
+                        ui.colored_label(
+                            egui::Color32::YELLOW,
+                            "Unsaved changes to the current project will be lost.",
+                        );
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Create").clicked() {
+                            create = Some(*selected);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            cancel = true;
+                        }
+                    });
+                });
+            if let Some(name) = create {
+                self.state.project = Project::from_template(name);
+                *self.state.timeline.write().unwrap() = self.state.project.timeline.clone();
+                self.state.history.reset(&self.state.project.timeline);
+                self.state.playback_state.playhead = 0.0;
+                self.state.video_player.invalidate_frames();
+                self.state.project_stats = None;
+                self.state.dirty = false;
+                self.apply_view_state();
+                self.apply_color_space();
+                log::info!("Created new project from template {}", name);
+            }
+            if create.is_some() || cancel {
+                self.state.new_project_template = None;
+            }
+        }
+
+        // Problems found when opening a project
+        if let Some(warnings) = &self.state.open_warnings {
+            let mut open = true;
+            egui::Window::new("Project Check")
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    ui.label(format!("{} problems found while opening:", warnings.len()));
+                    egui::ScrollArea::vertical()
+                        .max_height(240.0)
+                        .show(ui, |ui| {
+                            for warning in warnings {
+                                ui.label(warning.to_string());
+                            }
+                        });
+                });
+            if !open {
+                self.state.open_warnings = None;
+            }
+        }
+
+        // Render queue panel
+        if self.state.show_render_queue {
+            use crate::ops::render_queue::JobStatus;
+
+            let mut remove = None;
+            let mut reorder = None;
+            let mut queue_timeline = false;
+            let mut export_sequence = false;
+            let queue = &self.state.render_queue;
+            egui::Window::new("Render Queue")
+                .open(&mut self.state.show_render_queue)
+                .show(ctx, |ui| {
+                    let (finished, total) = queue.overall_progress();
+                    ui.label(format!("{} of {} jobs finished", finished, total));
+                    ui.separator();
+                    let jobs = queue.jobs();
+                    for (i, job) in jobs.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(&job.settings.output);
+                            match &job.status {
+                                JobStatus::Queued => {
+                                    ui.label("Queued");
+                                }
+                                JobStatus::Running(fraction) => {
+                                    ui.add(
+                                        egui::ProgressBar::new(*fraction)
+                                            .desired_width(120.0)
+                                            .show_percentage(),
+                                    );
+                                }
+                                JobStatus::Done => {
+                                    ui.colored_label(egui::Color32::GREEN, "Done");
+                                }
+                                JobStatus::Failed(e) => {
+                                    ui.colored_label(egui::Color32::RED, "Failed")
+                                        .on_hover_text(e);
+                                }
+                            }
+                            let running = matches!(job.status, JobStatus::Running(_));
+                            if ui.add_enabled(i > 0, egui::Button::new("⏶")).clicked() {
+                                reorder = Some((i, i - 1));
+                            }
+                            if ui
+                                .add_enabled(i + 1 < jobs.len(), egui::Button::new("⏷"))
+                                .clicked()
+                            {
+                                reorder = Some((i, i + 1));
+                            }
+                            if ui.add_enabled(!running, egui::Button::new("✖")).clicked() {
+                                remove = Some(job.id);
+                            }
+                        });
+                    }
+                    ui.separator();
+                    if ui.button("Queue Timeline Export…").clicked() {
+                        queue_timeline = true;
+                    }
+                    if ui.button("Export PNG Sequence…").clicked() {
+                        export_sequence = true;
+                    }
+                });
+            if let Some(id) = remove {
+                self.state.render_queue.remove(id);
+            }
+            if let Some((from, to)) = reorder {
+                self.state.render_queue.move_job(from, to);
+            }
+            let output = queue_timeline
+                .then(|| {
+                    rfd::FileDialog::new()
+                        .add_filter("MP4 Video", &["mp4"])
+                        .save_file()
+                })
+                .flatten();
+            let sequence_dir = export_sequence
+                .then(|| rfd::FileDialog::new().pick_folder())
+                .flatten();
+            if let Some(dir) = sequence_dir {
+                let timeline = self.state.timeline.read().unwrap().clone();
+                let (width, height) = self.state.project.settings.resolution;
+                let color_space = self.state.video_player.player_bridge.renderer.color_space;
+                let gap_fade = self.state.video_player.player_bridge.renderer.gap_fade;
+                // Renders on its own thread so the editor stays responsive
+                std::thread::spawn(move || {
+                    let (duration, frame_rate) = (timeline.duration, timeline.frame_rate);
+                    let mut renderer = crate::renderer::timeline_renderer::TimelineRenderer::new(
+                        Arc::new(RwLock::new(timeline)),
+                        width,
+                        height,
+                        frame_rate,
+                    );
+                    renderer.exporting = true;
+                    renderer.set_color_space(color_space);
+                    renderer.set_gap_fade(gap_fade);
+                    match renderer.export_image_sequence(&dir, 0.0..duration, "frame_#####.png") {
+                        Ok(count) => log::info!("Wrote {} frames to {}", count, dir.display()),
+                        Err(e) => log::error!("PNG sequence export failed: {}", e),
+                    }
+                });
+            }
+            if let Some(path) = output {
+                let timeline = self.state.timeline.read().unwrap().clone();
+                let (width, height) = self.state.project.settings.resolution;
+                let settings =
+                    crate::ops::export::ExportSettings::new(&path.to_string_lossy(), width, height)
+                        .color_space(self.state.video_player.player_bridge.renderer.color_space)
+                        .threads(std::thread::available_parallelism().map_or(1, |n| n.get()))
+                        .gap_fade(self.state.video_player.player_bridge.renderer.gap_fade);
+                let duration = timeline.duration;
+                self.state
+                    .render_queue
+                    .add(&timeline, settings, 0.0, duration);
+            }
+        }
+
+        // Project statistics dialog
+        if !self.state.show_stats {
+            self.state.project_stats = None;
+        } else {
+            // Stats read every source file's size, so they aren't redone every frame
+            let stats = match &self.state.project_stats {
+                Some(stats) => stats.clone(),
+                None => {
+                    // The timeline being edited lives behind the shared lock; mirror it
+                    // into the project
+                    self.state.project.timeline = self.state.timeline.read().unwrap().clone();
+                    let stats = self.state.project.stats();
+                    self.state.project_stats = Some(stats.clone());
+                    stats
+                }
+            };
+            egui::Window::new("Project Info")
+                .open(&mut self.state.show_stats)
+                .resizable(false)
+                .show(ctx, |ui| {
+                    egui::Grid::new("project_stats")
+                        .num_columns(2)
+                        .show(ui, |ui| {
+                            ui.label("Tracks");
+                            ui.label(stats.track_count.to_string());
+                            ui.end_row();
+                            for (name, count) in &stats.clips_per_track {
+                                ui.label(format!("  {}", name));
+                                ui.label(format!("{} clips", count));
+                                ui.end_row();
+                            }
+                            ui.label("Total clips");
+                            ui.label(stats.total_clips.to_string());
+                            ui.end_row();
+                            ui.label("Duration");
+                            ui.label(crate::ui::timeline_widget::format_time(
+                                stats.total_duration,
+                            ));
+                            ui.end_row();
+                            ui.label("Unique media");
+                            ui.label(stats.unique_media.to_string());
+                            ui.end_row();
+                            ui.label("Media size");
+                            ui.label(format!(
+                                "{:.1} MB",
+                                stats.total_media_size as f64 / (1024.0 * 1024.0)
+                            ));
+                            ui.end_row();
+                            ui.label("Still duration");
+                            let still = ui.add(
+                                egui::DragValue::new(
+                                    &mut self.state.project.settings.default_still_duration,
+                                )
+                                .range(0.1..=3600.0)
+                                .speed(0.1)
+                                .suffix(" s"),
+                            );
+                            if still.changed() {
+                                self.state.dirty = true;
+                            }
+                            ui.end_row();
+                        });
+                });
+        }
+
+        // Remembered as the user's window size on exit
+        if let Some(rect) = ctx.input(|i| i.viewport().inner_rect) {
+            self.state.ui_state.window_size = Some((rect.width(), rect.height()));
+        }
+
+        // Intercept window close while there are unsaved changes
+        if ctx.input(|i| i.viewport().close_requested())
+            && close_decision(self.state.dirty, None) == CloseAction::Prompt
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.state.close_prompt = Some(CloseReason::Exit);
+        }
+        if let Some(reason) = self.state.close_prompt {
+            let mut choice = None;
+            egui::Window::new("Unsaved Changes")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label("The project has unsaved changes. Save before closing?");
+                    ui.horizontal(|ui| {
+                        if ui.button("Save").clicked() {
+                            choice = Some(CloseChoice::Save);
+                        }
+                        if ui.button("Discard").clicked() {
+                            choice = Some(CloseChoice::Discard);
+                        }
+                        if ui.button("Cancel").clicked() {
+                            choice = Some(CloseChoice::Cancel);
+                        }
+                    });
+                });
+            if choice.is_some() {
+                match close_decision(self.state.dirty, choice) {
+                    CloseAction::SaveAndClose => {
+                        if self.save_project() {
+                            self.state.close_prompt = None;
+                            self.finish_close(ctx, reason);
+                        }
+                    }
+                    CloseAction::Close => {
+                        // Discarded: don't prompt again for the close we are about to send
+                        if reason == CloseReason::Exit {
+                            self.state.dirty = false;
+                        }
+                        self.state.close_prompt = None;
+                        self.finish_close(ctx, reason);
+                    }
+                    CloseAction::KeepOpen | CloseAction::Prompt => {
+                        self.state.close_prompt = None;
+                    }
+                }
+            }
+        }
+
         // Optionally, use CentralPanel for background or other content
         egui::CentralPanel::default().show(ctx, |_ui| {});
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        let view = self.view_state();
+        let ui_state = &mut self.state.ui_state;
+        ui_state.tool = self.state.timeline_state.tool;
+        ui_state.auto_ripple = self.state.timeline_state.auto_ripple;
+        ui_state.view = view;
+        let Some(path) = crate::types::ui_state::UiState::default_path() else {
+            return;
+        };
+        if let Err(e) = ui_state.save(&path) {
+            log::error!("Failed to save UI state to {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_close_decision_clean_project_closes() {
+        assert_eq!(close_decision(false, None), CloseAction::Close);
+        assert_eq!(
+            close_decision(false, Some(CloseChoice::Cancel)),
+            CloseAction::Close
+        );
+    }
+
+    #[test]
+    fn test_close_decision_dirty_project_prompts() {
+        assert_eq!(close_decision(true, None), CloseAction::Prompt);
+    }
+
+    #[test]
+    fn test_close_decision_user_choices() {
+        assert_eq!(
+            close_decision(true, Some(CloseChoice::Save)),
+            CloseAction::SaveAndClose
+        );
+        assert_eq!(
+            close_decision(true, Some(CloseChoice::Discard)),
+            CloseAction::Close
+        );
+        assert_eq!(
+            close_decision(true, Some(CloseChoice::Cancel)),
+            CloseAction::KeepOpen
+        );
+    }
 }
@@ -0,0 +1,442 @@
+use crate::ops::clip_ops::split_ids;
+use crate::types::timeline::Timeline;
+use crate::ui::timeline_widget::{TimelineEvent, TimelineState};
+
+/// A change made to the timeline by `apply_timeline_event`
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditRecord {
+    /// A clip was moved to a new start time
+    Moved { clip_id: String },
+    /// A clip was trimmed
+    Resized { clip_id: String },
+    /// A clip was removed
+    Deleted { clip_id: String },
+    /// Several clips were removed at once
+    DeletedMany { clip_ids: Vec<String> },
+    /// Clips were split in two
+    Split { clip_ids: Vec<String> },
+    /// The widget edited the timeline itself (see `TimelineEvent::Edited`)
+    Edited,
+}
+
+/// Apply a timeline widget event to the timeline and the widget state.
+/// Returns what changed in the timeline, or None if the event didn't edit it
+/// (selection changes, playhead moves and right clicks). After an edit, nothing is
+/// left before time 0 (see `Timeline::enforce_non_negative`) and the timeline
+/// duration is brought up to date.
+pub fn apply_timeline_event(
+    timeline: &mut Timeline,
+    state: &mut TimelineState,
+    event: TimelineEvent,
+) -> Option<EditRecord> {
+    let record = apply_event(timeline, state, event);
+    if record.is_some() {
+        if timeline.enforce_non_negative() > 0 {
+            log::debug!("Trimmed clips that started before 0");
+        }
+        timeline.recompute_duration();
+    }
+    record
+}
+
+fn apply_event(
+    timeline: &mut Timeline,
+    state: &mut TimelineState,
+    event: TimelineEvent,
+) -> Option<EditRecord> {
+    match event {
+        // The playhead belongs to the app's playback state
+        TimelineEvent::PlayheadMoved(_) => None,
+        TimelineEvent::ClipMoved {
+            clip_id,
+            new_start_time,
+            ..
+        } => timeline
+            .move_clip(&clip_id, new_start_time)
+            .then_some(EditRecord::Moved { clip_id }),
+        TimelineEvent::ClipResized {
+            clip_id,
+            new_start_time,
+            new_duration,
+            ..
+        } => {
+            // Trims stop at the media end once its length has been probed in the background
+            let source_duration = timeline
+                .clip_asset_path(&clip_id)
+                .and_then(|path| state.source_duration(path));
+            // With auto-ripple, tail trims move later content with the clip's end
+            let tail_trim = timeline
+                .clip_start_time(&clip_id)
+                .is_some_and(|start| (start - new_start_time).abs() < 1e-9);
+            let resized = if state.auto_ripple && tail_trim {
+                timeline.ripple_trim(&clip_id, new_duration, source_duration)
+            } else {
+                timeline.resize_clip(&clip_id, new_start_time, new_duration, source_duration)
+            };
+            resized.then_some(EditRecord::Resized { clip_id })
+        }
+        TimelineEvent::ClipDeleted { clip_id } => {
+            let deleted = if state.auto_ripple {
+                timeline.ripple_delete(&clip_id)
+            } else {
+                timeline.remove_clip(&clip_id)
+            };
+            state.selected_clips.remove(&clip_id);
+            deleted.then_some(EditRecord::Deleted { clip_id })
+        }
+        TimelineEvent::ClipsDeleted { clip_ids, ripple } => {
+            let ids: std::collections::HashSet<String> = clip_ids.iter().cloned().collect();
+            state.selected_clips.retain(|id| !ids.contains(id));
+            (timeline.delete_clips(&ids, ripple) > 0)
+                .then_some(EditRecord::DeletedMany { clip_ids })
+        }
+        TimelineEvent::ClipsSplit { clip_ids, time } => {
+            let ids: std::collections::HashSet<String> = clip_ids.iter().cloned().collect();
+            if timeline.split_selected_at(&ids, time) == 0 {
+                return None;
+            }
+            // Both halves of a split clip take its place in the selection
+            let split: Vec<String> = clip_ids
+                .into_iter()
+                .filter(|id| timeline.clip_track_index(id).is_none())
+                .collect();
+            for id in &split {
+                let (left, right) = split_ids(id);
+                if state.selected_clips.remove(id) {
+                    state.selected_clips.insert(left.clone());
+                    state.selected_clips.insert(right);
+                }
+                if state.focused_clip.as_ref() == Some(id) {
+                    state.focused_clip = Some(left);
+                }
+            }
+            Some(EditRecord::Split { clip_ids: split })
+        }
+        TimelineEvent::ClipSelected {
+            clip_id,
+            multi_select,
+            ..
+        } => {
+            state.selected_gap = None;
+            let selected = &mut state.selected_clips;
+            if !multi_select {
+                selected.clear();
+                selected.insert(clip_id);
+            } else if !selected.remove(&clip_id) {
+                selected.insert(clip_id);
+            }
+            None
+        }
+        TimelineEvent::ClipDoubleClicked { clip_id, .. } => {
+            state.selected_gap = None;
+            state.selected_clips.clear();
+            state.selected_clips.insert(clip_id);
+            None
+        }
+        // The widget shows its own context menu
+        TimelineEvent::RightClicked { .. } => None,
+        TimelineEvent::Edited => Some(EditRecord::Edited),
+        // Edit targets are app state
+        TimelineEvent::TrackTargeted { .. } => None,
+        // Dropped files need the media library; the app passes them to `handle_dropped_files`
+        TimelineEvent::FilesDropped { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::track::Track;
+
+    fn timeline() -> Timeline {
+        serde_json::from_str(
+            r#"{
+                "tracks": [
+                    { "Video": { "id": "vt", "name": "V", "muted": false, "clips": [
+                        { "id": "v1", "asset_path": "v.mp4", "in_point": 1.0, "out_point": 3.0,
+                          "start_time": 0.0, "duration": 2.0,
+                          "metadata": { "resolution": [1920, 1080], "frame_rate": 30.0,
+                                        "codec": "h264" } }
+                    ] } },
+                    { "Audio": { "id": "at", "name": "A", "muted": false, "clips": [
+                        { "id": "a1", "asset_path": "a.wav", "in_point": 0.0, "out_point": 4.0,
+                          "start_time": 5.0, "duration": 4.0,
+                          "metadata": { "sample_rate": 48000, "channels": 2, "codec": "pcm",
+                                        "bitrate": 1536 } }
+                    ] } }
+                ],
+                "duration": 10.0,
+                "frame_rate": 30.0,
+                "resolution": [1920, 1080]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn state() -> TimelineState {
+        let mut state = TimelineState::new();
+        // Known source lengths, so nothing is probed
+        state
+            .source_durations
+            .insert("v.mp4".to_string(), Some(4.0));
+        state.source_durations.insert("a.wav".to_string(), None);
+        state
+    }
+
+    /// (start_time, in_point, duration) of the first clip on a track
+    fn first_clip(timeline: &Timeline, track_idx: usize) -> (f64, f64, f64) {
+        match &timeline.tracks()[track_idx] {
+            Track::Video(v) => (
+                v.clips[0].start_time,
+                v.clips[0].in_point,
+                v.clips[0].duration,
+            ),
+            Track::Audio(a) => (
+                a.clips[0].start_time,
+                a.clips[0].in_point,
+                a.clips[0].duration,
+            ),
+        }
+    }
+
+    #[test]
+    fn test_clip_moved() {
+        let (mut timeline, mut state) = (timeline(), state());
+        let record = apply_timeline_event(
+            &mut timeline,
+            &mut state,
+            TimelineEvent::ClipMoved {
+                clip_id: "a1".to_string(),
+                track_idx: 1,
+                new_start_time: 2.5,
+            },
+        );
+        assert_eq!(
+            record,
+            Some(EditRecord::Moved {
+                clip_id: "a1".to_string()
+            })
+        );
+        assert_eq!(first_clip(&timeline, 1), (2.5, 0.0, 4.0));
+        assert_eq!(timeline.active_clips_at(3.0).len(), 1);
+        // The timeline now ends with the moved clip
+        assert_eq!(timeline.duration, 6.5);
+
+        let missing = TimelineEvent::ClipMoved {
+            clip_id: "nope".to_string(),
+            track_idx: 0,
+            new_start_time: 1.0,
+        };
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut state, missing),
+            None
+        );
+    }
+
+    #[test]
+    fn test_clip_resized_is_clamped_to_source() {
+        let (mut timeline, mut state) = (timeline(), state());
+        // Trim the head by 0.5s and try to run 2s past the 4s source
+        let record = apply_timeline_event(
+            &mut timeline,
+            &mut state,
+            TimelineEvent::ClipResized {
+                clip_id: "v1".to_string(),
+                track_idx: 0,
+                new_start_time: 0.5,
+                new_duration: 3.5,
+            },
+        );
+        assert_eq!(
+            record,
+            Some(EditRecord::Resized {
+                clip_id: "v1".to_string()
+            })
+        );
+        assert_eq!(first_clip(&timeline, 0), (0.5, 1.5, 2.5));
+    }
+
+    #[test]
+    fn test_edits_never_leave_clips_before_zero() {
+        let (mut timeline, mut state) = (timeline(), state());
+        // Pulling the head out by 1s would start the clip at -1s
+        let record = apply_timeline_event(
+            &mut timeline,
+            &mut state,
+            TimelineEvent::ClipResized {
+                clip_id: "v1".to_string(),
+                track_idx: 0,
+                new_start_time: -1.0,
+                new_duration: 3.0,
+            },
+        );
+        assert!(record.is_some());
+        assert_eq!(first_clip(&timeline, 0), (0.0, 1.0, 2.0));
+    }
+
+    #[test]
+    fn test_selection_events_do_not_edit() {
+        let (mut timeline, mut state) = (timeline(), state());
+        let before = timeline.clone();
+        let select = |clip_id: &str, multi_select: bool| TimelineEvent::ClipSelected {
+            clip_id: clip_id.to_string(),
+            track_idx: 0,
+            multi_select,
+        };
+
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut state, select("v1", false)),
+            None
+        );
+        apply_timeline_event(&mut timeline, &mut state, select("a1", true));
+        assert_eq!(state.selected_clips.len(), 2);
+        // Multi-select toggles
+        apply_timeline_event(&mut timeline, &mut state, select("v1", true));
+        assert!(state.selected_clips.contains("a1") && !state.selected_clips.contains("v1"));
+
+        let double_click = TimelineEvent::ClipDoubleClicked {
+            clip_id: "v1".to_string(),
+            track_idx: 0,
+        };
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut state, double_click),
+            None
+        );
+        assert_eq!(
+            state.selected_clips,
+            ["v1".to_string()].into_iter().collect()
+        );
+
+        for event in [
+            TimelineEvent::PlayheadMoved(4.0),
+            TimelineEvent::RightClicked {
+                time: 1.0,
+                track_idx: Some(0),
+            },
+        ] {
+            assert_eq!(apply_timeline_event(&mut timeline, &mut state, event), None);
+        }
+        assert_eq!(timeline, before);
+    }
+
+    #[test]
+    fn test_edited_is_recorded() {
+        let (mut timeline, mut state) = (timeline(), state());
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut state, TimelineEvent::Edited),
+            Some(EditRecord::Edited)
+        );
+    }
+
+    #[test]
+    fn test_delete_follows_auto_ripple() {
+        // A second video clip after a 1s gap
+        let with_next = || {
+            let mut timeline = timeline();
+            if let Track::Video(v) = &mut timeline.tracks_mut()[0] {
+                let mut next = v.clips[0].clone();
+                next.id = "v2".to_string();
+                next.start_time = 3.0;
+                v.clips.push(next);
+            }
+            timeline
+        };
+        let delete = || TimelineEvent::ClipDeleted {
+            clip_id: "v1".to_string(),
+        };
+
+        // Off: v2 stays put and a gap is left
+        let (mut timeline, mut lifting) = (with_next(), state());
+        lifting.selected_clips.insert("v1".to_string());
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut lifting, delete()),
+            Some(EditRecord::Deleted {
+                clip_id: "v1".to_string()
+            })
+        );
+        assert!(lifting.selected_clips.is_empty());
+        assert_eq!(first_clip(&timeline, 0).0, 3.0);
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut lifting, delete()),
+            None
+        );
+
+        // On: v2 moves left by the deleted clip's length
+        let (mut timeline, mut rippling) = (with_next(), state());
+        rippling.auto_ripple = true;
+        apply_timeline_event(&mut timeline, &mut rippling, delete());
+        assert_eq!(first_clip(&timeline, 0).0, 1.0);
+    }
+
+    #[test]
+    fn test_deleting_a_selection() {
+        let (mut timeline, mut state) = (timeline(), state());
+        state.selected_clips = ["v1".to_string(), "a1".to_string()].into_iter().collect();
+        let clip_ids = vec!["a1".to_string(), "v1".to_string()];
+        let delete = TimelineEvent::ClipsDeleted {
+            clip_ids: clip_ids.clone(),
+            ripple: false,
+        };
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut state, delete.clone()),
+            Some(EditRecord::DeletedMany { clip_ids })
+        );
+        assert!(state.selected_clips.is_empty());
+        assert!(timeline.active_clips_at(1.0).is_empty());
+        assert!(timeline.active_clips_at(6.0).is_empty());
+        // Nothing left to delete
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut state, delete),
+            None
+        );
+    }
+
+    #[test]
+    fn test_splitting_a_selection() {
+        let (mut timeline, mut state) = (timeline(), state());
+        state.selected_clips = ["v1".to_string(), "a1".to_string()].into_iter().collect();
+        state.focused_clip = Some("v1".to_string());
+        let split = TimelineEvent::ClipsSplit {
+            clip_ids: vec!["a1".to_string(), "v1".to_string()],
+            time: 1.0,
+        };
+        // Only v1 is under the playhead
+        assert_eq!(
+            apply_timeline_event(&mut timeline, &mut state, split.clone()),
+            Some(EditRecord::Split {
+                clip_ids: vec!["v1".to_string()]
+            })
+        );
+        let mut selected: Vec<&str> = state.selected_clips.iter().map(|s| s.as_str()).collect();
+        selected.sort();
+        assert_eq!(selected, vec!["a1", "v1_left", "v1_right"]);
+        assert_eq!(state.focused_clip.as_deref(), Some("v1_left"));
+        assert_eq!(timeline.clip_start_time("v1_right"), Some(1.0));
+        assert_eq!(timeline.clip_start_time("a1"), Some(5.0));
+
+        // Nothing selected is under the playhead any more
+        assert_eq!(apply_timeline_event(&mut timeline, &mut state, split), None);
+    }
+
+    #[test]
+    fn test_tail_trim_ripples_with_auto_ripple() {
+        let (mut timeline, mut state) = (timeline(), state());
+        state.auto_ripple = true;
+        // Lock both tracks so the audio clip follows the video trim
+        for track in timeline.tracks_mut() {
+            match track {
+                Track::Video(v) => v.sync_locked = true,
+                Track::Audio(a) => a.sync_locked = true,
+            }
+        }
+        let trim = TimelineEvent::ClipResized {
+            clip_id: "v1".to_string(),
+            track_idx: 0,
+            new_start_time: 0.0,
+            new_duration: 1.5,
+        };
+        apply_timeline_event(&mut timeline, &mut state, trim);
+        assert_eq!(first_clip(&timeline, 0), (0.0, 1.0, 1.5));
+        assert_eq!(first_clip(&timeline, 1).0, 4.5);
+    }
+}
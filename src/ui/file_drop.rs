@@ -0,0 +1,289 @@
+use std::path::PathBuf;
+
+use crate::types::media::{AudioClip, AudioMetadata, VideoClip};
+use crate::types::media_library::{MediaItem, MediaLibrary};
+use crate::types::timeline::Timeline;
+use crate::types::track::{AudioTrack, Track, VideoTrack};
+use crate::ui::timeline_widget::{
+    TimelineState, get_audio_metadata, get_media_duration, media_fits_track, probe_video_metadata,
+};
+
+/// Import files dropped from the OS into the library and, when dropped on the
+/// timeline, place them one after another starting at `drop_time`.
+///
+/// Files go on track `track_idx` when it holds their kind of media, otherwise on a
+/// new track of the right kind (shared by the rest of the drop). With
+/// `state.auto_ripple` on, later content moves right to make room for each clip;
+/// otherwise a clip that would overlap others goes on a new track instead. Files whose
+/// length can't be probed are imported but not placed. Videos with an audio stream are
+/// placed as grouped video and audio clips (see `Timeline::add_av_clip`) when
+/// `state.split_av_on_drop` is set. Returns the ids of the new clips.
+pub fn handle_dropped_files(
+    paths: &[PathBuf],
+    drop_time: f64,
+    track_idx: Option<usize>,
+    library: &mut MediaLibrary,
+    timeline: &mut Timeline,
+    state: &mut TimelineState,
+) -> Vec<String> {
+    let mut placed = Vec::new();
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis();
+    let mut cursor = drop_time.max(0.0);
+    // Track each kind of media goes to, picked on its first file
+    let (mut video_track, mut audio_track) = (None, None);
+    for path in paths {
+        let Some(item) = library.add_file(path).cloned() else {
+            log::warn!("Skipping unsupported file {}", path.display());
+            continue;
+        };
+        let Some(track_idx) = track_idx else {
+            continue;
+        };
+        let asset_path = item.file_descriptor().path.clone();
+        let Some(duration) = *state
+            .source_durations
+            .entry(asset_path.clone())
+            .or_insert_with(|| get_media_duration(&asset_path))
+        else {
+            log::warn!(
+                "Could not extract duration for {}, not adding clip.",
+                asset_path
+            );
+            continue;
+        };
+
+        // Videos with sound become a grouped pair on tracks of each kind
+        let sound = match item {
+            MediaItem::VideoItem(_) if state.split_av_on_drop => get_audio_metadata(&asset_path),
+            _ => None,
+        };
+        if let Some(audio) = sound {
+            let video = probe_video_metadata(&asset_path);
+            let (video_id, audio_id) =
+                timeline.add_av_clip(&asset_path, cursor, duration, video, audio);
+            cursor += duration;
+            placed.extend([video_id, audio_id]);
+            continue;
+        }
+
+        let slot = match item {
+            MediaItem::VideoItem(_) => &mut video_track,
+            MediaItem::AudioItem(_) => &mut audio_track,
+        };
+        let preferred = slot.unwrap_or(track_idx);
+        let target = match timeline.tracks().get(preferred) {
+            Some(track)
+                if media_fits_track(&item, track)
+                    && (state.auto_ripple || track.has_room(cursor, cursor + duration)) =>
+            {
+                preferred
+            }
+            _ => add_track_for(timeline, &item),
+        };
+        *slot = Some(target);
+
+        // Earlier drops in the same millisecond may have taken the plain id
+        let clip_id = (placed.len()..)
+            .map(|n| format!("clip_{}_{}", stamp, n))
+            .find(|id| timeline.clip_asset_path(id).is_none())
+            .unwrap();
+        match &mut timeline.tracks_mut()[target] {
+            Track::Video(track) => track.clips.push(VideoClip {
+                id: clip_id.clone(),
+                asset_path: asset_path.clone(),
+                in_point: 0.0,
+                out_point: duration,
+                start_time: cursor,
+                duration,
+                enabled: true,
+                effects: Vec::new(),
+                opacity_envelope: crate::types::envelope::Envelope::new(),
+                freeze: false,
+                speed: 1.0,
+                time_map: None,
+                reversed: false,
+                mute_audio: false,
+                crop: None,
+                group_id: None,
+                metadata: probe_video_metadata(&asset_path),
+            }),
+            Track::Audio(track) => track.clips.push(AudioClip {
+                id: clip_id.clone(),
+                asset_path,
+                in_point: 0.0,
+                out_point: duration,
+                start_time: cursor,
+                duration,
+                enabled: true,
+                gain_db: 0.0,
+                group_id: None,
+                metadata: AudioMetadata {
+                    sample_rate: 44100,
+                    channels: 2,
+                    codec: "unknown".to_string(),
+                    bitrate: 0,
+                },
+            }),
+        }
+        timeline.invalidate_index();
+        if state.auto_ripple {
+            timeline.ripple_insert(&clip_id);
+        }
+        cursor += duration;
+        placed.push(clip_id);
+    }
+    timeline.invalidate_index();
+    timeline.enforce_non_negative();
+    timeline.recompute_duration();
+    placed
+}
+
+/// Append an empty track that can hold `item` and return its index
+fn add_track_for(timeline: &mut Timeline, item: &MediaItem) -> usize {
+    let n = timeline.tracks().len() + 1;
+    timeline.tracks_mut().push(match item {
+        MediaItem::VideoItem(_) => Track::Video(VideoTrack {
+            id: format!("track_{}", n),
+            name: format!("Video Track {}", n),
+            clips: vec![],
+            muted: false,
+            sync_locked: false,
+        }),
+        MediaItem::AudioItem(_) => Track::Audio(AudioTrack {
+            id: format!("track_{}", n),
+            name: format!("Audio Track {}", n),
+            clips: vec![],
+            muted: false,
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        }),
+    });
+    n - 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Timeline with one empty video track and one empty audio track
+    fn timeline() -> Timeline {
+        use crate::types::media_library::{AudioProp, FileDescriptor, VideoProp};
+        let descriptor =
+            |kind: &str| FileDescriptor::new(String::new(), String::new(), 0, kind.to_string());
+        let mut timeline = Timeline::new();
+        let video = MediaItem::VideoItem(VideoProp {
+            file_descriptor: descriptor("video"),
+            thumbnail_path: None,
+        });
+        let audio = MediaItem::AudioItem(AudioProp {
+            file_descriptor: descriptor("audio"),
+        });
+        add_track_for(&mut timeline, &video);
+        add_track_for(&mut timeline, &audio);
+        timeline
+    }
+
+    fn state(durations: &[(&str, Option<f64>)]) -> TimelineState {
+        let mut state = TimelineState::new();
+        // Known source lengths, so nothing is probed
+        for (path, duration) in durations {
+            state.source_durations.insert(path.to_string(), *duration);
+        }
+        state
+    }
+
+    /// (start_time, duration) of the clips on a track
+    fn spans(timeline: &Timeline, track_idx: usize) -> Vec<(f64, f64)> {
+        match &timeline.tracks()[track_idx] {
+            Track::Video(v) => v.clips.iter().map(|c| (c.start_time, c.duration)).collect(),
+            Track::Audio(a) => a.clips.iter().map(|c| (c.start_time, c.duration)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_multiple_files_are_placed_one_after_another() {
+        let mut timeline = timeline();
+        let mut library = MediaLibrary::new();
+        let mut state = state(&[("one.wav", Some(2.5)), ("two.wav", Some(4.0))]);
+        let paths: Vec<PathBuf> = ["one.wav", "notes.txt", "two.wav"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let placed = handle_dropped_files(
+            &paths,
+            3.0,
+            Some(1),
+            &mut library,
+            &mut timeline,
+            &mut state,
+        );
+        assert_eq!(placed.len(), 2);
+        assert_eq!(spans(&timeline, 1), vec![(3.0, 2.5), (5.5, 4.0)]);
+        // The text file is neither imported nor placed
+        assert_eq!(library.all_items().len(), 2);
+        assert_eq!(timeline.tracks().len(), 2);
+    }
+
+    #[test]
+    fn test_dropped_files_go_to_a_track_of_their_kind() {
+        let mut timeline = timeline();
+        let mut library = MediaLibrary::new();
+        let mut state = state(&[
+            ("one.wav", Some(2.0)),
+            ("lost.wav", None),
+            ("two.wav", Some(1.0)),
+        ]);
+        let paths: Vec<PathBuf> = ["one.wav", "lost.wav", "two.wav"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        // Dropped on the video track: one new audio track takes all of them
+        handle_dropped_files(
+            &paths,
+            0.0,
+            Some(0),
+            &mut library,
+            &mut timeline,
+            &mut state,
+        );
+        assert_eq!(timeline.tracks().len(), 3);
+        assert!(spans(&timeline, 0).is_empty());
+        // The file without a known length is skipped without leaving a gap
+        assert_eq!(spans(&timeline, 2), vec![(0.0, 2.0), (2.0, 1.0)]);
+        assert_eq!(library.all_items().len(), 3);
+
+        // Dropped outside the timeline: imported only
+        let placed =
+            handle_dropped_files(&paths, 0.0, None, &mut library, &mut timeline, &mut state);
+        assert!(placed.is_empty());
+        assert_eq!(library.all_items().len(), 6);
+    }
+
+    #[test]
+    fn test_dropped_files_make_room_or_move_aside() {
+        let mut timeline = timeline();
+        let mut library = MediaLibrary::new();
+        let mut state = state(&[("one.wav", Some(2.0)), ("two.wav", Some(1.0))]);
+        let mut drop = |name: &str, time: f64, state: &mut TimelineState| {
+            let paths = [PathBuf::from(name)];
+            handle_dropped_files(&paths, time, Some(1), &mut library, &mut timeline, state);
+        };
+        drop("one.wav", 0.0, &mut state);
+
+        // Over a clip without ripple: a new track takes it
+        drop("two.wav", 1.0, &mut state);
+        // With ripple: the clip already there moves right
+        state.auto_ripple = true;
+        drop("two.wav", 0.0, &mut state);
+
+        assert_eq!(spans(&timeline, 1), vec![(1.0, 2.0), (0.0, 1.0)]);
+        assert_eq!(spans(&timeline, 2), vec![(1.0, 1.0)]);
+        assert_eq!(timeline.duration, 3.0);
+    }
+}
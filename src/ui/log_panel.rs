@@ -0,0 +1,222 @@
+use eframe::egui;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Maximum number of log lines kept in memory
+pub const LOG_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogLine {
+    pub level: log::Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// Fixed-capacity ring buffer of log lines, dropping the oldest when full
+#[derive(Debug)]
+pub struct LogBuffer {
+    lines: VecDeque<LogLine>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, line: LogLine) {
+        if self.capacity == 0 {
+            return;
+        }
+        while self.lines.len() >= self.capacity {
+            self.lines.pop_front();
+        }
+        self.lines.push_back(line);
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    /// Lines at or above the given severity, oldest first
+    pub fn filtered(&self, filter: log::LevelFilter) -> impl Iterator<Item = &LogLine> {
+        self.lines.iter().filter(move |line| line.level <= filter)
+    }
+}
+
+/// `log::Log` implementation that mirrors records to stdout and buffers them for the log panel
+pub struct BufferLogger {
+    buffer: Arc<Mutex<LogBuffer>>,
+}
+
+impl log::Log for BufferLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = LogLine {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+        };
+        println!("[{}] {}: {}", line.level, line.target, line.message);
+        if let Ok(mut buffer) = self.buffer.lock() {
+            buffer.push(line);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the buffering logger as the global logger and return its buffer.
+pub fn init_logger(level: log::LevelFilter) -> Arc<Mutex<LogBuffer>> {
+    let buffer = Arc::new(Mutex::new(LogBuffer::new(LOG_CAPACITY)));
+    let logger = BufferLogger {
+        buffer: buffer.clone(),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+    }
+    buffer
+}
+
+/// Collapsible console panel showing captured log lines
+pub struct LogPanel {
+    pub buffer: Arc<Mutex<LogBuffer>>,
+    pub open: bool,
+    pub level_filter: log::LevelFilter,
+}
+
+impl LogPanel {
+    pub fn new(buffer: Arc<Mutex<LogBuffer>>) -> Self {
+        Self {
+            buffer,
+            open: false,
+            level_filter: log::LevelFilter::Info,
+        }
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if !self.open {
+            return;
+        }
+        egui::TopBottomPanel::bottom("log_panel")
+            .resizable(true)
+            .default_height(160.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.heading("Console");
+                    egui::ComboBox::from_id_salt("log_level_filter")
+                        .selected_text(self.level_filter.to_string())
+                        .show_ui(ui, |ui| {
+                            for level in [
+                                log::LevelFilter::Error,
+                                log::LevelFilter::Warn,
+                                log::LevelFilter::Info,
+                                log::LevelFilter::Debug,
+                                log::LevelFilter::Trace,
+                            ] {
+                                ui.selectable_value(
+                                    &mut self.level_filter,
+                                    level,
+                                    level.to_string(),
+                                );
+                            }
+                        });
+                    if ui.button("Clear").clicked() {
+                        if let Ok(mut buffer) = self.buffer.lock() {
+                            buffer.clear();
+                        }
+                    }
+                    if ui.button("✖").clicked() {
+                        self.open = false;
+                    }
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical()
+                    .auto_shrink([false; 2])
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        if let Ok(buffer) = self.buffer.lock() {
+                            for line in buffer.filtered(self.level_filter) {
+                                let color = match line.level {
+                                    log::Level::Error => egui::Color32::LIGHT_RED,
+                                    log::Level::Warn => egui::Color32::YELLOW,
+                                    log::Level::Info => egui::Color32::WHITE,
+                                    _ => egui::Color32::GRAY,
+                                };
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "[{}] {}: {}",
+                                        line.level, line.target, line.message
+                                    ))
+                                    .monospace()
+                                    .size(11.0)
+                                    .color(color),
+                                );
+                            }
+                        }
+                    });
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(level: log::Level, message: &str) -> LogLine {
+        LogLine {
+            level,
+            target: "cutio".to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_ring_buffer_drops_oldest() {
+        let mut buffer = LogBuffer::new(3);
+        for i in 0..5 {
+            buffer.push(line(log::Level::Info, &format!("line {}", i)));
+        }
+        assert_eq!(buffer.len(), 3);
+        let messages: Vec<_> = buffer
+            .filtered(log::LevelFilter::Trace)
+            .map(|l| l.message.as_str())
+            .collect();
+        assert_eq!(messages, vec!["line 2", "line 3", "line 4"]);
+    }
+
+    #[test]
+    fn test_level_filter() {
+        let mut buffer = LogBuffer::new(10);
+        buffer.push(line(log::Level::Error, "error"));
+        buffer.push(line(log::Level::Warn, "warn"));
+        buffer.push(line(log::Level::Info, "info"));
+        buffer.push(line(log::Level::Debug, "debug"));
+
+        let warn_and_up: Vec<_> = buffer
+            .filtered(log::LevelFilter::Warn)
+            .map(|l| l.message.as_str())
+            .collect();
+        assert_eq!(warn_and_up, vec!["error", "warn"]);
+        assert_eq!(buffer.filtered(log::LevelFilter::Trace).count(), 4);
+        assert_eq!(buffer.filtered(log::LevelFilter::Off).count(), 0);
+    }
+}
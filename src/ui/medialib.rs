@@ -1,13 +1,41 @@
 use eframe::egui;
 use image::GenericImageView;
 
+use crate::ops::utils::reveal_in_explorer;
+use crate::renderer::asset_analyzer::{
+    AnalysisData, AnalysisKind, AnalysisParams, AssetAnalyzer, LIBRARY_WAVEFORM_BUCKETS,
+};
 use crate::types::media_library::{MediaItem, MediaLibrary};
 
+/// Frames sampled across a video for the hover scrub preview on library cards
+pub const SCRUB_FRAMES: u32 = 8;
+
+/// Lazily filled caches behind the library's hover scrub previews
+#[derive(Default)]
+pub struct ScrubPreviews {
+    /// Uploaded preview frames keyed by path and source time
+    textures: std::collections::HashMap<String, egui::TextureHandle>,
+}
+
+/// Source time shown when hovering `hover_x` pixels into a card `card_width` wide:
+/// the card is split into `frames` equal slots, each showing the middle of its
+/// share of the source.
+pub fn scrub_source_time(hover_x: f32, card_width: f32, duration: f64, frames: u32) -> f64 {
+    if card_width <= 0.0 || frames == 0 || duration <= 0.0 {
+        return 0.0;
+    }
+    let slot = ((hover_x / card_width).clamp(0.0, 1.0) * frames as f32).floor() as u32;
+    let slot = slot.min(frames - 1);
+    (slot as f64 + 0.5) * duration / frames as f64
+}
+
 pub fn medialib_panel(
     ui: &mut egui::Ui,
     medialib: &mut MediaLibrary,
+    analyzer: &mut AssetAnalyzer,
+    previews: &mut ScrubPreviews,
     _on_import: impl Fn(&mut MediaLibrary),
-    on_remove: impl Fn(&mut MediaLibrary, usize),
+    mut on_remove: impl FnMut(&mut MediaLibrary, usize),
 ) {
     ui.vertical(|ui| {
         ui.heading("Media Library");
@@ -28,31 +56,41 @@ pub fn medialib_panel(
             // Compact grid card layout
             let card_width = 56.0;
             let thumb_size = egui::vec2(48.0, 27.0);
-            let items_per_row = (ui.available_width() / card_width).floor() as usize;
+            let items_per_row = ((ui.available_width() / card_width).floor() as usize).max(1);
             let items = medialib.all_items();
             let mut to_remove = Vec::new();
+            let mut reorder = None;
 
-            for row in items.chunks(items_per_row.max(1)) {
+            for (row_idx, row) in items.chunks(items_per_row).enumerate() {
                 ui.horizontal(|ui| {
                     for (i, item) in row.iter().enumerate() {
-                        let item_id = egui::Id::new(("media_drag", i));
+                        let idx = row_idx * items_per_row + i;
+                        let item_id = egui::Id::new(("media_drag", idx));
                         let drag_payload = item.clone();
-                        ui.dnd_drag_source(item_id, drag_payload, |ui| {
+                        let card = ui.dnd_drag_source(item_id, drag_payload, |ui| {
                             ui.vertical(|ui| {
-                                // Icon only (no thumbnail)
+                                // Icon for video, waveform preview for audio
                                 match item {
-                                    MediaItem::VideoItem(_) => {
-                                        ui.label("🎬");
+                                    MediaItem::VideoItem(v) => {
+                                        video_preview(
+                                            ui,
+                                            analyzer,
+                                            previews,
+                                            &v.file_descriptor.path,
+                                            thumb_size,
+                                        );
                                     }
-                                    MediaItem::AudioItem(_) => {
-                                        ui.label("🎵");
+                                    MediaItem::AudioItem(a) => {
+                                        audio_preview(
+                                            ui,
+                                            analyzer,
+                                            &a.file_descriptor.path,
+                                            thumb_size,
+                                        );
                                     }
                                 }
                                 // Filename below, small font, ellipsized
-                                let name = match item {
-                                    MediaItem::AudioItem(audio) => &audio.file_descriptor.file_name,
-                                    MediaItem::VideoItem(video) => &video.file_descriptor.file_name,
-                                };
+                                let name = &item.file_descriptor().file_name;
                                 ui.label(
                                     egui::RichText::new(name)
                                         .size(9.0)
@@ -60,15 +98,48 @@ pub fn medialib_panel(
                                 );
                                 // Compact remove button
                                 if ui.button("✖").clicked() {
-                                    let idx = items
-                                        .iter()
-                                        .position(|x| std::ptr::eq(x, item))
-                                        .unwrap_or(i);
                                     to_remove.push(idx);
                                 }
                             });
                             ui.add_space(thumb_size.y + 20.0);
                         });
+
+                        // Right-click menu on the card (click-only, so dragging still works)
+                        let path = item.file_descriptor().path.clone();
+                        ui.interact(
+                            card.response.rect,
+                            item_id.with("menu"),
+                            egui::Sense::click(),
+                        )
+                        .context_menu(|ui| {
+                            if ui.button("Reveal in File Explorer").clicked() {
+                                if let Err(e) = reveal_in_explorer(std::path::Path::new(&path)) {
+                                    log::warn!("Failed to reveal {}: {}", path, e);
+                                }
+                                ui.close_menu();
+                            }
+                        });
+
+                        // Dropping a library card onto another card reorders the library.
+                        // Drops onto the timeline are handled by its own drop zone.
+                        let card_response = card.response;
+                        if let Some(hovered) = card_response.dnd_hover_payload::<MediaItem>() {
+                            if hovered.file_descriptor().path != item.file_descriptor().path {
+                                ui.painter().rect_stroke(
+                                    card_response.rect,
+                                    2.0,
+                                    egui::Stroke::new(1.5, egui::Color32::YELLOW),
+                                    egui::StrokeKind::Outside,
+                                );
+                            }
+                        }
+                        if let Some(dropped) = card_response.dnd_release_payload::<MediaItem>() {
+                            if let Some(from) = items.iter().position(|x| {
+                                x.file_descriptor().path == dropped.file_descriptor().path
+                            }) {
+                                reorder = Some((from, idx));
+                            }
+                        }
                     }
                 });
             }
@@ -78,6 +149,137 @@ pub fn medialib_panel(
             for i in to_remove.into_iter().rev() {
                 on_remove(medialib, i);
             }
+            if let Some((from, to)) = reorder {
+                medialib.move_item(from, to);
+            }
         }
     });
 }
+
+/// Video card icon that scrubs through the video while hovered: moving the pointer
+/// across the card shows frames from the start to the end of the source. Frames are
+/// requested from the background analyzer on first hover and cached.
+fn video_preview(
+    ui: &mut egui::Ui,
+    analyzer: &mut AssetAnalyzer,
+    previews: &mut ScrubPreviews,
+    path: &str,
+    size: egui::Vec2,
+) {
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let icon = || {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "🎬",
+            egui::FontId::proportional(14.0),
+            ui.visuals().text_color(),
+        );
+    };
+    let Some(hover_x) = response.hover_pos().map(|pos| pos.x - rect.left()) else {
+        return icon();
+    };
+    // Probed in the background; the icon stays until the length is known
+    analyzer.request(AnalysisKind::Duration, path, AnalysisParams::default());
+    let Some(&AnalysisData::Duration(duration)) =
+        analyzer.get(AnalysisKind::Duration, path, AnalysisParams::default())
+    else {
+        return icon();
+    };
+    if duration <= 0.0 {
+        return icon();
+    }
+
+    let params_at =
+        |time: f64| AnalysisParams::thumbnail(time, size.x as u32 * 2, size.y as u32 * 2);
+    for slot in 0..SCRUB_FRAMES {
+        let slot_x = (slot as f32 + 0.5) * rect.width() / SCRUB_FRAMES as f32;
+        let time = scrub_source_time(slot_x, rect.width(), duration, SCRUB_FRAMES);
+        analyzer.request(AnalysisKind::Thumbnail, path, params_at(time));
+    }
+    let params = params_at(scrub_source_time(
+        hover_x,
+        rect.width(),
+        duration,
+        SCRUB_FRAMES,
+    ));
+    let key = format!("{}@{}", path, params.time_ms);
+    if !previews.textures.contains_key(&key) {
+        let Some(AnalysisData::Thumbnail {
+            width,
+            height,
+            data,
+        }) = analyzer.get(AnalysisKind::Thumbnail, path, params)
+        else {
+            return icon();
+        };
+        let image =
+            egui::ColorImage::from_rgba_unmultiplied([*width as usize, *height as usize], data);
+        let texture = ui
+            .ctx()
+            .load_texture(&key, image, egui::TextureOptions::default());
+        previews.textures.insert(key.clone(), texture);
+    }
+    if let Some(texture) = previews.textures.get(&key) {
+        painter.image(
+            texture.id(),
+            rect,
+            egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+            egui::Color32::WHITE,
+        );
+    }
+}
+
+/// Tiny waveform of an audio item, requested from the background analyzer the
+/// first time the card is drawn. Falls back to the 🎵 icon until the peaks arrive
+/// or if the file can't be analyzed.
+fn audio_preview(ui: &mut egui::Ui, analyzer: &mut AssetAnalyzer, path: &str, size: egui::Vec2) {
+    let params = AnalysisParams::waveform(LIBRARY_WAVEFORM_BUCKETS);
+    analyzer.request(AnalysisKind::Waveform, path, params);
+    let peaks = match analyzer.get(AnalysisKind::Waveform, path, params) {
+        Some(AnalysisData::Waveform(peaks)) if !peaks.is_empty() => peaks,
+        _ => {
+            ui.label("🎵");
+            return;
+        }
+    };
+
+    let (rect, _) = ui.allocate_exact_size(size, egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_gray(40));
+    let step = rect.width() / peaks.len() as f32;
+    let half_height = rect.height() / 2.0 - 1.0;
+    for (i, peak) in peaks.iter().enumerate() {
+        let x = rect.left() + (i as f32 + 0.5) * step;
+        painter.line_segment(
+            [
+                egui::pos2(x, rect.center().y - peak * half_height),
+                egui::pos2(x, rect.center().y + peak * half_height),
+            ],
+            egui::Stroke::new((step - 0.5).max(1.0), egui::Color32::LIGHT_GREEN),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrub_source_time_across_card() {
+        // 80px card, 10s video, 8 slots of 10px / 1.25s each
+        let time = |x: f32| scrub_source_time(x, 80.0, 10.0, 8);
+        assert_eq!(time(0.0), 0.625);
+        assert_eq!(time(9.9), 0.625);
+        assert_eq!(time(10.0), 1.875);
+        assert_eq!(time(45.0), 5.625);
+        assert_eq!(time(79.9), 9.375);
+        // The pointer can sit on the card's edges or just outside it
+        assert_eq!(time(80.0), 9.375);
+        assert_eq!(time(-3.0), 0.625);
+        // Nothing to scrub
+        assert_eq!(scrub_source_time(40.0, 80.0, 0.0, 8), 0.0);
+        assert_eq!(scrub_source_time(40.0, 0.0, 10.0, 8), 0.0);
+    }
+}
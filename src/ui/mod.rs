@@ -1,4 +1,7 @@
 pub mod app;
+pub mod edit_dispatch;
+pub mod file_drop;
+pub mod log_panel;
 pub mod medialib;
 pub mod timeline_widget;
 pub mod track_widget;
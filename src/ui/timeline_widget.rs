@@ -1,5 +1,8 @@
 use eframe::egui::{self, StrokeKind};
 
+use crate::renderer::asset_analyzer::{AnalysisData, AnalysisKind, AnalysisParams, AssetAnalyzer};
+use crate::types::media::Clip;
+
 // For video duration extraction
 use gstreamer as gst;
 use gstreamer_pbutils as gst_pbutils;
@@ -17,6 +20,127 @@ pub struct TimelineState {
     pub drag_state: Option<DragState>,
     /// Timeline duration cache
     pub cached_duration: f64,
+    /// Snap grid interval in seconds (falls back to 100ms when unset)
+    pub snap_interval: f64,
+    /// Snap to frame boundaries instead of `snap_interval`
+    pub snap_to_frames: bool,
+    /// Frame rate used when snapping to frames
+    pub frame_rate: f64,
+    /// Uploaded clip thumbnails keyed by path and source time
+    pub thumbnail_textures: std::collections::HashMap<String, egui::TextureHandle>,
+    /// Snap target the dragged edge is currently locked to, drawn as a guide line
+    pub snap_indicator: Option<f64>,
+    /// Probed source durations by asset path (None if probing failed), filled in by
+    /// `store_durations`
+    pub source_durations: std::collections::HashMap<String, Option<f64>>,
+    /// Units used for ruler labels
+    pub ruler_units: RulerUnits,
+    /// Beat grid copied from the timeline; grid snapping uses beats when set
+    pub beat_grid: Option<crate::types::beat_grid::BeatGrid>,
+    /// A click on empty track space moves the playhead there
+    pub click_to_seek: bool,
+    /// Playhead moves snap to nearby markers
+    pub snap_to_markers: bool,
+    /// Marker times copied from the timeline
+    pub markers: Vec<f64>,
+    /// Deletes, inserts and tail trims ripple later content by default
+    pub auto_ripple: bool,
+    /// Gap picked by clicking empty space between clips, as (track_idx, start, end)
+    pub selected_gap: Option<(usize, f64, f64)>,
+    /// Clip with keyboard focus
+    pub focused_clip: Option<String>,
+    /// Clips previewed without their effects
+    pub bypassed_effects: std::collections::HashSet<String>,
+    /// Text of the clip search box
+    pub clip_search: String,
+    /// Horizontal scroll offset to jump to, in pixels
+    pub scroll_to_x: Option<f32>,
+    /// Clip widths below which labels, thumbnails, waveforms and handles are left out
+    pub clip_display: ClipDisplayConfig,
+    /// What clicking a clip does
+    pub tool: crate::types::ui_state::ToolMode,
+    /// Dropped videos with sound are placed as grouped video and audio clips
+    pub split_av_on_drop: bool,
+    /// Draw the waveform of a video clip's sound along its bottom
+    pub show_video_audio: bool,
+}
+
+/// Clip widths, in pixels, from which each part of a clip is drawn
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipDisplayConfig {
+    pub min_label_width: f32,
+    pub min_thumbnail_width: f32,
+    pub min_waveform_width: f32,
+    pub min_handle_width: f32,
+    /// Narrowest area a clip reacts to clicks and drags in, whatever its drawn width
+    pub min_clickable_width: f32,
+}
+
+impl Default for ClipDisplayConfig {
+    fn default() -> Self {
+        Self {
+            min_label_width: 40.0,
+            min_thumbnail_width: 30.0,
+            min_waveform_width: 12.0,
+            min_handle_width: 24.0,
+            min_clickable_width: 3.0,
+        }
+    }
+}
+
+/// Parts of a clip to draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ClipElements {
+    pub label: bool,
+    pub thumbnail: bool,
+    pub waveform: bool,
+    pub handles: bool,
+}
+
+impl ClipDisplayConfig {
+    /// Parts drawn on a clip `clip_width` pixels wide; thumbnails are only drawn on
+    /// video clips and waveforms on audio clips
+    pub fn elements(&self, clip_width: f32, is_video: bool) -> ClipElements {
+        ClipElements {
+            label: clip_width >= self.min_label_width,
+            thumbnail: is_video && clip_width >= self.min_thumbnail_width,
+            waveform: !is_video && clip_width >= self.min_waveform_width,
+            handles: clip_width >= self.min_handle_width,
+        }
+    }
+
+    /// Area a clip drawn in `clip_rect` takes clicks and drags in: clips narrower than
+    /// `min_clickable_width` are widened to it around their center
+    pub fn interaction_rect(&self, clip_rect: egui::Rect) -> egui::Rect {
+        let extra = self.min_clickable_width - clip_rect.width();
+        if extra > 0.0 {
+            clip_rect.expand2(egui::vec2(extra / 2.0, 0.0))
+        } else {
+            clip_rect
+        }
+    }
+}
+
+/// A keyboard move of the clip focus
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FocusMove {
+    /// Tab: the next clip on the track
+    Next,
+    /// Shift+Tab: the previous clip on the track
+    Previous,
+    /// Up: the nearest clip on the closest non-empty track above
+    Up,
+    /// Down: the nearest clip on the closest non-empty track below
+    Down,
+}
+
+/// How the time ruler labels its ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RulerUnits {
+    #[default]
+    Seconds,
+    Timecode,
+    Frames,
 }
 
 #[derive(Debug, Clone)]
@@ -43,6 +167,7 @@ pub enum DragState {
         clip_id: String,
         track_idx: usize,
         start_pos: egui::Pos2,
+        original_start_time: f64,
         original_duration: f64,
     },
     Selection {
@@ -68,19 +193,129 @@ fn path_to_file_uri(path: &str) -> String {
     format!("file://{}", path)
 }
 
-// Helper function to extract video duration using gstreamer-pbutils
-pub fn get_video_duration(path: &str) -> Option<f64> {
+/// Length of a video or audio file in seconds, probed with the GStreamer discoverer
+pub fn get_media_duration(path: &str) -> Option<f64> {
     let _ = gst::init();
     let abs_path = std::fs::canonicalize(path).ok()?;
     let uri = path_to_file_uri(&abs_path.to_string_lossy());
-    println!("Trying to extract duration from URI: {}", uri);
+    log::debug!("Trying to extract duration from URI: {}", uri);
     let discoverer = gst_pbutils::Discoverer::new(gst::ClockTime::from_seconds(5)).ok()?;
     let info = discoverer.discover_uri(&uri).ok()?;
     let duration = info.duration();
-    println!("Duration for {}: {:?}", path, duration);
-    duration.map(|d| d.seconds() as f64)
+    log::debug!("Duration for {}: {:?}", path, duration);
+    duration.map(|d| d.nseconds() as f64 / 1e9)
+}
+
+/// Resolution, frame rate and codec of the first video stream of a file
+pub fn get_video_metadata(path: &str) -> Option<crate::types::media::VideoMetadata> {
+    use gst_pbutils::prelude::DiscovererStreamInfoExt;
+    let _ = gst::init();
+    let abs_path = std::fs::canonicalize(path).ok()?;
+    let uri = path_to_file_uri(&abs_path.to_string_lossy());
+    let discoverer = gst_pbutils::Discoverer::new(gst::ClockTime::from_seconds(5)).ok()?;
+    let info = discoverer.discover_uri(&uri).ok()?;
+    let stream = info.video_streams().into_iter().next()?;
+    let framerate = stream.framerate();
+    let caps = stream.caps();
+    let structure = caps.as_ref().and_then(|caps| caps.structure(0));
+    let codec = structure
+        .map(|s| s.name().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let color_space = structure
+        .and_then(|s| s.get::<String>("colorimetry").ok())
+        .and_then(|colorimetry| crate::types::media::ColorSpace::from_colorimetry(&colorimetry));
+    Some(crate::types::media::VideoMetadata {
+        resolution: (stream.width(), stream.height()),
+        frame_rate: if framerate.denom() > 0 {
+            framerate.numer() as f64 / framerate.denom() as f64
+        } else {
+            30.0
+        },
+        codec,
+        color_space,
+    })
+}
+
+/// Sample rate, channels and codec of the first audio stream of a file, if it has one
+pub fn get_audio_metadata(path: &str) -> Option<crate::types::media::AudioMetadata> {
+    use gst_pbutils::prelude::DiscovererStreamInfoExt;
+    let _ = gst::init();
+    let abs_path = std::fs::canonicalize(path).ok()?;
+    let uri = path_to_file_uri(&abs_path.to_string_lossy());
+    let discoverer = gst_pbutils::Discoverer::new(gst::ClockTime::from_seconds(5)).ok()?;
+    let info = discoverer.discover_uri(&uri).ok()?;
+    let stream = info.audio_streams().into_iter().next()?;
+    let codec = stream
+        .caps()
+        .as_ref()
+        .and_then(|caps| caps.structure(0))
+        .map(|s| s.name().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    Some(crate::types::media::AudioMetadata {
+        sample_rate: stream.sample_rate(),
+        channels: stream.channels(),
+        codec,
+        bitrate: stream.bitrate() / 1000,
+    })
+}
+
+/// Metadata probed from `path`, or 1080p30 placeholders when probing fails
+pub fn probe_video_metadata(path: &str) -> crate::types::media::VideoMetadata {
+    get_video_metadata(path).unwrap_or_else(|| crate::types::media::VideoMetadata {
+        resolution: (1920, 1080),
+        frame_rate: 30.0,
+        codec: "unknown".to_string(),
+        color_space: None,
+    })
 }
 
+/// Zoom limits, in pixels per second
+pub const MIN_ZOOM: f32 = 1.0;
+pub const MAX_ZOOM: f32 = 2000.0;
+
+/// Default snap grid interval (100ms)
+pub const DEFAULT_SNAP_INTERVAL: f64 = 0.1;
+/// Distance in pixels within which a dragged edge locks to a snap target
+pub const SNAP_TARGET_DISTANCE: f32 = 8.0;
+/// Distance in pixels from a clip edge within which a drag resizes the clip
+pub const RESIZE_EDGE_WIDTH: f32 = 8.0;
+/// Peaks drawn across a clip's waveform
+const WAVEFORM_BUCKETS: u32 = 128;
+
+/// Mark media that is still being analyzed in the bottom left of `rect`
+fn media_placeholder(painter: &egui::Painter, rect: egui::Rect) {
+    painter.text(
+        rect.left_bottom() + egui::vec2(4.0, -2.0),
+        egui::Align2::LEFT_BOTTOM,
+        "…",
+        egui::FontId::proportional(10.0),
+        egui::Color32::from_black_alpha(120),
+    );
+}
+
+/// Share of a video clip's height taken by the waveform strip of its sound
+pub const AUDIO_STRIP_FRACTION: f32 = 0.3;
+
+/// Split a video clip's rect into the picture area and, with `audio_strip`, the strip
+/// along its bottom where the sound's waveform goes
+pub fn video_clip_layout(
+    clip_rect: egui::Rect,
+    audio_strip: bool,
+) -> (egui::Rect, Option<egui::Rect>) {
+    if !audio_strip {
+        return (clip_rect, None);
+    }
+    let strip_top = clip_rect.bottom() - clip_rect.height() * AUDIO_STRIP_FRACTION;
+    let (picture, strip) = clip_rect.split_top_bottom_at_y(strip_top);
+    (picture, Some(strip))
+}
+
+// Layout constants
+const TRACK_HEIGHT: f32 = 60.0;
+const CLIP_HEIGHT: f32 = 40.0;
+const RULER_HEIGHT: f32 = 30.0;
+const TRACK_LABEL_WIDTH: f32 = 120.0;
+
 #[derive(Debug, Clone)]
 pub enum TimelineEvent {
     /// Playhead position changed
@@ -98,6 +333,12 @@ pub enum TimelineEvent {
         new_start_time: f64,
         new_duration: f64,
     },
+    /// Clip was deleted; ripples when `TimelineState::auto_ripple` is on
+    ClipDeleted { clip_id: String },
+    /// The selected clips were deleted, closing their gaps when `ripple` is set
+    ClipsDeleted { clip_ids: Vec<String>, ripple: bool },
+    /// The selected clips under `time` are to be split there
+    ClipsSplit { clip_ids: Vec<String>, time: f64 },
     /// Clip was selected
     ClipSelected {
         clip_id: String,
@@ -108,6 +349,16 @@ pub enum TimelineEvent {
     ClipDoubleClicked { clip_id: String, track_idx: usize },
     /// Timeline was right-clicked
     RightClicked { time: f64, track_idx: Option<usize> },
+    /// The widget changed the timeline (tracks or clips added, clips toggled)
+    Edited,
+    /// A track header was clicked to make it the target for edits of its kind
+    TrackTargeted { track_idx: usize },
+    /// Files were dropped from the OS onto a track at `time`
+    FilesDropped {
+        paths: Vec<std::path::PathBuf>,
+        time: f64,
+        track_idx: usize,
+    },
 }
 
 impl TimelineState {
@@ -118,9 +369,59 @@ impl TimelineState {
             selected_clips: std::collections::HashSet::new(),
             drag_state: None,
             cached_duration: 0.0,
+            snap_interval: DEFAULT_SNAP_INTERVAL,
+            snap_to_frames: false,
+            frame_rate: 30.0,
+            thumbnail_textures: std::collections::HashMap::new(),
+            snap_indicator: None,
+            source_durations: std::collections::HashMap::new(),
+            ruler_units: RulerUnits::Seconds,
+            beat_grid: None,
+            click_to_seek: true,
+            snap_to_markers: true,
+            markers: Vec::new(),
+            auto_ripple: false,
+            selected_gap: None,
+            focused_clip: None,
+            bypassed_effects: std::collections::HashSet::new(),
+            clip_search: String::new(),
+            scroll_to_x: None,
+            clip_display: ClipDisplayConfig::default(),
+            tool: crate::types::ui_state::ToolMode::Select,
+            split_av_on_drop: true,
+            show_video_audio: false,
         }
     }
 
+    /// Keep the source lengths among finished analyses (see `AnalysisKind::Duration`)
+    pub fn store_durations(&mut self, results: &[crate::renderer::asset_analyzer::AnalysisResult]) {
+        for result in results
+            .iter()
+            .filter(|r| r.request.kind == AnalysisKind::Duration)
+        {
+            let duration = match result.data {
+                AnalysisData::Duration(duration) => Some(duration),
+                _ => None,
+            };
+            self.source_durations
+                .insert(result.request.path.clone(), duration);
+        }
+    }
+
+    /// Probed length of the source at `path`; None when unknown or not probed yet
+    pub fn source_duration(&self, path: &str) -> Option<f64> {
+        self.source_durations.get(path).copied().flatten()
+    }
+
+    /// Zoom and scroll so `start..end` fills most of a view `view_width` pixels wide,
+    /// with a little room on either side
+    pub fn zoom_to_range(&mut self, start: f64, end: f64, view_width: f32) {
+        let span = (end - start).max(MIN_RESIZE_DURATION) as f32;
+        let margin = view_width * 0.1;
+        self.zoom = ((view_width - 2.0 * margin) / span).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.scroll_to_x = Some((start as f32 * self.zoom - margin).max(0.0));
+    }
+
     /// Convert time to screen x position
     pub fn time_to_x(&self, time: f64) -> f32 {
         let a = (time as f32 * self.zoom) - self.scroll_x;
@@ -133,15 +434,155 @@ impl TimelineState {
         a
     }
 
-    /// Snap time to grid if enabled
+    /// Effective snap interval in seconds, derived from the frame rate in frame mode
+    pub fn snap_interval(&self) -> f64 {
+        if self.snap_to_frames && self.frame_rate > 0.0 {
+            1.0 / self.frame_rate
+        } else if self.snap_interval > 0.0 {
+            self.snap_interval
+        } else {
+            DEFAULT_SNAP_INTERVAL
+        }
+    }
+
+    /// Snap time to grid (or to the nearest beat with a beat grid) if enabled
     pub fn snap_time(&self, time: f64, snap_enabled: bool) -> f64 {
-        if snap_enabled {
-            let snap_interval = 0.1; // Snap to 100ms intervals
+        if let Some(beat_grid) = self.beat_grid.filter(|_| snap_enabled) {
+            beat_grid.nearest_beat(time)
+        } else if snap_enabled {
+            let snap_interval = self.snap_interval();
             (time / snap_interval).round() * snap_interval
         } else {
             time
         }
     }
+
+    /// Snap time to the nearest target (clip edge, playhead) within
+    /// `SNAP_TARGET_DISTANCE` pixels, falling back to the grid.
+    /// Returns the new time and whether it locked to a target.
+    pub fn snap_to_targets(&self, time: f64, snap_enabled: bool, targets: &[f64]) -> (f64, bool) {
+        if !snap_enabled {
+            return (time, false);
+        }
+        let max_distance = (SNAP_TARGET_DISTANCE / self.zoom.max(f32::EPSILON)) as f64;
+        let nearest = targets
+            .iter()
+            .copied()
+            .filter(|target| (target - time).abs() <= max_distance)
+            .min_by(|a, b| (a - time).abs().total_cmp(&(b - time).abs()));
+        match nearest {
+            Some(target) => (target, true),
+            None => (self.snap_time(time, true), false),
+        }
+    }
+
+    /// Move the clip focus. With nothing focused, the first clip of the first
+    /// non-empty track gets it.
+    pub fn move_clip_focus(
+        &mut self,
+        timeline: &crate::types::timeline::Timeline,
+        step: FocusMove,
+    ) {
+        let focused = self.focused_clip.take().and_then(|clip_id| {
+            let track_idx = timeline.clip_track_index(&clip_id)?;
+            Some((track_idx, clip_id))
+        });
+        let track_count = timeline.tracks().len();
+        self.focused_clip = match (focused, step) {
+            (None, _) => (0..track_count).find_map(|track_idx| timeline.next_clip(track_idx, None)),
+            (Some((track_idx, clip_id)), FocusMove::Next) => {
+                timeline.next_clip(track_idx, Some(&clip_id))
+            }
+            (Some((track_idx, clip_id)), FocusMove::Previous) => {
+                timeline.previous_clip(track_idx, Some(&clip_id))
+            }
+            (Some((track_idx, clip_id)), FocusMove::Up | FocusMove::Down) => {
+                let time = timeline.clip_start_time(&clip_id).unwrap_or(0.0);
+                let nearest = |i: usize| timeline.clip_nearest(i, time);
+                let moved = if step == FocusMove::Up {
+                    (0..track_idx).rev().find_map(nearest)
+                } else {
+                    (track_idx + 1..track_count).find_map(nearest)
+                };
+                // At the top or bottom the focus stays put
+                moved.or(Some(clip_id))
+            }
+        };
+    }
+
+    /// Drag begun at `pointer` on the clip `clip_id` drawn in `clip_rect`: within
+    /// `RESIZE_EDGE_WIDTH` of either edge of a clip wide enough for trim handles it
+    /// resizes the clip from that edge, elsewhere it moves the clip
+    pub fn begin_clip_drag(
+        &self,
+        clip_id: &str,
+        track_idx: usize,
+        (start_time, duration): (f64, f64),
+        clip_rect: egui::Rect,
+        pointer: egui::Pos2,
+    ) -> DragState {
+        let clip_id = clip_id.to_string();
+        let resizable = self.clip_display.elements(clip_rect.width(), true).handles;
+        if resizable && pointer.x - clip_rect.left() <= RESIZE_EDGE_WIDTH {
+            DragState::ResizeLeft {
+                clip_id,
+                track_idx,
+                start_pos: pointer,
+                original_start_time: start_time,
+                original_duration: duration,
+            }
+        } else if resizable && clip_rect.right() - pointer.x <= RESIZE_EDGE_WIDTH {
+            DragState::ResizeRight {
+                clip_id,
+                track_idx,
+                start_pos: pointer,
+                original_start_time: start_time,
+                original_duration: duration,
+            }
+        } else {
+            DragState::Clip {
+                clip_id,
+                track_idx,
+                start_pos: pointer,
+                original_start_time: start_time,
+            }
+        }
+    }
+
+    /// Gap under a point given relative to the top left of the track area, as
+    /// (track_idx, start, end)
+    pub fn gap_at(
+        &self,
+        timeline: &crate::types::timeline::Timeline,
+        offset: egui::Vec2,
+    ) -> Option<(usize, f64, f64)> {
+        if offset.y < 0.0 {
+            return None;
+        }
+        let track_idx = (offset.y / TRACK_HEIGHT) as usize;
+        let (start, end) = timeline.gap_at(track_idx, self.x_to_time(offset.x))?;
+        Some((track_idx, start, end))
+    }
+
+    /// Nearest marker within `SNAP_TARGET_DISTANCE` pixels of `time`, if marker
+    /// snapping is on.
+    pub fn marker_snap(&self, time: f64, snap_enabled: bool) -> Option<f64> {
+        if !snap_enabled || !self.snap_to_markers {
+            return None;
+        }
+        let max_distance = (SNAP_TARGET_DISTANCE / self.zoom.max(f32::EPSILON)) as f64;
+        self.markers
+            .iter()
+            .copied()
+            .filter(|marker| (marker - time).abs() <= max_distance)
+            .min_by(|a, b| (a - time).abs().total_cmp(&(b - time).abs()))
+    }
+
+    /// Snap a playhead move to a nearby marker, falling back to the grid
+    pub fn snap_playhead(&self, time: f64, snap_enabled: bool) -> f64 {
+        self.marker_snap(time, snap_enabled)
+            .unwrap_or_else(|| self.snap_time(time, snap_enabled))
+    }
 }
 
 /// Timeline widget implementation
@@ -151,6 +592,10 @@ pub struct TimelineWidget<'a> {
     playhead: f64,
     snap_enabled: bool,
     show_waveforms: bool,
+    default_still_duration: f64,
+    analyzer: Option<&'a mut AssetAnalyzer>,
+    /// Ids of the tracks picked as video and audio edit targets
+    target_tracks: (Option<&'a str>, Option<&'a str>),
 }
 
 impl<'a> TimelineWidget<'a> {
@@ -165,6 +610,9 @@ impl<'a> TimelineWidget<'a> {
             playhead,
             snap_enabled: true,
             show_waveforms: false,
+            default_still_duration: crate::types::project::DEFAULT_STILL_DURATION,
+            analyzer: None,
+            target_tracks: (None, None),
         }
     }
 
@@ -178,49 +626,235 @@ impl<'a> TimelineWidget<'a> {
         self
     }
 
+    /// Length of new titles, and of dropped audio whose length can't be probed
+    pub fn default_still_duration(mut self, seconds: f64) -> Self {
+        self.default_still_duration = seconds;
+        self
+    }
+
+    /// Background analyzer used for clip waveforms and thumbnails
+    pub fn analyzer(mut self, analyzer: &'a mut AssetAnalyzer) -> Self {
+        self.analyzer = Some(analyzer);
+        self
+    }
+
+    /// Tracks picked as edit targets; see `Timeline::target_track` for the fallback
+    pub fn target_tracks(mut self, video: Option<&'a str>, audio: Option<&'a str>) -> Self {
+        self.target_tracks = (video, audio);
+        self
+    }
+
     pub fn show(&mut self, ui: &mut egui::Ui) -> Vec<TimelineEvent> {
         let mut events = Vec::new();
 
-        // Layout constants
-        const TRACK_HEIGHT: f32 = 60.0;
-        const CLIP_HEIGHT: f32 = 40.0;
-        const RULER_HEIGHT: f32 = 30.0;
-        const TRACK_LABEL_WIDTH: f32 = 120.0;
-        const RESIZE_HANDLE_WIDTH: f32 = 8.0;
+        self.state.frame_rate = self.timeline.frame_rate;
+        self.state.beat_grid = self.timeline.beat_grid;
+        self.state.markers = self.timeline.markers.iter().map(|m| m.time).collect();
+
+        // Visible width of the tracks, for framing found clips
+        let view_width = ui.available_width() - TRACK_LABEL_WIDTH;
+        let mut found_clip = None;
 
         // --- Add Track Button and Playback Controls Bar ---
         ui.horizontal(|ui| {
             if ui.button("+ Add Track").clicked() {
                 // Add a new empty video track for demonstration (customize as needed)
-                self.timeline.tracks.push(crate::types::track::Track::Video(
-                    crate::types::track::VideoTrack {
-                        id: format!("track{}", self.timeline.tracks.len() + 1),
-                        name: format!("Video Track {}", self.timeline.tracks.len() + 1),
-                        clips: vec![],
-                        muted: false,
-                    },
-                ));
+                let n = self.timeline.tracks().len() + 1;
+                self.timeline
+                    .tracks_mut()
+                    .push(crate::types::track::Track::Video(
+                        crate::types::track::VideoTrack {
+                            id: format!("track{}", n),
+                            name: format!("Video Track {}", n),
+                            clips: vec![],
+                            muted: false,
+                            sync_locked: false,
+                        },
+                    ));
+                events.push(TimelineEvent::Edited);
+            }
+            if ui.button("+ Add Title").clicked() {
+                self.timeline
+                    .text_clips
+                    .push(crate::types::media::TextClip::new(
+                        format!(
+                            "text_{}",
+                            std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap()
+                                .as_millis()
+                        ),
+                        "Title".to_string(),
+                        self.playhead,
+                        still_clip_duration(self.default_still_duration, ui.input(|i| i.modifiers)),
+                    ));
+                events.push(TimelineEvent::Edited);
             }
+            ui.toggle_value(&mut self.state.show_video_audio, "Audio on Video")
+                .on_hover_text("Show the waveform of each video clip's sound along its bottom");
+            if ui
+                .button("Close All Gaps")
+                .on_hover_text("Pull every clip left over the empty space before it")
+                .clicked()
+                && self.timeline.close_all_gaps() > 0
+            {
+                self.state.selected_gap = None;
+                events.push(TimelineEvent::Edited);
+            }
+            ui.menu_button("Find Clip", |ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.state.clip_search)
+                        .hint_text("Clip id or file name"),
+                );
+                let found = self.timeline.find_clips(&self.state.clip_search);
+                for (track_idx, clip_id, start) in found.into_iter().take(20) {
+                    let label = format!(
+                        "{}  {}  (track {})",
+                        clip_id,
+                        format_time(start),
+                        track_idx + 1
+                    );
+                    if ui.button(label).clicked() {
+                        found_clip = Some((track_idx, clip_id));
+                        ui.close_menu();
+                    }
+                }
+            });
             if ui.button("⏮").clicked() { /* jump to start logic */ }
             if ui.button("⏪").clicked() { /* step back logic */ }
             if ui.button("⏯").clicked() { /* play/pause logic */ }
             if ui.button("⏩").clicked() { /* step forward logic */ }
             ui.label(format!("Speed: {:.1}x", 1.0));
             ui.label(format!("Time: {}", format_time(self.playhead)));
+            ui.separator();
+            ui.selectable_value(
+                &mut self.state.tool,
+                crate::types::ui_state::ToolMode::Select,
+                "Select",
+            );
+            ui.selectable_value(
+                &mut self.state.tool,
+                crate::types::ui_state::ToolMode::Razor,
+                "Razor",
+            )
+            .on_hover_text("Click a clip to split it there");
+            ui.separator();
+            ui.label("Snap:");
+            ui.checkbox(&mut self.state.snap_to_frames, "Frames");
+            ui.checkbox(&mut self.state.snap_to_markers, "Markers");
+            ui.add_enabled(
+                !self.state.snap_to_frames,
+                egui::DragValue::new(&mut self.state.snap_interval)
+                    .range(0.01..=10.0)
+                    .speed(0.01)
+                    .suffix("s"),
+            );
+            ui.separator();
+            ui.checkbox(&mut self.state.click_to_seek, "Click to Seek")
+                .on_hover_text("Clicking empty track space moves the playhead");
+            ui.toggle_value(&mut self.state.auto_ripple, "Ripple")
+                .on_hover_text("Deletes, inserts and trims move later content");
+            if ui
+                .checkbox(&mut self.timeline.sync_lock_enabled, "Sync Lock")
+                .on_hover_text("Ripple edits also move other sync-locked tracks")
+                .changed()
+            {
+                events.push(TimelineEvent::Edited);
+            }
+            let mut beats = self.timeline.beat_grid.is_some();
+            if ui.checkbox(&mut beats, "Beats").changed() {
+                self.timeline.beat_grid =
+                    beats.then(|| crate::types::beat_grid::BeatGrid::new(120.0, 0.0));
+                events.push(TimelineEvent::Edited);
+            }
+            if let Some(beat_grid) = &mut self.timeline.beat_grid {
+                let bpm = ui.add(
+                    egui::DragValue::new(&mut beat_grid.bpm)
+                        .range(20.0..=300.0)
+                        .speed(0.5)
+                        .suffix(" BPM"),
+                );
+                let offset = ui.add(
+                    egui::DragValue::new(&mut beat_grid.offset)
+                        .range(0.0..=60.0)
+                        .speed(0.01)
+                        .prefix("+")
+                        .suffix("s"),
+                );
+                if bpm.changed() || offset.changed() {
+                    events.push(TimelineEvent::Edited);
+                }
+            }
+            ui.separator();
+            let srt_path = ui.button("Subtitles…").clicked().then(|| {
+                rfd::FileDialog::new()
+                    .add_filter("SubRip Subtitles", &["srt"])
+                    .pick_file()
+            });
+            if let Some(path) = srt_path.flatten() {
+                match crate::ops::srt::load_srt(&path) {
+                    Ok(cues) => {
+                        log::info!("Loaded {} cues from {}", cues.len(), path.display());
+                        self.timeline.subtitles = Some(crate::types::subtitle::SubtitleTrack::new(
+                            path.to_string_lossy().to_string(),
+                            cues,
+                        ));
+                        events.push(TimelineEvent::Edited);
+                    }
+                    Err(e) => log::error!("Failed to load subtitles {}: {}", path.display(), e),
+                }
+            }
+            if let Some(subtitles) = &mut self.timeline.subtitles {
+                let preview = ui
+                    .checkbox(&mut subtitles.show_in_preview, "Captions")
+                    .on_hover_text("Show subtitles over the preview");
+                let burn_in = ui
+                    .checkbox(&mut subtitles.burn_in, "Burn In")
+                    .on_hover_text("Draw subtitles into exported video");
+                if preview.changed() || burn_in.changed() {
+                    events.push(TimelineEvent::Edited);
+                }
+                if ui
+                    .small_button("✖")
+                    .on_hover_text("Detach subtitles")
+                    .clicked()
+                {
+                    self.timeline.subtitles = None;
+                    events.push(TimelineEvent::Edited);
+                }
+            }
         });
         ui.add_space(4.0);
 
+        // Reveal and select the clip picked in the search
+        if let Some((track_idx, clip_id)) = found_clip {
+            if let Some((start, _, duration)) = self.clip_times(&clip_id) {
+                self.state
+                    .zoom_to_range(start, start + duration, view_width);
+            }
+            events.push(TimelineEvent::ClipSelected {
+                clip_id,
+                track_idx,
+                multi_select: false,
+            });
+        }
+
         // Calculate dimensions
         let timeline_width =
             (self.timeline.duration as f32 * self.state.zoom).max(ui.available_width());
         let min_tracks = 3;
-        let timeline_height = (self.timeline.tracks.len().max(min_tracks) as f32) * TRACK_HEIGHT;
+        let timeline_height = (self.timeline.tracks().len().max(min_tracks) as f32) * TRACK_HEIGHT;
         let total_height = RULER_HEIGHT + timeline_height;
 
         // --- Scrollable Timeline Viewport with Drop Zone ---
         egui::ScrollArea::both()
             .auto_shrink([false; 2])
             .show(ui, |ui| {
+                // Jump to a clip picked in the search
+                if let Some(x) = self.state.scroll_to_x.take() {
+                    let offset = ui.clip_rect().left() - ui.min_rect().left();
+                    ui.scroll_with_delta(egui::vec2(offset - x, 0.0));
+                }
                 // Set a large inner area for scrolling
                 ui.set_min_size(egui::vec2(timeline_width + TRACK_LABEL_WIDTH, total_height));
 
@@ -272,6 +906,25 @@ impl<'a> TimelineWidget<'a> {
                                             / TRACK_HEIGHT)
                                             .floor()
                                             as usize;
+                                        // Red when the hovered track can't take the dragged
+                                        // media; the drop then goes to a new track instead
+                                        let payload = egui::DragAndDrop::payload::<
+                                            crate::types::media_library::MediaItem,
+                                        >(ui.ctx());
+                                        let fits = match (
+                                            payload,
+                                            self.timeline.tracks().get(drop_track_idx),
+                                        ) {
+                                            (Some(item), Some(track)) => {
+                                                media_fits_track(&item, track)
+                                            }
+                                            _ => true,
+                                        };
+                                        let indicator_color = if fits {
+                                            egui::Color32::YELLOW
+                                        } else {
+                                            egui::Color32::RED
+                                        };
 
                                         // Draw drop indicator line
                                         painter.line_segment(
@@ -285,11 +938,11 @@ impl<'a> TimelineWidget<'a> {
                                                     tracks_rect.bottom(),
                                                 ),
                                             ],
-                                            egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                                            egui::Stroke::new(2.0, indicator_color),
                                         );
 
                                         // Highlight the track where drop will occur
-                                        if drop_track_idx < self.timeline.tracks.len() {
+                                        if drop_track_idx < self.timeline.tracks().len() {
                                             let track_y = tracks_rect.top()
                                                 + drop_track_idx as f32 * TRACK_HEIGHT;
                                             let track_rect = egui::Rect::from_min_size(
@@ -299,7 +952,7 @@ impl<'a> TimelineWidget<'a> {
                                             painter.rect_stroke(
                                                 track_rect,
                                                 0.0,
-                                                egui::Stroke::new(2.0, egui::Color32::YELLOW),
+                                                egui::Stroke::new(2.0, indicator_color),
                                                 egui::StrokeKind::Outside,
                                             );
                                         }
@@ -308,39 +961,96 @@ impl<'a> TimelineWidget<'a> {
                             }
 
                             // --- Track List (Left) ---
-                            for (track_idx, track) in self.timeline.tracks.iter_mut().enumerate() {
+                            let targets = [
+                                self.timeline.target_track(self.target_tracks.0, true),
+                                self.timeline.target_track(self.target_tracks.1, false),
+                            ];
+                            for track_idx in 0..self.timeline.tracks().len() {
+                                let track = &self.timeline.tracks()[track_idx];
                                 let y = track_list_rect.top() + track_idx as f32 * TRACK_HEIGHT;
                                 let rect = egui::Rect::from_min_size(
                                     egui::pos2(track_list_rect.left(), y),
                                     egui::vec2(track_list_rect.width(), TRACK_HEIGHT),
                                 );
-                                painter.rect_filled(rect, 0.0, egui::Color32::DARK_GRAY);
+                                // Clicking the header (outside its buttons) targets the track
+                                let header = ui.interact(
+                                    rect,
+                                    ui.id().with(("track_header", track_idx)),
+                                    egui::Sense::click(),
+                                );
+                                if header.clicked() {
+                                    events.push(TimelineEvent::TrackTargeted { track_idx });
+                                }
+                                let fill = if targets.contains(&Some(track_idx)) {
+                                    egui::Color32::from_rgb(50, 70, 100)
+                                } else {
+                                    egui::Color32::DARK_GRAY
+                                };
+                                painter.rect_filled(rect, 0.0, fill);
 
-                                // Mute/unmute button
-                                let (track_name, is_muted) = match track {
+                                // Header settings are edited on copies, written back below
+                                let (track_name, mut muted) = match track {
                                     crate::types::track::Track::Video(video_track) => {
-                                        (&video_track.name, &mut video_track.muted)
+                                        (video_track.name.clone(), video_track.muted)
                                     }
                                     crate::types::track::Track::Audio(audio_track) => {
-                                        (&audio_track.name, &mut audio_track.muted)
+                                        (audio_track.name.clone(), audio_track.muted)
                                     }
                                 };
-                                let mute_label = if *is_muted { "🔇" } else { "🔊" };
+                                let mut sync_locked = track.is_sync_locked();
+                                let mut changed = false;
+
+                                // Mute/unmute button
+                                let mute_label = if muted { "🔇" } else { "🔊" };
                                 let button_rect = egui::Rect::from_min_size(
                                     rect.left_top() + egui::vec2(4.0, 4.0),
                                     egui::vec2(28.0, 28.0),
                                 );
                                 if ui.put(button_rect, egui::Button::new(mute_label)).clicked() {
-                                    *is_muted = !*is_muted;
+                                    muted = !muted;
+                                    changed = true;
                                 }
 
                                 painter.text(
                                     rect.center(),
                                     egui::Align2::CENTER_CENTER,
-                                    track_name,
+                                    &track_name,
                                     egui::FontId::proportional(14.0),
                                     egui::Color32::WHITE,
                                 );
+
+                                // Sync lock toggle
+                                let lock_rect = egui::Rect::from_min_size(
+                                    rect.right_top() + egui::vec2(-32.0, 4.0),
+                                    egui::vec2(28.0, 28.0),
+                                );
+                                let lock_label = if sync_locked { "🔒" } else { "🔓" };
+                                if ui
+                                    .put(lock_rect, egui::Button::new(lock_label))
+                                    .on_hover_text(
+                                        "Sync lock: follow ripple edits on other locked tracks",
+                                    )
+                                    .clicked()
+                                {
+                                    sync_locked = !sync_locked;
+                                    changed = true;
+                                }
+
+                                // Only real changes go through `tracks_mut`, which drops the
+                                // clip index
+                                if changed {
+                                    match &mut self.timeline.tracks_mut()[track_idx] {
+                                        crate::types::track::Track::Video(video_track) => {
+                                            video_track.muted = muted;
+                                            video_track.sync_locked = sync_locked;
+                                        }
+                                        crate::types::track::Track::Audio(audio_track) => {
+                                            audio_track.muted = muted;
+                                            audio_track.sync_locked = sync_locked;
+                                        }
+                                    }
+                                    events.push(TimelineEvent::Edited);
+                                }
                             }
 
                             // --- Draw time ruler ---
@@ -352,15 +1062,83 @@ impl<'a> TimelineWidget<'a> {
                             if ruler_response.clicked() || ruler_response.dragged() {
                                 if let Some(pointer_pos) = ruler_response.interact_pointer_pos() {
                                     let local_x = pointer_pos.x - ruler_rect.left();
-                                    let max_time = self.timeline.duration.max(999.0);
+                                    let max_time = self.timeline.duration;
                                     let new_time =
                                         self.state.x_to_time(local_x).max(0.0).min(max_time);
+                                    let new_time = self
+                                        .state
+                                        .marker_snap(new_time, self.snap_enabled)
+                                        .unwrap_or(new_time);
                                     events.push(TimelineEvent::PlayheadMoved(new_time));
                                 }
                             }
+                            ruler_response.context_menu(|ui| {
+                                for (units, label) in [
+                                    (RulerUnits::Seconds, "Seconds"),
+                                    (RulerUnits::Timecode, "Timecode"),
+                                    (RulerUnits::Frames, "Frames"),
+                                ] {
+                                    if ui
+                                        .radio_value(&mut self.state.ruler_units, units, label)
+                                        .clicked()
+                                    {
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+
+                            // --- Empty track space: click to seek, drag to select ---
+                            // Allocated before the clips so they take the pointer first
+                            let background_response = ui.interact(
+                                tracks_rect,
+                                ui.id().with("tracks_background"),
+                                egui::Sense::click_and_drag(),
+                            );
+                            if let Some(start_pos) = ui
+                                .input(|i| i.pointer.press_origin())
+                                .filter(|_| background_response.drag_started())
+                                .filter(|_| self.state.drag_state.is_none())
+                            {
+                                self.state.drag_state = Some(DragState::Selection {
+                                    start_pos,
+                                    current_pos: start_pos,
+                                });
+                            }
+                            // A click also picks the gap under it, if any
+                            if let Some(pos) = background_response
+                                .interact_pointer_pos()
+                                .filter(|_| background_response.clicked())
+                            {
+                                self.state.selected_gap = self
+                                    .state
+                                    .gap_at(self.timeline, pos - tracks_rect.left_top());
+                                if self.state.selected_gap.is_some() {
+                                    self.state.selected_clips.clear();
+                                }
+                            }
+                            if let Some(pos) = background_response
+                                .interact_pointer_pos()
+                                .filter(|_| background_response.clicked())
+                                .filter(|_| self.state.click_to_seek)
+                            {
+                                let time = self.state.x_to_time(pos.x - tracks_rect.left());
+                                events.push(TimelineEvent::PlayheadMoved(
+                                    self.state.snap_playhead(time, self.snap_enabled).max(0.0),
+                                ));
+                            }
 
                             // --- Draw tracks and clips ---
-                            for (track_idx, track) in self.timeline.tracks.iter().enumerate() {
+                            let mut toggled_clips: Vec<String> = Vec::new();
+                            let mut keyframe_adds: Vec<String> = Vec::new();
+                            let mut ripple_deletes: Vec<String> = Vec::new();
+                            let mut extends: Vec<(String, String)> = Vec::new();
+                            let mut audio_mute_toggles: Vec<String> = Vec::new();
+                            let mut crops: Vec<(String, Option<crate::types::media::CropRect>)> =
+                                Vec::new();
+                            let mut patches: Vec<(String, crate::types::media::ClipPropsPatch)> =
+                                Vec::new();
+                            let mut speed_changes: Vec<(String, String, f64)> = Vec::new();
+                            for (track_idx, track) in self.timeline.tracks().iter().enumerate() {
                                 let track_y = tracks_rect.top() + track_idx as f32 * TRACK_HEIGHT;
                                 let track_rect = egui::Rect::from_min_size(
                                     egui::pos2(tracks_rect.left(), track_y),
@@ -370,6 +1148,22 @@ impl<'a> TimelineWidget<'a> {
                                 let track_bg_color =
                                     ui.style().visuals.widgets.noninteractive.bg_fill;
                                 painter.rect_filled(track_rect, 0.0, track_bg_color);
+                                if let Some((_, start, end)) = self
+                                    .state
+                                    .selected_gap
+                                    .filter(|(gap_track, ..)| *gap_track == track_idx)
+                                {
+                                    let gap_rect = egui::Rect::from_x_y_ranges(
+                                        tracks_rect.left() + self.state.time_to_x(start)
+                                            ..=tracks_rect.left() + self.state.time_to_x(end),
+                                        track_rect.shrink(10.0).y_range(),
+                                    );
+                                    painter.rect_filled(
+                                        gap_rect,
+                                        2.0,
+                                        egui::Color32::from_rgba_unmultiplied(100, 160, 255, 60),
+                                    );
+                                }
                                 painter.line_segment(
                                     [track_rect.left_bottom(), track_rect.right_bottom()],
                                     egui::Stroke::new(
@@ -383,16 +1177,45 @@ impl<'a> TimelineWidget<'a> {
                                     crate::types::track::Track::Video(video_track) => video_track
                                         .clips
                                         .iter()
-                                        .map(|c| (&c.id, c.start_time, c.duration))
+                                        .map(|c| {
+                                            (
+                                                &c.id,
+                                                &c.asset_path,
+                                                c.in_point,
+                                                c.start_time,
+                                                c.duration,
+                                                c.enabled,
+                                                Some(c),
+                                            )
+                                        })
                                         .collect(),
                                     crate::types::track::Track::Audio(audio_track) => audio_track
                                         .clips
                                         .iter()
-                                        .map(|c| (&c.id, c.start_time, c.duration))
+                                        .map(|c| {
+                                            (
+                                                &c.id,
+                                                &c.asset_path,
+                                                c.in_point,
+                                                c.start_time,
+                                                c.duration,
+                                                c.enabled,
+                                                None,
+                                            )
+                                        })
                                         .collect(),
                                 };
 
-                                for (clip_id, start_time, duration) in clips {
+                                for (
+                                    clip_id,
+                                    asset_path,
+                                    in_point,
+                                    start_time,
+                                    duration,
+                                    enabled,
+                                    video_clip,
+                                ) in clips
+                                {
                                     let clip_x = self.state.time_to_x(start_time);
                                     let clip_width = duration as f32 * self.state.zoom;
 
@@ -422,8 +1245,26 @@ impl<'a> TimelineWidget<'a> {
                                     } else {
                                         base_color
                                     };
+                                    let clip_color = if enabled {
+                                        clip_color
+                                    } else {
+                                        clip_color.gamma_multiply(0.35)
+                                    };
 
                                     painter.rect_filled(clip_rect, 4.0, clip_color);
+                                    if !enabled {
+                                        Self::draw_disabled_stripes(&painter, clip_rect);
+                                    }
+                                    if let Some(video_clip) = video_clip {
+                                        for point in video_clip.opacity_envelope.points() {
+                                            let x = track_rect.left()
+                                                + self.state.time_to_x(start_time + point.time);
+                                            Self::draw_keyframe(
+                                                &painter,
+                                                egui::pos2(x, clip_rect.bottom() - 6.0),
+                                            );
+                                        }
+                                    }
 
                                     let border_color = if is_selected {
                                         egui::Color32::WHITE
@@ -436,28 +1277,107 @@ impl<'a> TimelineWidget<'a> {
                                         egui::Stroke::new(1.0, border_color),
                                         egui::StrokeKind::Inside,
                                     );
+                                    if self.state.focused_clip.as_ref() == Some(clip_id) {
+                                        painter.rect_stroke(
+                                            clip_rect.expand(2.0),
+                                            5.0,
+                                            egui::Stroke::new(
+                                                2.0,
+                                                ui.visuals().selection.stroke.color,
+                                            ),
+                                            egui::StrokeKind::Outside,
+                                        );
+                                    }
+
+                                    let elements = self
+                                        .state
+                                        .clip_display
+                                        .elements(clip_width, track.is_video());
+                                    let audio_strip =
+                                        self.state.show_video_audio && elements.thumbnail;
+                                    let draw_media = self.show_waveforms
+                                        && (elements.thumbnail || elements.waveform);
+                                    // Trims and extends read the source length; probe it ahead
+                                    let unprobed =
+                                        !self.state.source_durations.contains_key(asset_path);
+                                    if let Some(analyzer) =
+                                        self.analyzer.as_deref_mut().filter(|_| unprobed)
+                                    {
+                                        analyzer.request(
+                                            AnalysisKind::Duration,
+                                            asset_path,
+                                            AnalysisParams::default(),
+                                        );
+                                    }
+                                    if let Some(analyzer) =
+                                        self.analyzer.as_deref_mut().filter(|_| draw_media)
+                                    {
+                                            Self::draw_clip_media(
+                                                ui.ctx(),
+                                                &painter,
+                                                analyzer,
+                                                &mut self.state.thumbnail_textures,
+                                                track.is_video(),
+                                                asset_path,
+                                                video_clip.map_or(
+                                                    (in_point, in_point + duration),
+                                                    |c| c.source_span(),
+                                                ),
+                                                clip_rect,
+                                                audio_strip,
+                                            );
+                                    }
 
-                                    if clip_width > 40.0 {
+                                    if elements.handles && is_selected {
+                                        Self::draw_trim_handles(&painter, clip_rect);
+                                    }
+                                    if elements.label {
                                         painter.text(
                                             clip_rect.center(),
                                             egui::Align2::CENTER_CENTER,
                                             clip_id,
                                             egui::FontId::proportional(12.0),
-                                            egui::Color32::BLACK,
+                                            if enabled {
+                                                egui::Color32::BLACK
+                                            } else {
+                                                egui::Color32::GRAY
+                                            },
                                         );
                                     }
 
-                                    // Drag/click support
-                                    let clip_response =
-                                        ui.allocate_rect(clip_rect, egui::Sense::click_and_drag());
+                                    // Drag/click support; tiny clips stay clickable
+                                    let clip_response = ui.allocate_rect(
+                                        self.state.clip_display.interaction_rect(clip_rect),
+                                        egui::Sense::click_and_drag(),
+                                    );
 
                                     if clip_response.clicked() {
-                                        let multi_select = ui.input(|i| i.modifiers.ctrl);
-                                        events.push(TimelineEvent::ClipSelected {
-                                            clip_id: clip_id.clone(),
-                                            track_idx,
-                                            multi_select,
-                                        });
+                                        match self.state.tool {
+                                            crate::types::ui_state::ToolMode::Select => {
+                                                let multi_select =
+                                                    ui.input(|i| i.modifiers.ctrl);
+                                                events.push(TimelineEvent::ClipSelected {
+                                                    clip_id: clip_id.clone(),
+                                                    track_idx,
+                                                    multi_select,
+                                                });
+                                            }
+                                            crate::types::ui_state::ToolMode::Razor => {
+                                                if let Some(pos) =
+                                                    clip_response.interact_pointer_pos()
+                                                {
+                                                    let time = self.state.snap_time(
+                                                        self.state
+                                                            .x_to_time(pos.x - track_rect.left()),
+                                                        self.snap_enabled,
+                                                    );
+                                                    events.push(TimelineEvent::ClipsSplit {
+                                                        clip_ids: vec![clip_id.clone()],
+                                                        time,
+                                                    });
+                                                }
+                                            }
+                                        }
                                     }
                                     if clip_response.double_clicked() {
                                         events.push(TimelineEvent::ClipDoubleClicked {
@@ -465,47 +1385,414 @@ impl<'a> TimelineWidget<'a> {
                                             track_idx,
                                         });
                                     }
+                                    if clip_response.hovered()
+                                        && ui.input(|i| i.key_pressed(egui::Key::D))
+                                    {
+                                        toggled_clips.push(clip_id.clone());
+                                    }
+                                    clip_response.context_menu(|ui| {
+                                        let label = if enabled {
+                                            "Disable Clip (D)"
+                                        } else {
+                                            "Enable Clip (D)"
+                                        };
+                                        if ui.button(label).clicked() {
+                                            toggled_clips.push(clip_id.clone());
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("Delete").clicked() {
+                                            events.push(TimelineEvent::ClipDeleted {
+                                                clip_id: clip_id.clone(),
+                                            });
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("Ripple Delete").clicked() {
+                                            ripple_deletes.push(clip_id.clone());
+                                            ui.close_menu();
+                                        }
+                                        if ui.button("Extend to Next Clip").clicked() {
+                                            extends.push((track.id().to_string(), clip_id.clone()));
+                                            ui.close_menu();
+                                        }
+                                        if video_clip.is_some()
+                                            && self.playhead >= start_time
+                                            && self.playhead < start_time + duration
+                                            && ui
+                                                .button("Add Opacity Keyframe at Playhead")
+                                                .clicked()
+                                        {
+                                            keyframe_adds.push(clip_id.clone());
+                                            ui.close_menu();
+                                        }
+                                        if let Some(video_clip) = video_clip {
+                                            let label = if video_clip.mute_audio {
+                                                "Unmute Clip Audio"
+                                            } else {
+                                                "Mute Clip Audio"
+                                            };
+                                            if ui.button(label).clicked() {
+                                                audio_mute_toggles.push(clip_id.clone());
+                                                ui.close_menu();
+                                            }
+                                            let bypassed = &mut self.state.bypassed_effects;
+                                            let mut bypass = bypassed.contains(clip_id);
+                                            if !video_clip.effects.is_empty()
+                                                && ui
+                                                    .checkbox(&mut bypass, "Bypass Effects")
+                                                    .changed()
+                                                && !bypassed.remove(clip_id)
+                                            {
+                                                bypassed.insert(clip_id.clone());
+                                            }
+                                            ui.horizontal(|ui| {
+                                                ui.label("Speed");
+                                                let mut speed = video_clip.speed;
+                                                if ui
+                                                    .add(
+                                                        egui::DragValue::new(&mut speed)
+                                                            .range(0.1..=16.0)
+                                                            .speed(0.01)
+                                                            .suffix("x"),
+                                                    )
+                                                    .changed()
+                                                {
+                                                    speed_changes.push((
+                                                        track.id().to_string(),
+                                                        clip_id.clone(),
+                                                        speed,
+                                                    ));
+                                                }
+                                            });
+                                            ui.menu_button("Crop", |ui| {
+                                                let (mut l, mut t, mut r, mut b) =
+                                                    video_clip.crop.unwrap_or((0.0, 0.0, 1.0, 1.0));
+                                                let mut changed = false;
+                                                for (label, value) in [
+                                                    ("Left", &mut l),
+                                                    ("Top", &mut t),
+                                                    ("Right", &mut r),
+                                                    ("Bottom", &mut b),
+                                                ] {
+                                                    ui.horizontal(|ui| {
+                                                        ui.label(label);
+                                                        changed |= ui
+                                                            .add(
+                                                                egui::DragValue::new(value)
+                                                                    .range(0.0..=1.0)
+                                                                    .speed(0.005),
+                                                            )
+                                                            .changed();
+                                                    });
+                                                }
+                                                if changed {
+                                                    let crop = Some((l, t, r, b));
+                                                    crops.push((clip_id.clone(), crop));
+                                                }
+                                                if ui.button("Full Frame").clicked() {
+                                                    crops.push((clip_id.clone(), None));
+                                                    ui.close_menu();
+                                                }
+                                            });
+                                            if ui
+                                                .button("Apply Properties to Same Source")
+                                                .on_hover_text(
+                                                    "Copy opacity, speed and color to every clip of this file",
+                                                )
+                                                .clicked()
+                                            {
+                                                patches.push((
+                                                    clip_id.clone(),
+                                                    crate::types::media::ClipPropsPatch::from_clip(
+                                                        video_clip,
+                                                    ),
+                                                ));
+                                                ui.close_menu();
+                                            }
+                                            ui.separator();
+                                            let source_duration =
+                                                self.state.source_duration(asset_path);
+                                            Self::draw_source_coverage(
+                                                ui,
+                                                video_clip,
+                                                source_duration,
+                                            );
+                                        }
+                                    });
                                     if clip_response.drag_started() {
-                                        self.state.drag_state = Some(DragState::Clip {
-                                            clip_id: clip_id.clone(),
-                                            track_idx,
-                                            start_pos: clip_response
-                                                .interact_pointer_pos()
-                                                .unwrap_or(clip_rect.center()),
-                                            original_start_time: start_time,
-                                        });
+                                        self.state.drag_state =
+                                            Some(self.state.begin_clip_drag(
+                                                clip_id,
+                                                track_idx,
+                                                (start_time, duration),
+                                                clip_rect,
+                                                clip_response
+                                                    .interact_pointer_pos()
+                                                    .unwrap_or(clip_rect.center()),
+                                            ));
                                     }
                                 }
                             }
 
-                            // --- Draw playhead ---
-                            self.draw_playhead(&painter, ruler_rect, &mut events);
-
-                            // --- Handle drag operations ---
-                            self.handle_drag_operations(ui, timeline_rect, &mut events);
-
-                            // --- Handle selection box ---
-                            if let Some(DragState::Selection {
-                                start_pos,
-                                current_pos,
-                            }) = &self.state.drag_state
+                            for (clip_id, patch) in patches {
+                                let patched =
+                                    self.timeline.apply_properties_to_same_source(&clip_id, patch);
+                                if patched > 0 {
+                                    events.push(TimelineEvent::Edited);
+                                    log::info!(
+                                        "Applied properties of {} to {} clips",
+                                        clip_id,
+                                        patched
+                                    );
+                                }
+                            }
+                            for clip_id in ripple_deletes {
+                                if self.timeline.ripple_delete(&clip_id) {
+                                    self.state.selected_clips.remove(&clip_id);
+                                    events.push(TimelineEvent::Edited);
+                                }
+                            }
+                            for (track_id, clip_id) in extends {
+                                let Some(asset_path) =
+                                    self.timeline.clip_asset_path(&clip_id).map(str::to_string)
+                                else {
+                                    continue;
+                                };
+                                let source_duration = self.state.source_duration(&asset_path);
+                                if self.timeline.extend_to_next(
+                                    &track_id,
+                                    &clip_id,
+                                    source_duration,
+                                ) {
+                                    events.push(TimelineEvent::Edited);
+                                }
+                            }
+                            for clip_id in keyframe_adds {
+                                if self.add_opacity_keyframe(&clip_id, self.playhead) {
+                                    events.push(TimelineEvent::Edited);
+                                }
+                            }
+                            // [ and ]: jump between keyframes of the selected clips
+                            if ui.memory(|m| m.focused().is_none()) {
+                                let (prev, next) = ui.input(|i| {
+                                    (
+                                        i.key_pressed(egui::Key::OpenBracket),
+                                        i.key_pressed(egui::Key::CloseBracket),
+                                    )
+                                });
+                                let jump = if next {
+                                    self.keyframe_jump(true)
+                                } else if prev {
+                                    self.keyframe_jump(false)
+                                } else {
+                                    None
+                                };
+                                if let Some(time) = jump {
+                                    events.push(TimelineEvent::PlayheadMoved(time));
+                                }
+                            }
+                            // Delete closes the selected gap or removes the selected clips;
+                            // Shift+Delete ripples even with auto-ripple off
+                            let delete_pressed = ui.memory(|m| m.focused().is_none())
+                                && ui.input(|i| i.key_pressed(egui::Key::Delete));
+                            let force_ripple = ui.input(|i| i.modifiers.shift);
+                            if let Some((track_idx, start, end)) =
+                                self.state.selected_gap.filter(|_| delete_pressed)
                             {
-                                let selection_rect =
-                                    egui::Rect::from_two_pos(*start_pos, *current_pos);
-                                painter.rect_stroke(
-                                    selection_rect,
-                                    0.0,
-                                    egui::Stroke::new(1.0, egui::Color32::WHITE),
-                                    egui::StrokeKind::Outside,
-                                );
-                                painter.rect_filled(
-                                    selection_rect,
-                                    0.0,
-                                    egui::Color32::from_white_alpha(20),
-                                );
+                                self.state.selected_gap = None;
+                                if self.timeline.close_gap(track_idx, start, end) {
+                                    events.push(TimelineEvent::Edited);
+                                }
+                            } else if delete_pressed && !self.state.selected_clips.is_empty() {
+                                let mut clip_ids: Vec<String> =
+                                    self.state.selected_clips.iter().cloned().collect();
+                                clip_ids.sort();
+                                events.push(TimelineEvent::ClipsDeleted {
+                                    clip_ids,
+                                    ripple: self.state.auto_ripple || force_ripple,
+                                });
                             }
-
-                            // --- Handle right-click context menu ---
+                            // Tab/Shift+Tab move clip focus along the track, Up/Down across
+                            // tracks; Enter selects the focused clip
+                            if ui.memory(|m| m.focused().is_none()) {
+                                let has_focus = self.state.focused_clip.is_some();
+                                let step = ui.input_mut(|i| {
+                                    if i.consume_key(egui::Modifiers::SHIFT, egui::Key::Tab) {
+                                        Some(FocusMove::Previous)
+                                    } else if i.consume_key(egui::Modifiers::NONE, egui::Key::Tab) {
+                                        Some(FocusMove::Next)
+                                    } else if has_focus
+                                        && i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)
+                                    {
+                                        Some(FocusMove::Up)
+                                    } else if has_focus
+                                        && i.consume_key(
+                                            egui::Modifiers::NONE,
+                                            egui::Key::ArrowDown,
+                                        )
+                                    {
+                                        Some(FocusMove::Down)
+                                    } else {
+                                        None
+                                    }
+                                });
+                                if let Some(step) = step {
+                                    self.state.move_clip_focus(self.timeline, step);
+                                }
+                                if let Some((clip_id, track_idx)) = self
+                                    .state
+                                    .focused_clip
+                                    .clone()
+                                    .filter(|_| ui.input(|i| i.key_pressed(egui::Key::Enter)))
+                                    .and_then(|clip_id| {
+                                        let track_idx = self.timeline.clip_track_index(&clip_id)?;
+                                        Some((clip_id, track_idx))
+                                    })
+                                {
+                                    events.push(TimelineEvent::ClipSelected {
+                                        clip_id,
+                                        track_idx,
+                                        multi_select: false,
+                                    });
+                                }
+                            }
+                            // M adds a marker; Alt+Left/Right jump between markers, ignoring snap.
+                            // E extends the nearest edit on the focused or selected clip's track,
+                            // or on the target video track when no clip is picked.
+                            // Ctrl+K splits the selected clips at the playhead.
+                            if ui.memory(|m| m.focused().is_none()) {
+                                let (add, prev, next, extend, split) = ui.input(|i| {
+                                    (
+                                        i.key_pressed(egui::Key::M) && i.modifiers.is_none(),
+                                        i.modifiers.alt && i.key_pressed(egui::Key::ArrowLeft),
+                                        i.modifiers.alt && i.key_pressed(egui::Key::ArrowRight),
+                                        i.key_pressed(egui::Key::E) && i.modifiers.is_none(),
+                                        i.key_pressed(egui::Key::K) && i.modifiers.command_only(),
+                                    )
+                                });
+                                if split && !self.state.selected_clips.is_empty() {
+                                    let mut clip_ids: Vec<String> =
+                                        self.state.selected_clips.iter().cloned().collect();
+                                    clip_ids.sort();
+                                    events.push(TimelineEvent::ClipsSplit {
+                                        clip_ids,
+                                        time: self.playhead,
+                                    });
+                                }
+                                if add && self.timeline.add_marker(self.playhead, String::new()) {
+                                    events.push(TimelineEvent::Edited);
+                                }
+                                let extend_track = self
+                                    .state
+                                    .focused_clip
+                                    .iter()
+                                    .chain(&self.state.selected_clips)
+                                    .find_map(|id| self.timeline.clip_track_index(id))
+                                    .or_else(|| {
+                                        self.timeline.target_track(self.target_tracks.0, true)
+                                    })
+                                    .map(|track_idx| {
+                                        self.timeline.tracks()[track_idx].id().to_string()
+                                    })
+                                    .filter(|_| extend);
+                                let state = &*self.state;
+                                let extended = extend_track.is_some_and(|track_id| {
+                                    self.timeline.extend_edit(&track_id, self.playhead, |path| {
+                                        state.source_duration(path)
+                                    })
+                                });
+                                if extended {
+                                    events.push(TimelineEvent::Edited);
+                                }
+                                let jump = if next {
+                                    self.timeline.next_marker(self.playhead)
+                                } else if prev {
+                                    self.timeline.previous_marker(self.playhead)
+                                } else {
+                                    None
+                                };
+                                if let Some(time) = jump {
+                                    events.push(TimelineEvent::PlayheadMoved(time));
+                                }
+                            }
+
+                            // Speed changes ripple later content along with auto-ripple
+                            for (track_id, clip_id, speed) in speed_changes {
+                                if self.timeline.set_clip_speed(
+                                    &track_id,
+                                    &clip_id,
+                                    speed,
+                                    self.state.auto_ripple,
+                                ) {
+                                    events.push(TimelineEvent::Edited);
+                                }
+                            }
+                            for (clip_id, crop) in crops {
+                                if self.timeline.set_clip_crop(&clip_id, crop) {
+                                    events.push(TimelineEvent::Edited);
+                                }
+                            }
+                            for clip_id in audio_mute_toggles {
+                                if let Some(muted) = self.timeline.toggle_clip_audio_muted(&clip_id)
+                                {
+                                    events.push(TimelineEvent::Edited);
+                                    log::info!(
+                                        "Clip {} audio {}",
+                                        clip_id,
+                                        if muted { "muted" } else { "unmuted" }
+                                    );
+                                }
+                            }
+                            for clip_id in toggled_clips {
+                                if let Some(enabled) = self.timeline.toggle_clip_enabled(&clip_id) {
+                                    events.push(TimelineEvent::Edited);
+                                    log::info!(
+                                        "Clip {} {}",
+                                        clip_id,
+                                        if enabled { "enabled" } else { "disabled" }
+                                    );
+                                }
+                            }
+
+                            // --- Draw playhead ---
+                            self.draw_playhead(&painter, ruler_rect, &mut events);
+
+                            // --- Handle drag operations ---
+                            self.handle_drag_operations(ui, timeline_rect, &mut events);
+
+                            // --- Draw snap guide ---
+                            if let Some(snap_time) = self.state.snap_indicator {
+                                let x = tracks_rect.left() + self.state.time_to_x(snap_time);
+                                painter.line_segment(
+                                    [
+                                        egui::pos2(x, ruler_rect.top()),
+                                        egui::pos2(x, tracks_rect.bottom()),
+                                    ],
+                                    egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 0, 255)),
+                                );
+                            }
+
+                            // --- Handle selection box ---
+                            if let Some(DragState::Selection {
+                                start_pos,
+                                current_pos,
+                            }) = &self.state.drag_state
+                            {
+                                let selection_rect =
+                                    egui::Rect::from_two_pos(*start_pos, *current_pos);
+                                painter.rect_stroke(
+                                    selection_rect,
+                                    0.0,
+                                    egui::Stroke::new(1.0, egui::Color32::WHITE),
+                                    egui::StrokeKind::Outside,
+                                );
+                                painter.rect_filled(
+                                    selection_rect,
+                                    0.0,
+                                    egui::Color32::from_white_alpha(20),
+                                );
+                            }
+
+                            // --- Handle right-click context menu ---
                             if ui.ctx().input(|i| i.pointer.secondary_down()) {
                                 if let Some(click_pos) =
                                     ui.ctx().input(|i| i.pointer.press_origin())
@@ -521,10 +1808,10 @@ impl<'a> TimelineWidget<'a> {
                                             ((click_pos.y - timeline_rect.top() - RULER_HEIGHT)
                                                 / TRACK_HEIGHT)
                                                 as usize;
-                                        let clamped_idx = if self.timeline.tracks.is_empty() {
+                                        let clamped_idx = if self.timeline.tracks().is_empty() {
                                             0
                                         } else {
-                                            idx.min(self.timeline.tracks.len().saturating_sub(1))
+                                            idx.min(self.timeline.tracks().len().saturating_sub(1))
                                         };
                                         Some(clamped_idx)
                                     } else {
@@ -557,8 +1844,12 @@ impl<'a> TimelineWidget<'a> {
                                 self.state.x_to_time(pos.x - tracks_rect.left()).max(0.0);
                             let drop_track_idx =
                                 ((pos.y - tracks_rect.top()) / TRACK_HEIGHT).floor() as usize;
+                            let still_duration = still_clip_duration(
+                                self.default_still_duration,
+                                ui.input(|i| i.modifiers),
+                            );
 
-                            println!(
+                            log::info!(
                                 "Media dropped: {:?} at time: {:.2}, track: {}",
                                 match &media {
                                     crate::types::media_library::MediaItem::VideoItem(v) =>
@@ -570,14 +1861,76 @@ impl<'a> TimelineWidget<'a> {
                                 drop_track_idx
                             );
 
+                            // Alt-drop onto a clip of the same kind swaps its source
+                            let replace_target = ui
+                                .input(|i| i.modifiers.alt)
+                                .then(|| self.timeline.tracks().get(drop_track_idx))
+                                .flatten()
+                                .filter(|track| {
+                                    track.is_video()
+                                        == matches!(
+                                            media,
+                                            crate::types::media_library::MediaItem::VideoItem(_)
+                                        )
+                                })
+                                .and_then(|track| {
+                                    let clip_id = match track {
+                                        crate::types::track::Track::Video(v) => v
+                                            .clips
+                                            .iter()
+                                            .find(|c| c.is_active_at(drop_time))
+                                            .map(|c| c.id.clone()),
+                                        crate::types::track::Track::Audio(a) => a
+                                            .clips
+                                            .iter()
+                                            .find(|c| c.is_active_at(drop_time))
+                                            .map(|c| c.id.clone()),
+                                    }?;
+                                    Some((track.id().to_string(), clip_id))
+                                });
+                            let replaced = replace_target.is_some_and(|(track_id, clip_id)| {
+                                let new_path = media.file_descriptor().path.clone();
+                                let source_duration = *self
+                                    .state
+                                    .source_durations
+                                    .entry(new_path.clone())
+                                    .or_insert_with(|| get_media_duration(&new_path));
+                                let metadata = match &media {
+                                    crate::types::media_library::MediaItem::VideoItem(_) => {
+                                        get_video_metadata(&new_path)
+                                    }
+                                    crate::types::media_library::MediaItem::AudioItem(_) => None,
+                                };
+                                let replaced = self.timeline.replace_clip_source(
+                                    &track_id,
+                                    &clip_id,
+                                    &new_path,
+                                    source_duration,
+                                    metadata,
+                                );
+                                if replaced {
+                                    log::info!(
+                                        "Replaced source of clip {} with {}",
+                                        clip_id,
+                                        new_path
+                                    );
+                                } else {
+                                    log::warn!("Could not replace source of clip {}", clip_id);
+                                }
+                                replaced
+                            });
+
+                            // Clip added to an existing track, rippled in with auto-ripple on
+                            let mut inserted: Option<String> = None;
                             match media {
+                                _ if replaced => {}
                                 crate::types::media_library::MediaItem::VideoItem(video) => {
                                     // Try to add to an existing video track at drop_track_idx
                                     let mut added = false;
 
-                                    if drop_track_idx < self.timeline.tracks.len() {
+                                    if drop_track_idx < self.timeline.tracks().len() {
                                         if let Some(track) =
-                                            self.timeline.tracks.get_mut(drop_track_idx)
+                                            self.timeline.tracks_mut().get_mut(drop_track_idx)
                                         {
                                             if let crate::types::track::Track::Video(video_track) =
                                                 track
@@ -592,25 +1945,33 @@ impl<'a> TimelineWidget<'a> {
                                                 // Use real video duration if possible
                                                 let asset_path = video.file_descriptor.path.clone();
                                                 let duration =
-                                                    get_video_duration(&asset_path).unwrap();
+                                                    get_media_duration(&asset_path).unwrap();
                                                 video_track.clips.push(
                                                     crate::types::media::VideoClip {
-                                                        id: clip_id,
+                                                        id: clip_id.clone(),
                                                         asset_path,
                                                         in_point: 0.0,
                                                         out_point: duration,
                                                         start_time: drop_time,
                                                         duration,
-                                                        metadata:
-                                                            crate::types::media::VideoMetadata {
-                                                                resolution: (1920, 1080),
-                                                                frame_rate: 30.0,
-                                                                codec: "unknown".to_string(),
-                                                            },
+                                                        enabled: true,
+                                                        effects: Vec::new(),
+                                                        opacity_envelope: crate::types::envelope::Envelope::new(),
+                                                        freeze: false,
+                                                        speed: 1.0,
+                                                        time_map: None,
+                                                        reversed: false,
+                                                        mute_audio: false,
+                                                        crop: None,
+                                                        group_id: None,
+                                                        metadata: probe_video_metadata(
+                                                            &video.file_descriptor.path,
+                                                        ),
                                                     },
                                                 );
                                                 added = true;
-                                                println!(
+                                                inserted = Some(clip_id);
+                                                log::info!(
                                                     "Added video clip to existing track {}",
                                                     drop_track_idx
                                                 );
@@ -621,15 +1982,16 @@ impl<'a> TimelineWidget<'a> {
                                     // If not added to existing track, create new video track
                                     if !added {
                                         let track_id =
-                                            format!("track_{}", self.timeline.tracks.len() + 1);
+                                            format!("track_{}", self.timeline.tracks().len() + 1);
                                         let mut video_track = crate::types::track::VideoTrack {
                                             id: track_id.clone(),
                                             name: format!(
                                                 "Video Track {}",
-                                                self.timeline.tracks.len() + 1
+                                                self.timeline.tracks().len() + 1
                                             ),
                                             clips: vec![],
                                             muted: false,
+                                            sync_locked: false,
                                         };
 
                                         let clip_id = format!(
@@ -641,9 +2003,9 @@ impl<'a> TimelineWidget<'a> {
                                         );
                                         // Use real video duration if possible
                                         let asset_path = video.file_descriptor.path.clone();
-                                        match get_video_duration(&asset_path) {
+                                        match get_media_duration(&asset_path) {
                                             Some(duration) if duration > 0.0 => {
-                                                println!("Created VideoClip with duration: {}", duration);
+                                                log::info!("Created VideoClip with duration: {}", duration);
                                                 video_track.clips.push(crate::types::media::VideoClip {
                                                     id: clip_id,
                                                     asset_path,
@@ -651,31 +2013,37 @@ impl<'a> TimelineWidget<'a> {
                                                     out_point: duration,
                                                     start_time: drop_time,
                                                     duration,
-                                                    metadata: crate::types::media::VideoMetadata {
-                                                        resolution: (1920, 1080),
-                                                        frame_rate: 30.0,
-                                                        codec: "unknown".to_string(),
-                                                    },
+                                                    enabled: true,
+                                                    effects: Vec::new(),
+                                                    opacity_envelope: crate::types::envelope::Envelope::new(),
+                                                    freeze: false,
+                                                    speed: 1.0,
+                                                    time_map: None,
+                                                    reversed: false,
+                                                    mute_audio: false,
+                                                    crop: None,
+                                                    group_id: None,
+                                                    metadata: probe_video_metadata(&video.file_descriptor.path),
                                                 });
                                             }
                                             _ => {
-                                                println!("Warning: Could not extract duration for {}, not adding clip.", asset_path);
+                                                log::warn!("Could not extract duration for {}, not adding clip.", asset_path);
                                             }
                                         }
 
                                         self.timeline
-                                            .tracks
+                                            .tracks_mut()
                                             .push(crate::types::track::Track::Video(video_track));
-                                        println!("Created new video track with clip");
+                                        log::info!("Created new video track with clip");
                                     }
                                 }
                                 crate::types::media_library::MediaItem::AudioItem(audio) => {
                                     // Try to add to an existing audio track at drop_track_idx
                                     let mut added = false;
 
-                                    if drop_track_idx < self.timeline.tracks.len() {
+                                    if drop_track_idx < self.timeline.tracks().len() {
                                         if let Some(track) =
-                                            self.timeline.tracks.get_mut(drop_track_idx)
+                                            self.timeline.tracks_mut().get_mut(drop_track_idx)
                                         {
                                             if let crate::types::track::Track::Audio(audio_track) =
                                                 track
@@ -687,17 +2055,25 @@ impl<'a> TimelineWidget<'a> {
                                                         .unwrap()
                                                         .as_millis()
                                                 );
+                                                let duration = audio_clip_duration(
+                                                    self.state,
+                                                    &audio.file_descriptor.path,
+                                                    still_duration,
+                                                );
                                                 audio_track.clips.push(
                                                     crate::types::media::AudioClip {
-                                                        id: clip_id,
+                                                        id: clip_id.clone(),
                                                         asset_path: audio
                                                             .file_descriptor
                                                             .path
                                                             .clone(),
                                                         in_point: 0.0,
-                                                        out_point: 5.0,
+                                                        out_point: duration,
                                                         start_time: drop_time,
-                                                        duration: 5.0,
+                                                        duration,
+                                                        enabled: true,
+                                                        gain_db: 0.0,
+                                                        group_id: None,
                                                         metadata:
                                                             crate::types::media::AudioMetadata {
                                                                 sample_rate: 44100,
@@ -708,7 +2084,8 @@ impl<'a> TimelineWidget<'a> {
                                                     },
                                                 );
                                                 added = true;
-                                                println!(
+                                                inserted = Some(clip_id);
+                                                log::info!(
                                                     "Added audio clip to existing track {}",
                                                     drop_track_idx
                                                 );
@@ -719,15 +2096,18 @@ impl<'a> TimelineWidget<'a> {
                                     // If not added to existing track, create new audio track
                                     if !added {
                                         let track_id =
-                                            format!("track_{}", self.timeline.tracks.len() + 1);
+                                            format!("track_{}", self.timeline.tracks().len() + 1);
                                         let mut audio_track = crate::types::track::AudioTrack {
                                             id: track_id.clone(),
                                             name: format!(
                                                 "Audio Track {}",
-                                                self.timeline.tracks.len() + 1
+                                                self.timeline.tracks().len() + 1
                                             ),
                                             clips: vec![],
                                             muted: false,
+                                            sync_locked: false,
+                                            volume_db: 0.0,
+                                            pan: 0.0,
                                         };
 
                                         let clip_id = format!(
@@ -737,13 +2117,21 @@ impl<'a> TimelineWidget<'a> {
                                                 .unwrap()
                                                 .as_millis()
                                         );
+                                        let duration = audio_clip_duration(
+                                            self.state,
+                                            &audio.file_descriptor.path,
+                                            still_duration,
+                                        );
                                         audio_track.clips.push(crate::types::media::AudioClip {
                                             id: clip_id,
                                             asset_path: audio.file_descriptor.path.clone(),
                                             in_point: 0.0,
-                                            out_point: 5.0,
+                                            out_point: duration,
                                             start_time: drop_time,
-                                            duration: 5.0,
+                                            duration,
+                                            enabled: true,
+                                            gain_db: 0.0,
+                                            group_id: None,
                                             metadata: crate::types::media::AudioMetadata {
                                                 sample_rate: 44100,
                                                 channels: 2,
@@ -753,17 +2141,53 @@ impl<'a> TimelineWidget<'a> {
                                         });
 
                                         self.timeline
-                                            .tracks
+                                            .tracks_mut()
                                             .push(crate::types::track::Track::Audio(audio_track));
-                                        println!("Created new audio track with clip");
+                                        log::info!("Created new audio track with clip");
                                     }
                                 }
                             }
+                            self.timeline.invalidate_index();
+                            if let Some(clip_id) = inserted.filter(|_| self.state.auto_ripple) {
+                                self.timeline.ripple_insert(&clip_id);
+                            }
+                            events.push(TimelineEvent::Edited);
                         }
                     }
                 }
+
+                // Files dropped from the OS onto the tracks; the app imports and places them
+                let dropped_files: Vec<std::path::PathBuf> = ui.ctx().input(|i| {
+                    i.raw
+                        .dropped_files
+                        .iter()
+                        .filter_map(|file| file.path.clone())
+                        .collect()
+                });
+                let drop_pos = ui
+                    .ctx()
+                    .input(|i| i.pointer.latest_pos())
+                    .filter(|_| !dropped_files.is_empty());
+                if let Some(pos) = drop_pos {
+                    let tracks_rect = egui::Rect::from_min_size(
+                        ui.min_rect().min + egui::vec2(TRACK_LABEL_WIDTH, RULER_HEIGHT),
+                        egui::vec2(timeline_width, total_height - RULER_HEIGHT),
+                    );
+                    if tracks_rect.contains(pos) {
+                        events.push(TimelineEvent::FilesDropped {
+                            paths: dropped_files,
+                            time: self.state.x_to_time(pos.x - tracks_rect.left()).max(0.0),
+                            track_idx: ((pos.y - tracks_rect.top()) / TRACK_HEIGHT).floor()
+                                as usize,
+                        });
+                    }
+                }
             }); // close .show(ui, |ui| { ... })
 
+        // Edits made here are done by now; moves and trims are applied by the caller
+        if events.iter().any(|e| matches!(e, TimelineEvent::Edited)) {
+            self.timeline.recompute_duration();
+        }
         events
     }
 
@@ -776,63 +2200,303 @@ impl<'a> TimelineWidget<'a> {
         // Draw ruler background
         painter.rect_filled(ruler_rect, 0.0, egui::Color32::from_gray(40));
 
-        // Calculate tick intervals based on zoom
-        let pixels_per_second = self.state.zoom;
-        let (major_interval, minor_interval) = if pixels_per_second > 200.0 {
-            (1.0, 0.1) // 1 second major, 0.1 second minor
-        } else if pixels_per_second > 50.0 {
-            (5.0, 1.0) // 5 second major, 1 second minor
+        let units = self.state.ruler_units;
+        let frame_rate = if self.state.frame_rate > 0.0 {
+            self.state.frame_rate
         } else {
-            (10.0, 5.0) // 10 second major, 5 second minor
+            30.0
         };
-
-        // Draw time ticks
-        let start_time = self.state.x_to_time(0.0);
+        let start_time = self.state.x_to_time(0.0).max(0.0);
         let end_time = self.state.x_to_time(timeline_rect.width());
 
+        // Space major ticks so the widest visible label fits between them
+        let font = egui::FontId::proportional(11.0);
+        let sample = format_ruler_label(end_time, units, frame_rate, 1.0 / frame_rate);
+        let label_width = painter
+            .layout_no_wrap(sample, font.clone(), egui::Color32::WHITE)
+            .size()
+            .x
+            + 8.0;
+        let (major_interval, minor_interval) =
+            ruler_intervals(self.state.zoom, label_width, units, frame_rate);
+
+        let tick_x = |time: f64| timeline_rect.left() + self.state.time_to_x(time);
+        let visible = |x: f32| x >= timeline_rect.left() && x <= timeline_rect.right();
+
         // Minor ticks
-        let minor_start = (start_time / minor_interval).floor() * minor_interval;
-        let mut time = minor_start;
-        while time <= end_time {
-            let x = self.state.time_to_x(time);
-            if x >= 0.0 && x <= timeline_rect.width() {
+        let mut i = (start_time / minor_interval).floor() as i64;
+        while i as f64 * minor_interval <= end_time {
+            let x = tick_x(i as f64 * minor_interval);
+            if visible(x) {
                 painter.line_segment(
                     [
-                        egui::pos2(timeline_rect.left() + x, ruler_rect.bottom() - 5.0),
-                        egui::pos2(timeline_rect.left() + x, ruler_rect.bottom()),
+                        egui::pos2(x, ruler_rect.bottom() - 5.0),
+                        egui::pos2(x, ruler_rect.bottom()),
                     ],
                     egui::Stroke::new(1.0, egui::Color32::from_gray(120)),
                 );
             }
-            time += minor_interval;
+            i += 1;
         }
 
         // Major ticks with labels
-        let major_start = (start_time / major_interval).floor() * major_interval;
-        let mut time = major_start;
-        while time <= end_time {
-            let x = self.state.time_to_x(time);
-            if x >= 0.0 && x <= timeline_rect.width() {
-                // Draw major tick
+        let mut i = (start_time / major_interval).floor() as i64;
+        while i as f64 * major_interval <= end_time {
+            let time = i as f64 * major_interval;
+            let x = tick_x(time);
+            if visible(x) {
                 painter.line_segment(
                     [
-                        egui::pos2(timeline_rect.left() + x, ruler_rect.bottom() - 15.0),
-                        egui::pos2(timeline_rect.left() + x, ruler_rect.bottom()),
+                        egui::pos2(x, ruler_rect.bottom() - 15.0),
+                        egui::pos2(x, ruler_rect.bottom()),
                     ],
                     egui::Stroke::new(2.0, egui::Color32::WHITE),
                 );
-
-                // Draw time label
-                let time_str = format!("{:.1}s", time);
                 painter.text(
-                    egui::pos2(timeline_rect.left() + x + 2.0, ruler_rect.center().y),
+                    egui::pos2(x + 2.0, ruler_rect.center().y),
                     egui::Align2::LEFT_CENTER,
-                    time_str,
-                    egui::FontId::proportional(11.0),
+                    format_ruler_label(time, units, frame_rate, major_interval),
+                    font.clone(),
                     egui::Color32::WHITE,
                 );
             }
-            time += major_interval;
+            i += 1;
+        }
+
+        // Beat lines, unless they would be too dense to tell apart
+        let zoom = self.state.zoom as f64;
+        if let Some(beat_grid) = self
+            .state
+            .beat_grid
+            .filter(|grid| grid.beat_duration() * zoom >= 4.0)
+        {
+            for beat in beat_grid.beats_in(start_time, end_time) {
+                let x = tick_x(beat);
+                if visible(x) {
+                    painter.line_segment(
+                        [
+                            egui::pos2(x, ruler_rect.top()),
+                            egui::pos2(x, ruler_rect.top() + 8.0),
+                        ],
+                        egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 170, 60)),
+                    );
+                }
+            }
+        }
+
+        // Markers, as small downward triangles along the ruler's bottom edge
+        let marker_color = egui::Color32::from_rgb(90, 200, 120);
+        for &marker in &self.state.markers {
+            let x = tick_x(marker);
+            if visible(x) {
+                let bottom = ruler_rect.bottom();
+                painter.add(egui::Shape::convex_polygon(
+                    vec![
+                        egui::pos2(x - 4.0, bottom - 8.0),
+                        egui::pos2(x + 4.0, bottom - 8.0),
+                        egui::pos2(x, bottom),
+                    ],
+                    marker_color,
+                    egui::Stroke::NONE,
+                ));
+            }
+        }
+    }
+
+    /// Readout of the source range a clip uses, with a filled coverage bar.
+    fn draw_source_coverage(
+        ui: &mut egui::Ui,
+        clip: &crate::types::media::VideoClip,
+        source_duration: Option<f64>,
+    ) {
+        let Some(source_duration) = source_duration else {
+            ui.label(format!(
+                "Source: {:.2}s – {:.2}s (duration unknown)",
+                clip.in_point, clip.out_point
+            ));
+            return;
+        };
+        ui.label(format!(
+            "Source: {:.2}s – {:.2}s of {:.2}s",
+            clip.in_point, clip.out_point, source_duration
+        ));
+
+        let (start, end) = clip.source_coverage(source_duration);
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(160.0, 8.0), egui::Sense::hover());
+        let painter = ui.painter();
+        painter.rect_filled(rect, 2.0, egui::Color32::from_gray(50));
+        let used = egui::Rect::from_x_y_ranges(
+            (rect.left() + rect.width() * start as f32)..=(rect.left() + rect.width() * end as f32),
+            rect.y_range(),
+        );
+        painter.rect_filled(used, 2.0, egui::Color32::from_rgb(100, 150, 255));
+
+        if clip.exceeds_source(source_duration) {
+            ui.colored_label(
+                egui::Color32::from_rgb(255, 170, 60),
+                "Out point is past the end of the source",
+            );
+        }
+    }
+
+    /// Small diamond marking a keyframe.
+    fn draw_keyframe(painter: &egui::Painter, center: egui::Pos2) {
+        let r = 4.0;
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                center + egui::vec2(0.0, -r),
+                center + egui::vec2(r, 0.0),
+                center + egui::vec2(0.0, r),
+                center + egui::vec2(-r, 0.0),
+            ],
+            egui::Color32::from_rgb(255, 220, 80),
+            egui::Stroke::new(1.0, egui::Color32::BLACK),
+        ));
+    }
+
+    /// Hatch a disabled clip with diagonal stripes.
+    fn draw_disabled_stripes(painter: &egui::Painter, clip_rect: egui::Rect) {
+        const STRIPE_SPACING: f32 = 8.0;
+        let painter = painter.with_clip_rect(clip_rect);
+        let stroke = egui::Stroke::new(1.0, egui::Color32::from_black_alpha(90));
+        let height = clip_rect.height();
+        let mut x = clip_rect.left() - height;
+        while x < clip_rect.right() {
+            painter.line_segment(
+                [
+                    egui::pos2(x, clip_rect.bottom()),
+                    egui::pos2(x + height, clip_rect.top()),
+                ],
+                stroke,
+            );
+            x += STRIPE_SPACING;
+        }
+    }
+
+    /// Grips on both edges of a selected clip
+    fn draw_trim_handles(painter: &egui::Painter, clip_rect: egui::Rect) {
+        const HANDLE_WIDTH: f32 = 4.0;
+        let height = clip_rect.height() - 8.0;
+        for x in [
+            clip_rect.left() + 2.0 + HANDLE_WIDTH / 2.0,
+            clip_rect.right() - 2.0 - HANDLE_WIDTH / 2.0,
+        ] {
+            painter.rect_filled(
+                egui::Rect::from_center_size(
+                    egui::pos2(x, clip_rect.center().y),
+                    egui::vec2(HANDLE_WIDTH, height),
+                ),
+                1.0,
+                egui::Color32::from_black_alpha(110),
+            );
+        }
+    }
+
+    /// Draw a clip's thumbnail (video) or waveform (audio) from the background analyzer,
+    /// submitting a request and drawing a placeholder until the result arrives.
+    /// `source_span` holds the source times at the clip's start and end
+    /// (see `VideoClip::source_span`). With `audio_strip`, video clips also get the
+    /// waveform of their sound along the bottom (see `video_clip_layout`).
+    #[allow(clippy::too_many_arguments)]
+    fn draw_clip_media(
+        ctx: &egui::Context,
+        painter: &egui::Painter,
+        analyzer: &mut AssetAnalyzer,
+        textures: &mut std::collections::HashMap<String, egui::TextureHandle>,
+        is_video: bool,
+        asset_path: &str,
+        source_span: (f64, f64),
+        clip_rect: egui::Rect,
+        audio_strip: bool,
+    ) {
+        const THUMB_WIDTH: u32 = 96;
+        const THUMB_HEIGHT: u32 = 54;
+
+        if is_video {
+            let (clip_rect, strip) = video_clip_layout(clip_rect, audio_strip);
+            if let Some(strip) = strip {
+                Self::draw_waveform(painter, analyzer, asset_path, source_span, strip);
+            }
+            // The first slice stays inside the source when a reversed clip starts on its out point
+            let time = media_sample_times(source_span, WAVEFORM_BUCKETS as usize)[0];
+            let params = AnalysisParams::thumbnail(time, THUMB_WIDTH, THUMB_HEIGHT);
+            analyzer.request(AnalysisKind::Thumbnail, asset_path, params);
+            let key = format!("{}@{}", asset_path, params.time_ms);
+            if !textures.contains_key(&key) {
+                match analyzer.get(AnalysisKind::Thumbnail, asset_path, params) {
+                    Some(AnalysisData::Thumbnail {
+                        width,
+                        height,
+                        data,
+                    }) => {
+                        let image = egui::ColorImage::from_rgba_unmultiplied(
+                            [*width as usize, *height as usize],
+                            data,
+                        );
+                        textures.insert(
+                            key.clone(),
+                            ctx.load_texture(&key, image, egui::TextureOptions::default()),
+                        );
+                    }
+                    Some(_) => return,
+                    None => return media_placeholder(painter, clip_rect),
+                }
+            }
+            if let Some(texture) = textures.get(&key) {
+                let thumb_height = clip_rect.height() - 4.0;
+                let thumb_width = (thumb_height * THUMB_WIDTH as f32 / THUMB_HEIGHT as f32)
+                    .min(clip_rect.width() - 4.0);
+                if thumb_width > 0.0 {
+                    let thumb_rect = egui::Rect::from_min_size(
+                        clip_rect.left_top() + egui::vec2(2.0, 2.0),
+                        egui::vec2(thumb_width, thumb_height),
+                    );
+                    painter.image(
+                        texture.id(),
+                        thumb_rect,
+                        egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0)),
+                        egui::Color32::WHITE,
+                    );
+                }
+            }
+        } else {
+            Self::draw_waveform(painter, analyzer, asset_path, source_span, clip_rect);
+        }
+    }
+
+    /// Draw the waveform of `source_span` of `asset_path` across `rect`, or a placeholder
+    /// until the analyzer has it
+    fn draw_waveform(
+        painter: &egui::Painter,
+        analyzer: &mut AssetAnalyzer,
+        asset_path: &str,
+        source_span: (f64, f64),
+        rect: egui::Rect,
+    ) {
+        let (start, end) = source_span;
+        let params =
+            AnalysisParams::waveform_range(start.min(end), (end - start).abs(), WAVEFORM_BUCKETS);
+        analyzer.request(AnalysisKind::Waveform, asset_path, params);
+        match analyzer.get(AnalysisKind::Waveform, asset_path, params) {
+            Some(AnalysisData::Waveform(peaks)) if !peaks.is_empty() => {
+                let step = rect.width() / peaks.len() as f32;
+                let center_y = rect.center().y;
+                let half_height = rect.height() / 2.0 - 2.0;
+                for (i, peak) in peaks.iter().enumerate() {
+                    // Peaks come in source order; reversed clips draw them right to left
+                    let slot = if start > end { peaks.len() - 1 - i } else { i };
+                    let x = rect.left() + (slot as f32 + 0.5) * step;
+                    painter.line_segment(
+                        [
+                            egui::pos2(x, center_y - peak * half_height),
+                            egui::pos2(x, center_y + peak * half_height),
+                        ],
+                        egui::Stroke::new(step.max(1.0), egui::Color32::from_black_alpha(90)),
+                    );
+                }
+            }
+            Some(_) => {}
+            None => media_placeholder(painter, rect),
         }
     }
 
@@ -866,6 +2530,254 @@ impl<'a> TimelineWidget<'a> {
         }
     }
 
+    /// Times a dragged edge can lock to: the start and end of the timeline, every clip edge
+    /// except the dragged clip's, and the playhead
+    fn snap_targets(&self, exclude_clip: &str) -> Vec<f64> {
+        let mut targets = vec![0.0, self.playhead];
+        if self.timeline.duration > 0.0 {
+            targets.push(self.timeline.duration);
+        }
+        for track in self.timeline.tracks() {
+            let edges: Vec<(f64, f64)> = match track {
+                crate::types::track::Track::Video(v) => v
+                    .clips
+                    .iter()
+                    .filter(|c| c.id != exclude_clip)
+                    .map(|c| (c.start_time, c.start_time + c.duration))
+                    .collect(),
+                crate::types::track::Track::Audio(a) => a
+                    .clips
+                    .iter()
+                    .filter(|c| c.id != exclude_clip)
+                    .map(|c| (c.start_time, c.start_time + c.duration))
+                    .collect(),
+            };
+            for (start, end) in edges {
+                targets.push(start);
+                targets.push(end);
+            }
+        }
+        targets
+    }
+
+    /// Absolute times of a clip's keyframes
+    fn keyframe_targets(&self, clip_id: &str) -> Vec<f64> {
+        self.timeline
+            .tracks()
+            .iter()
+            .filter_map(|track| match track {
+                crate::types::track::Track::Video(v) => v.clips.iter().find(|c| c.id == clip_id),
+                crate::types::track::Track::Audio(_) => None,
+            })
+            .flat_map(|clip| {
+                clip.opacity_envelope
+                    .points()
+                    .iter()
+                    .map(|p| clip.start_time + p.time)
+            })
+            .collect()
+    }
+
+    /// Nearest keyframe of a selected clip after (or before) the playhead
+    fn keyframe_jump(&self, forward: bool) -> Option<f64> {
+        let playhead = self.playhead;
+        self.timeline
+            .tracks()
+            .iter()
+            .filter_map(|track| match track {
+                crate::types::track::Track::Video(v) => Some(&v.clips),
+                crate::types::track::Track::Audio(_) => None,
+            })
+            .flatten()
+            .filter(|c| self.state.selected_clips.contains(&c.id))
+            .filter_map(|c| {
+                let envelope = &c.opacity_envelope;
+                let local = playhead - c.start_time;
+                let point = if forward {
+                    envelope.next_point_after(local)
+                } else {
+                    envelope.prev_point_before(local)
+                };
+                point.map(|p| c.start_time + p.time)
+            })
+            .min_by(|a, b| (a - playhead).abs().total_cmp(&(b - playhead).abs()))
+    }
+
+    /// Key the clip's current opacity at `time`. Returns false if there is no such video clip.
+    fn add_opacity_keyframe(&mut self, clip_id: &str, time: f64) -> bool {
+        for track in self.timeline.tracks_mut() {
+            if let crate::types::track::Track::Video(v) = track {
+                if let Some(clip) = v.clips.iter_mut().find(|c| c.id == clip_id) {
+                    let local = time - clip.start_time;
+                    let value = clip.opacity_envelope.value_at(local).unwrap_or(1.0);
+                    clip.opacity_envelope.add_point(local, value);
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    /// Ids of the clips drawn inside `tracks_rect` that intersect `rect`
+    fn clips_in_rect(
+        &self,
+        tracks_rect: egui::Rect,
+        rect: egui::Rect,
+    ) -> std::collections::HashSet<String> {
+        let mut hits = std::collections::HashSet::new();
+        for (track_idx, track) in self.timeline.tracks().iter().enumerate() {
+            let spans: Vec<(&String, f64, f64)> = match track {
+                crate::types::track::Track::Video(v) => v
+                    .clips
+                    .iter()
+                    .map(|c| (&c.id, c.start_time, c.duration))
+                    .collect(),
+                crate::types::track::Track::Audio(a) => a
+                    .clips
+                    .iter()
+                    .map(|c| (&c.id, c.start_time, c.duration))
+                    .collect(),
+            };
+            let top = tracks_rect.top() + track_idx as f32 * TRACK_HEIGHT + 10.0;
+            for (id, start_time, duration) in spans {
+                let clip_rect = egui::Rect::from_min_size(
+                    egui::pos2(tracks_rect.left() + self.state.time_to_x(start_time), top),
+                    egui::vec2(duration as f32 * self.state.zoom, CLIP_HEIGHT),
+                );
+                if clip_rect.intersects(rect) {
+                    hits.insert(id.clone());
+                }
+            }
+        }
+        hits
+    }
+
+    /// Start time, in point and duration of a clip on the timeline
+    fn clip_times(&self, clip_id: &str) -> Option<(f64, f64, f64)> {
+        self.timeline.tracks().iter().find_map(|track| match track {
+            crate::types::track::Track::Video(v) => v
+                .clips
+                .iter()
+                .find(|c| c.id == clip_id)
+                .map(|c| (c.start_time, c.in_point, c.duration)),
+            crate::types::track::Track::Audio(a) => a
+                .clips
+                .iter()
+                .find(|c| c.id == clip_id)
+                .map(|c| (c.start_time, c.in_point, c.duration)),
+        })
+    }
+
+    /// Provisional clip times for an edge resize with the pointer at `current_pos`,
+    /// snapped like the final edit. None for other drags.
+    fn resize_drag_preview(
+        &self,
+        drag_state: &DragState,
+        current_pos: egui::Pos2,
+    ) -> Option<ResizePreview> {
+        match drag_state {
+            DragState::ResizeLeft {
+                clip_id,
+                original_start_time,
+                original_duration,
+                ..
+            } => {
+                // The clip itself is only updated on release
+                let (_, in_point, _) = self.clip_times(clip_id)?;
+                let (new_start_time, _) = self.drag_start_time(drag_state, current_pos)?;
+                Some(resize_preview(
+                    ResizeEdge::Left,
+                    *original_start_time,
+                    in_point,
+                    *original_duration,
+                    new_start_time - original_start_time,
+                ))
+            }
+            DragState::ResizeRight {
+                clip_id,
+                start_pos,
+                original_start_time,
+                original_duration,
+                ..
+            } => {
+                let start_time = *original_start_time;
+                let (_, in_point, _) = self.clip_times(clip_id)?;
+                let delta_time = ((current_pos.x - start_pos.x) / self.state.zoom) as f64;
+                // The end locks to targets, otherwise the length snaps to the grid
+                let (end, snapped) = self.state.snap_to_targets(
+                    start_time + original_duration + delta_time,
+                    self.snap_enabled,
+                    &self.snap_targets(clip_id),
+                );
+                let new_duration = if snapped {
+                    end - start_time
+                } else {
+                    self.state
+                        .snap_time(original_duration + delta_time, self.snap_enabled)
+                };
+                Some(resize_preview(
+                    ResizeEdge::Right,
+                    start_time,
+                    in_point,
+                    *original_duration,
+                    new_duration - original_duration,
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// Snapped new start time for a clip move or left-edge resize at `current_pos`, and
+    /// the target the clip's start or end locked to, if any. Targets include clip edges
+    /// on every track, so cuts can be lined up across tracks.
+    fn drag_start_time(
+        &self,
+        drag_state: &DragState,
+        current_pos: egui::Pos2,
+    ) -> Option<(f64, Option<f64>)> {
+        let (clip_id, start_pos, original_start_time) = match drag_state {
+            DragState::Clip {
+                clip_id,
+                start_pos,
+                original_start_time,
+                ..
+            }
+            | DragState::ResizeLeft {
+                clip_id,
+                start_pos,
+                original_start_time,
+                ..
+            } => (clip_id, start_pos, *original_start_time),
+            _ => return None,
+        };
+        let delta_time = (current_pos.x - start_pos.x) / self.state.zoom;
+        let mut targets = self.snap_targets(clip_id);
+        if matches!(drag_state, DragState::ResizeLeft { .. }) {
+            // Trimming the head can stop on one of the clip's own keyframes
+            targets.extend(self.keyframe_targets(clip_id));
+        }
+        let start_time = original_start_time + delta_time as f64;
+        let (time, snapped) = self
+            .state
+            .snap_to_targets(start_time, self.snap_enabled, &targets);
+        // A moved clip can lock its end instead, e.g. to the end of the timeline
+        let end_snap = matches!(drag_state, DragState::Clip { .. })
+            .then(|| self.clip_times(clip_id))
+            .flatten()
+            .map(|(_, _, duration)| {
+                let (end, snapped) =
+                    self.state
+                        .snap_to_targets(start_time + duration, self.snap_enabled, &targets);
+                (end - duration, snapped.then_some(end))
+            })
+            .filter(|&(_, end_target)| end_target.is_some() && !snapped);
+        let (time, target) = end_snap.unwrap_or((time, snapped.then_some(time)));
+        if time < 0.0 {
+            return Some((0.0, None));
+        }
+        Some((time, target))
+    }
+
     fn handle_drag_operations(
         &mut self,
         ui: &mut egui::Ui,
@@ -873,26 +2785,50 @@ impl<'a> TimelineWidget<'a> {
         events: &mut Vec<TimelineEvent>,
     ) {
         if let Some(ref drag_state) = self.state.drag_state.clone() {
+            // Show which target the dragged edge is locked to
+            self.state.snap_indicator = ui
+                .input(|i| i.pointer.latest_pos())
+                .and_then(|pos| self.drag_start_time(drag_state, pos))
+                .and_then(|(_, target)| target);
+
+            // Live in point and duration while resizing
+            if let Some(preview) = ui
+                .input(|i| i.pointer.latest_pos())
+                .and_then(|pos| self.resize_drag_preview(drag_state, pos))
+            {
+                egui::show_tooltip_at_pointer(
+                    ui.ctx(),
+                    ui.layer_id(),
+                    egui::Id::new("clip_resize_preview"),
+                    |ui| {
+                        ui.label(format!(
+                            "in {} · dur {}",
+                            format_time(preview.in_point),
+                            format_time(preview.duration)
+                        ))
+                    },
+                );
+            }
+
+            if let (DragState::Selection { start_pos, .. }, Some(current_pos)) =
+                (drag_state, ui.input(|i| i.pointer.latest_pos()))
+            {
+                self.state.drag_state = Some(DragState::Selection {
+                    start_pos: *start_pos,
+                    current_pos,
+                });
+            }
+
             if ui.input(|i| i.pointer.any_released()) {
                 // End drag operation
                 match drag_state {
                     DragState::Clip {
-                        clip_id,
-                        track_idx,
-                        start_pos,
-                        original_start_time,
+                        clip_id, track_idx, ..
                     } => {
-                        if let Some(current_pos) = ui.input(|i| i.pointer.latest_pos()) {
-                            let delta_x = current_pos.x - start_pos.x;
-                            let delta_time = delta_x / self.state.zoom;
-                            let new_start_time = self
-                                .state
-                                .snap_time(
-                                    original_start_time + delta_time as f64,
-                                    self.snap_enabled,
-                                )
-                                .max(0.0);
-
+                        if let Some((new_start_time, _)) = ui
+                            .input(|i| i.pointer.latest_pos())
+                            .and_then(|pos| self.drag_start_time(drag_state, pos))
+                        {
                             events.push(TimelineEvent::ClipMoved {
                                 clip_id: clip_id.clone(),
                                 track_idx: *track_idx,
@@ -901,56 +2837,32 @@ impl<'a> TimelineWidget<'a> {
                         }
                     }
                     DragState::ResizeLeft {
-                        clip_id,
-                        track_idx,
-                        start_pos,
-                        original_start_time,
-                        original_duration,
+                        clip_id, track_idx, ..
                     } => {
-                        if let Some(current_pos) = ui.input(|i| i.pointer.latest_pos()) {
-                            let delta_x = current_pos.x - start_pos.x;
-                            let delta_time = delta_x / self.state.zoom;
-                            let new_start_time = self
-                                .state
-                                .snap_time(
-                                    original_start_time + delta_time as f64,
-                                    self.snap_enabled,
-                                )
-                                .max(0.0);
-                            let new_duration = (original_duration
-                                - (new_start_time - original_start_time))
-                                .max(0.1);
-
+                        if let Some(preview) = ui
+                            .input(|i| i.pointer.latest_pos())
+                            .and_then(|pos| self.resize_drag_preview(drag_state, pos))
+                        {
                             events.push(TimelineEvent::ClipResized {
                                 clip_id: clip_id.clone(),
                                 track_idx: *track_idx,
-                                new_start_time,
-                                new_duration,
+                                new_start_time: preview.start_time,
+                                new_duration: preview.duration,
                             });
                         }
                     }
                     DragState::ResizeRight {
-                        clip_id,
-                        track_idx,
-                        start_pos,
-                        original_duration,
+                        clip_id, track_idx, ..
                     } => {
-                        if let Some(current_pos) = ui.input(|i| i.pointer.latest_pos()) {
-                            let delta_x = current_pos.x - start_pos.x;
-                            let delta_time = delta_x / self.state.zoom;
-                            let new_duration = self
-                                .state
-                                .snap_time(original_duration + delta_time as f64, self.snap_enabled)
-                                .max(0.1);
-
-                            // For resize right, we need to find the original start time
-                            // This is a simplified approach - in a real implementation,
-                            // you'd track this in the drag state
+                        if let Some(preview) = ui
+                            .input(|i| i.pointer.latest_pos())
+                            .and_then(|pos| self.resize_drag_preview(drag_state, pos))
+                        {
                             events.push(TimelineEvent::ClipResized {
                                 clip_id: clip_id.clone(),
                                 track_idx: *track_idx,
-                                new_start_time: 0.0, // You'd need to track this
-                                new_duration,
+                                new_start_time: preview.start_time,
+                                new_duration: preview.duration,
                             });
                         }
                     }
@@ -960,15 +2872,40 @@ impl<'a> TimelineWidget<'a> {
                                 .state
                                 .x_to_time(current_pos.x - timeline_rect.left())
                                 .max(0.0);
-                            let snapped_time =
-                                self.state.snap_time(new_time, self.snap_enabled).max(0.0);
+                            let snapped_time = self
+                                .state
+                                .snap_playhead(new_time, self.snap_enabled)
+                                .max(0.0);
                             events.push(TimelineEvent::PlayheadMoved(snapped_time));
                         }
                     }
-                    _ => {}
+                    DragState::Selection { start_pos, .. } => {
+                        let release_pos =
+                            ui.input(|i| i.pointer.latest_pos()).unwrap_or(*start_pos);
+                        let tracks_rect = egui::Rect::from_min_max(
+                            timeline_rect.left_top() + egui::vec2(TRACK_LABEL_WIDTH, RULER_HEIGHT),
+                            timeline_rect.right_bottom(),
+                        );
+                        match empty_space_gesture(*start_pos, release_pos) {
+                            EmptySpaceGesture::Seek if self.state.click_to_seek => {
+                                let time = self.state.x_to_time(start_pos.x - tracks_rect.left());
+                                events.push(TimelineEvent::PlayheadMoved(
+                                    self.state.snap_playhead(time, self.snap_enabled).max(0.0),
+                                ));
+                            }
+                            EmptySpaceGesture::Seek => {}
+                            EmptySpaceGesture::Select => {
+                                self.state.selected_clips = self.clips_in_rect(
+                                    tracks_rect,
+                                    egui::Rect::from_two_pos(*start_pos, release_pos),
+                                );
+                            }
+                        }
+                    }
                 }
 
                 self.state.drag_state = None;
+                self.state.snap_indicator = None;
             }
         }
 
@@ -994,9 +2931,993 @@ impl<'a> TimelineWidget<'a> {
     }
 }
 
+/// Pointer travel in pixels below which a press on empty track space is a click
+pub const CLICK_DRAG_THRESHOLD: f32 = 4.0;
+
+/// What a press and release on empty track space does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmptySpaceGesture {
+    /// Move the playhead to the press position
+    Seek,
+    /// Select the clips inside the marquee
+    Select,
+}
+
+/// Tell a click-to-seek from a marquee drag by how far the pointer moved
+pub fn empty_space_gesture(press: egui::Pos2, release: egui::Pos2) -> EmptySpaceGesture {
+    if press.distance(release) < CLICK_DRAG_THRESHOLD {
+        EmptySpaceGesture::Seek
+    } else {
+        EmptySpaceGesture::Select
+    }
+}
+
+/// Source time sampled for each of `buckets` equal slices of a clip (at the slice centers),
+/// given the source times at its start and end on the timeline
+pub fn media_sample_times(source_span: (f64, f64), buckets: usize) -> Vec<f64> {
+    let (start, end) = source_span;
+    (0..buckets)
+        .map(|i| start + (end - start) * (i as f64 + 0.5) / buckets as f64)
+        .collect()
+}
+
+/// Whether a dragged media item can be dropped onto `track`
+pub fn media_fits_track(
+    item: &crate::types::media_library::MediaItem,
+    track: &crate::types::track::Track,
+) -> bool {
+    use crate::types::media_library::MediaItem;
+    use crate::types::track::Track;
+    matches!(
+        (item, track),
+        (MediaItem::VideoItem(_), Track::Video(_)) | (MediaItem::AudioItem(_), Track::Audio(_))
+    )
+}
+
+/// Length of new stills and titles while Alt is held, in seconds
+pub const SHORT_STILL_DURATION: f64 = 1.0;
+
+/// Length of a new still or title: the configured default, or `SHORT_STILL_DURATION`
+/// while Alt is held
+pub fn still_clip_duration(default_duration: f64, modifiers: egui::Modifiers) -> f64 {
+    if modifiers.alt {
+        SHORT_STILL_DURATION
+    } else {
+        default_duration
+    }
+}
+
+/// Length of a new clip of the audio at `asset_path`: the source's own length, or
+/// `still_duration` when it can't be probed
+pub fn audio_clip_duration(
+    state: &mut TimelineState,
+    asset_path: &str,
+    still_duration: f64,
+) -> f64 {
+    state
+        .source_durations
+        .entry(asset_path.to_string())
+        .or_insert_with(|| get_media_duration(asset_path))
+        .unwrap_or(still_duration)
+}
+
+/// Shortest clip an edge resize can produce, in seconds
+pub const MIN_RESIZE_DURATION: f64 = 0.1;
+
+/// Which clip edge a resize drag moves
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeEdge {
+    Left,
+    Right,
+}
+
+/// Clip times while an edge is being dragged
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResizePreview {
+    pub start_time: f64,
+    pub in_point: f64,
+    pub duration: f64,
+}
+
+/// Clip times after moving `edge` by `delta` seconds. The clip keeps at least
+/// `MIN_RESIZE_DURATION`, and the left edge stops at the start of the source.
+pub fn resize_preview(
+    edge: ResizeEdge,
+    start_time: f64,
+    in_point: f64,
+    duration: f64,
+    delta: f64,
+) -> ResizePreview {
+    match edge {
+        ResizeEdge::Left => {
+            let delta = delta
+                .min(duration - MIN_RESIZE_DURATION)
+                .max(-in_point.max(0.0));
+            ResizePreview {
+                start_time: start_time + delta,
+                in_point: in_point + delta,
+                duration: duration - delta,
+            }
+        }
+        ResizeEdge::Right => ResizePreview {
+            start_time,
+            in_point,
+            duration: (duration + delta).max(MIN_RESIZE_DURATION),
+        },
+    }
+}
+
+/// Smallest 1/2/5 × 10^n interval (in units shown `pixels_per_unit` apart)
+/// whose ticks are at least `min_spacing` pixels apart.
+pub fn nice_tick_interval(pixels_per_unit: f64, min_spacing: f64) -> f64 {
+    if pixels_per_unit <= 0.0 || min_spacing <= 0.0 {
+        return 1.0;
+    }
+    let min_interval = min_spacing / pixels_per_unit;
+    let magnitude = 10f64.powf(min_interval.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|interval| *interval >= min_interval * (1.0 - 1e-9))
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Major and minor ruler tick intervals in seconds for a zoom level.
+///
+/// Frame and timecode units never tick finer than one frame.
+pub fn ruler_intervals(
+    pixels_per_second: f32,
+    label_width: f32,
+    units: RulerUnits,
+    frame_rate: f64,
+) -> (f64, f64) {
+    let pps = pixels_per_second as f64;
+    let seconds = nice_tick_interval(pps, label_width as f64);
+    let major = match units {
+        RulerUnits::Seconds => seconds,
+        RulerUnits::Timecode if seconds >= 1.0 => seconds,
+        RulerUnits::Timecode | RulerUnits::Frames => {
+            nice_tick_interval(pps / frame_rate, label_width as f64).max(1.0) / frame_rate
+        }
+    };
+    // 1 and 5 split into fifths, 2 into quarters
+    let mantissa = (major / 10f64.powf(major.log10().floor())).round();
+    let mut minor = major / if mantissa == 2.0 { 4.0 } else { 5.0 };
+    if units != RulerUnits::Seconds && minor * frame_rate < 1.0 - 1e-9 {
+        minor = major;
+    }
+    (major, minor)
+}
+
+/// Ruler label for `time`, with precision matching the tick `interval`
+pub fn format_ruler_label(time: f64, units: RulerUnits, frame_rate: f64, interval: f64) -> String {
+    let frame = (time * frame_rate).round().max(0.0) as u64;
+    match units {
+        RulerUnits::Seconds => {
+            let decimals = (-interval.log10().floor()).clamp(0.0, 3.0) as usize;
+            format!("{:.*}s", decimals, time)
+        }
+        RulerUnits::Frames => frame.to_string(),
+        RulerUnits::Timecode => to_timecode_df(frame, frame_rate).unwrap_or_else(|| {
+            let fps = frame_rate.round().max(1.0) as u64;
+            format!(
+                "{:02}:{:02}:{:02}:{:02}",
+                frame / (fps * 3600),
+                frame / (fps * 60) % 60,
+                frame / fps % 60,
+                frame % fps
+            )
+        }),
+    }
+}
+
 // Helper function to format time as MM:SS.mmm
 pub fn format_time(seconds: f64) -> String {
     let minutes = (seconds / 60.0) as i32;
     let secs = seconds % 60.0;
     format!("{:02}:{:06.3}", minutes, secs)
 }
+
+/// Nominal frame rate and frames dropped per minute for drop-frame rates
+/// (29.97 and 59.94), or None if `frame_rate` has no drop-frame timecode.
+fn drop_frame_params(frame_rate: f64) -> Option<(u64, u64)> {
+    if (frame_rate - 30000.0 / 1001.0).abs() < 0.01 {
+        Some((30, 2))
+    } else if (frame_rate - 60000.0 / 1001.0).abs() < 0.01 {
+        Some((60, 4))
+    } else {
+        None
+    }
+}
+
+/// Format a frame number as drop-frame timecode (`HH;MM;SS;FF`).
+///
+/// Frame labels 0 and 1 (0-3 at 59.94) are skipped at the start of every minute
+/// except each tenth minute. Returns None unless `frame_rate` is 29.97 or 59.94.
+pub fn to_timecode_df(frame: u64, frame_rate: f64) -> Option<String> {
+    let (nominal, drop) = drop_frame_params(frame_rate)?;
+    let frames_per_minute = nominal * 60 - drop;
+    let frames_per_ten_minutes = frames_per_minute * 10 + drop;
+
+    let tens = frame / frames_per_ten_minutes;
+    let rest = frame % frames_per_ten_minutes;
+    let mut label = frame + drop * 9 * tens;
+    if rest > drop {
+        label += drop * ((rest - drop) / frames_per_minute);
+    }
+
+    Some(format!(
+        "{:02};{:02};{:02};{:02}",
+        label / (nominal * 3600),
+        label / (nominal * 60) % 60,
+        label / nominal % 60,
+        label % nominal
+    ))
+}
+
+/// Parse drop-frame timecode (`HH;MM;SS;FF`) into a frame number.
+///
+/// Rejects frame labels that drop-frame counting skips. Returns None unless
+/// `frame_rate` is 29.97 or 59.94.
+pub fn from_timecode_df(s: &str, frame_rate: f64) -> Option<u64> {
+    let (nominal, drop) = drop_frame_params(frame_rate)?;
+    let fields: Vec<u64> = s
+        .trim()
+        .split(';')
+        .map(|p| {
+            if p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()) {
+                None
+            } else {
+                p.parse().ok()
+            }
+        })
+        .collect::<Option<_>>()?;
+    let [hh, mm, ss, ff] = fields[..] else {
+        return None;
+    };
+    if mm >= 60 || ss >= 60 || ff >= nominal {
+        return None;
+    }
+    if ss == 0 && ff < drop && mm % 10 != 0 {
+        return None;
+    }
+
+    let total_minutes = hh * 60 + mm;
+    let label = (total_minutes * 60 + ss) * nominal + ff;
+    Some(label - drop * (total_minutes - total_minutes / 10))
+}
+
+/// Parse a user-typed time into seconds.
+///
+/// Accepts raw seconds (`12.5`), `MM:SS.mmm` (`01:02.500`), `HH:MM:SS(.mmm)` and
+/// `HH:MM:SS:FF` timecode, where frames are converted using `frame_rate`.
+/// At 29.97 and 59.94 drop-frame timecode (`HH;MM;SS;FF`) is accepted too.
+pub fn parse_time_input(s: &str, frame_rate: f64) -> Option<f64> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+    if s.contains(';') {
+        return from_timecode_df(s, frame_rate).map(|frame| frame as f64 / frame_rate);
+    }
+    let parts: Vec<&str> = s.split(':').collect();
+    let whole = |p: &str| -> Option<f64> {
+        if p.is_empty() || !p.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        p.parse::<u64>().ok().map(|v| v as f64)
+    };
+    let seconds = |p: &str| -> Option<f64> {
+        if p.is_empty() || !p.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        p.parse::<f64>().ok()
+    };
+
+    let time = match parts.as_slice() {
+        [secs] => seconds(secs)?,
+        [mm, ss] => {
+            let secs = seconds(ss)?;
+            if secs >= 60.0 {
+                return None;
+            }
+            whole(mm)? * 60.0 + secs
+        }
+        [hh, mm, ss] => {
+            let (mins, secs) = (whole(mm)?, seconds(ss)?);
+            if mins >= 60.0 || secs >= 60.0 {
+                return None;
+            }
+            whole(hh)? * 3600.0 + mins * 60.0 + secs
+        }
+        [hh, mm, ss, ff] => {
+            let (mins, secs, frames) = (whole(mm)?, whole(ss)?, whole(ff)?);
+            if mins >= 60.0 || secs >= 60.0 || frame_rate <= 0.0 || frames >= frame_rate.ceil() {
+                return None;
+            }
+            whole(hh)? * 3600.0 + mins * 60.0 + secs + frames / frame_rate
+        }
+        _ => return None,
+    };
+    time.is_finite().then_some(time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_input_raw_seconds() {
+        assert_eq!(parse_time_input("12.5", 30.0), Some(12.5));
+        assert_eq!(parse_time_input(" 7 ", 30.0), Some(7.0));
+    }
+
+    #[test]
+    fn test_parse_time_input_minutes_seconds() {
+        assert_eq!(parse_time_input("01:02.500", 30.0), Some(62.5));
+        assert_eq!(parse_time_input("00:05", 30.0), Some(5.0));
+    }
+
+    #[test]
+    fn test_parse_time_input_hours_minutes_seconds() {
+        assert_eq!(parse_time_input("01:00:30", 30.0), Some(3630.0));
+        assert_eq!(parse_time_input("00:01:02.250", 30.0), Some(62.25));
+    }
+
+    #[test]
+    fn test_clip_elements_by_width() {
+        let config = ClipDisplayConfig::default();
+        assert_eq!(config.elements(5.0, true), ClipElements::default());
+        assert_eq!(
+            config.elements(12.0, false),
+            ClipElements {
+                waveform: true,
+                ..ClipElements::default()
+            }
+        );
+        assert_eq!(
+            config.elements(30.0, true),
+            ClipElements {
+                thumbnail: true,
+                handles: true,
+                ..ClipElements::default()
+            }
+        );
+        let all_audio = ClipElements {
+            label: true,
+            waveform: true,
+            handles: true,
+            ..ClipElements::default()
+        };
+        assert_eq!(config.elements(200.0, false), all_audio);
+
+        let config = ClipDisplayConfig {
+            min_label_width: 100.0,
+            ..config
+        };
+        assert!(!config.elements(80.0, false).label);
+    }
+
+    #[test]
+    fn test_video_clip_layout_splits_off_audio_strip() {
+        let clip_rect =
+            egui::Rect::from_min_size(egui::pos2(10.0, 20.0), egui::vec2(200.0, CLIP_HEIGHT));
+        assert_eq!(video_clip_layout(clip_rect, false), (clip_rect, None));
+
+        let (picture, strip) = video_clip_layout(clip_rect, true);
+        let strip = strip.unwrap();
+        assert_eq!(strip.height(), CLIP_HEIGHT * AUDIO_STRIP_FRACTION);
+        assert_eq!(picture.height() + strip.height(), CLIP_HEIGHT);
+        // The strip runs along the bottom, full width, right under the picture
+        assert_eq!(strip.bottom(), clip_rect.bottom());
+        assert_eq!(strip.top(), picture.bottom());
+        assert_eq!((strip.width(), picture.width()), (200.0, 200.0));
+        assert_eq!(picture.top(), clip_rect.top());
+    }
+
+    #[test]
+    fn test_tiny_clips_get_a_wider_interaction_rect() {
+        let config = ClipDisplayConfig::default();
+        let tiny = egui::Rect::from_min_size(egui::pos2(100.0, 10.0), egui::vec2(0.5, 40.0));
+        let rect = config.interaction_rect(tiny);
+        assert_eq!(rect.width(), config.min_clickable_width);
+        assert_eq!(rect.center(), tiny.center());
+        assert_eq!((rect.top(), rect.bottom()), (tiny.top(), tiny.bottom()));
+
+        // Clips at least that wide keep their drawn rect
+        let wide = egui::Rect::from_min_size(egui::pos2(100.0, 10.0), egui::vec2(3.0, 40.0));
+        assert_eq!(config.interaction_rect(wide), wide);
+    }
+
+    #[test]
+    fn test_parse_time_input_timecode_frames() {
+        assert_eq!(parse_time_input("00:00:01:15", 30.0), Some(1.5));
+        let time = parse_time_input("00:01:00:05", 25.0).unwrap();
+        assert!((time - 60.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_nice_tick_interval_labels_do_not_overlap() {
+        let label_width = 40.0;
+        for pps in [0.5, 3.0, 17.0, 50.0, 100.0, 333.0, 2000.0, 12000.0] {
+            let interval = nice_tick_interval(pps, label_width);
+            // Labels fit between ticks
+            assert!(interval * pps >= label_width - 1e-6, "{} px/s", pps);
+            // ...and the next smaller nice step would not
+            let mantissa = (interval / 10f64.powf(interval.log10().floor())).round();
+            assert!([1.0, 2.0, 5.0].contains(&mantissa), "{}", interval);
+            let smaller = interval / if mantissa == 5.0 { 2.5 } else { 2.0 };
+            assert!(smaller * pps < label_width, "{} px/s", pps);
+        }
+        assert_eq!(nice_tick_interval(100.0, 40.0), 0.5);
+        assert_eq!(nice_tick_interval(10.0, 40.0), 5.0);
+    }
+
+    #[test]
+    fn test_ruler_intervals_respect_frames() {
+        // Zoomed far in: seconds can go below a frame, frames cannot
+        let (major, minor) = ruler_intervals(20000.0, 40.0, RulerUnits::Seconds, 25.0);
+        assert!(major < 0.04 && minor < major);
+        let (major, minor) = ruler_intervals(20000.0, 40.0, RulerUnits::Frames, 25.0);
+        assert!((major - 0.04).abs() < 1e-12);
+        assert_eq!(minor, major);
+        assert_eq!(
+            format_ruler_label(2.0, RulerUnits::Frames, 25.0, major),
+            "50"
+        );
+        assert_eq!(
+            format_ruler_label(61.5, RulerUnits::Timecode, 30.0, 1.0),
+            "00:01:01:15"
+        );
+        assert_eq!(
+            format_ruler_label(1.5, RulerUnits::Seconds, 30.0, 0.5),
+            "1.5s"
+        );
+        assert_eq!(
+            format_ruler_label(10.0, RulerUnits::Seconds, 30.0, 5.0),
+            "10s"
+        );
+    }
+
+    #[test]
+    fn test_to_timecode_df_reference_values() {
+        let fps = 30000.0 / 1001.0;
+        assert_eq!(to_timecode_df(0, fps).as_deref(), Some("00;00;00;00"));
+        assert_eq!(to_timecode_df(1799, fps).as_deref(), Some("00;00;59;29"));
+        // Labels ;00 and ;01 are skipped at minute one
+        assert_eq!(to_timecode_df(1800, fps).as_deref(), Some("00;01;00;02"));
+        // ...but not at minute ten
+        assert_eq!(to_timecode_df(17982, fps).as_deref(), Some("00;10;00;00"));
+        assert_eq!(to_timecode_df(18000, fps).as_deref(), Some("00;10;00;18"));
+        assert_eq!(to_timecode_df(107892, fps).as_deref(), Some("01;00;00;00"));
+
+        let fps = 60000.0 / 1001.0;
+        assert_eq!(to_timecode_df(3600, fps).as_deref(), Some("00;01;00;04"));
+        assert_eq!(to_timecode_df(35964, fps).as_deref(), Some("00;10;00;00"));
+
+        assert_eq!(to_timecode_df(100, 25.0), None);
+    }
+
+    #[test]
+    fn test_from_timecode_df_round_trip() {
+        for fps in [30000.0 / 1001.0, 60000.0 / 1001.0] {
+            for frame in (0..300_000).step_by(7) {
+                let tc = to_timecode_df(frame, fps).unwrap();
+                assert_eq!(from_timecode_df(&tc, fps), Some(frame), "{}", tc);
+            }
+        }
+        let fps = 30000.0 / 1001.0;
+        assert_eq!(from_timecode_df("00;10;00;00", fps), Some(17982));
+        // Dropped labels do not exist
+        assert_eq!(from_timecode_df("00;01;00;00", fps), None);
+        assert_eq!(from_timecode_df("00;01;00;01", fps), None);
+        assert_eq!(from_timecode_df("00;00;00;30", fps), None);
+        assert_eq!(from_timecode_df("00;00;00;00", 30.0), None);
+
+        let time = parse_time_input("00;10;00;00", fps).unwrap();
+        assert!((time - 599.9994).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_parse_time_input_rejects_malformed() {
+        assert_eq!(parse_time_input("", 30.0), None);
+        assert_eq!(parse_time_input("abc", 30.0), None);
+        assert_eq!(parse_time_input("-5", 30.0), None);
+        assert_eq!(parse_time_input("1:2:3:4:5", 30.0), None);
+        assert_eq!(parse_time_input("00:75", 30.0), None);
+        assert_eq!(parse_time_input("00:00:01:30", 30.0), None);
+        assert_eq!(parse_time_input("00::01", 30.0), None);
+        assert_eq!(parse_time_input("1.5:00", 30.0), None);
+    }
+
+    #[test]
+    fn test_snap_time_default_interval() {
+        let state = TimelineState::new();
+        assert!((state.snap_time(1.234, true) - 1.2).abs() < 1e-9);
+        assert_eq!(state.snap_time(1.234, false), 1.234);
+
+        // A zeroed (derived Default) state keeps the 100ms behavior
+        let state = TimelineState::default();
+        assert!((state.snap_time(1.26, true) - 1.3).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_snap_time_custom_interval() {
+        let mut state = TimelineState::new();
+        state.snap_interval = 0.5;
+        assert!((state.snap_time(1.2, true) - 1.0).abs() < 1e-9);
+        assert!((state.snap_time(1.3, true) - 1.5).abs() < 1e-9);
+        assert!((state.snap_time(2.74, true) - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_snap_time_to_frames() {
+        let mut state = TimelineState::new();
+        state.snap_to_frames = true;
+        state.frame_rate = 25.0;
+        assert!((state.snap_interval() - 0.04).abs() < 1e-12);
+        assert!((state.snap_time(1.01, true) - 1.0).abs() < 1e-9);
+        assert!((state.snap_time(1.03, true) - 1.04).abs() < 1e-9);
+        // Frame mode ignores the configured interval
+        state.snap_interval = 0.5;
+        assert!((state.snap_time(0.13, true) - 0.12).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_empty_space_gesture_click_vs_drag() {
+        let press = egui::pos2(100.0, 50.0);
+        assert_eq!(empty_space_gesture(press, press), EmptySpaceGesture::Seek);
+        // A little jitter while clicking still seeks
+        assert_eq!(
+            empty_space_gesture(press, egui::pos2(102.0, 51.0)),
+            EmptySpaceGesture::Seek
+        );
+        // Moving past the threshold in any direction is a marquee
+        assert_eq!(
+            empty_space_gesture(press, egui::pos2(100.0 + CLICK_DRAG_THRESHOLD, 50.0)),
+            EmptySpaceGesture::Select
+        );
+        assert_eq!(
+            empty_space_gesture(press, egui::pos2(60.0, 90.0)),
+            EmptySpaceGesture::Select
+        );
+    }
+
+    #[test]
+    fn test_resize_preview_left_edge() {
+        // Clip at 4s using source 2s..6s
+        let preview = resize_preview(ResizeEdge::Left, 4.0, 2.0, 4.0, 1.5);
+        assert_eq!(
+            preview,
+            ResizePreview {
+                start_time: 5.5,
+                in_point: 3.5,
+                duration: 2.5
+            }
+        );
+        // Dragging left extends into earlier source material
+        let preview = resize_preview(ResizeEdge::Left, 4.0, 2.0, 4.0, -1.0);
+        assert_eq!(
+            (preview.start_time, preview.in_point, preview.duration),
+            (3.0, 1.0, 5.0)
+        );
+        // ...but not before the start of the source
+        let preview = resize_preview(ResizeEdge::Left, 4.0, 2.0, 4.0, -3.0);
+        assert_eq!(
+            (preview.start_time, preview.in_point, preview.duration),
+            (2.0, 0.0, 6.0)
+        );
+        // Past the right edge the clip keeps its minimum length
+        let preview = resize_preview(ResizeEdge::Left, 4.0, 2.0, 4.0, 10.0);
+        assert!((preview.duration - MIN_RESIZE_DURATION).abs() < 1e-9);
+        assert!((preview.start_time + preview.duration - 8.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_resize_preview_right_edge() {
+        let preview = resize_preview(ResizeEdge::Right, 4.0, 2.0, 4.0, 1.5);
+        assert_eq!(
+            preview,
+            ResizePreview {
+                start_time: 4.0,
+                in_point: 2.0,
+                duration: 5.5
+            }
+        );
+        let preview = resize_preview(ResizeEdge::Right, 4.0, 2.0, 4.0, -3.0);
+        assert_eq!(preview.duration, 1.0);
+        let preview = resize_preview(ResizeEdge::Right, 4.0, 2.0, 4.0, -10.0);
+        assert_eq!(preview.duration, MIN_RESIZE_DURATION);
+    }
+
+    #[test]
+    fn test_snap_time_to_beats() {
+        let mut state = TimelineState::new();
+        state.beat_grid = Some(crate::types::beat_grid::BeatGrid::new(120.0, 0.25));
+        // Beats at 0.25, 0.75, 1.25, ... replace the 100ms grid
+        assert!((state.snap_time(1.1, true) - 1.25).abs() < 1e-9);
+        assert!((state.snap_time(0.9, true) - 0.75).abs() < 1e-9);
+        assert_eq!(state.snap_time(0.9, false), 0.9);
+    }
+
+    #[test]
+    fn test_playhead_scrub_snaps_to_marker() {
+        let mut state = TimelineState::new(); // 100 px/s: markers lock within 0.08s
+        state.markers = vec![2.345, 6.0];
+
+        assert_eq!(state.snap_playhead(2.3, true), 2.345);
+        assert_eq!(state.snap_playhead(5.95, true), 6.0);
+        // Out of reach falls back to the grid
+        assert!((state.snap_playhead(4.02, true) - 4.0).abs() < 1e-9);
+        assert_eq!(state.snap_playhead(2.3, false), 2.3);
+
+        state.snap_to_markers = false;
+        assert_eq!(state.marker_snap(2.3, true), None);
+        assert!((state.snap_playhead(2.3, true) - 2.3).abs() < 1e-9);
+    }
+
+    /// Track v1 holds a (0..2s) and b (4..6s), track v2 holds c (1..3s)
+    fn two_track_timeline() -> crate::types::timeline::Timeline {
+        let clip = |id: &str, start: f64| {
+            format!(
+                r#"{{ "id": "{}", "asset_path": "v.mp4", "in_point": 0.0, "out_point": 2.0,
+                     "start_time": {}, "duration": 2.0,
+                     "metadata": {{ "resolution": [1920, 1080], "frame_rate": 30.0,
+                                   "codec": "h264" }} }}"#,
+                id, start
+            )
+        };
+        let json = format!(
+            r#"{{
+                "tracks": [
+                    {{ "Video": {{ "id": "v1", "name": "V1", "muted": false,
+                                  "clips": [{}, {}] }} }},
+                    {{ "Video": {{ "id": "v2", "name": "V2", "muted": false,
+                                  "clips": [{}] }} }}
+                ],
+                "duration": 10.0,
+                "frame_rate": 30.0,
+                "resolution": [1920, 1080]
+            }}"#,
+            clip("a", 0.0),
+            clip("b", 4.0),
+            clip("c", 1.0)
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_gap_hit_test() {
+        let timeline = two_track_timeline();
+        let state = TimelineState::new(); // 100 px/s, no scroll
+
+        // Between a and b on the first track
+        assert_eq!(
+            state.gap_at(&timeline, egui::vec2(300.0, 30.0)),
+            Some((0, 2.0, 4.0))
+        );
+        // Same x on the second track is inside c; before c is a leading gap
+        assert_eq!(state.gap_at(&timeline, egui::vec2(300.0, 90.0)), None);
+        assert_eq!(
+            state.gap_at(&timeline, egui::vec2(50.0, 90.0)),
+            Some((1, 0.0, 1.0))
+        );
+        // On a clip, past the last clip, below the last track
+        assert_eq!(state.gap_at(&timeline, egui::vec2(100.0, 30.0)), None);
+        assert_eq!(state.gap_at(&timeline, egui::vec2(700.0, 30.0)), None);
+        assert_eq!(state.gap_at(&timeline, egui::vec2(50.0, 150.0)), None);
+    }
+
+    #[test]
+    fn test_move_clip_focus() {
+        let mut timeline = two_track_timeline();
+        let mut state = TimelineState::new();
+        let step = |state: &mut TimelineState, step| {
+            state.move_clip_focus(&timeline, step);
+            state.focused_clip.clone()
+        };
+
+        assert_eq!(step(&mut state, FocusMove::Next).as_deref(), Some("a"));
+        assert_eq!(step(&mut state, FocusMove::Next).as_deref(), Some("b"));
+        assert_eq!(step(&mut state, FocusMove::Next).as_deref(), Some("a"));
+        assert_eq!(step(&mut state, FocusMove::Previous).as_deref(), Some("b"));
+        // Down to the clip starting nearest b, and back up
+        assert_eq!(step(&mut state, FocusMove::Down).as_deref(), Some("c"));
+        assert_eq!(step(&mut state, FocusMove::Down).as_deref(), Some("c"));
+        assert_eq!(step(&mut state, FocusMove::Up).as_deref(), Some("a"));
+        assert_eq!(step(&mut state, FocusMove::Up).as_deref(), Some("a"));
+
+        // A focused clip that was deleted starts over from the first clip
+        timeline.remove_clip("a");
+        state.focused_clip = Some("a".to_string());
+        state.move_clip_focus(&timeline, FocusMove::Down);
+        assert_eq!(state.focused_clip.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn test_snap_to_targets_reports_snap() {
+        let state = TimelineState::new(); // 100 px/s: targets lock within 0.08s
+        let targets = [2.0, 5.0];
+
+        assert_eq!(state.snap_to_targets(2.05, true, &targets), (2.0, true));
+        assert_eq!(state.snap_to_targets(4.93, true, &targets), (5.0, true));
+
+        // Out of reach: grid snap, not reported as a target snap
+        let (time, snapped) = state.snap_to_targets(3.33, true, &targets);
+        assert!((time - 3.3).abs() < 1e-9);
+        assert!(!snapped);
+
+        // Disabled: untouched
+        assert_eq!(state.snap_to_targets(2.05, false, &targets), (2.05, false));
+    }
+
+    #[test]
+    fn test_clip_drag_snaps_to_timeline_start_and_end() {
+        let mut timeline = two_track_timeline();
+        timeline.remove_clip("a");
+        let mut state = TimelineState::new(); // 100 px/s: targets lock within 0.08s
+        let widget = TimelineWidget::new(&mut timeline, &mut state, 5.0);
+        let start_pos = egui::pos2(100.0, 0.0);
+        let drag = DragState::Clip {
+            clip_id: "c".to_string(),
+            track_idx: 1,
+            start_pos,
+            original_start_time: 1.0,
+        };
+
+        // Dropped 0.06s after zero: locks to 0 rather than the 0.1s grid
+        assert_eq!(
+            widget.drag_start_time(&drag, egui::pos2(6.0, 0.0)),
+            Some((0.0, Some(0.0)))
+        );
+        // Past zero: clamped
+        assert_eq!(
+            widget.drag_start_time(&drag, egui::pos2(-50.0, 0.0)),
+            Some((0.0, None))
+        );
+        // The 2s clip ending 0.05s before the 10s timeline end lines up with it; the
+        // guide marks the end
+        assert_eq!(
+            widget.drag_start_time(&drag, egui::pos2(795.0, 0.0)),
+            Some((8.0, Some(10.0)))
+        );
+
+        // Resizing the right edge near the end locks to it as well
+        let resize = DragState::ResizeRight {
+            clip_id: "c".to_string(),
+            track_idx: 1,
+            start_pos,
+            original_start_time: 1.0,
+            original_duration: 2.0,
+        };
+        let preview = widget
+            .resize_drag_preview(&resize, egui::pos2(796.0, 0.0))
+            .unwrap();
+        assert_eq!(preview.duration, 9.0);
+    }
+
+    #[test]
+    fn test_right_edge_drag_keeps_clip_start() {
+        let mut timeline = two_track_timeline();
+        let mut state = TimelineState::new();
+        // c runs 1s..3s, 200px wide at 100 px/s
+        let clip_rect =
+            egui::Rect::from_min_size(egui::pos2(100.0, 70.0), egui::vec2(200.0, CLIP_HEIGHT));
+        let begin = |state: &TimelineState, x: f32| {
+            state.begin_clip_drag("c", 1, (1.0, 2.0), clip_rect, egui::pos2(x, 80.0))
+        };
+        let drag = begin(&state, 297.0);
+        let DragState::ResizeRight {
+            original_start_time,
+            original_duration,
+            ..
+        } = drag
+        else {
+            panic!("expected a right edge resize, got {:?}", drag);
+        };
+        assert_eq!((original_start_time, original_duration), (1.0, 2.0));
+        assert!(matches!(begin(&state, 103.0), DragState::ResizeLeft { .. }));
+        assert!(matches!(begin(&state, 200.0), DragState::Clip { .. }));
+        // Clips too narrow for handles only move
+        let narrow = egui::Rect::from_min_size(clip_rect.min, egui::vec2(20.0, CLIP_HEIGHT));
+        assert!(matches!(
+            state.begin_clip_drag("c", 1, (1.0, 0.2), narrow, egui::pos2(118.0, 80.0)),
+            DragState::Clip { .. }
+        ));
+
+        // Dragging the end half a second out keeps the start where it was
+        let widget = TimelineWidget::new(&mut timeline, &mut state, 9.0);
+        let preview = widget
+            .resize_drag_preview(&drag, egui::pos2(347.0, 80.0))
+            .unwrap();
+        assert_eq!((preview.start_time, preview.duration), (1.0, 2.5));
+    }
+
+    #[test]
+    fn test_clip_drag_snaps_to_edges_on_other_tracks() {
+        let mut timeline = two_track_timeline();
+        let mut state = TimelineState::new(); // 100 px/s: targets lock within 0.08s
+        let widget = TimelineWidget::new(&mut timeline, &mut state, 9.0);
+        let start_pos = egui::pos2(100.0, 0.0);
+        let drag = DragState::Clip {
+            clip_id: "c".to_string(),
+            track_idx: 1,
+            start_pos,
+            original_start_time: 1.0,
+        };
+
+        // c on the second track starts 0.06s after a (first track) ends
+        assert_eq!(
+            widget.drag_start_time(&drag, egui::pos2(206.0, 0.0)),
+            Some((2.0, Some(2.0)))
+        );
+        // ...and 0.05s after b starts
+        assert_eq!(
+            widget.drag_start_time(&drag, egui::pos2(405.0, 0.0)),
+            Some((4.0, Some(4.0)))
+        );
+        // Too far from any edge: only the grid applies
+        let (time, target) = widget
+            .drag_start_time(&drag, egui::pos2(250.0, 0.0))
+            .unwrap();
+        assert!((time - 2.5).abs() < 1e-9);
+        assert_eq!(target, None);
+    }
+
+    #[test]
+    fn test_zoom_to_range() {
+        let mut state = TimelineState::new();
+        // 4s in 1000px: 800px for the clip, 100px either side
+        state.zoom_to_range(10.0, 14.0, 1000.0);
+        assert_eq!(state.zoom, 200.0);
+        assert_eq!(state.scroll_to_x, Some(1900.0));
+
+        // Near the start the offset stops at 0; very long ranges hit the zoom limit
+        state.zoom_to_range(0.0, 100_000.0, 1000.0);
+        assert_eq!(state.zoom, MIN_ZOOM);
+        assert_eq!(state.scroll_to_x, Some(0.0));
+    }
+
+    #[test]
+    fn test_media_fits_track() {
+        use crate::types::media_library::{AudioProp, FileDescriptor, MediaItem, VideoProp};
+        use crate::types::track::{AudioTrack, Track, VideoTrack};
+
+        let file =
+            |name: &str| FileDescriptor::new(name.to_string(), name.to_string(), 0, String::new());
+        let video = MediaItem::VideoItem(VideoProp {
+            file_descriptor: file("a.mp4"),
+            thumbnail_path: None,
+        });
+        let audio = MediaItem::AudioItem(AudioProp {
+            file_descriptor: file("a.wav"),
+        });
+        let video_track = Track::Video(VideoTrack {
+            id: "v1".to_string(),
+            name: "Video".to_string(),
+            clips: vec![],
+            muted: false,
+            sync_locked: false,
+        });
+        let audio_track = Track::Audio(AudioTrack {
+            id: "a1".to_string(),
+            name: "Audio".to_string(),
+            clips: vec![],
+            muted: false,
+            sync_locked: false,
+            volume_db: 0.0,
+            pan: 0.0,
+        });
+
+        assert!(!media_fits_track(&video, &audio_track));
+        assert!(!media_fits_track(&audio, &video_track));
+        assert!(media_fits_track(&video, &video_track));
+        assert!(media_fits_track(&audio, &audio_track));
+    }
+
+    #[test]
+    fn test_reversed_fast_clip_sampling() {
+        // Source 2..6 played backwards at 2x: 2s on the timeline from t=10
+        let mut clip = match &two_track_timeline().tracks()[0] {
+            crate::types::track::Track::Video(v) => v.clips[0].clone(),
+            crate::types::track::Track::Audio(_) => unreachable!(),
+        };
+        clip.in_point = 2.0;
+        clip.out_point = 6.0;
+        clip.start_time = 10.0;
+        clip.speed = 2.0;
+        clip.reversed = true;
+
+        assert_eq!(clip.source_time_at(10.0), 6.0);
+        assert_eq!(clip.source_time_at(10.5), 5.0);
+        assert_eq!(clip.source_time_at(11.25), 3.5);
+        assert_eq!(clip.source_span(), (6.0, 2.0));
+        assert_eq!(
+            media_sample_times(clip.source_span(), 4),
+            vec![5.5, 4.5, 3.5, 2.5]
+        );
+
+        // Forward at 2x covers the same source range in order
+        clip.reversed = false;
+        assert_eq!(
+            media_sample_times(clip.source_span(), 4),
+            vec![2.5, 3.5, 4.5, 5.5]
+        );
+    }
+
+    #[test]
+    fn test_new_clip_durations_use_still_default() {
+        let settings = crate::types::project::Project::from_template("1080p30").settings;
+        let still = settings.default_still_duration;
+        assert_eq!(
+            still_clip_duration(still, egui::Modifiers::NONE),
+            crate::types::project::DEFAULT_STILL_DURATION
+        );
+        assert_eq!(still_clip_duration(3.0, egui::Modifiers::NONE), 3.0);
+        // Holding Alt overrides the default for one drop
+        assert_eq!(
+            still_clip_duration(3.0, egui::Modifiers::ALT),
+            SHORT_STILL_DURATION
+        );
+
+        let mut state = TimelineState::new();
+        state
+            .source_durations
+            .insert("song.wav".to_string(), Some(42.0));
+        state
+            .source_durations
+            .insert("broken.wav".to_string(), None);
+        assert_eq!(audio_clip_duration(&mut state, "song.wav", 3.0), 42.0);
+        assert_eq!(audio_clip_duration(&mut state, "broken.wav", 3.0), 3.0);
+    }
+
+    #[test]
+    fn test_probed_durations_are_stored() {
+        use crate::renderer::asset_analyzer::{AnalysisRequest, AnalysisResult};
+        let result = |kind, path: &str, data| AnalysisResult {
+            request: AnalysisRequest {
+                kind,
+                path: path.to_string(),
+                params: AnalysisParams::default(),
+            },
+            data,
+        };
+        let mut state = TimelineState::new();
+        state.store_durations(&[
+            result(
+                AnalysisKind::Duration,
+                "song.wav",
+                AnalysisData::Duration(42.0),
+            ),
+            result(AnalysisKind::Duration, "broken.wav", AnalysisData::Failed),
+            result(AnalysisKind::Waveform, "other.wav", AnalysisData::Failed),
+        ]);
+        assert_eq!(state.source_duration("song.wav"), Some(42.0));
+        // A failed probe is remembered so it isn't requested again
+        assert_eq!(state.source_durations.get("broken.wav"), Some(&None));
+        assert_eq!(state.source_duration("other.wav"), None);
+        assert!(!state.source_durations.contains_key("other.wav"));
+    }
+
+    #[test]
+    fn test_dropped_audio_uses_probed_length() {
+        // Needs GStreamer's WAV demuxer
+        if gst::init().is_err() || gst::ElementFactory::find("wavparse").is_none() {
+            return;
+        }
+        // 1.5s of silence
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("speech.wav");
+        crate::renderer::asset_analyzer::write_test_wav(&path, 8000, &[0; 12000]);
+
+        let mut state = TimelineState::new();
+        let path = path.to_string_lossy();
+        let duration = audio_clip_duration(&mut state, &path, 5.0);
+        // Not rounded down to whole seconds, and not the fallback
+        assert!((duration - 1.5).abs() < 0.01, "{}", duration);
+        assert_eq!(
+            audio_clip_duration(&mut state, "/no/such/file.wav", 5.0),
+            5.0
+        );
+    }
+}
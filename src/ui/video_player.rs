@@ -1,10 +1,13 @@
 use eframe::egui;
 use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
 
 use crate::renderer::time_player_bridge::TimelinePlayerBridge;
-use crate::renderer::timeline_renderer::{TimelineRenderer, VideoFrame};
+use crate::renderer::timeline_renderer::{
+    DecodeBackend, SeekMode, TimelineRenderer, VideoFrame, catch_decode_panic,
+};
 use crate::types::playback_state::PlaybackState;
-use crate::types::timeline::Timeline;
+use crate::types::timeline::{GapFade, Timeline};
 
 /// A video player widget that displays frames rendered from the timeline.
 pub struct VideoPlayer {
@@ -15,6 +18,141 @@ pub struct VideoPlayer {
     pub width: u32,
     pub height: u32,
     pub frame_rate: f64,
+    /// Draw rule-of-thirds lines over the preview
+    pub show_thirds: bool,
+    /// Draw the action- and title-safe rectangles over the preview
+    pub show_safe_areas: bool,
+    /// Shown where the frame is fully transparent (preview only)
+    pub background: PreviewBackground,
+    /// Whether the playhead is being dragged; seeks are then rate-limited
+    pub scrubbing: bool,
+    scrub_throttle: ScrubThrottle,
+}
+
+/// Default cap on preview decodes per second while scrubbing
+pub const DEFAULT_SCRUB_FPS: u32 = 20;
+
+/// Rate limit for scrub seeks: requests are held until the interval since the last
+/// released one has passed, and only the latest held request is kept
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrubThrottle {
+    min_interval: Duration,
+    last_release: Option<Instant>,
+    pending: Option<f64>,
+}
+
+impl ScrubThrottle {
+    /// At most `max_fps` releases per second; 0 releases every request
+    pub fn new(max_fps: u32) -> Self {
+        let mut throttle = Self {
+            min_interval: Duration::ZERO,
+            last_release: None,
+            pending: None,
+        };
+        throttle.set_max_fps(max_fps);
+        throttle
+    }
+
+    pub fn set_max_fps(&mut self, max_fps: u32) {
+        self.min_interval = match max_fps {
+            0 => Duration::ZERO,
+            fps => Duration::from_secs(1) / fps,
+        };
+    }
+
+    /// Ask for a seek to `time`, replacing any request still held
+    pub fn request(&mut self, time: f64) {
+        self.pending = Some(time);
+    }
+
+    /// The held request, once the interval since the last release has passed at `now`
+    pub fn poll(&mut self, now: Instant) -> Option<f64> {
+        self.time_until_due(now).filter(|wait| wait.is_zero())?;
+        self.last_release = Some(now);
+        self.pending.take()
+    }
+
+    /// How long until the held request can be released, if there is one
+    pub fn time_until_due(&self, now: Instant) -> Option<Duration> {
+        self.pending?;
+        Some(self.last_release.map_or(Duration::ZERO, |last| {
+            (last + self.min_interval).saturating_duration_since(now)
+        }))
+    }
+
+    /// Drop the held request
+    pub fn clear(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// What the preview shows where the composited frame is fully transparent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreviewBackground {
+    /// A solid sRGB color
+    Color([u8; 3]),
+    /// Light and dark grey squares
+    Checkerboard,
+}
+
+/// Side of a checkerboard square, in frame pixels
+pub const CHECKER_SIZE: usize = 8;
+
+/// Paint `background` into the fully transparent pixels of a `width`-pixel-wide RGBA
+/// frame, making them opaque. Partly transparent pixels are left alone.
+pub fn fill_transparent(data: &mut [u8], width: usize, background: PreviewBackground) {
+    for (i, pixel) in data.chunks_exact_mut(4).enumerate() {
+        if pixel[3] != 0 {
+            continue;
+        }
+        let rgb = match background {
+            PreviewBackground::Color(rgb) => rgb,
+            PreviewBackground::Checkerboard => {
+                let (x, y) = (i % width.max(1), i / width.max(1));
+                if (x / CHECKER_SIZE + y / CHECKER_SIZE).is_multiple_of(2) {
+                    [204, 204, 204]
+                } else {
+                    [153, 153, 153]
+                }
+            }
+        };
+        pixel.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+    }
+}
+
+/// Framing guides for a displayed preview image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PreviewGuides {
+    /// x positions of the two vertical rule-of-thirds lines
+    pub thirds_x: [f32; 2],
+    /// y positions of the two horizontal rule-of-thirds lines
+    pub thirds_y: [f32; 2],
+    /// Inner 90% of the image
+    pub action_safe: egui::Rect,
+    /// Inner 80% of the image
+    pub title_safe: egui::Rect,
+}
+
+/// Guides for a preview image drawn in `rect`
+pub fn preview_guides(rect: egui::Rect) -> PreviewGuides {
+    let inset = |fraction: f32| {
+        rect.shrink2(egui::vec2(
+            rect.width() * fraction / 2.0,
+            rect.height() * fraction / 2.0,
+        ))
+    };
+    PreviewGuides {
+        thirds_x: [
+            rect.left() + rect.width() / 3.0,
+            rect.left() + rect.width() * 2.0 / 3.0,
+        ],
+        thirds_y: [
+            rect.top() + rect.height() / 3.0,
+            rect.top() + rect.height() * 2.0 / 3.0,
+        ],
+        action_safe: inset(0.1),
+        title_safe: inset(0.2),
+    }
 }
 
 impl VideoPlayer {
@@ -41,15 +179,68 @@ impl VideoPlayer {
             width,
             height,
             frame_rate,
+            show_thirds: false,
+            show_safe_areas: false,
+            background: PreviewBackground::Color([0, 0, 0]),
+            scrubbing: false,
+            scrub_throttle: ScrubThrottle::new(DEFAULT_SCRUB_FPS),
         }
     }
 
-    /// Set the playhead time and update the frame.
+    /// Set the playhead time (negative times show the start) and update the frame.
+    /// While scrubbing, seeks are rate-limited and only the latest position is shown.
     pub fn set_playhead(&mut self, time: f64, ctx: &egui::Context) {
-        self.player_bridge.seek(time);
+        let time = if self.scrubbing {
+            let now = Instant::now();
+            self.scrub_throttle.request(time);
+            match self.scrub_throttle.poll(now) {
+                Some(time) => time,
+                None => {
+                    // Come back for the held position once it is due
+                    if let Some(wait) = self.scrub_throttle.time_until_due(now) {
+                        ctx.request_repaint_after(wait);
+                    }
+                    return;
+                }
+            }
+        } else {
+            self.scrub_throttle.clear();
+            time
+        };
+        let bridge = &mut self.player_bridge;
+        // A frame that panics while decoding is skipped rather than closing the editor
+        catch_decode_panic("the preview frame", || {
+            bridge.seek(time.max(0.0));
+            Some(())
+        });
         self.update_texture(ctx);
     }
 
+    /// Draw the preview again from the timeline, e.g. after it was edited.
+    pub fn invalidate_frames(&mut self) {
+        self.player_bridge.invalidate();
+    }
+
+    /// Cap preview decodes while scrubbing at `max` per second (0 for no limit).
+    pub fn set_scrub_fps(&mut self, max: u32) {
+        self.scrub_throttle.set_max_fps(max);
+    }
+
+    /// Choose fast keyframe seeks (while scrubbing) or exact ones.
+    pub fn set_seek_mode(&mut self, mode: SeekMode) {
+        self.player_bridge.renderer.set_seek_mode(mode);
+    }
+
+    /// Composite the preview in the project's working color space.
+    pub fn set_color_space(&mut self, space: crate::types::media::ColorSpace) {
+        self.player_bridge.renderer.set_color_space(space);
+    }
+
+    /// Skip the effects of every clip (`all`) or of `clips` in the preview.
+    pub fn set_effects_bypass(&mut self, all: bool, clips: &std::collections::HashSet<String>) {
+        self.player_bridge.renderer.set_effects_bypass(all, clips);
+    }
+
     /// Advance playback and update the frame.
     pub fn update_playback(&mut self, is_playing: bool, ctx: &egui::Context) {
         if is_playing {
@@ -57,16 +248,22 @@ impl VideoPlayer {
         } else {
             self.player_bridge.pause();
         }
-        self.player_bridge.update();
+        let bridge = &mut self.player_bridge;
+        catch_decode_panic("the preview frame", || {
+            bridge.update();
+            Some(())
+        });
         self.update_texture(ctx);
     }
 
     /// Update the egui texture from the current VideoFrame.
     pub fn update_texture(&mut self, ctx: &egui::Context) {
         if let Some(frame) = self.player_bridge.current_frame() {
+            let mut data = frame.data.clone();
+            fill_transparent(&mut data, frame.width as usize, self.background);
             let color_img = egui::ColorImage::from_rgba_unmultiplied(
                 [frame.width as usize, frame.height as usize],
-                &frame.data,
+                &data,
             );
             self.texture = Some(ctx.load_texture(
                 "timeline_video_frame",
@@ -79,14 +276,180 @@ impl VideoPlayer {
     }
 
     /// Show the video player panel in egui.
-    pub fn show(&mut self, ui: &mut egui::Ui, _ctx: &egui::Context) {
+    pub fn show(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         ui.vertical(|ui| {
-            ui.heading("Video Player");
+            ui.horizontal(|ui| {
+                ui.heading("Video Player");
+                ui.toggle_value(&mut self.show_thirds, "Thirds");
+                ui.toggle_value(&mut self.show_safe_areas, "Safe Areas");
+                // Background behind transparent areas
+                let before = self.background;
+                let mut checkerboard = self.background == PreviewBackground::Checkerboard;
+                ui.toggle_value(&mut checkerboard, "Checkerboard");
+                self.background = match self.background {
+                    PreviewBackground::Color(mut rgb) if !checkerboard => {
+                        ui.color_edit_button_srgb(&mut rgb);
+                        PreviewBackground::Color(rgb)
+                    }
+                    _ if checkerboard => PreviewBackground::Checkerboard,
+                    _ => PreviewBackground::Color([0, 0, 0]),
+                };
+                if self.background != before {
+                    self.update_texture(ctx);
+                }
+                let mut gap_fade = self.player_bridge.renderer.gap_fade.is_some();
+                if ui
+                    .toggle_value(&mut gap_fade, "Gap Fade")
+                    .on_hover_text("Dissolve to and from black across short gaps")
+                    .changed()
+                {
+                    self.player_bridge
+                        .renderer
+                        .set_gap_fade(gap_fade.then(GapFade::default));
+                    let playhead = self.player_bridge.playback_state.playhead;
+                    self.set_playhead(playhead, ctx);
+                }
+                let mut video_off = self.player_bridge.renderer.video_disabled;
+                if ui
+                    .toggle_value(&mut video_off, "Video Off")
+                    .on_hover_text("Skip video decoding while editing audio")
+                    .changed()
+                {
+                    self.player_bridge.renderer.set_video_disabled(video_off);
+                    let playhead = self.player_bridge.playback_state.playhead;
+                    self.set_playhead(playhead, ctx);
+                }
+                let backend = self.player_bridge.renderer.decode_backend;
+                let mut requested = backend;
+                egui::ComboBox::from_id_salt("decode_backend")
+                    .selected_text(backend.label())
+                    .show_ui(ui, |ui| {
+                        for option in [
+                            DecodeBackend::Software,
+                            DecodeBackend::VaApi,
+                            DecodeBackend::D3d11,
+                            DecodeBackend::NvDec,
+                        ] {
+                            ui.selectable_value(&mut requested, option, option.label());
+                        }
+                    })
+                    .response
+                    .on_hover_text("Decoders for source video, software if the choice is missing");
+                if requested != backend {
+                    self.player_bridge.renderer.set_decode_backend(requested);
+                    let playhead = self.player_bridge.playback_state.playhead;
+                    self.set_playhead(playhead, ctx);
+                }
+            });
             if let Some(texture) = &self.texture {
-                ui.image(texture);
+                let rect = ui.image(texture).rect;
+                self.draw_guides(ui.painter_at(rect), rect);
+                if self.player_bridge.renderer.video_disabled {
+                    ui.painter_at(rect).text(
+                        rect.center(),
+                        egui::Align2::CENTER_CENTER,
+                        "Video preview off",
+                        egui::FontId::proportional(16.0),
+                        egui::Color32::from_white_alpha(200),
+                    );
+                }
             } else {
                 ui.label("No frame loaded");
             }
         });
     }
+
+    /// Draw the enabled framing guides over the image shown in `rect`
+    fn draw_guides(&self, painter: egui::Painter, rect: egui::Rect) {
+        let guides = preview_guides(rect);
+        let stroke = egui::Stroke::new(1.0, egui::Color32::from_white_alpha(140));
+        if self.show_thirds {
+            for x in guides.thirds_x {
+                painter.vline(x, rect.y_range(), stroke);
+            }
+            for y in guides.thirds_y {
+                painter.hline(rect.x_range(), y, stroke);
+            }
+        }
+        if self.show_safe_areas {
+            for safe in [guides.action_safe, guides.title_safe] {
+                painter.rect_stroke(safe, 0.0, stroke, egui::StrokeKind::Inside);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_guides() {
+        let rect = egui::Rect::from_min_size(egui::pos2(10.0, 20.0), egui::vec2(300.0, 150.0));
+        let guides = preview_guides(rect);
+        assert_eq!(guides.thirds_x, [110.0, 210.0]);
+        assert_eq!(guides.thirds_y, [70.0, 120.0]);
+        assert_eq!(
+            guides.action_safe,
+            egui::Rect::from_min_max(egui::pos2(25.0, 27.5), egui::pos2(295.0, 162.5))
+        );
+        assert_eq!(
+            guides.title_safe,
+            egui::Rect::from_min_max(egui::pos2(40.0, 35.0), egui::pos2(280.0, 155.0))
+        );
+        assert_eq!(guides.action_safe.center(), rect.center());
+    }
+
+    #[test]
+    fn test_checkerboard_fills_only_transparent_pixels() {
+        // 16x1 frame: transparent, except an opaque black pixel and a half-transparent one
+        let mut data = vec![0u8; 16 * 4];
+        data[4..8].copy_from_slice(&[0, 0, 0, 255]);
+        data[40..44].copy_from_slice(&[200, 0, 0, 128]);
+        fill_transparent(&mut data, 16, PreviewBackground::Checkerboard);
+
+        let pixel = |x: usize| &data[x * 4..x * 4 + 4];
+        assert_eq!(pixel(0), [204, 204, 204, 255]);
+        assert_eq!(pixel(1), [0, 0, 0, 255]);
+        assert_eq!(pixel(7), [204, 204, 204, 255]);
+        assert_eq!(pixel(8), [153, 153, 153, 255]);
+        assert_eq!(pixel(10), [200, 0, 0, 128]);
+
+        // Second row starts a square further along
+        let mut data = vec![0u8; 16 * 9 * 4];
+        fill_transparent(&mut data, 16, PreviewBackground::Checkerboard);
+        assert_eq!(data[16 * 8 * 4], 153);
+
+        let mut data = vec![0u8, 0, 0, 0, 9, 9, 9, 255];
+        fill_transparent(&mut data, 2, PreviewBackground::Color([10, 20, 30]));
+        assert_eq!(data, vec![10, 20, 30, 255, 9, 9, 9, 255]);
+    }
+
+    #[test]
+    fn test_scrub_throttle_keeps_only_the_last_request() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut throttle = ScrubThrottle::new(20);
+        assert_eq!(throttle.poll(at(0)), None);
+
+        throttle.request(1.0);
+        assert_eq!(throttle.poll(at(0)), Some(1.0));
+        // Requests within 50ms of it are held, each replacing the one before
+        throttle.request(2.0);
+        assert_eq!(throttle.poll(at(10)), None);
+        throttle.request(3.0);
+        assert_eq!(throttle.poll(at(20)), None);
+        assert_eq!(
+            throttle.time_until_due(at(20)),
+            Some(Duration::from_millis(30))
+        );
+        // The last one is honored once the interval has passed
+        assert_eq!(throttle.poll(at(50)), Some(3.0));
+        assert_eq!(throttle.poll(at(200)), None);
+
+        // No limit releases every request at once
+        throttle.set_max_fps(0);
+        throttle.request(4.0);
+        assert_eq!(throttle.poll(at(51)), Some(4.0));
+    }
 }